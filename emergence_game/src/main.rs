@@ -1,9 +1,28 @@
 use bevy::prelude::*;
 use bevy::window::{PresentMode, WindowMode, WindowPlugin};
 use bevy_framepace::FramepacePlugin;
-use emergence_lib::world_gen::GenerationConfig;
+use emergence_lib::player_interaction::colony_rules::ColonyRules;
+use emergence_lib::world_gen::{
+    parse_bug_report, ConfigError, Difficulty, GameVersion, GenerationConfig, MapCode,
+    MapCodeSettings,
+};
 
 fn main() {
+    let args = match CliArgs::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(error) => {
+            eprintln!("{error}");
+            eprintln!("{}", CliArgs::USAGE);
+            std::process::exit(1);
+        }
+    };
+
+    let mut gen_config = generation_config(args.world_source);
+    if let Some(difficulty) = args.difficulty {
+        gen_config = gen_config.with_difficulty(difficulty);
+    }
+    let colony_rules = colony_rules(args.rules);
+
     App::new()
         .add_plugins(DefaultPlugins.set(WindowPlugin {
             primary_window: Some(Window {
@@ -17,11 +36,239 @@ fn main() {
         // This is turned on and off in the world gen state management code.
         .add_plugin(FramepacePlugin)
         .add_plugin(emergence_lib::asset_management::AssetManagementPlugin)
-        .add_plugin(emergence_lib::simulation::SimulationPlugin {
-            gen_config: GenerationConfig::standard(),
-        })
+        .add_plugin(emergence_lib::simulation::SimulationPlugin { gen_config })
         .add_plugin(emergence_lib::player_interaction::InteractionPlugin)
         .add_plugin(emergence_lib::graphics::GraphicsPlugin)
         .add_plugin(emergence_lib::ui::UiPlugin)
+        .insert_resource(colony_rules)
+        .insert_resource(GameVersion(env!("CARGO_PKG_VERSION")))
         .run();
 }
+
+/// Where the world generation settings are read from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum WorldSource {
+    /// `--map-code <CODE>` regenerates a shared world.
+    MapCode(String),
+    /// `--bug-report <REPORT>` regenerates the world described by a copied bug report.
+    BugReport(String),
+    /// `--config <PATH>` loads the settings from a JSON file like `assets/generation.json`.
+    Config(String),
+}
+
+impl WorldSource {
+    /// The flag used to choose this source.
+    fn flag(&self) -> &'static str {
+        match self {
+            WorldSource::MapCode(_) => "--map-code",
+            WorldSource::BugReport(_) => "--bug-report",
+            WorldSource::Config(_) => "--config",
+        }
+    }
+}
+
+/// The options chosen on the command line.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct CliArgs {
+    /// Where the world generation settings come from.
+    ///
+    /// If this is `None`, the standard settings are used.
+    world_source: Option<WorldSource>,
+    /// `--difficulty <peaceful|normal|harsh>` replaces the difficulty of the chosen world generation settings.
+    difficulty: Option<Difficulty>,
+    /// `--rules <PATH>` loads the colony rules from a JSON file.
+    rules: Option<String>,
+}
+
+impl CliArgs {
+    /// Describes the accepted arguments.
+    const USAGE: &'static str = "Usage: emergence_game [--map-code <CODE> | --bug-report <REPORT> | --config <PATH>] [--difficulty <peaceful|normal|harsh>] [--rules <PATH>]";
+
+    /// Every flag that is accepted, each of which takes a single value.
+    const FLAGS: [&'static str; 5] = [
+        "--map-code",
+        "--bug-report",
+        "--config",
+        "--difficulty",
+        "--rules",
+    ];
+
+    /// Reads the options from the command line arguments, not including the program name.
+    ///
+    /// Unknown flags, repeated flags and conflicting world sources are rejected,
+    /// rather than silently ignored.
+    /// Map codes and bug reports already record a difficulty, so they cannot be combined with `--difficulty`.
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut cli_args = CliArgs::default();
+        let mut args = args.into_iter();
+
+        while let Some(flag) = args.next() {
+            if !Self::FLAGS.contains(&flag.as_str()) {
+                return Err(format!("Unknown argument {flag}"));
+            }
+            let Some(value) = args.next().filter(|value| !value.starts_with("--")) else {
+                return Err(format!("{flag} requires a value to be provided"));
+            };
+
+            match flag.as_str() {
+                "--map-code" | "--bug-report" | "--config" => {
+                    let source = match flag.as_str() {
+                        "--map-code" => WorldSource::MapCode(value),
+                        "--bug-report" => WorldSource::BugReport(value),
+                        _ => WorldSource::Config(value),
+                    };
+
+                    if let Some(existing) = &cli_args.world_source {
+                        return Err(format!(
+                            "{} cannot be combined with {}: choose one way to generate the world",
+                            source.flag(),
+                            existing.flag()
+                        ));
+                    }
+                    cli_args.world_source = Some(source);
+                }
+                "--difficulty" => {
+                    if cli_args.difficulty.is_some() {
+                        return Err("--difficulty can only be provided once".to_string());
+                    }
+                    let difficulty = value
+                        .parse()
+                        .map_err(|error| format!("Could not use --difficulty: {error}"))?;
+                    cli_args.difficulty = Some(difficulty);
+                }
+                "--rules" => {
+                    if cli_args.rules.is_some() {
+                        return Err("--rules can only be provided once".to_string());
+                    }
+                    cli_args.rules = Some(value);
+                }
+                _ => unreachable!("{flag} is not in CliArgs::FLAGS"),
+            }
+        }
+
+        if let (Some(_), Some(source @ (WorldSource::MapCode(_) | WorldSource::BugReport(_)))) =
+            (cli_args.difficulty, &cli_args.world_source)
+        {
+            return Err(format!(
+                "--difficulty cannot be combined with {}, which already records the difficulty",
+                source.flag()
+            ));
+        }
+
+        Ok(cli_args)
+    }
+}
+
+/// Loads the colony rules from the file at `path`.
+///
+/// If no path is provided, the colony starts without any rules.
+fn colony_rules(path: Option<String>) -> ColonyRules {
+    let Some(path) = path else {
+        return ColonyRules::default();
+    };
+
+    match ColonyRules::from_file(&path) {
+        Ok(rules) => rules,
+        Err(error) => {
+            eprintln!("Could not use {path}: {error}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Builds the world generation settings from the chosen `source`.
+///
+/// If no source is provided, or the `--config` file does not exist, the standard settings are used.
+fn generation_config(source: Option<WorldSource>) -> GenerationConfig {
+    match source {
+        None => GenerationConfig::standard(),
+        Some(WorldSource::MapCode(code)) => {
+            match MapCode::decode(&code).and_then(MapCodeSettings::into_config) {
+                Ok(config) => config,
+                Err(error) => {
+                    eprintln!("Could not use map code {code}: {error}");
+                    std::process::exit(1);
+                }
+            }
+        }
+        Some(WorldSource::BugReport(report)) => {
+            match parse_bug_report(&report).map(|report| report.generation_config()) {
+                Ok(Ok(config)) => config,
+                Ok(Err(error)) => {
+                    eprintln!("Could not use the map code in the bug report: {error}");
+                    std::process::exit(1);
+                }
+                Err(error) => {
+                    eprintln!("Could not read bug report: {error}");
+                    std::process::exit(1);
+                }
+            }
+        }
+        Some(WorldSource::Config(path)) => match GenerationConfig::from_file(&path) {
+            Ok(config) => config,
+            Err(ConfigError::Io(error)) if error.kind() == std::io::ErrorKind::NotFound => {
+                eprintln!("Could not find {path}, using the standard world generation settings");
+                GenerationConfig::standard()
+            }
+            Err(error) => {
+                eprintln!("Could not use {path}: {error}");
+                std::process::exit(1);
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parses the provided arguments.
+    fn parse(args: &[&str]) -> Result<CliArgs, String> {
+        CliArgs::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn no_arguments_use_the_defaults() {
+        assert_eq!(parse(&[]), Ok(CliArgs::default()));
+    }
+
+    #[test]
+    fn compatible_flags_are_combined() {
+        let args = parse(&[
+            "--config",
+            "world.json",
+            "--difficulty",
+            "harsh",
+            "--rules",
+            "rules.json",
+        ])
+        .unwrap();
+
+        assert_eq!(
+            args,
+            CliArgs {
+                world_source: Some(WorldSource::Config("world.json".to_string())),
+                difficulty: Some(Difficulty::Harsh),
+                rules: Some("rules.json".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn conflicting_world_sources_are_rejected() {
+        assert!(parse(&["--map-code", "ABC", "--config", "world.json"]).is_err());
+        assert!(parse(&["--bug-report", "report", "--map-code", "ABC"]).is_err());
+        assert!(parse(&["--config", "a.json", "--config", "b.json"]).is_err());
+        assert!(parse(&["--map-code", "ABC", "--difficulty", "harsh"]).is_err());
+    }
+
+    #[test]
+    fn unknown_and_incomplete_flags_are_rejected() {
+        assert!(parse(&["--seed", "42"]).is_err());
+        assert!(parse(&["--fullscreen"]).is_err());
+        assert!(parse(&["--rules"]).is_err());
+        assert!(parse(&["--map-code", "--config", "world.json"]).is_err());
+        assert!(parse(&["--difficulty", "impossible"]).is_err());
+        assert!(parse(&["--rules", "a.json", "--rules", "b.json"]).is_err());
+    }
+}
@@ -1,6 +1,9 @@
 //! Code for a generic identifier type
 
-use bevy::{prelude::Component, reflect::Reflect};
+use bevy::{
+    prelude::Component,
+    reflect::{FromReflect, Reflect, ReflectRef},
+};
 use serde::{Deserialize, Serialize};
 use std::{fmt::Debug, hash::Hash, marker::PhantomData};
 
@@ -104,3 +107,23 @@ impl<T> Clone for Id<T> {
 }
 
 impl<T> Copy for Id<T> {}
+
+// Deriving this would require `T` itself to be reflectable, but `T` is only a marker type
+impl<T: Send + Sync + 'static> FromReflect for Id<T> {
+    fn from_reflect(reflect: &dyn Reflect) -> Option<Self> {
+        if let Some(id) = reflect.as_any().downcast_ref::<Self>() {
+            return Some(*id);
+        }
+
+        // Values read back from serialized data are dynamic structs
+        let ReflectRef::Struct(dynamic_struct) = reflect.reflect_ref() else {
+            return None;
+        };
+        let value = dynamic_struct
+            .field("value")?
+            .as_any()
+            .downcast_ref::<u64>()?;
+
+        Some(Id::new(*value))
+    }
+}
@@ -16,16 +16,18 @@ use std::{any::type_name, fmt::Debug};
 /// Write-only data definitions.
 ///
 /// These are intended to be created a single time, via [`Manifest::new`].
-#[derive(Debug, Resource)]
+#[derive(Debug, Resource, Reflect)]
 pub struct Manifest<T, Data>
 where
     T: 'static,
     Data: Debug,
 {
     /// The internal mapping to the data
+    #[reflect(ignore)]
     data_map: HashMap<Id<T>, Data>,
 
     /// The human-readable name associated with each Id.
+    #[reflect(ignore)]
     name_map: HashMap<Id<T>, String>,
 }
 
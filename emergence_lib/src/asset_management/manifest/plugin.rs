@@ -46,6 +46,8 @@ where
         app.init_asset_loader::<RawManifestLoader<M>>()
             .add_asset::<M>()
             .add_asset_collection::<RawManifestHandle<M>>()
+            .register_type::<RawManifestHandle<M>>()
+            .register_type::<Manifest<M::Marker, M::Data>>()
             .add_system(
                 detect_manifest_creation::<M>
                     .in_set(DetectManifestCreationSet)
@@ -61,7 +63,7 @@ where
 /// Resource to store the handle to a [`IsRawManifest`] type while it is being loaded.
 ///
 /// This is necessary to stop the asset from being discarded.
-#[derive(Debug, Clone, Resource)]
+#[derive(Debug, Clone, Resource, Reflect)]
 pub struct RawManifestHandle<M>
 where
     M: IsRawManifest,
@@ -69,6 +71,7 @@ where
     /// The handle to the raw manifest asset.
     ///
     /// We mainly need this for the asset to not be unloaded.
+    #[reflect(ignore)]
     handle: Handle<M>,
 }
 
@@ -56,9 +56,11 @@ pub enum AssetState {
 }
 
 /// The set of all assets that need to be loaded.
-#[derive(Resource, Debug, Default)]
+#[derive(Resource, Debug, Default, Reflect)]
+#[reflect(Resource)]
 pub struct AssetsToLoad {
     /// The set of [`Loadable`] types that still need to be loaded
+    #[reflect(ignore)]
     remaining: HashMap<TypeId, String>,
 }
 
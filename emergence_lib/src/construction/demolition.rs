@@ -4,13 +4,14 @@ use bevy::{ecs::system::SystemParam, prelude::*};
 
 use crate::{
     asset_management::manifest::Id,
-    geometry::{Height, MapGeometry, VoxelPos},
+    geometry::{MapGeometry, VoxelPos},
     signals::{Emitter, SignalStrength, SignalType},
     structures::structure_manifest::Structure,
 };
 
 /// Marker component for structures that are intended to be deconstructed
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Reflect, FromReflect, Default)]
+#[reflect(Component)]
 pub(crate) struct MarkedForDemolition;
 
 /// A query for the structures that need to be demolished.
@@ -32,7 +33,7 @@ impl<'w, 's> DemolitionQuery<'w, 's> {
         map_geometry: &MapGeometry,
     ) -> Option<Entity> {
         // This is only a viable target if the unit can reach it!
-        if current.abs_height_diff(target) > Height::MAX_STEP {
+        if current.abs_height_diff(target) > map_geometry.traversal().max_climb {
             return None;
         }
 
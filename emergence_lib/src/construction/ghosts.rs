@@ -11,7 +11,7 @@ use crate::enum_iter::IterableEnum;
 use crate::geometry::MapGeometry;
 use crate::organisms::energy::StartingEnergy;
 use crate::player_interaction::picking::PickableVoxel;
-use crate::simulation::SimulationSet;
+use crate::simulation::phases::{SimulationAppExt, TickPhase};
 use crate::structures::commands::StructureCommandsExt;
 use crate::structures::structure_manifest::{Structure, StructureManifest};
 use crate::terrain::terrain_manifest::TerrainManifest;
@@ -26,7 +26,7 @@ use crate::{
     crafting::inventories::{CraftingState, InputInventory},
     geometry::{Facing, VoxelPos},
     player_interaction::clipboard::ClipboardData,
-    signals::{Emitter, SignalStrength, SignalType},
+    signals::{Emitter, ManageSignals, SignalStrength, SignalType},
 };
 
 use super::terraform::TerraformingAction;
@@ -37,22 +37,26 @@ pub struct GhostPlugin;
 
 impl Plugin for GhostPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<GhostHandles>().add_systems(
-            (
-                validate_ghost_structures,
-                ghost_structure_signals.after(validate_ghost_structures),
-                ghost_structure_lifecycle.after(validate_ghost_structures),
+        app.init_resource::<GhostHandles>()
+            .add_simulation_systems(
+                TickPhase::Action,
+                (
+                    validate_ghost_structures,
+                    ghost_structure_lifecycle.after(validate_ghost_structures),
+                ),
             )
-                .in_set(SimulationSet)
-                .in_schedule(CoreSchedule::FixedUpdate),
-        );
+            .add_simulation_system(
+                TickPhase::Perception,
+                ghost_structure_signals.before(ManageSignals),
+            );
     }
 }
 
 /// Stores the assets needed to render ghosts.
-#[derive(Debug, Resource)]
+#[derive(Debug, Resource, Reflect)]
 pub(crate) struct GhostHandles {
     /// The materials used to render ghosts.
+    #[reflect(ignore)]
     materials: HashMap<GhostKind, Handle<StandardMaterial>>,
 }
 
@@ -76,7 +80,8 @@ impl FromWorld for GhostHandles {
 }
 
 /// A marker component that indicates that a structure or terrain element is planned to be built, rather than actually existing.
-#[derive(Reflect, FromReflect, Component, Clone, Copy, Debug)]
+#[derive(Reflect, FromReflect, Component, Clone, Copy, Debug, Default)]
+#[reflect(Component)]
 pub(crate) struct Ghost;
 
 /// A marker component indicating that this structure should be rendered in a transparent style.
@@ -258,7 +263,8 @@ impl GhostKind {
 }
 
 /// A marker component that indicates that this structure or terrain modification is planned to be built, rather than actually existing.
-#[derive(Component, Clone, Copy, Debug)]
+#[derive(Component, Clone, Copy, Debug, Reflect, FromReflect, Default)]
+#[reflect(Component)]
 pub(crate) struct Preview;
 
 /// The set of components needed to spawn a structure or terraforming preview.
@@ -404,7 +410,7 @@ pub(super) fn ghost_structure_signals(
 }
 
 /// An identifier for a workplace.
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Reflect, FromReflect)]
 pub enum WorkplaceId {
     /// This workplace is a structure
     Structure(Id<Structure>),
@@ -7,7 +7,8 @@ use serde::{Deserialize, Serialize};
 
 use crate::crafting::inventories::InputInventory;
 use crate::items::slot::ItemSlot;
-use crate::simulation::SimulationSet;
+use crate::signals::ManageSignals;
+use crate::simulation::phases::{SimulationAppExt, TickPhase};
 use crate::{asset_management::manifest::Id, structures::structure_manifest::Structure};
 
 use self::demolition::set_emitter_for_structures_to_be_demolished;
@@ -25,17 +26,16 @@ impl Plugin for ConstructionPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugin(ghosts::GhostPlugin)
             .add_plugin(zoning::ZoningPlugin)
-            // Must run after crafting emitters in order to wipe out their signals
-            .add_system(
-                set_emitter_for_structures_to_be_demolished
-                    .after(crate::crafting::set_crafting_emitter)
-                    .in_set(SimulationSet)
-                    .in_schedule(CoreSchedule::FixedUpdate),
-            )
-            .add_systems(
-                (terraforming_lifecycle, terraforming_signals)
-                    .in_set(SimulationSet)
-                    .in_schedule(CoreSchedule::FixedUpdate),
+            .add_simulation_system(TickPhase::Action, terraforming_lifecycle)
+            .add_simulation_systems(
+                TickPhase::Perception,
+                (
+                    // Must run after crafting emitters in order to wipe out their signals
+                    set_emitter_for_structures_to_be_demolished
+                        .after(crate::crafting::set_crafting_emitter),
+                    terraforming_signals,
+                )
+                    .before(ManageSignals),
             );
     }
 }
@@ -18,6 +18,7 @@ use crate::{
     player_interaction::selection::ObjectInteraction,
     signals::{Emitter, SignalStrength, SignalType},
     terrain::{
+        commands::SetTerrainCommand,
         terrain_assets::TerrainHandles,
         terrain_manifest::{Terrain, TerrainManifest},
     },
@@ -29,7 +30,7 @@ use super::ghosts::{GhostTerraformBundle, TerraformPreviewBundle};
 ///
 /// These are generally higher level than the actual [`TerraformingAction`]s,
 /// which represent the actual changes to the terrain that can be performed by units.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect, FromReflect)]
 pub(crate) enum TerraformingTool {
     /// Raise the height of this tile once
     Raise,
@@ -42,7 +43,21 @@ pub(crate) enum TerraformingTool {
 /// Added as a component to terrain tiles, tracking the work needed to terraform them.
 ///
 /// When set to a non-null value, units will take action to manipulate them.
-#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+#[derive(
+    Component,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    Default,
+    Reflect,
+    FromReflect,
+)]
+#[reflect(Component)]
 pub enum TerraformingAction {
     /// No terraforming action is being performed.
     #[default]
@@ -229,7 +244,13 @@ struct TerraformCommand {
 impl Command for TerraformCommand {
     fn write(self, world: &mut World) {
         let map_geometry = world.resource::<MapGeometry>();
-        let starting_height = map_geometry.get_height(self.hex).unwrap();
+        let Ok(starting_height) = map_geometry.get_height(self.hex) else {
+            warn!(
+                "Tried to terraform at {:?}, outside of the map bounds.",
+                self.hex
+            );
+            return;
+        };
         let final_height = self.action.final_height(starting_height);
         let voxel_pos = VoxelPos {
             hex: self.hex,
@@ -301,7 +322,13 @@ struct CancelTerraformCommand {
 impl Command for CancelTerraformCommand {
     fn write(self, world: &mut World) {
         let map_geometry = world.resource::<MapGeometry>();
-        let terrain_entity = map_geometry.get_terrain(self.hex).unwrap();
+        let Ok(terrain_entity) = map_geometry.get_terrain(self.hex) else {
+            warn!(
+                "Tried to cancel terraforming at {:?}, outside of the map bounds.",
+                self.hex
+            );
+            return;
+        };
         let mut terraforming_action = world.get_mut::<TerraformingAction>(terrain_entity).unwrap();
         *terraforming_action = TerraformingAction::None;
 
@@ -330,44 +357,43 @@ impl Command for ApplyTerraformingCommand {
         // Just using system state makes satisfying the borrow checker a lot easier
         let mut system_state = SystemState::<(
             ResMut<MapGeometry>,
-            Res<TerrainHandles>,
-            Query<(
-                &mut Id<Terrain>,
-                &mut VoxelPos,
-                &mut TerraformingAction,
-                &mut Handle<Scene>,
-            )>,
+            Query<(&mut VoxelPos, &mut TerraformingAction)>,
         )>::new(world);
 
-        let (mut map_geometry, terrain_handles, mut terrain_query) = system_state.get_mut(world);
+        let (mut map_geometry, mut terrain_query) = system_state.get_mut(world);
 
-        let terrain_entity = map_geometry.get_terrain(self.hex).unwrap();
+        let Ok(terrain_entity) = map_geometry.get_terrain(self.hex) else {
+            warn!(
+                "Tried to apply terraforming at {:?}, outside of the map bounds.",
+                self.hex
+            );
+            return;
+        };
 
-        let (mut current_terrain_id, mut voxel_pos, mut terraforming_action, mut scene_handle) =
+        let (mut voxel_pos, mut terraforming_action) =
             terrain_query.get_mut(terrain_entity).unwrap();
 
+        let mut changed_terrain_id = None;
         match *terraforming_action {
             TerraformingAction::None => (),
             TerraformingAction::Raise => voxel_pos.height = voxel_pos.height.above(),
             TerraformingAction::Lower => {
                 voxel_pos.height = voxel_pos.height.below();
             }
-            TerraformingAction::Change(changed_terrain_id) => {
-                *current_terrain_id = changed_terrain_id;
-            }
+            TerraformingAction::Change(terrain_id) => changed_terrain_id = Some(terrain_id),
         };
 
-        // We can't do this above, as we need to drop the previous query before borrowing from the world again
-        if let TerraformingAction::Change(changed_terrain_id) = *terraforming_action {
-            *scene_handle = terrain_handles
-                .scenes
-                .get(&changed_terrain_id)
-                .unwrap()
-                .clone_weak();
-        }
-
         *terraforming_action = TerraformingAction::None;
 
         map_geometry.update_height(voxel_pos.hex, voxel_pos.height);
+
+        // We can't do this above, as we need to drop the previous query before borrowing from the world again
+        if let Some(terrain_id) = changed_terrain_id {
+            SetTerrainCommand {
+                hex: self.hex,
+                terrain_id,
+            }
+            .write(world);
+        }
     }
 }
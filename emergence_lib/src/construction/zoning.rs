@@ -50,8 +50,9 @@ fn cleanup_previews(
 }
 
 /// A marker component for previews that should be deleted.
-#[derive(Component)]
-struct CleanMeUp;
+#[derive(Component, Reflect, FromReflect, Default)]
+#[reflect(Component)]
+pub(crate) struct CleanMeUp;
 
 /// Applies zoning to an area, causing structures to be created (or removed) there.
 ///
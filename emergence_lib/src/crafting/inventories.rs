@@ -23,7 +23,8 @@ use rand::{distributions::Uniform, prelude::Distribution, rngs::ThreadRng, Rng};
 use serde::{Deserialize, Serialize};
 
 /// The current state in the crafting progress.
-#[derive(Component, Debug, Default, Clone, PartialEq)]
+#[derive(Component, Debug, Default, Clone, PartialEq, Reflect, FromReflect)]
+#[reflect(Component)]
 pub(crate) enum CraftingState {
     /// There are resources missing for the recipe.
     #[default]
@@ -79,7 +80,7 @@ impl Display for CraftingState {
 }
 
 /// The input inventory for a structure.
-#[derive(Component, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Component, Clone, Debug, PartialEq, Serialize, Deserialize, Reflect, FromReflect)]
 pub enum InputInventory {
     /// Accepts precisely the provided inputs
     Exact {
@@ -283,7 +284,8 @@ pub enum AddToInputError {
 }
 
 /// The output inventory for a structure.
-#[derive(Component, Clone, Debug, Default, Deref, DerefMut)]
+#[derive(Component, Clone, Debug, Default, Deref, DerefMut, Reflect, FromReflect)]
+#[reflect(Component)]
 pub(crate) struct OutputInventory {
     /// Inner storage
     pub(crate) inventory: Inventory,
@@ -353,7 +355,8 @@ impl OutputInventory {
 }
 
 /// An inventory that simply stores items
-#[derive(Component, Clone, Debug, Default, Deref, DerefMut)]
+#[derive(Component, Clone, Debug, Default, Deref, DerefMut, Reflect, FromReflect)]
+#[reflect(Component)]
 pub(crate) struct StorageInventory {
     /// Inner storage
     pub(crate) inventory: Inventory,
@@ -2,6 +2,7 @@
 //!
 //! Items can belong to multiple tags, and correspond to fields on [`ItemData`](crate::items::item_manifest::ItemData).
 
+use bevy::reflect::{FromReflect, Reflect};
 use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
 
@@ -11,7 +12,20 @@ use crate::{
 };
 
 /// A category of items.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    Reflect,
+    FromReflect,
+)]
 pub enum ItemTag {
     /// Items that can be composted.
     Compostable,
@@ -42,7 +56,20 @@ impl Display for ItemTag {
 }
 
 /// An item or collection of items that shares a property.
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Serialize, Deserialize)]
+#[derive(
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    Reflect,
+    FromReflect,
+)]
 pub enum ItemKind {
     /// Exactly one type of item.
     Single(Id<Item>),
@@ -14,9 +14,12 @@ use crate::{
     light::shade::ReceivedLight,
     organisms::{energy::EnergyPool, lifecycle::Lifecycle, Organism},
     player_interaction::InteractionSystem,
-    signals::{Emitter, SignalStrength, SignalType},
-    simulation::SimulationSet,
-    structures::structure_manifest::{Structure, StructureManifest},
+    signals::{Emitter, ManageSignals, SignalStrength, SignalType},
+    simulation::phases::{SimulationAppExt, TickPhase},
+    structures::{
+        adjacency::{update_adjacency_bonuses, AdjacencyBonus},
+        structure_manifest::{Structure, StructureManifest},
+    },
 };
 
 use std::time::Duration;
@@ -26,12 +29,14 @@ use bevy::{ecs::query::WorldQuery, prelude::*};
 use self::{
     inventories::{CraftingState, InputInventory, OutputInventory, StorageInventory},
     item_tags::{ItemKind, ItemTag},
+    production_queue::ProductionQueue,
     recipe::{ActiveRecipe, RecipeInput},
     workers::WorkersPresent,
 };
 
 pub mod inventories;
 pub mod item_tags;
+pub mod production_queue;
 pub mod recipe;
 pub mod workers;
 
@@ -42,19 +47,26 @@ impl Plugin for CraftingPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugin(ManifestPlugin::<RawItemManifest>::new())
             .add_plugin(ManifestPlugin::<RawRecipeManifest>::new())
-            .add_systems(
+            .add_simulation_systems(
+                TickPhase::Perception,
                 (
-                    progress_crafting,
-                    gain_energy_when_crafting_completes.after(progress_crafting),
+                    update_adjacency_bonuses,
                     set_crafting_emitter
-                        .after(progress_crafting)
                         // This must run before zoning, to avoid wiping out the destruction signal
                         .before(InteractionSystem::ApplyZoning),
                     set_storage_emitter.before(InteractionSystem::ApplyZoning),
-                    clear_empty_storage_slots,
                 )
-                    .in_set(SimulationSet)
-                    .in_schedule(CoreSchedule::FixedUpdate),
+                    .before(ManageSignals),
+            )
+            .add_simulation_systems(
+                TickPhase::Action,
+                (
+                    progress_crafting,
+                    gain_energy_when_crafting_completes.after(progress_crafting),
+                    production_queue::advance_production_queues
+                        .after(gain_energy_when_crafting_completes),
+                    clear_empty_storage_slots,
+                ),
             );
     }
 }
@@ -71,6 +83,9 @@ pub(crate) struct CraftingBundle {
     /// The recipe that is currently being crafted.
     active_recipe: ActiveRecipe,
 
+    /// Orders that take priority over the active recipe.
+    production_queue: ProductionQueue,
+
     /// The current state for the crafting process.
     craft_state: CraftingState,
 
@@ -79,6 +94,9 @@ pub(crate) struct CraftingBundle {
 
     /// The number of workers present / allowed at this structure
     workers_present: WorkersPresent,
+
+    /// The production multiplier granted by neighboring tiles
+    adjacency_bonus: AdjacencyBonus,
 }
 
 impl CraftingBundle {
@@ -99,9 +117,11 @@ impl CraftingBundle {
                 input_inventory: recipe.input_inventory(item_manifest),
                 output_inventory: recipe.output_inventory(item_manifest),
                 active_recipe: ActiveRecipe(Some(recipe_id)),
+                production_queue: ProductionQueue::default(),
                 craft_state: CraftingState::NeedsInput,
                 emitter: Emitter::default(),
                 workers_present: WorkersPresent::new(max_workers),
+                adjacency_bonus: AdjacencyBonus::default(),
             }
         } else {
             Self {
@@ -112,9 +132,11 @@ impl CraftingBundle {
                     inventory: Inventory::new(1, None),
                 },
                 active_recipe: ActiveRecipe(None),
+                production_queue: ProductionQueue::default(),
                 craft_state: CraftingState::NeedsInput,
                 emitter: Emitter::default(),
                 workers_present: WorkersPresent::new(max_workers),
+                adjacency_bonus: AdjacencyBonus::default(),
             }
         }
     }
@@ -136,6 +158,8 @@ struct CraftingQuery {
     workers_present: &'static WorkersPresent,
     /// The current position of the crafter
     voxel_pos: &'static VoxelPos,
+    /// The production multiplier granted by neighboring tiles
+    adjacency_bonus: &'static AdjacencyBonus,
     /// Is the structure an organism?
     maybe_organism: Option<&'static Organism>,
 }
@@ -190,15 +214,18 @@ fn progress_crafting(
                     // Check if we can make progress
                     if recipe.satisfied(crafter.workers_present.current(), received_light) {
                         // Many hands make light work!
-                        if recipe.workers_required() > 0 {
-                            updated_progress += Duration::from_secs_f32(
-                                time.period.as_secs_f32()
-                                    * crafter.workers_present.effective_workers()
-                                    / recipe.workers_required() as f32,
-                            );
+                        let work_rate = if recipe.workers_required() > 0 {
+                            crafter.workers_present.effective_workers()
+                                / recipe.workers_required() as f32
                         } else {
-                            updated_progress += time.period;
-                        }
+                            1.
+                        };
+
+                        updated_progress += Duration::from_secs_f32(
+                            time.period.as_secs_f32()
+                                * work_rate
+                                * crafter.adjacency_bonus.multiplier(),
+                        );
 
                         if updated_progress >= required {
                             CraftingState::RecipeComplete
@@ -412,3 +439,141 @@ fn clear_empty_storage_slots(mut query: Query<&mut StorageInventory>) {
         storage_inventory.clear_empty_slots();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use hexx::{Direction, Hex};
+
+    use super::*;
+    use crate::{
+        crafting::{
+            recipe::{RecipeConditions, RecipeData, RecipeInput, RecipeOutput},
+            workers::WorkersPresent,
+        },
+        geometry::{DiscreteHeight, Facing},
+        structures::{
+            adjacency::{AdjacencyRule, NeighborCondition},
+            structure_manifest::StructureData,
+            Footprint,
+        },
+        terrain::terrain_manifest::Terrain,
+        water::WaterDepth,
+    };
+
+    /// The time it takes to craft the test recipe, without any bonuses.
+    const CRAFT_TIME: Duration = Duration::from_secs(100);
+
+    /// Spawns a farm at `hex` that is partway through crafting, returning its entity.
+    fn spawn_farm(app: &mut App, hex: Hex) -> Entity {
+        app.world
+            .spawn((
+                Id::<Structure>::from_name("farm".to_string()),
+                VoxelPos {
+                    hex,
+                    height: DiscreteHeight::ZERO.above(),
+                },
+                ActiveRecipe::new(Id::from_name("grow".to_string())),
+                CraftingState::InProgress {
+                    progress: Duration::ZERO,
+                    required: CRAFT_TIME,
+                },
+                InputInventory::NULL,
+                OutputInventory::NULL,
+                WorkersPresent::new(0),
+                AdjacencyBonus::default(),
+            ))
+            .id()
+    }
+
+    /// The crafting progress made by the `farm` so far.
+    fn progress(app: &App, farm: Entity) -> Duration {
+        match app.world.get::<CraftingState>(farm).unwrap() {
+            CraftingState::InProgress { progress, .. } => *progress,
+            state => panic!("Expected the farm to still be crafting, but it was {state:?}"),
+        }
+    }
+
+    #[test]
+    fn neighbors_speed_up_crafting() {
+        let mut app = App::new();
+
+        let mut structure_manifest = StructureManifest::default();
+        let mut farm_data = StructureData::organism("farm");
+        farm_data.adjacency_rules = vec![AdjacencyRule {
+            neighbor: NeighborCondition::Structure(Id::from_name("hive".to_string())),
+            multiplier: 2.,
+        }];
+        structure_manifest.insert("farm".to_string(), farm_data);
+        structure_manifest.insert("hive".to_string(), StructureData::impassable());
+
+        let mut recipe_manifest = RecipeManifest::default();
+        recipe_manifest.insert(
+            "grow".to_string(),
+            RecipeData {
+                inputs: RecipeInput::EMPTY,
+                outputs: RecipeOutput::Deterministic(Vec::new()),
+                craft_time: CRAFT_TIME,
+                conditions: RecipeConditions::NONE,
+                energy: None,
+            },
+        );
+
+        let mut map_geometry = MapGeometry::new(&mut app.world, 3);
+        let terrain_entities: Vec<Entity> = map_geometry
+            .all_hexes()
+            .map(|&hex| map_geometry.get_terrain(hex).unwrap())
+            .collect();
+        for terrain_entity in terrain_entities {
+            app.world.entity_mut(terrain_entity).insert((
+                Id::<Terrain>::from_name("ground".to_string()),
+                WaterDepth::Dry,
+                ReceivedLight::default(),
+            ));
+        }
+
+        let boosted_hex = Hex::new(-2, 0);
+        let hive_hex = boosted_hex.neighbor(Direction::Top);
+        let hive = app
+            .world
+            .spawn(Id::<Structure>::from_name("hive".to_string()))
+            .id();
+        map_geometry
+            .add_structure(
+                VoxelPos {
+                    hex: hive_hex,
+                    height: DiscreteHeight::ZERO.above(),
+                },
+                Facing::default(),
+                &Footprint::single(),
+                false,
+                false,
+                hive,
+            )
+            .unwrap();
+
+        app.insert_resource(map_geometry)
+            .insert_resource(structure_manifest)
+            .insert_resource(recipe_manifest)
+            .insert_resource(ItemManifest::default())
+            .insert_resource(FixedTime::new(Duration::from_secs(1)))
+            .add_systems((update_adjacency_bonuses, progress_crafting).chain());
+
+        let boosted = spawn_farm(&mut app, boosted_hex);
+        let isolated = spawn_farm(&mut app, Hex::new(2, 0));
+
+        for _ in 0..10 {
+            app.update();
+        }
+
+        assert_eq!(
+            *app.world.get::<AdjacencyBonus>(boosted).unwrap(),
+            AdjacencyBonus(2.)
+        );
+        assert_eq!(
+            *app.world.get::<AdjacencyBonus>(isolated).unwrap(),
+            AdjacencyBonus(1.)
+        );
+        assert_eq!(progress(&app, isolated), Duration::from_secs(10));
+        assert_eq!(progress(&app, boosted), Duration::from_secs(20));
+    }
+}
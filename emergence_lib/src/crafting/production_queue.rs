@@ -0,0 +1,606 @@
+//! Explicit production orders, which take priority over a structure's usual recipe.
+//!
+//! Orders are crafted in the order they appear in the [`ProductionQueue`],
+//! which the player can rearrange at any time.
+//! Once the queue is empty, the structure goes back to crafting the recipe it was using before.
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asset_management::manifest::Id,
+    geometry::VoxelPos,
+    items::{item_manifest::ItemManifest, ItemCount},
+    litter::LitterCommandsExt,
+};
+
+use super::{
+    inventories::{CraftingState, InputInventory, OutputInventory},
+    recipe::{ActiveRecipe, Recipe, RecipeInput, RecipeManifest},
+};
+
+/// The fraction of the inputs of an in-progress craft that is returned when its order is cancelled.
+///
+/// The number of each item refunded is rounded down.
+pub const REFUND_FRACTION: f32 = 0.5;
+
+/// A request to craft a recipe a set number of times.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Reflect, FromReflect)]
+pub struct ProductionOrder {
+    /// The recipe to craft.
+    pub recipe_id: Id<Recipe>,
+    /// The number of crafts still to be completed.
+    pub remaining: u32,
+    /// Has a craft for this order begun?
+    ///
+    /// The inputs for a craft are consumed when it begins, so they are set aside for this order
+    /// until the craft is completed or the order is cancelled.
+    pub in_progress: bool,
+}
+
+/// The orders that a crafting structure will work through, front to back.
+#[derive(
+    Component, Debug, Clone, Default, PartialEq, Serialize, Deserialize, Reflect, FromReflect,
+)]
+#[reflect(Component)]
+pub struct ProductionQueue {
+    /// The orders, with the one currently being worked on at the front.
+    orders: VecDeque<ProductionOrder>,
+    /// The recipe that the structure was crafting before the queue took over.
+    ///
+    /// This is restored once the queue is empty.
+    #[serde(with = "serde_fallback")]
+    fallback: Option<ActiveRecipe>,
+    /// The recipe of a cancelled order whose craft was in progress, and still needs to be refunded.
+    abandoned: Option<Id<Recipe>>,
+}
+
+impl ProductionQueue {
+    /// The most orders that a single queue can hold.
+    pub const MAX_ORDERS: usize = 32;
+
+    /// Adds an order to craft `recipe_id` `count` times to the back of the queue.
+    ///
+    /// Returns `false` if the queue already holds [`ProductionQueue::MAX_ORDERS`] orders,
+    /// in which case the order is dropped.
+    pub fn push(&mut self, recipe_id: Id<Recipe>, count: u32) -> bool {
+        if self.orders.len() >= Self::MAX_ORDERS {
+            return false;
+        }
+
+        if count > 0 {
+            self.orders.push_back(ProductionOrder {
+                recipe_id,
+                remaining: count,
+                in_progress: false,
+            });
+        }
+        true
+    }
+
+    /// The order that is currently being worked on, if any.
+    pub fn current(&self) -> Option<&ProductionOrder> {
+        self.orders.front()
+    }
+
+    /// The orders, from first to last.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = &ProductionOrder> {
+        self.orders.iter()
+    }
+
+    /// Are there no orders left?
+    pub fn is_empty(&self) -> bool {
+        self.orders.is_empty()
+    }
+
+    /// Moves the order at index `from` so that it ends up at index `to`.
+    ///
+    /// Orders whose craft is already in progress cannot be moved away from the front,
+    /// and other orders cannot be moved in front of them: cancel them instead.
+    /// Returns `false` if the order could not be moved.
+    pub fn move_order(&mut self, from: usize, to: usize) -> bool {
+        let len = self.orders.len();
+        if from >= len || to >= len {
+            return false;
+        }
+
+        let front_locked = self.orders[0].in_progress;
+        if front_locked && (from == 0 || to == 0) {
+            return from == to;
+        }
+
+        let order = self.orders.remove(from).unwrap();
+        self.orders.insert(to, order);
+        true
+    }
+
+    /// Removes the order at `index`, if it exists.
+    ///
+    /// If the order had a craft in progress, that craft is abandoned on the next tick,
+    /// and [`REFUND_FRACTION`] of its inputs are dropped as litter next to the structure,
+    /// along with anything else left in its inventories.
+    /// The structure then moves straight on to the next order.
+    pub fn cancel(&mut self, index: usize) -> Option<ProductionOrder> {
+        let order = self.orders.remove(index)?;
+        if order.in_progress {
+            self.abandoned = Some(order.recipe_id);
+        }
+
+        Some(order)
+    }
+
+    /// Records that a craft of the current order has been completed.
+    fn complete_craft(&mut self) {
+        if let Some(order) = self.orders.front_mut() {
+            order.in_progress = false;
+            order.remaining = order.remaining.saturating_sub(1);
+            if order.remaining == 0 {
+                self.orders.pop_front();
+            }
+        }
+    }
+
+    /// The recipe that the structure should be crafting, if it differs from the `active_recipe`.
+    fn desired_recipe(&self, active_recipe: &ActiveRecipe) -> Option<ActiveRecipe> {
+        let desired = match self.current() {
+            Some(order) => ActiveRecipe::new(order.recipe_id),
+            None => self.fallback.clone()?,
+        };
+
+        (desired != *active_recipe).then_some(desired)
+    }
+
+    /// The pretty formatting for this type.
+    pub(crate) fn display(&self, recipe_manifest: &RecipeManifest) -> String {
+        if self.orders.is_empty() {
+            return "Empty".to_string();
+        }
+
+        self.orders
+            .iter()
+            .enumerate()
+            .map(|(i, order)| {
+                let status = if order.in_progress {
+                    " (in progress)"
+                } else {
+                    ""
+                };
+                format!(
+                    "\n{}. {} x{}{status}",
+                    i + 1,
+                    recipe_manifest.name(order.recipe_id),
+                    order.remaining
+                )
+            })
+            .collect()
+    }
+}
+
+/// (De)serializes the fallback recipe of a [`ProductionQueue`].
+///
+/// [`ActiveRecipe`] is itself optional, so a fallback of [`ActiveRecipe::NONE`] would otherwise be
+/// indistinguishable from having no fallback at all once serialized.
+mod serde_fallback {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use crate::crafting::recipe::ActiveRecipe;
+
+    /// Serializes the fallback as a list with zero or one elements.
+    pub(super) fn serialize<S: Serializer>(
+        fallback: &Option<ActiveRecipe>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        fallback.iter().collect::<Vec<_>>().serialize(serializer)
+    }
+
+    /// Deserializes the fallback from a list with zero or one elements.
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<ActiveRecipe>, D::Error> {
+        Ok(Vec::<ActiveRecipe>::deserialize(deserializer)?
+            .into_iter()
+            .next())
+    }
+}
+
+/// The items returned when a craft with the provided `inputs` is cancelled.
+///
+/// The items consumed by flexible recipes are not recorded, so nothing is refunded for them.
+fn refunded_items(inputs: &RecipeInput) -> Vec<ItemCount> {
+    match inputs {
+        RecipeInput::Exact(item_counts) => item_counts
+            .iter()
+            .map(|item_count| {
+                ItemCount::new(
+                    item_count.item_id,
+                    (item_count.count as f32 * REFUND_FRACTION).floor() as u32,
+                )
+            })
+            .filter(|item_count| item_count.count > 0)
+            .collect(),
+        RecipeInput::Flexible { .. } => Vec::new(),
+    }
+}
+
+/// Works through each [`ProductionQueue`], switching recipes as orders begin and end.
+///
+/// Recipes are only switched between crafts, once the structure's inventories are empty,
+/// so that no items are lost.
+/// The exception is a cancelled craft, whose items are dropped as litter so that the next order can begin at once.
+/// An order that cannot be crafted stalls the queue until it is cancelled or moved.
+pub(super) fn advance_production_queues(
+    mut query: Query<(
+        &VoxelPos,
+        &mut ProductionQueue,
+        &mut ActiveRecipe,
+        &mut CraftingState,
+        &mut InputInventory,
+        &mut OutputInventory,
+    )>,
+    recipe_manifest: Res<RecipeManifest>,
+    item_manifest: Res<ItemManifest>,
+    mut commands: Commands,
+) {
+    for (&voxel_pos, mut queue, mut active_recipe, mut crafting_state, mut input, mut output) in
+        query.iter_mut()
+    {
+        if let Some(recipe_id) = queue.abandoned.take() {
+            if matches!(*crafting_state, CraftingState::InProgress { .. }) {
+                let recipe = recipe_manifest.get(recipe_id);
+                let refund = refunded_items(&recipe.inputs);
+                let leftovers = input
+                    .iter()
+                    .chain(output.iter())
+                    .map(|slot| slot.item_count());
+                for item_count in refund.into_iter().chain(leftovers) {
+                    for _ in 0..item_count.count {
+                        commands.spawn_litter(voxel_pos, item_count.item_id);
+                    }
+                }
+
+                // Everything has been dropped, so the structure is free to switch recipes right away
+                *input = recipe.input_inventory(&item_manifest);
+                *output = recipe.output_inventory(&item_manifest);
+                *crafting_state = CraftingState::NeedsInput;
+            }
+        }
+
+        let working_on_current_order = queue
+            .current()
+            .is_some_and(|order| active_recipe.recipe_id() == &Some(order.recipe_id));
+
+        match *crafting_state {
+            CraftingState::InProgress { .. } if working_on_current_order => {
+                if let Some(order) = queue.orders.front_mut() {
+                    order.in_progress = true;
+                }
+            }
+            CraftingState::RecipeComplete if working_on_current_order => {
+                queue.complete_craft();
+            }
+            _ => (),
+        }
+
+        let between_crafts = matches!(
+            *crafting_state,
+            CraftingState::NeedsInput | CraftingState::NoRecipe
+        );
+        let inventories_empty =
+            input.iter().all(|slot| slot.is_empty()) && output.iter().all(|slot| slot.is_empty());
+        if !between_crafts || !inventories_empty {
+            continue;
+        }
+
+        let Some(desired) = queue.desired_recipe(&active_recipe) else {
+            continue;
+        };
+
+        if queue.is_empty() {
+            queue.fallback = None;
+        } else if queue.fallback.is_none() {
+            queue.fallback = Some(active_recipe.clone());
+        }
+
+        match desired.recipe_id() {
+            Some(recipe_id) => {
+                let recipe = recipe_manifest.get(*recipe_id);
+                *input = recipe.input_inventory(&item_manifest);
+                *output = recipe.output_inventory(&item_manifest);
+                *crafting_state = CraftingState::NeedsInput;
+            }
+            None => {
+                *input = InputInventory::NULL;
+                *crafting_state = CraftingState::NoRecipe;
+            }
+        }
+        *active_recipe = desired;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{
+        crafting::recipe::{RecipeConditions, RecipeData, RecipeOutput},
+        geometry::MapGeometry,
+        items::item_manifest::{Item, ItemData},
+        litter::Litter,
+    };
+
+    use super::*;
+
+    /// The [`Id`] of a recipe with the provided `name`.
+    fn recipe(name: &str) -> Id<Recipe> {
+        Id::from_name(name.to_string())
+    }
+
+    /// The only item used in these tests.
+    fn leaf() -> Id<Item> {
+        Id::from_name("leaf".to_string())
+    }
+
+    /// The recipes named in a queue, from first to last.
+    fn recipe_order(queue: &ProductionQueue) -> Vec<Id<Recipe>> {
+        queue.iter().map(|order| order.recipe_id).collect()
+    }
+
+    /// A headless app with a crafting structure, which needs 5 leaves to craft `"mulch"`,
+    /// and nothing to craft `"air"`.
+    fn app() -> (App, Entity) {
+        let mut item_manifest = ItemManifest::new();
+        item_manifest.insert(
+            "leaf".to_string(),
+            ItemData {
+                stack_size: 10,
+                compostable: true,
+                fluid: false,
+                buoyant: false,
+                seed: None,
+            },
+        );
+
+        let mut recipe_manifest = RecipeManifest::new();
+        for (name, inputs) in [
+            ("mulch", RecipeInput::Exact(vec![ItemCount::new(leaf(), 5)])),
+            ("air", RecipeInput::EMPTY),
+        ] {
+            recipe_manifest.insert(
+                name.to_string(),
+                RecipeData {
+                    inputs,
+                    outputs: RecipeOutput::Deterministic(Vec::new()),
+                    craft_time: Duration::from_secs(1),
+                    conditions: RecipeConditions::NONE,
+                    energy: None,
+                },
+            );
+        }
+
+        let mut app = App::new();
+        let map_geometry = MapGeometry::new(&mut app.world, 1);
+        app.insert_resource(map_geometry);
+        let structure = app
+            .world
+            .spawn((
+                VoxelPos::ZERO,
+                ProductionQueue::default(),
+                ActiveRecipe::NONE,
+                CraftingState::NoRecipe,
+                InputInventory::NULL,
+                OutputInventory::NULL,
+            ))
+            .id();
+        app.insert_resource(item_manifest)
+            .insert_resource(recipe_manifest)
+            .add_system(advance_production_queues);
+
+        (app, structure)
+    }
+
+    /// The production queue of the `structure`.
+    fn queue_mut(app: &mut App, structure: Entity) -> Mut<'_, ProductionQueue> {
+        app.world.get_mut::<ProductionQueue>(structure).unwrap()
+    }
+
+    /// The recipe that the `structure` is crafting.
+    fn active_recipe(app: &App, structure: Entity) -> Option<Id<Recipe>> {
+        *app.world
+            .get::<ActiveRecipe>(structure)
+            .unwrap()
+            .recipe_id()
+    }
+
+    /// The number of leaves lying around as litter.
+    fn littered_leaves(app: &mut App) -> u32 {
+        app.world
+            .query::<&Litter>()
+            .iter(&app.world)
+            .map(|litter| litter.contents.item_count(leaf()))
+            .sum()
+    }
+
+    /// Starts crafting the current recipe, then cancels the order at the front of the queue.
+    fn cancel_mid_craft(app: &mut App, structure: Entity) {
+        set_state(
+            app,
+            structure,
+            CraftingState::InProgress {
+                progress: Duration::from_millis(500),
+                required: Duration::from_secs(1),
+            },
+        );
+        app.update();
+
+        let cancelled = queue_mut(app, structure).cancel(0).unwrap();
+        assert!(cancelled.in_progress);
+        app.update();
+    }
+
+    /// Sets the crafting state of the `structure`, as if crafting had progressed.
+    fn set_state(app: &mut App, structure: Entity, crafting_state: CraftingState) {
+        *app.world.get_mut::<CraftingState>(structure).unwrap() = crafting_state;
+    }
+
+    /// Simulates a full craft of the current recipe.
+    fn craft_once(app: &mut App, structure: Entity) {
+        set_state(
+            app,
+            structure,
+            CraftingState::InProgress {
+                progress: Duration::ZERO,
+                required: Duration::from_secs(1),
+            },
+        );
+        app.update();
+        set_state(app, structure, CraftingState::RecipeComplete);
+        app.update();
+        set_state(app, structure, CraftingState::NeedsInput);
+        app.update();
+    }
+
+    #[test]
+    fn orders_are_crafted_in_sequence() {
+        let (mut app, structure) = app();
+        queue_mut(&mut app, structure).push(recipe("air"), 1);
+        queue_mut(&mut app, structure).push(recipe("mulch"), 2);
+        queue_mut(&mut app, structure).push(recipe("air"), 0);
+        app.update();
+
+        let mut crafted = Vec::new();
+        while let Some(recipe_id) = active_recipe(&app, structure) {
+            if queue_mut(&mut app, structure).is_empty() {
+                break;
+            }
+            crafted.push(recipe_id);
+            craft_once(&mut app, structure);
+        }
+
+        assert_eq!(
+            crafted,
+            vec![recipe("air"), recipe("mulch"), recipe("mulch")]
+        );
+        // The structure goes back to what it was doing before
+        assert_eq!(active_recipe(&app, structure), None);
+    }
+
+    #[test]
+    fn orders_can_be_reordered() {
+        let mut queue = ProductionQueue::default();
+        queue.push(recipe("a"), 1);
+        queue.push(recipe("b"), 1);
+        queue.push(recipe("c"), 1);
+
+        assert!(queue.move_order(2, 0));
+        assert_eq!(
+            recipe_order(&queue),
+            vec![recipe("c"), recipe("a"), recipe("b")]
+        );
+        assert!(!queue.move_order(0, 3));
+    }
+
+    #[test]
+    fn full_queues_refuse_new_orders() {
+        let mut queue = ProductionQueue::default();
+        for _ in 0..ProductionQueue::MAX_ORDERS {
+            assert!(queue.push(recipe("a"), 1));
+        }
+
+        assert!(!queue.push(recipe("b"), 1));
+        assert_eq!(queue.iter().len(), ProductionQueue::MAX_ORDERS);
+        assert!(queue.iter().all(|order| order.recipe_id == recipe("a")));
+    }
+
+    #[test]
+    fn in_progress_orders_stay_at_the_front() {
+        let mut queue = ProductionQueue::default();
+        queue.push(recipe("a"), 1);
+        queue.push(recipe("b"), 1);
+        queue.orders[0].in_progress = true;
+
+        assert!(!queue.move_order(1, 0));
+        assert!(!queue.move_order(0, 1));
+        assert_eq!(recipe_order(&queue), vec![recipe("a"), recipe("b")]);
+    }
+
+    #[test]
+    fn stalled_orders_can_still_be_reordered() {
+        let (mut app, structure) = app();
+        // The leaves for this never arrive
+        queue_mut(&mut app, structure).push(recipe("mulch"), 1);
+        queue_mut(&mut app, structure).push(recipe("air"), 1);
+        for _ in 0..3 {
+            app.update();
+        }
+        assert_eq!(active_recipe(&app, structure), Some(recipe("mulch")));
+
+        assert!(queue_mut(&mut app, structure).move_order(1, 0));
+        app.update();
+        assert_eq!(active_recipe(&app, structure), Some(recipe("air")));
+    }
+
+    #[test]
+    fn cancelling_mid_craft_refunds_part_of_the_inputs() {
+        let (mut app, structure) = app();
+        queue_mut(&mut app, structure).push(recipe("mulch"), 3);
+        app.update();
+        cancel_mid_craft(&mut app, structure);
+
+        // Half of the 5 leaves, rounded down
+        assert_eq!(littered_leaves(&mut app), 2);
+        let input = app.world.get::<InputInventory>(structure).unwrap();
+        assert_eq!(input.inventory().item_count(leaf()), 0);
+    }
+
+    #[test]
+    fn cancelling_mid_craft_starts_the_next_order() {
+        let (mut app, structure) = app();
+        queue_mut(&mut app, structure).push(recipe("mulch"), 3);
+        queue_mut(&mut app, structure).push(recipe("air"), 1);
+        app.update();
+        assert_eq!(active_recipe(&app, structure), Some(recipe("mulch")));
+
+        // The leftover leaves must not block the switch
+        app.world
+            .resource_scope(|world, item_manifest: Mut<ItemManifest>| {
+                let mut input = world.get_mut::<InputInventory>(structure).unwrap();
+                input.fill_with_items(&ItemCount::new(leaf(), 3), &item_manifest)
+            })
+            .unwrap();
+        cancel_mid_craft(&mut app, structure);
+
+        assert_eq!(active_recipe(&app, structure), Some(recipe("air")));
+        assert_eq!(
+            *app.world.get::<CraftingState>(structure).unwrap(),
+            CraftingState::NeedsInput
+        );
+        assert_eq!(littered_leaves(&mut app), 5);
+    }
+
+    #[test]
+    fn queue_survives_serialization() {
+        let (mut app, structure) = app();
+        queue_mut(&mut app, structure).push(recipe("mulch"), 3);
+        queue_mut(&mut app, structure).push(recipe("air"), 1);
+        app.update();
+        set_state(
+            &mut app,
+            structure,
+            CraftingState::InProgress {
+                progress: Duration::ZERO,
+                required: Duration::from_secs(1),
+            },
+        );
+        app.update();
+
+        let queue = app.world.get::<ProductionQueue>(structure).unwrap();
+        let json = serde_json::to_string(queue).unwrap();
+        let loaded: ProductionQueue = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(&loaded, queue);
+        assert!(loaded.current().unwrap().in_progress);
+        assert_eq!(loaded.fallback, Some(ActiveRecipe::NONE));
+    }
+}
@@ -400,7 +400,10 @@ impl IsRawManifest for RawRecipeManifest {
 }
 
 /// The recipe that is currently being crafted, if any.
-#[derive(Component, Debug, Default, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[derive(
+    Component, Debug, Default, PartialEq, Eq, Clone, Serialize, Deserialize, Reflect, FromReflect,
+)]
+#[reflect(Component)]
 pub struct ActiveRecipe(pub(super) Option<Id<Recipe>>);
 
 /// The raw version of [`ActiveRecipe`].
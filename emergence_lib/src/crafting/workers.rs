@@ -5,7 +5,7 @@ use bevy::{prelude::*, utils::HashSet};
 use std::fmt::Display;
 
 /// The number of workers present / allowed at this structure.
-#[derive(Component, Debug, Clone, PartialEq)]
+#[derive(Component, Debug, Clone, PartialEq, Reflect, FromReflect)]
 pub(crate) struct WorkersPresent {
     /// The list of workers present
     workers: HashSet<Entity>,
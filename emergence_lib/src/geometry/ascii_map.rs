@@ -0,0 +1,763 @@
+//! Renders the world map as plain text, for bug reports and golden-file tests.
+//!
+//! Maps in the same format can also be parsed back into per-tile data with [`parse_ascii_map`].
+
+use bevy::{prelude::*, utils::HashMap};
+use hexx::{shapes::hexagon, Hex};
+use std::fmt::Display;
+
+use crate::{
+    asset_management::manifest::Id,
+    signals::{SignalType, Signals},
+    structures::structure_manifest::{Structure, StructureManifest},
+    terrain::terrain_manifest::{Terrain, TerrainManifest},
+    units::unit_manifest::{Unit, UnitManifest},
+};
+
+use super::{MapGeometry, VoxelKind, VoxelPos};
+
+/// The character used for a tile occupied by a unit without a glyph of its own.
+const UNIT_GLYPH: char = '@';
+/// The character used for a tile occupied by a structure without a glyph of its own.
+const STRUCTURE_GLYPH: char = '#';
+/// The character used for a tile occupied by a ghost structure.
+const GHOST_GLYPH: char = '+';
+/// The character used for a tile occupied by litter.
+const LITTER_GLYPH: char = '%';
+/// The character used for the selected entity.
+const SELECTED_GLYPH: char = '*';
+/// The character used for empty tiles in the occupancy layer.
+const EMPTY_GLYPH: char = '.';
+/// The character used when a tile has no known terrain.
+const UNKNOWN_GLYPH: char = '?';
+
+/// Which data should be used as the base character of each tile in [`render_ascii_map`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AsciiMapLayer {
+    /// One letter per terrain type.
+    Terrain,
+    /// The height of the terrain, capped at 9.
+    Height,
+    /// The strength of the provided signal just above the terrain, bucketed from 0 to 9 relative to the strongest tile.
+    Signal(SignalType),
+    /// Whether or not the tile is occupied. Empty tiles are shown as `.`.
+    Occupancy,
+}
+
+/// Controls the output of [`render_ascii_map`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct AsciiMapOptions {
+    /// The base layer to display.
+    pub layer: AsciiMapLayer,
+    /// Should units, structures and litter be drawn over the base layer?
+    pub show_objects: bool,
+    /// This entity is drawn as `*`, if it has a [`VoxelPos`].
+    pub selected: Option<Entity>,
+}
+
+impl Default for AsciiMapOptions {
+    fn default() -> Self {
+        AsciiMapOptions {
+            layer: AsciiMapLayer::Terrain,
+            show_objects: true,
+            selected: None,
+        }
+    }
+}
+
+/// Assigns a unique character to each terrain type.
+///
+/// Terrain types are processed in alphabetical order, and take the first character of their name that has not already been claimed.
+pub fn terrain_glyphs(terrain_manifest: &TerrainManifest) -> HashMap<Id<Terrain>, char> {
+    let mut variants: Vec<Id<Terrain>> = terrain_manifest.variants().into_iter().collect();
+    variants.sort_by_key(|&id| terrain_manifest.name(id).to_string());
+
+    let mut taken = Vec::new();
+    let mut glyphs = HashMap::default();
+    for id in variants {
+        let name = terrain_manifest.name(id).to_lowercase();
+        let glyph = claim_glyph(&name, 'a'..='z', &mut taken).unwrap_or(UNKNOWN_GLYPH);
+        glyphs.insert(id, glyph);
+    }
+
+    glyphs
+}
+
+/// The characters used for each kind of unit and structure.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ObjectGlyphs {
+    /// The character used for each kind of unit.
+    pub units: HashMap<Id<Unit>, char>,
+    /// The character used for each kind of structure.
+    pub structures: HashMap<Id<Structure>, char>,
+}
+
+impl ObjectGlyphs {
+    /// The character used for a unit of type `unit_id`.
+    fn unit(&self, unit_id: Id<Unit>) -> char {
+        self.units.get(&unit_id).copied().unwrap_or(UNIT_GLYPH)
+    }
+
+    /// The character used for a structure of type `structure_id`.
+    fn structure(&self, structure_id: Id<Structure>) -> char {
+        self.structures
+            .get(&structure_id)
+            .copied()
+            .unwrap_or(STRUCTURE_GLYPH)
+    }
+}
+
+/// Assigns a unique upper-case letter to each kind of unit and structure.
+///
+/// Units are processed before structures, and each group in alphabetical order.
+/// Each kind takes the first letter of its name that has not already been claimed.
+/// Once all 26 letters are taken, the remaining units are drawn as `@` and the remaining structures as `#`.
+pub fn object_glyphs(
+    unit_manifest: &UnitManifest,
+    structure_manifest: &StructureManifest,
+) -> ObjectGlyphs {
+    let mut unit_variants: Vec<Id<Unit>> = unit_manifest.variants().into_iter().collect();
+    unit_variants.sort_by_key(|&id| unit_manifest.name(id).to_string());
+    let mut structure_variants: Vec<Id<Structure>> =
+        structure_manifest.variants().into_iter().collect();
+    structure_variants.sort_by_key(|&id| structure_manifest.name(id).to_string());
+
+    let mut taken = Vec::new();
+    let mut glyphs = ObjectGlyphs::default();
+    for id in unit_variants {
+        let name = unit_manifest.name(id).to_uppercase();
+        if let Some(glyph) = claim_glyph(&name, 'A'..='Z', &mut taken) {
+            glyphs.units.insert(id, glyph);
+        }
+    }
+    for id in structure_variants {
+        let name = structure_manifest.name(id).to_uppercase();
+        if let Some(glyph) = claim_glyph(&name, 'A'..='Z', &mut taken) {
+            glyphs.structures.insert(id, glyph);
+        }
+    }
+
+    glyphs
+}
+
+/// Returns the first character of `name` and then of `fallbacks` that is in `fallbacks` and has not been `taken`.
+///
+/// The returned character is added to `taken`.
+fn claim_glyph(
+    name: &str,
+    fallbacks: std::ops::RangeInclusive<char>,
+    taken: &mut Vec<char>,
+) -> Option<char> {
+    let glyph = name
+        .chars()
+        .chain(fallbacks.clone())
+        .find(|c| fallbacks.contains(c) && !taken.contains(c))?;
+    taken.push(glyph);
+    Some(glyph)
+}
+
+/// Returns a human-readable description of each character that can appear in the output of [`render_ascii_map`].
+///
+/// Entries are sorted by character.
+pub fn ascii_map_legend(
+    terrain_manifest: &TerrainManifest,
+    unit_manifest: &UnitManifest,
+    structure_manifest: &StructureManifest,
+) -> Vec<(char, String)> {
+    let mut legend: Vec<(char, String)> = terrain_glyphs(terrain_manifest)
+        .into_iter()
+        .map(|(id, glyph)| (glyph, terrain_manifest.name(id).to_string()))
+        .collect();
+
+    let object_glyphs = object_glyphs(unit_manifest, structure_manifest);
+    legend.extend(
+        object_glyphs
+            .units
+            .into_iter()
+            .map(|(id, glyph)| (glyph, format!("unit: {}", unit_manifest.name(id)))),
+    );
+    legend.extend(
+        object_glyphs
+            .structures
+            .into_iter()
+            .map(|(id, glyph)| (glyph, format!("structure: {}", structure_manifest.name(id)))),
+    );
+
+    legend.extend([
+        (UNIT_GLYPH, "other unit".to_string()),
+        (STRUCTURE_GLYPH, "other structure".to_string()),
+        (GHOST_GLYPH, "ghost structure".to_string()),
+        (LITTER_GLYPH, "litter".to_string()),
+        (SELECTED_GLYPH, "selected".to_string()),
+        (EMPTY_GLYPH, "empty".to_string()),
+    ]);
+
+    legend.sort();
+    legend
+}
+
+/// Converts a hex into the `(row, column)` of its character in the output of [`render_ascii_map`].
+///
+/// Each column of hexes occupies every second character, and neighboring columns are offset by a single row,
+/// producing the staggered layout of a flat-topped hex grid.
+fn text_position(hex: Hex, radius: u32) -> (usize, usize) {
+    let radius = radius as i32;
+    let row = hex.y - hex.z() + 2 * radius;
+    let column = 2 * (hex.x + radius);
+    (row as usize, column as usize)
+}
+
+/// Renders the map stored in the `world` as text, with one character per tile.
+///
+/// The output is deterministic, and a radius 10 map fits in 41 rows of 41 characters.
+/// Trailing whitespace is trimmed from each row.
+///
+/// # Panics
+///
+/// Panics if there is no [`MapGeometry`] resource.
+pub fn render_ascii_map(world: &mut World, options: &AsciiMapOptions) -> String {
+    let map_geometry = world.resource::<MapGeometry>().clone();
+    let radius = map_geometry.radius;
+
+    let mut tiles: HashMap<Hex, char> = HashMap::default();
+
+    match options.layer {
+        AsciiMapLayer::Terrain => {
+            let glyphs = world
+                .get_resource::<TerrainManifest>()
+                .map(terrain_glyphs)
+                .unwrap_or_default();
+
+            for &hex in map_geometry.all_hexes() {
+                let glyph = map_geometry
+                    .get_terrain(hex)
+                    .ok()
+                    .and_then(|entity| world.get::<Id<Terrain>>(entity))
+                    .and_then(|terrain_id| glyphs.get(terrain_id).copied())
+                    .unwrap_or(UNKNOWN_GLYPH);
+                tiles.insert(hex, glyph);
+            }
+        }
+        AsciiMapLayer::Height => {
+            for &hex in map_geometry.all_hexes() {
+                let height = map_geometry
+                    .get_height(hex)
+                    .map(|h| h.0)
+                    .unwrap_or_default();
+                let glyph = char::from_digit(height.min(9) as u32, 10).unwrap();
+                tiles.insert(hex, glyph);
+            }
+        }
+        AsciiMapLayer::Signal(signal_type) => {
+            let signals = world.resource::<Signals>();
+            let strengths: Vec<(Hex, f32)> = map_geometry
+                .all_hexes()
+                .map(|&hex| {
+                    let height = map_geometry.get_height(hex).unwrap_or_default();
+                    let voxel_pos = VoxelPos {
+                        hex,
+                        height: height.above(),
+                    };
+                    (hex, signals.get(signal_type, voxel_pos).value())
+                })
+                .collect();
+
+            let max_strength = strengths.iter().map(|(_, s)| *s).fold(0., f32::max);
+
+            for (hex, strength) in strengths {
+                let bucket = if max_strength > 0. {
+                    (strength / max_strength * 9.).ceil() as u32
+                } else {
+                    0
+                };
+                tiles.insert(hex, char::from_digit(bucket.min(9), 10).unwrap());
+            }
+        }
+        AsciiMapLayer::Occupancy => {
+            for &hex in map_geometry.all_hexes() {
+                tiles.insert(hex, EMPTY_GLYPH);
+            }
+        }
+    }
+
+    if options.show_objects || options.layer == AsciiMapLayer::Occupancy {
+        let glyphs = match (
+            world.get_resource::<UnitManifest>(),
+            world.get_resource::<StructureManifest>(),
+        ) {
+            (Some(unit_manifest), Some(structure_manifest)) => {
+                object_glyphs(unit_manifest, structure_manifest)
+            }
+            _ => ObjectGlyphs::default(),
+        };
+
+        // Structures take priority over litter and ghosts, no matter which voxel of the column they are in
+        let mut objects: Vec<(VoxelPos, char)> = map_geometry
+            .all_voxels()
+            .filter_map(|(voxel_pos, voxel_object)| {
+                let glyph = match voxel_object.object_kind {
+                    VoxelKind::Terrain => return None,
+                    VoxelKind::Litter { .. } => LITTER_GLYPH,
+                    VoxelKind::GhostStructure => GHOST_GLYPH,
+                    VoxelKind::Structure { .. } => world
+                        .get::<Id<Structure>>(voxel_object.entity)
+                        .map(|&structure_id| glyphs.structure(structure_id))
+                        .unwrap_or(STRUCTURE_GLYPH),
+                };
+                Some((*voxel_pos, glyph))
+            })
+            .collect();
+        objects.sort_by_key(|(voxel_pos, glyph)| (object_priority(*glyph), voxel_pos.height));
+
+        for (voxel_pos, glyph) in objects {
+            tiles.insert(voxel_pos.hex, glyph);
+        }
+
+        // Sorted so that the same unit is drawn when several share a tile, regardless of query order
+        let mut units: Vec<(VoxelPos, char)> = world
+            .query::<(&VoxelPos, &Id<Unit>)>()
+            .iter(world)
+            .map(|(&voxel_pos, &unit_id)| (voxel_pos, glyphs.unit(unit_id)))
+            .collect();
+        units.sort_by_key(|(voxel_pos, glyph)| (voxel_pos.height, *glyph));
+
+        for (voxel_pos, glyph) in units {
+            tiles.insert(voxel_pos.hex, glyph);
+        }
+    }
+
+    if let Some(selected) = options.selected {
+        if let Some(voxel_pos) = world.get::<VoxelPos>(selected) {
+            tiles.insert(voxel_pos.hex, SELECTED_GLYPH);
+        }
+    }
+
+    let n_rows = 4 * radius as usize + 1;
+    let n_columns = 4 * radius as usize + 1;
+    let mut grid = vec![vec![' '; n_columns]; n_rows];
+
+    for (hex, glyph) in tiles {
+        let (row, column) = text_position(hex, radius);
+        grid[row][column] = glyph;
+    }
+
+    let mut output = String::new();
+    for row in grid {
+        let line: String = row.into_iter().collect();
+        output.push_str(line.trim_end());
+        output.push('\n');
+    }
+
+    output
+}
+
+/// An error encountered while reading a map with [`parse_ascii_map`].
+///
+/// Lines and columns are counted from 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AsciiMapParseError {
+    /// The map has the wrong number of rows for its radius.
+    WrongRowCount {
+        /// The number of rows needed for the declared radius.
+        expected: usize,
+        /// The number of rows found.
+        found: usize,
+    },
+    /// A tile used a character that is not in the legend.
+    UnknownSymbol {
+        /// The unrecognized character.
+        symbol: char,
+        /// The line of the character.
+        line: usize,
+        /// The column of the character.
+        column: usize,
+    },
+    /// A character was found where no tile exists, such as between tiles or past the edge of the map.
+    UnexpectedSymbol {
+        /// The misplaced character.
+        symbol: char,
+        /// The line of the character.
+        line: usize,
+        /// The column of the character.
+        column: usize,
+    },
+    /// A tile was left blank.
+    MissingTile {
+        /// The line where the tile should be.
+        line: usize,
+        /// The column where the tile should be.
+        column: usize,
+    },
+}
+
+impl Display for AsciiMapParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AsciiMapParseError::WrongRowCount { expected, found } => {
+                write!(f, "expected {expected} rows, found {found}")
+            }
+            AsciiMapParseError::UnknownSymbol {
+                symbol,
+                line,
+                column,
+            } => write!(
+                f,
+                "unknown symbol '{symbol}' at line {line}, column {column}"
+            ),
+            AsciiMapParseError::UnexpectedSymbol {
+                symbol,
+                line,
+                column,
+            } => write!(
+                f,
+                "'{symbol}' at line {line}, column {column} is not on a tile"
+            ),
+            AsciiMapParseError::MissingTile { line, column } => {
+                write!(f, "missing tile at line {line}, column {column}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AsciiMapParseError {}
+
+/// Reads a map in the format produced by [`render_ascii_map`], translating each tile's character using the `legend`.
+///
+/// Every tile of a map with the provided `radius` must be present.
+/// Trailing whitespace on each row is ignored.
+///
+/// To combine the result with explicitly specified tiles, [`Extend`] the returned map with them:
+/// later entries replace those read from the text.
+pub fn parse_ascii_map<T: Clone>(
+    text: &str,
+    radius: u32,
+    legend: &HashMap<char, T>,
+) -> Result<HashMap<Hex, T>, AsciiMapParseError> {
+    let lines: Vec<&str> = text.lines().collect();
+    let expected_rows = 4 * radius as usize + 1;
+    if lines.len() != expected_rows {
+        return Err(AsciiMapParseError::WrongRowCount {
+            expected: expected_rows,
+            found: lines.len(),
+        });
+    }
+
+    let hexes_by_position: HashMap<(usize, usize), Hex> = hexagon(Hex::ZERO, radius)
+        .map(|hex| (text_position(hex, radius), hex))
+        .collect();
+
+    let mut tiles = HashMap::default();
+
+    for (row, line) in lines.iter().enumerate() {
+        for (column, symbol) in line.trim_end().chars().enumerate() {
+            match hexes_by_position.get(&(row, column)) {
+                Some(&hex) => {
+                    if symbol == ' ' {
+                        continue;
+                    }
+
+                    let value = legend
+                        .get(&symbol)
+                        .ok_or(AsciiMapParseError::UnknownSymbol {
+                            symbol,
+                            line: row + 1,
+                            column: column + 1,
+                        })?;
+                    tiles.insert(hex, value.clone());
+                }
+                None => {
+                    if symbol != ' ' {
+                        return Err(AsciiMapParseError::UnexpectedSymbol {
+                            symbol,
+                            line: row + 1,
+                            column: column + 1,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    let mut missing: Vec<(usize, usize)> = hexes_by_position
+        .iter()
+        .filter(|(_, hex)| !tiles.contains_key(*hex))
+        .map(|(position, _)| *position)
+        .collect();
+    missing.sort();
+
+    if let Some(&(row, column)) = missing.first() {
+        return Err(AsciiMapParseError::MissingTile {
+            line: row + 1,
+            column: column + 1,
+        });
+    }
+
+    Ok(tiles)
+}
+
+/// Objects with a higher priority are drawn over those with a lower priority in the same column.
+fn object_priority(glyph: char) -> u8 {
+    match glyph {
+        GHOST_GLYPH => 0,
+        LITTER_GLYPH => 1,
+        _ => 2,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::{DiscreteHeight, Facing};
+    use crate::structures::{structure_manifest::StructureData, Footprint};
+    use crate::terrain::terrain_manifest::TerrainData;
+    use crate::units::basic_needs::Diet;
+    use crate::units::unit_manifest::UnitData;
+
+    #[test]
+    fn every_terrain_type_has_a_unique_glyph() {
+        let mut terrain_manifest = TerrainManifest::new();
+        terrain_manifest.insert("grassy".to_string(), TerrainData::default());
+        terrain_manifest.insert("rocky".to_string(), TerrainData::default());
+        // Shares a first letter with "rocky"
+        terrain_manifest.insert("rubble".to_string(), TerrainData::default());
+
+        let glyphs = terrain_glyphs(&terrain_manifest);
+        assert_eq!(glyphs.len(), 3);
+
+        let mut unique_glyphs: Vec<char> = glyphs.values().copied().collect();
+        unique_glyphs.sort();
+        unique_glyphs.dedup();
+        assert_eq!(unique_glyphs.len(), 3);
+
+        let mut unit_manifest = UnitManifest::new();
+        unit_manifest.insert(
+            "ant".to_string(),
+            UnitData::simple("ant", Diet::simple("leaf")),
+        );
+        // Shares a first letter with "ant"
+        unit_manifest.insert(
+            "aphid".to_string(),
+            UnitData::simple("aphid", Diet::simple("leaf")),
+        );
+
+        let mut structure_manifest = StructureManifest::new();
+        structure_manifest.insert("acacia".to_string(), StructureData::organism("acacia"));
+        structure_manifest.insert("leuco".to_string(), StructureData::organism("leuco"));
+
+        let object_glyphs = object_glyphs(&unit_manifest, &structure_manifest);
+        assert_eq!(object_glyphs.units.len(), 2);
+        assert_eq!(object_glyphs.structures.len(), 2);
+
+        let legend = ascii_map_legend(&terrain_manifest, &unit_manifest, &structure_manifest);
+        for name in terrain_manifest.names() {
+            assert!(legend.iter().any(|(_, description)| description == name));
+        }
+        for name in unit_manifest.names() {
+            let description = format!("unit: {name}");
+            assert!(legend.iter().any(|(_, d)| *d == description));
+        }
+        for name in structure_manifest.names() {
+            let description = format!("structure: {name}");
+            assert!(legend.iter().any(|(_, d)| *d == description));
+        }
+        for glyph in [
+            UNIT_GLYPH,
+            STRUCTURE_GLYPH,
+            GHOST_GLYPH,
+            LITTER_GLYPH,
+            SELECTED_GLYPH,
+        ] {
+            assert!(legend.iter().any(|(c, _)| *c == glyph));
+        }
+
+        let mut legend_glyphs: Vec<char> = legend.iter().map(|(c, _)| *c).collect();
+        legend_glyphs.dedup();
+        assert_eq!(legend_glyphs.len(), legend.len());
+    }
+
+    #[test]
+    fn organisms_are_drawn_by_kind() {
+        let mut world = World::new();
+        let mut map_geometry = MapGeometry::new(&mut world, 1);
+
+        let mut unit_manifest = UnitManifest::new();
+        unit_manifest.insert(
+            "ant".to_string(),
+            UnitData::simple("ant", Diet::simple("leaf")),
+        );
+        unit_manifest.insert(
+            "beetle".to_string(),
+            UnitData::simple("beetle", Diet::simple("leaf")),
+        );
+        let mut structure_manifest = StructureManifest::new();
+        structure_manifest.insert("acacia".to_string(), StructureData::organism("acacia"));
+
+        let acacia_id: Id<Structure> = Id::from_name("acacia".to_string());
+        let acacia = world.spawn(acacia_id).id();
+        map_geometry
+            .add_structure(
+                VoxelPos {
+                    hex: Hex::ZERO,
+                    height: DiscreteHeight(1),
+                },
+                Facing::default(),
+                &Footprint::default(),
+                true,
+                true,
+                acacia,
+            )
+            .unwrap();
+
+        for (name, hex) in [("ant", Hex::new(1, 0)), ("beetle", Hex::new(-1, 0))] {
+            let unit_id: Id<Unit> = Id::from_name(name.to_string());
+            world.spawn((
+                unit_id,
+                VoxelPos {
+                    hex,
+                    height: DiscreteHeight::ZERO,
+                },
+            ));
+        }
+
+        world.insert_resource(map_geometry);
+        world.insert_resource(unit_manifest);
+        world.insert_resource(structure_manifest);
+
+        let options = AsciiMapOptions {
+            layer: AsciiMapLayer::Occupancy,
+            ..Default::default()
+        };
+
+        // Ants and acacias share a first letter, so the acacia takes the next free letter
+        let expected = "  .\nB   .\n  C\n.   A\n  .\n";
+        assert_eq!(render_ascii_map(&mut world, &options), expected);
+    }
+
+    #[test]
+    fn rows_are_staggered() {
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 1);
+        world.insert_resource(map_geometry);
+
+        let options = AsciiMapOptions {
+            layer: AsciiMapLayer::Occupancy,
+            ..Default::default()
+        };
+
+        let expected = "  .\n.   .\n  .\n.   .\n  .\n";
+        assert_eq!(render_ascii_map(&mut world, &options), expected);
+    }
+
+    #[test]
+    fn radius_ten_map_fits_in_terminal() {
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 10);
+        world.insert_resource(map_geometry);
+
+        let output = render_ascii_map(&mut world, &AsciiMapOptions::default());
+        assert_eq!(output.lines().count(), 41);
+        assert!(output.lines().all(|line| line.len() <= 41));
+        assert_eq!(output.chars().filter(|c| *c == UNKNOWN_GLYPH).count(), 331);
+    }
+
+    #[test]
+    fn rendered_maps_round_trip() {
+        let mut terrain_manifest = TerrainManifest::new();
+        terrain_manifest.insert("grassy".to_string(), TerrainData::default());
+        terrain_manifest.insert("rocky".to_string(), TerrainData::default());
+        terrain_manifest.insert("swampy".to_string(), TerrainData::default());
+        let variants: Vec<Id<Terrain>> = ["grassy", "rocky", "swampy"]
+            .into_iter()
+            .map(|name| Id::from_name(name.to_string()))
+            .collect();
+
+        let radius = 4;
+        let mut world = World::new();
+        let mut map_geometry = MapGeometry::new(&mut world, radius);
+
+        let mut expected_terrain = HashMap::default();
+        let mut expected_heights = HashMap::default();
+        for hex in hexagon(Hex::ZERO, radius) {
+            let terrain_id = variants[(hex.x + 2 * hex.y).rem_euclid(3) as usize];
+            let height = DiscreteHeight(hex.x.unsigned_abs() as u8);
+
+            let entity = map_geometry.get_terrain(hex).unwrap();
+            world.entity_mut(entity).insert(terrain_id);
+            map_geometry.update_height(hex, height);
+
+            expected_terrain.insert(hex, terrain_id);
+            expected_heights.insert(hex, height);
+        }
+
+        let terrain_legend: HashMap<char, Id<Terrain>> = terrain_glyphs(&terrain_manifest)
+            .into_iter()
+            .map(|(id, glyph)| (glyph, id))
+            .collect();
+        world.insert_resource(map_geometry);
+        world.insert_resource(terrain_manifest);
+        let rendered_terrain = render_ascii_map(
+            &mut world,
+            &AsciiMapOptions {
+                show_objects: false,
+                ..Default::default()
+            },
+        );
+        let parsed_terrain = parse_ascii_map(&rendered_terrain, radius, &terrain_legend).unwrap();
+        assert_eq!(parsed_terrain, expected_terrain);
+
+        let height_legend: HashMap<char, DiscreteHeight> = (0..=9)
+            .map(|height| {
+                (
+                    char::from_digit(height as u32, 10).unwrap(),
+                    DiscreteHeight(height),
+                )
+            })
+            .collect();
+        let rendered_heights = render_ascii_map(
+            &mut world,
+            &AsciiMapOptions {
+                layer: AsciiMapLayer::Height,
+                show_objects: false,
+                selected: None,
+            },
+        );
+        let parsed_heights = parse_ascii_map(&rendered_heights, radius, &height_legend).unwrap();
+        assert_eq!(parsed_heights, expected_heights);
+    }
+
+    #[test]
+    fn parse_errors_report_positions() {
+        let legend = HashMap::from_iter([('.', ())]);
+
+        assert_eq!(
+            parse_ascii_map("  .\n.   x\n  .\n.   .\n  .\n", 1, &legend),
+            Err(AsciiMapParseError::UnknownSymbol {
+                symbol: 'x',
+                line: 2,
+                column: 5
+            })
+        );
+
+        assert_eq!(
+            parse_ascii_map("  .\n. . .\n  .\n.   .\n  .\n", 1, &legend),
+            Err(AsciiMapParseError::UnexpectedSymbol {
+                symbol: '.',
+                line: 2,
+                column: 3
+            })
+        );
+
+        assert_eq!(
+            parse_ascii_map("  .\n.\n  .\n.   .\n  .\n", 1, &legend),
+            Err(AsciiMapParseError::MissingTile { line: 2, column: 5 })
+        );
+
+        assert_eq!(
+            parse_ascii_map("  .\n", 1, &legend),
+            Err(AsciiMapParseError::WrongRowCount {
+                expected: 5,
+                found: 1
+            })
+        );
+    }
+}
@@ -8,14 +8,20 @@ use bevy::{
 use hexx::{shapes::hexagon, Hex};
 
 use crate::{
-    items::inventory::InventoryState, structures::Footprint, units::actions::DeliveryMode,
+    asset_management::manifest::Id,
+    items::inventory::InventoryState,
+    simulation::sim_resources::SimResource,
+    structures::Footprint,
+    terrain::terrain_manifest::Terrain,
+    units::actions::DeliveryMode,
+    utils::memory::{MemoryFootprint, MemoryUsage},
 };
 
-use super::{DiscreteHeight, Facing, VoxelKind, VoxelObject, VoxelPos};
+use super::{DiscreteHeight, Facing, TraversalConfig, VoxelKind, VoxelObject, VoxelPos};
 use core::fmt::Display;
 
 /// The overall size and arrangement of the map.
-#[derive(Debug, Resource, Clone)]
+#[derive(Debug, Resource, Clone, Reflect)]
 pub struct MapGeometry {
     /// The number of tiles from the center to the edge of the map.
     ///
@@ -24,21 +30,43 @@ pub struct MapGeometry {
     /// Which [`Terrain`](crate::terrain::terrain_manifest::Terrain) entity is stored at each tile position
     ///
     /// The set of keys is the set of all valid [`Hex`] positions on the map.
+    #[reflect(ignore)]
     terrain_index: HashMap<Hex, Entity>,
+    /// The type of terrain at each tile position.
+    ///
+    /// This is empty until the terrain has been generated,
+    /// after which the set of keys matches that of the `terrain_index`.
+    #[reflect(ignore)]
+    terrain_id_index: HashMap<Hex, Id<Terrain>>,
     /// The terraforming ghost entity at each hex, if any.
+    #[reflect(ignore)]
     terraforming_index: HashMap<Hex, Entity>,
     /// The height of the terrain at each tile position.
     ///
     /// The set of keys is the set of all valid [`Hex`] positions on the map.
+    #[reflect(ignore)]
     height_index: HashMap<Hex, DiscreteHeight>,
     /// Tracks which objects are stored in each voxel.
     ///
     /// The set of keys is the set of all non-empty [`VoxelPos`] positions on the map.
+    #[reflect(ignore)]
     voxel_index: HashMap<VoxelPos, VoxelObject>,
     /// The list of all passable neighbors for each tile position.
     ///
     /// The set of keys is the set of all [`VoxelPos`] that units could be found.
+    #[reflect(ignore)]
     walkable_neighbors: HashMap<VoxelPos, Neighbors>,
+    /// The list of neighbors that units can walk *from* to reach each tile position.
+    ///
+    /// Units can jump down ledges that they cannot climb, so this is not the same as `walkable_neighbors`.
+    /// The set of keys matches that of `walkable_neighbors`.
+    #[reflect(ignore)]
+    walkable_predecessors: HashMap<VoxelPos, Neighbors>,
+    /// The tiles that units can never stand on, regardless of what is built there.
+    #[reflect(ignore)]
+    impassable_hexes: HashSet<Hex>,
+    /// How large a height difference units can cross in a single step.
+    traversal: TraversalConfig,
 }
 
 /// The six neighbors of a voxel position.
@@ -165,10 +193,14 @@ impl MapGeometry {
         let mut map_geometry = MapGeometry {
             radius,
             terrain_index,
+            terrain_id_index: HashMap::default(),
             terraforming_index: HashMap::default(),
             height_index,
             voxel_index,
             walkable_neighbors: HashMap::default(),
+            walkable_predecessors: HashMap::default(),
+            impassable_hexes: HashSet::default(),
+            traversal: TraversalConfig::IN_GAME,
         };
 
         map_geometry.recompute_walkable_neighbors();
@@ -179,6 +211,18 @@ impl MapGeometry {
         map_geometry
     }
 
+    /// How large a height difference units can cross in a single step.
+    #[inline]
+    pub(crate) fn traversal(&self) -> TraversalConfig {
+        self.traversal
+    }
+
+    /// Changes how large a height difference units can cross in a single step, and updates which voxels are connected.
+    pub(crate) fn set_traversal(&mut self, traversal: TraversalConfig) {
+        self.traversal = traversal;
+        self.recompute_walkable_neighbors();
+    }
+
     /// Returns the list of all valid [`Hex`] positions on the map.
     #[inline]
     pub fn all_hexes(&self) -> impl Iterator<Item = &Hex> {
@@ -198,6 +242,18 @@ impl MapGeometry {
         distance <= self.radius as i32
     }
 
+    /// Returns every valid [`Hex`] within `radius` steps of `center`, including `center` itself.
+    ///
+    /// Near the edge of the map, this yields fewer hexes rather than positions off the map.
+    #[inline]
+    pub(crate) fn hexes_in_range(
+        &self,
+        center: Hex,
+        radius: u32,
+    ) -> impl Iterator<Item = Hex> + '_ {
+        hexagon(center, radius).filter(|&hex| self.is_valid(hex))
+    }
+
     /// Gets the voxel object at the provided `voxel_pos`.
     #[inline]
     #[must_use]
@@ -217,7 +273,25 @@ impl MapGeometry {
         footprint
             .normalized(facing, center)
             .iter()
-            .all(|voxel_pos| self.is_valid(voxel_pos.hex))
+            .all(|voxel_pos| self.is_valid(voxel_pos.hex) && self.is_passable(voxel_pos.hex))
+    }
+
+    /// Can units ever stand on the provided `hex`?
+    #[inline]
+    #[must_use]
+    pub(crate) fn is_passable(&self, hex: Hex) -> bool {
+        !self.impassable_hexes.contains(&hex)
+    }
+
+    /// Prevents units from ever standing on any of the provided `hexes`.
+    ///
+    /// Nothing can be built on these tiles either.
+    pub(crate) fn make_impassable(&mut self, hexes: impl IntoIterator<Item = Hex>) {
+        self.impassable_hexes.extend(hexes);
+        self.recompute_walkable_neighbors();
+
+        #[cfg(test)]
+        self.validate();
     }
 
     /// Is there enough space for a structure with the provided `footprint` located at the `center` tile?
@@ -327,8 +401,7 @@ impl MapGeometry {
     #[inline]
     #[must_use]
     pub(crate) fn average_height(&self, voxel_pos: VoxelPos, radius: u32) -> f32 {
-        let hex_iter = hexagon(voxel_pos.hex, radius);
-        let heights = hex_iter.filter(|hex| self.is_valid(*hex)).map(|hex| {
+        let heights = self.hexes_in_range(voxel_pos.hex, radius).map(|hex| {
             let height = self.get_height(hex).unwrap();
             height.into_world_pos()
         });
@@ -395,6 +468,24 @@ impl MapGeometry {
         }
     }
 
+    /// Gets the type of terrain at the provided `hex`.
+    #[inline]
+    pub fn get_terrain_id(&self, hex: Hex) -> Result<Id<Terrain>, IndexError> {
+        match self.terrain_id_index.get(&hex).copied() {
+            Some(terrain_id) => Ok(terrain_id),
+            None => Err(IndexError { hex }),
+        }
+    }
+
+    /// Records that the terrain at the provided `hex` is now of type `terrain_id`.
+    ///
+    /// This must be called whenever the [`Id<Terrain>`] component of a terrain entity is changed.
+    #[inline]
+    pub fn update_terrain_id(&mut self, hex: Hex, terrain_id: Id<Terrain>) {
+        assert!(self.is_valid(hex), "Hex {hex:?} is not on the map");
+        self.terrain_id_index.insert(hex, terrain_id);
+    }
+
     /// Updates the [`DiscreteHeight`] of the terrain at the provided `hex` to `height`.
     #[inline]
     pub fn update_height(&mut self, hex: Hex, height: DiscreteHeight) {
@@ -739,6 +830,40 @@ impl MapGeometry {
         neighbors.in_direction(direction)
     }
 
+    /// The set of tiles from which a basket crab can walk to `voxel_pos`.
+    ///
+    /// Signals spread along these edges, so that following a signal upstream only ever requires steps that units can take.
+    #[inline]
+    pub(crate) fn walkable_predecessors(
+        &self,
+        voxel_pos: VoxelPos,
+    ) -> impl Iterator<Item = VoxelPos> + '_ {
+        let neighbors = self
+            .walkable_predecessors
+            .get(&voxel_pos)
+            .unwrap_or(&Neighbors::NONE);
+
+        NeighborIter {
+            neighbors,
+            index: 0,
+        }
+    }
+
+    /// Returns the walkable predecessor in the provided direction from `voxel_pos`, if any.
+    #[inline]
+    #[must_use]
+    pub(crate) fn walkable_predecessor_in_direction(
+        &self,
+        voxel_pos: VoxelPos,
+        direction: hexx::Direction,
+    ) -> Option<VoxelPos> {
+        let neighbors = self
+            .walkable_predecessors
+            .get(&voxel_pos)
+            .unwrap_or(&Neighbors::NONE);
+        neighbors.in_direction(direction)
+    }
+
     /// Returns an iterator over the set of empty voxels that are walkalbe from `voxel_pos`.
     pub(crate) fn empty_neighbors(
         &self,
@@ -753,7 +878,7 @@ impl MapGeometry {
         let mut walkable_voxels = HashSet::new();
 
         for (voxel_pos, voxel_data) in self.voxel_index.iter() {
-            if voxel_data.object_kind.can_walk_on_roof() {
+            if voxel_data.object_kind.can_walk_on_roof() && self.is_passable(voxel_pos.hex) {
                 let can_walk_through: bool = match self.get_voxel(voxel_pos.above()) {
                     Some(voxel_data) => voxel_data.object_kind.can_walk_through(),
                     None => true,
@@ -768,6 +893,122 @@ impl MapGeometry {
         walkable_voxels
     }
 
+    /// Finds the regions that units can jump down into, but can never leave.
+    ///
+    /// The walkable voxels are split into regions in which every voxel can be reached from every other.
+    /// The largest of these is treated as the main body of the map, and is never reported.
+    /// Each other region that can be entered from outside, but has no way out, is returned as a sorted list of voxels.
+    pub(crate) fn basins(&self) -> Vec<Vec<VoxelPos>> {
+        let mut walkable_voxels: Vec<VoxelPos> = self.walkable_voxels().into_iter().collect();
+        walkable_voxels.sort();
+
+        let mut reversed_edges: HashMap<VoxelPos, Vec<VoxelPos>> = HashMap::new();
+        for &voxel_pos in &walkable_voxels {
+            for neighbor in self.walkable_neighbors(voxel_pos) {
+                reversed_edges.entry(neighbor).or_default().push(voxel_pos);
+            }
+        }
+
+        // Kosaraju's algorithm: order the voxels by when their depth-first search finishes...
+        let mut visited: HashSet<VoxelPos> = HashSet::new();
+        let mut finish_order: Vec<VoxelPos> = Vec::with_capacity(walkable_voxels.len());
+        for &start in &walkable_voxels {
+            if !visited.insert(start) {
+                continue;
+            }
+
+            let mut stack = vec![(start, self.walkable_neighbors(start).collect::<Vec<_>>())];
+            while let Some((voxel_pos, unexplored)) = stack.last_mut() {
+                if let Some(next) = unexplored.pop() {
+                    if visited.insert(next) {
+                        stack.push((next, self.walkable_neighbors(next).collect()));
+                    }
+                } else {
+                    finish_order.push(*voxel_pos);
+                    stack.pop();
+                }
+            }
+        }
+
+        // ...then flood fill along the reversed edges, in reverse finishing order
+        let mut region_of: HashMap<VoxelPos, usize> = HashMap::new();
+        let mut regions: Vec<Vec<VoxelPos>> = Vec::new();
+        for &start in finish_order.iter().rev() {
+            if region_of.contains_key(&start) {
+                continue;
+            }
+
+            let region_index = regions.len();
+            let mut region = Vec::new();
+            let mut stack = vec![start];
+            region_of.insert(start, region_index);
+            while let Some(voxel_pos) = stack.pop() {
+                region.push(voxel_pos);
+                for &predecessor in reversed_edges.get(&voxel_pos).into_iter().flatten() {
+                    if !region_of.contains_key(&predecessor) {
+                        region_of.insert(predecessor, region_index);
+                        stack.push(predecessor);
+                    }
+                }
+            }
+            region.sort();
+            regions.push(region);
+        }
+
+        let mut has_exit = vec![false; regions.len()];
+        let mut has_entrance = vec![false; regions.len()];
+        for &voxel_pos in &walkable_voxels {
+            for neighbor in self.walkable_neighbors(voxel_pos) {
+                let (from, to) = (region_of[&voxel_pos], region_of[&neighbor]);
+                if from != to {
+                    has_exit[from] = true;
+                    has_entrance[to] = true;
+                }
+            }
+        }
+
+        let main_region = regions
+            .iter()
+            .enumerate()
+            .max_by_key(|(index, region)| (region.len(), std::cmp::Reverse(*index)))
+            .map(|(index, _)| index);
+
+        let mut basins: Vec<Vec<VoxelPos>> = regions
+            .into_iter()
+            .enumerate()
+            .filter(|&(index, _)| {
+                Some(index) != main_region && has_entrance[index] && !has_exit[index]
+            })
+            .map(|(_, region)| region)
+            .collect();
+        basins.sort();
+        basins
+    }
+
+    /// The [`basins`](Self::basins) that include any of the provided `hexes` or their neighbors.
+    ///
+    /// Changing the height of a tile can only trap units in the area around it.
+    pub(crate) fn basins_near(&self, hexes: impl IntoIterator<Item = Hex>) -> Vec<Vec<VoxelPos>> {
+        let mut nearby_hexes: HashSet<Hex> = HashSet::new();
+        for hex in hexes {
+            nearby_hexes.insert(hex);
+            nearby_hexes.extend(hex.all_neighbors());
+        }
+
+        if nearby_hexes.is_empty() {
+            return Vec::new();
+        }
+
+        self.basins()
+            .into_iter()
+            .filter(|basin| {
+                basin
+                    .iter()
+                    .any(|voxel_pos| nearby_hexes.contains(&voxel_pos.hex))
+            })
+            .collect()
+    }
+
     /// The set of voxels that units and signals can originate from.
     fn origin_voxels(&self) -> HashSet<VoxelPos> {
         let mut origin_voxels = HashSet::new();
@@ -787,44 +1028,107 @@ impl MapGeometry {
     // PERF: only update the neighborhood of the provided `voxel_pos`
     fn recompute_walkable_neighbors(&mut self) {
         let walkable_voxels = self.walkable_voxels();
+        let origin_voxels = self.origin_voxels();
+        let climb_steps = self.traversal.climb_steps();
+        let drop_steps = self.traversal.drop_steps();
+        // The voxel `steps` above `voxel_pos`, or below it if `steps` is negative
+        let offset = |voxel_pos: VoxelPos, steps: i16| VoxelPos {
+            hex: voxel_pos.hex,
+            height: DiscreteHeight(
+                (voxel_pos.height.0 as i16 + steps).clamp(0, u8::MAX as i16) as u8
+            ),
+        };
         self.walkable_neighbors.clear();
+        self.walkable_predecessors.clear();
 
         // We need to compute paths *from* (but not *to*) any place where signals or units could possibly originate
         // This includes solid structures, in addition to empty or walkable voxels
-        for origin_voxel in &self.origin_voxels() {
+        for origin_voxel in &origin_voxels {
             let mut local_neighbors = Neighbors::NONE;
 
             for (i, &direction) in hexx::Direction::ALL_DIRECTIONS.iter().enumerate() {
-                let neighbor_hex = origin_voxel.hex.neighbor(direction);
                 let neighbor_flat = VoxelPos {
-                    hex: neighbor_hex,
+                    hex: origin_voxel.hex.neighbor(direction),
                     height: origin_voxel.height,
                 };
-                let neighbor_above = neighbor_flat.above();
-                let neighbor_below = neighbor_flat.below();
 
-                // Preferentially walk up, then level, then down
+                // Preferentially walk up, then level, then down, and finally jump down any ledges
                 // So far, this is an arbitrary priority system
-                local_neighbors.maybe_neighbors[i] = if walkable_voxels.contains(&neighbor_above) {
-                    Some(neighbor_above)
-                } else if walkable_voxels.contains(&neighbor_flat) {
-                    Some(neighbor_flat)
-                } else if walkable_voxels.contains(&neighbor_below) {
-                    Some(neighbor_below)
-                } else {
-                    None
-                }
+                local_neighbors.maybe_neighbors[i] = (-(drop_steps as i16)..=climb_steps as i16)
+                    .rev()
+                    .map(|steps| offset(neighbor_flat, steps))
+                    .find(|candidate| walkable_voxels.contains(candidate));
             }
 
             self.walkable_neighbors
                 .insert(*origin_voxel, local_neighbors);
         }
 
+        // Reverse the edges, so that signals can flow along them in the opposite direction to units
+        for origin_voxel in &origin_voxels {
+            let mut local_predecessors = Neighbors::NONE;
+            let origin_is_walkable = walkable_voxels.contains(origin_voxel);
+
+            for (i, &direction) in hexx::Direction::ALL_DIRECTIONS.iter().enumerate() {
+                let neighbor_flat = VoxelPos {
+                    hex: origin_voxel.hex.neighbor(direction),
+                    height: origin_voxel.height,
+                };
+
+                // Mirrors the priority used above: a step up onto the origin is taken from below it
+                let ledges = (climb_steps as i16 + 1)..=drop_steps as i16;
+
+                local_predecessors.maybe_neighbors[i] = (-(climb_steps as i16)
+                    ..=climb_steps as i16)
+                    .rev()
+                    .chain(ledges)
+                    .map(|steps| offset(neighbor_flat, steps))
+                    .find(|candidate| {
+                        if !walkable_voxels.contains(candidate) {
+                            return false;
+                        }
+
+                        if origin_is_walkable {
+                            self.walkable_neighbors(*candidate)
+                                .any(|neighbor| neighbor == *origin_voxel)
+                        } else {
+                            // Structures cannot be walked into, but units can reach them from any adjacent step
+                            candidate.abs_height_diff(*origin_voxel) <= self.traversal.max_climb
+                        }
+                    });
+            }
+
+            self.walkable_predecessors
+                .insert(*origin_voxel, local_predecessors);
+        }
+
         #[cfg(test)]
         self.validate();
     }
 }
 
+impl MemoryFootprint for MapGeometry {
+    fn memory_usage(&self) -> MemoryUsage {
+        MemoryUsage::of_hash_map(&self.terrain_index)
+            + MemoryUsage::of_hash_map(&self.terrain_id_index)
+            + MemoryUsage::of_hash_map(&self.terraforming_index)
+            + MemoryUsage::of_hash_map(&self.height_index)
+            + MemoryUsage::of_hash_map(&self.voxel_index)
+            + MemoryUsage::of_hash_map(&self.walkable_neighbors)
+            + MemoryUsage::of_hash_map(&self.walkable_predecessors)
+            + MemoryUsage::of_hash_set(&self.impassable_hexes)
+    }
+}
+
+impl SimResource for MapGeometry {
+    const NAME: &'static str = "map_geometry";
+    const PERSIST: bool = true;
+
+    fn measure_memory(&self) -> Option<MemoryUsage> {
+        Some(MemoryFootprint::memory_usage(self))
+    }
+}
+
 #[cfg(test)]
 impl MapGeometry {
     /// Runs all of the validation checks on the map.
@@ -838,7 +1142,7 @@ impl MapGeometry {
 
     /// Asserts that all of the heights in the map are between `Height::ZERO` and `Height::MAX`.
     fn validate_heights(&self) {
-        use crate::geometry::Height;
+        use super::Height;
 
         for voxel_pos in self.voxel_index.keys() {
             let height = voxel_pos.height();
@@ -900,6 +1204,25 @@ impl MapGeometry {
                 assert!(walkable_voxels.contains(maybe_neighbor));
             }
         }
+
+        assert_eq!(
+            self.walkable_predecessors.keys().collect::<HashSet<_>>(),
+            walkable_neighbors_keys.iter().collect::<HashSet<_>>(),
+            "Walkable predecessors and walkable neighbors keys have desynced."
+        );
+
+        for (voxel_pos, predecessors) in self.walkable_predecessors.iter() {
+            for predecessor in predecessors.maybe_neighbors.iter().flatten() {
+                assert!(walkable_voxels.contains(predecessor));
+                if walkable_voxels.contains(voxel_pos) {
+                    assert!(
+                        self.walkable_neighbors(*predecessor)
+                            .any(|neighbor| neighbor == *voxel_pos),
+                        "{predecessor} is recorded as a predecessor of {voxel_pos}, but cannot walk there."
+                    );
+                }
+            }
+        }
     }
 
     /// Asserts that the keys in the height index and the terrain index match.
@@ -909,6 +1232,13 @@ impl MapGeometry {
             self.terrain_index.keys().collect::<HashSet<_>>(),
             "Height index keys do not match terrain index keys"
         );
+
+        for hex in self.terrain_id_index.keys() {
+            assert!(
+                self.terrain_index.contains_key(hex),
+                "Terrain type recorded at {hex:?}, which is not on the map"
+            );
+        }
     }
 
     /// Asserts that the entities recorded in the voxel index match the entities recorded in the terrain map.
@@ -932,10 +1262,89 @@ impl MapGeometry {
 
 #[cfg(test)]
 mod tests {
-    use crate::geometry::position::DiscreteHeight;
+    use crate::geometry::position::{DiscreteHeight, Height};
 
     use super::*;
 
+    #[test]
+    fn memory_footprint_scales_with_tile_count() {
+        let mut world = World::new();
+        let small = MapGeometry::new(&mut world, 5);
+        let large = MapGeometry::new(&mut world, 10);
+
+        let tile_ratio = hexagon(Hex::ZERO, 10).len() as f32 / hexagon(Hex::ZERO, 5).len() as f32;
+        let byte_ratio = large.bytes() as f32 / small.bytes() as f32;
+
+        assert!(
+            (byte_ratio - tile_ratio).abs() < 0.01,
+            "Expected a ratio of {tile_ratio}, found {byte_ratio}"
+        );
+        assert!(large.memory_usage().allocated_bytes >= large.bytes());
+    }
+
+    #[test]
+    fn memory_footprint_includes_impassable_hexes() {
+        let mut world = World::new();
+        let mut map_geometry = MapGeometry::new(&mut world, 5);
+        map_geometry.make_impassable(hexagon(Hex::ZERO, 2));
+
+        let mut without_impassable = map_geometry.clone();
+        without_impassable.impassable_hexes = HashSet::default();
+
+        let impassable = MemoryUsage::of_hash_set(&map_geometry.impassable_hexes);
+        assert!(impassable.used_bytes > 0);
+        assert_eq!(
+            map_geometry.bytes(),
+            without_impassable.bytes() + impassable.used_bytes
+        );
+    }
+
+    #[test]
+    fn hex_distance_is_a_metric() {
+        let hexes: Vec<Hex> = hexagon(Hex::ZERO, 3).collect();
+
+        for &a in &hexes {
+            assert_eq!(a.unsigned_distance_to(a), 0);
+            for &b in &hexes {
+                assert_eq!(a.unsigned_distance_to(b), b.unsigned_distance_to(a));
+                for &c in &hexes {
+                    assert!(
+                        a.unsigned_distance_to(c)
+                            <= a.unsigned_distance_to(b) + b.unsigned_distance_to(c)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn hexes_in_range_match_breadth_first_search() {
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 4);
+
+        // The center, an edge and a corner of the map
+        for center in [Hex::ZERO, Hex::new(4, -2), Hex::new(4, 0)] {
+            for radius in 0..=3 {
+                let mut reached = HashSet::from_iter([center]);
+                let mut frontier = vec![center];
+                for _ in 0..radius {
+                    frontier = frontier
+                        .iter()
+                        .flat_map(|&hex| map_geometry.adjacent_hexes(hex))
+                        .flatten()
+                        .filter(|&hex| reached.insert(hex))
+                        .collect();
+                }
+
+                let in_range: HashSet<Hex> = map_geometry.hexes_in_range(center, radius).collect();
+                assert_eq!(in_range, reached, "center {center:?}, radius {radius}");
+                assert!(in_range
+                    .iter()
+                    .all(|hex| hex.unsigned_distance_to(center) <= radius));
+            }
+        }
+    }
+
     #[test]
     fn map_geometry_is_initialized_successfully() {
         let radius = 10;
@@ -1015,6 +1424,34 @@ mod tests {
         map_geometry.validate();
     }
 
+    #[test]
+    fn terrain_ids_can_be_updated() {
+        let mut map_geometry = MapGeometry::new(&mut World::new(), 1);
+        let hex = Hex::new(1, 0);
+        let grassy = Id::from_name("grassy".to_string());
+        let rocky = Id::from_name("rocky".to_string());
+
+        // Terrain types are unknown until they are recorded
+        assert_eq!(map_geometry.get_terrain_id(hex), Err(IndexError { hex }));
+
+        map_geometry.update_terrain_id(hex, grassy);
+        assert_eq!(map_geometry.get_terrain_id(hex), Ok(grassy));
+
+        map_geometry.update_terrain_id(hex, rocky);
+        assert_eq!(map_geometry.get_terrain_id(hex), Ok(rocky));
+        // The terrain entity is unchanged
+        assert!(map_geometry.get_terrain(hex).is_ok());
+
+        map_geometry.validate();
+    }
+
+    #[test]
+    #[should_panic]
+    fn terrain_ids_must_be_on_the_map() {
+        let mut map_geometry = MapGeometry::new(&mut World::new(), 1);
+        map_geometry.update_terrain_id(Hex::new(5, 0), Id::from_name("grassy".to_string()));
+    }
+
     #[test]
     fn walkable_voxels_respond_to_changes_correctly() {
         let mut map_geometry = MapGeometry::new(&mut World::new(), 0);
@@ -1239,6 +1676,154 @@ mod tests {
         );
     }
 
+    #[test]
+    fn units_can_climb_one_step_but_not_cliffs() {
+        let mut world = World::new();
+        let mut map_geometry = MapGeometry::new(&mut world, 1);
+        let slope = Hex::new(1, 0);
+        let cliff = Hex::new(-1, 0);
+
+        map_geometry.update_height(slope, DiscreteHeight(1));
+        map_geometry.update_height(cliff, DiscreteHeight(3));
+
+        // Units stand on top of the terrain
+        let standing_on = |hex: Hex| VoxelPos {
+            hex,
+            height: map_geometry.get_height(hex).unwrap().above(),
+        };
+        let can_step = |from: Hex, to: Hex| {
+            map_geometry
+                .walkable_neighbors(standing_on(from))
+                .any(|neighbor| neighbor == standing_on(to))
+        };
+
+        // A one-step slope can be walked up and down
+        assert!(can_step(Hex::ZERO, slope));
+        assert!(can_step(slope, Hex::ZERO));
+
+        // A three-step cliff cannot be climbed or jumped down
+        assert!(!can_step(Hex::ZERO, cliff));
+        assert!(!can_step(cliff, Hex::ZERO));
+    }
+
+    #[test]
+    fn ledges_can_be_jumped_down_but_not_climbed() {
+        let mut world = World::new();
+        let mut map_geometry = MapGeometry::new(&mut world, 1);
+        let ledge = Hex::new(1, 0);
+
+        map_geometry.update_height(ledge, DiscreteHeight(2));
+
+        let below = VoxelPos {
+            hex: Hex::ZERO,
+            height: DiscreteHeight(1),
+        };
+        let above = VoxelPos {
+            hex: ledge,
+            height: DiscreteHeight(3),
+        };
+
+        assert!(map_geometry.walkable_neighbors(above).any(|n| n == below));
+        assert!(!map_geometry.walkable_neighbors(below).any(|n| n == above));
+
+        // The reversed edges only lead from the top of the ledge to the bottom
+        assert!(map_geometry
+            .walkable_predecessors(below)
+            .any(|n| n == above));
+        assert!(!map_geometry
+            .walkable_predecessors(above)
+            .any(|n| n == below));
+    }
+
+    #[test]
+    fn basins_are_detected() {
+        let mut world = World::new();
+        let mut map_geometry = MapGeometry::new(&mut world, 2);
+
+        // A pit in the center of the map, surrounded by ledges
+        let hexes: Vec<Hex> = map_geometry.all_hexes().copied().collect();
+        for &hex in &hexes {
+            if hex != Hex::ZERO {
+                map_geometry.update_height(hex, DiscreteHeight(2));
+            }
+        }
+
+        let pit = VoxelPos {
+            hex: Hex::ZERO,
+            height: DiscreteHeight(1),
+        };
+        assert_eq!(map_geometry.basins(), vec![vec![pit]]);
+
+        // Adding a slope provides a way out
+        map_geometry.update_height(Hex::new(1, 0), DiscreteHeight(1));
+        assert!(map_geometry.basins().is_empty());
+    }
+
+    #[test]
+    fn traversal_thresholds_control_which_ledges_can_be_crossed() {
+        let mut world = World::new();
+        let mut map_geometry = MapGeometry::new(&mut world, 1);
+        map_geometry.update_height(Hex::new(1, 0), DiscreteHeight(4));
+
+        let low = VoxelPos {
+            hex: Hex::ZERO,
+            height: DiscreteHeight(1),
+        };
+        let high = VoxelPos {
+            hex: Hex::new(1, 0),
+            height: DiscreteHeight(5),
+        };
+
+        // A ledge 4 tall is too far to drop with the default settings
+        assert!(!map_geometry.walkable_neighbors(high).any(|n| n == low));
+
+        map_geometry.set_traversal(TraversalConfig {
+            max_climb: Height::ONE,
+            max_drop: Height(4.),
+        });
+        assert!(map_geometry.walkable_neighbors(high).any(|n| n == low));
+        assert!(!map_geometry.walkable_neighbors(low).any(|n| n == high));
+        assert!(map_geometry.walkable_predecessors(low).any(|n| n == high));
+
+        map_geometry.set_traversal(TraversalConfig {
+            max_climb: Height(4.),
+            max_drop: Height(4.),
+        });
+        assert!(map_geometry.walkable_neighbors(low).any(|n| n == high));
+    }
+
+    #[test]
+    fn lowering_a_tile_can_create_a_basin() {
+        let mut world = World::new();
+        let mut map_geometry = MapGeometry::new(&mut world, 2);
+        let hexes: Vec<Hex> = map_geometry.all_hexes().copied().collect();
+        for &hex in &hexes {
+            map_geometry.update_height(hex, DiscreteHeight(2));
+        }
+        assert!(map_geometry.basins_near([Hex::ZERO]).is_empty());
+
+        // Digging out the center leaves a pit that units can drop into, but not climb out of
+        map_geometry.update_height(Hex::ZERO, DiscreteHeight(0));
+        assert_eq!(map_geometry.basins_near([Hex::ZERO]).len(), 1);
+        assert_eq!(map_geometry.basins_near([Hex::new(1, 0)]).len(), 1);
+        assert!(map_geometry.basins_near([Hex::new(2, 0)]).is_empty());
+
+        // Units that can climb further are not trapped
+        map_geometry.set_traversal(TraversalConfig {
+            max_climb: Height(2.),
+            max_drop: Height(2.),
+        });
+        assert!(map_geometry.basins_near([Hex::ZERO]).is_empty());
+    }
+
+    #[test]
+    fn flat_maps_have_no_basins() {
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 3);
+
+        assert!(map_geometry.basins().is_empty());
+    }
+
     // TODO: add tests for litter
 
     #[test]
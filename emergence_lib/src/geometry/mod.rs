@@ -1,5 +1,11 @@
 //! Manages the game world's grid and data tied to that grid
 
+mod ascii_map;
+pub use ascii_map::{
+    ascii_map_legend, object_glyphs, parse_ascii_map, render_ascii_map, terrain_glyphs,
+    AsciiMapLayer, AsciiMapOptions, AsciiMapParseError, ObjectGlyphs,
+};
+
 mod indexing;
 use hexx::HexLayout;
 pub use indexing::MapGeometry;
@@ -7,8 +13,11 @@ pub use indexing::MapGeometry;
 mod meshes;
 pub(crate) use meshes::hexagonal_column;
 
+mod pathfinding;
+pub use pathfinding::find_path;
+
 mod position;
-pub use position::{DiscreteHeight, Height, Volume, VoxelPos};
+pub use position::{DiscreteHeight, Height, TraversalConfig, Volume, VoxelPos};
 
 mod rotation;
 pub(crate) use rotation::{sync_rotation_to_facing, Facing, RotationDirection};
@@ -0,0 +1,396 @@
+//! Finds the quickest route between two voxels, using A* search over the walkable neighbors in the [`MapGeometry`].
+//!
+//! Units mostly navigate by following signals, which is cheap and emergent but can be fooled by local maxima.
+//! This is used when an exact route is needed.
+
+use std::{cmp::Ordering, cmp::Reverse, collections::BinaryHeap};
+
+use bevy::utils::{HashMap, HashSet};
+
+use crate::terrain::terrain_manifest::TerrainManifest;
+
+use super::{MapGeometry, VoxelPos};
+
+/// Finds the quickest path that a unit could walk from `start` to `goal`.
+///
+/// The returned path does not include `start`, but does include `goal`,
+/// so a unit that is already at its goal receives an empty path.
+/// Returns `None` if the goal cannot be reached, including when it is on an impassable tile.
+///
+/// Each step takes time inversely proportional to the walking speed of the terrain that it starts on,
+/// just like when units move forward.
+/// Tiles whose terrain type is unknown are walked across at normal speed.
+pub fn find_path(
+    start: VoxelPos,
+    goal: VoxelPos,
+    map_geometry: &MapGeometry,
+    terrain_manifest: &TerrainManifest,
+) -> Option<Vec<VoxelPos>> {
+    if start == goal {
+        return Some(Vec::new());
+    }
+
+    // Avoid searching the whole map for goals that can obviously never be reached
+    if !map_geometry.is_passable(goal.hex)
+        || map_geometry.walkable_predecessors(goal).next().is_none()
+    {
+        return None;
+    }
+
+    let step_cost = |voxel_pos: VoxelPos| -> f32 {
+        map_geometry
+            .get_terrain_id(voxel_pos.hex)
+            .ok()
+            .and_then(|terrain_id| terrain_manifest.data_map().get(&terrain_id))
+            .map_or(1., |terrain_data| 1. / terrain_data.walking_speed)
+    };
+
+    // The heuristic must never overestimate the remaining cost, so assume that every remaining step is on the fastest terrain
+    let fastest_walking_speed = terrain_manifest
+        .data_map()
+        .values()
+        .map(|terrain_data| terrain_data.walking_speed)
+        .fold(1., f32::max);
+    let heuristic = |voxel_pos: VoxelPos| -> f32 {
+        voxel_pos.hex.unsigned_distance_to(goal.hex) as f32 / fastest_walking_speed
+    };
+
+    let mut frontier = BinaryHeap::new();
+    let mut explored: HashSet<VoxelPos> = HashSet::new();
+    let mut cost_so_far: HashMap<VoxelPos, f32> = HashMap::new();
+    let mut came_from: HashMap<VoxelPos, VoxelPos> = HashMap::new();
+
+    cost_so_far.insert(start, 0.);
+    frontier.push(Reverse(Candidate {
+        estimated_cost: heuristic(start),
+        voxel_pos: start,
+    }));
+
+    while let Some(Reverse(Candidate { voxel_pos, .. })) = frontier.pop() {
+        if voxel_pos == goal {
+            let mut path = vec![goal];
+            let mut current = goal;
+            while let Some(&previous) = came_from.get(&current) {
+                if previous == start {
+                    break;
+                }
+                path.push(previous);
+                current = previous;
+            }
+            path.reverse();
+            return Some(path);
+        }
+
+        // The same voxel may be queued several times, but only the cheapest route to it matters
+        if !explored.insert(voxel_pos) {
+            continue;
+        }
+
+        let cost_after_step = cost_so_far[&voxel_pos] + step_cost(voxel_pos);
+        for neighbor in map_geometry.walkable_neighbors(voxel_pos) {
+            let is_improvement = cost_so_far
+                .get(&neighbor)
+                .is_none_or(|&previous_cost| cost_after_step < previous_cost);
+
+            if is_improvement {
+                cost_so_far.insert(neighbor, cost_after_step);
+                came_from.insert(neighbor, voxel_pos);
+                frontier.push(Reverse(Candidate {
+                    estimated_cost: cost_after_step + heuristic(neighbor),
+                    voxel_pos: neighbor,
+                }));
+            }
+        }
+    }
+
+    None
+}
+
+/// A voxel waiting to be explored by [`find_path`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Candidate {
+    /// The cost to reach this voxel, plus the estimated cost from here to the goal.
+    estimated_cost: f32,
+    /// The voxel to explore.
+    voxel_pos: VoxelPos,
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Ties are broken by position, so that the chosen path is deterministic
+        self.estimated_cost
+            .total_cmp(&other.estimated_cost)
+            .then_with(|| self.voxel_pos.cmp(&other.voxel_pos))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use bevy::prelude::*;
+    use hexx::Hex;
+
+    use super::*;
+    use crate::{
+        asset_management::manifest::Id,
+        geometry::{parse_ascii_map, DiscreteHeight},
+        terrain::terrain_manifest::TerrainData,
+    };
+
+    /// The contents of a tile in a test map.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum Tile {
+        /// An open tile.
+        Open,
+        /// An impassable tile.
+        Wall,
+        /// The tile that the path starts on.
+        Start,
+        /// The tile that the path should end on.
+        Goal,
+    }
+
+    /// A radius 2 map, split by a wall with a single gap at the top.
+    const WALLED_MAP: &str = "    .
+  .   .
+.   #   .
+  .   .
+S   #   G
+  .   .
+.   #   .
+  .   .
+    #";
+
+    /// A radius 2 map, split in two by an unbroken wall.
+    const SEALED_MAP: &str = "    #
+  .   .
+.   #   .
+  .   .
+S   #   G
+  .   .
+.   #   .
+  .   .
+    #";
+
+    /// A hand-built test map.
+    struct TestMap {
+        /// The map itself.
+        map_geometry: MapGeometry,
+        /// The voxel that units stand in on the start tile.
+        start: VoxelPos,
+        /// The voxel that units stand in on the goal tile.
+        goal: VoxelPos,
+    }
+
+    impl TestMap {
+        /// Builds a flat radius 2 map from `text`, where `#` is impassable and `S` and `G` mark the start and goal.
+        fn parse(text: &str) -> Self {
+            let legend = HashMap::from_iter([
+                ('.', Tile::Open),
+                ('#', Tile::Wall),
+                ('S', Tile::Start),
+                ('G', Tile::Goal),
+            ]);
+            let tiles = parse_ascii_map(text, 2, &legend).unwrap();
+
+            let mut map_geometry = MapGeometry::new(&mut World::new(), 2);
+            let walls = tiles
+                .iter()
+                .filter(|(_, &tile)| tile == Tile::Wall)
+                .map(|(&hex, _)| hex);
+            map_geometry.make_impassable(walls.collect::<Vec<_>>());
+
+            let find = |target: Tile| {
+                let hex = tiles
+                    .iter()
+                    .find(|(_, &tile)| tile == target)
+                    .map(|(&hex, _)| hex)
+                    .unwrap();
+                standing_on(hex)
+            };
+
+            TestMap {
+                start: find(Tile::Start),
+                goal: find(Tile::Goal),
+                map_geometry,
+            }
+        }
+
+        /// Finds the path from the start to the goal, with no terrain information.
+        fn find_path(&self) -> Option<Vec<VoxelPos>> {
+            find_path(
+                self.start,
+                self.goal,
+                &self.map_geometry,
+                &TerrainManifest::new(),
+            )
+        }
+    }
+
+    /// The voxel that units stand in on the flat tile at `hex`.
+    fn standing_on(hex: Hex) -> VoxelPos {
+        VoxelPos {
+            hex,
+            height: DiscreteHeight::ONE,
+        }
+    }
+
+    /// Counts the steps on the shortest path between `start` and `goal`, using a breadth-first search.
+    fn shortest_distance(
+        start: VoxelPos,
+        goal: VoxelPos,
+        map_geometry: &MapGeometry,
+    ) -> Option<usize> {
+        let mut distances = HashMap::from_iter([(start, 0)]);
+        let mut queue = VecDeque::from([start]);
+
+        while let Some(voxel_pos) = queue.pop_front() {
+            let distance = distances[&voxel_pos];
+            if voxel_pos == goal {
+                return Some(distance);
+            }
+
+            for neighbor in map_geometry.walkable_neighbors(voxel_pos) {
+                if !distances.contains_key(&neighbor) {
+                    distances.insert(neighbor, distance + 1);
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Asserts that each step of the `path` from `start` is to a walkable neighbor.
+    fn assert_walkable(start: VoxelPos, path: &[VoxelPos], map_geometry: &MapGeometry) {
+        let mut previous = start;
+        for &voxel_pos in path {
+            assert!(
+                map_geometry
+                    .walkable_neighbors(previous)
+                    .any(|neighbor| neighbor == voxel_pos),
+                "Cannot walk from {previous} to {voxel_pos}"
+            );
+            previous = voxel_pos;
+        }
+    }
+
+    #[test]
+    fn paths_across_open_ground_are_straight() {
+        let map_geometry = MapGeometry::new(&mut World::new(), 3);
+        let start = standing_on(Hex::new(-3, 0));
+        let goal = standing_on(Hex::new(3, 0));
+
+        let path = find_path(start, goal, &map_geometry, &TerrainManifest::new()).unwrap();
+
+        assert_eq!(path.len(), 6);
+        assert_eq!(path.last(), Some(&goal));
+        assert_walkable(start, &path, &map_geometry);
+    }
+
+    #[test]
+    fn paths_go_around_walls() {
+        let test_map = TestMap::parse(WALLED_MAP);
+
+        let path = test_map.find_path().unwrap();
+
+        assert_walkable(test_map.start, &path, &test_map.map_geometry);
+        assert!(path.contains(&standing_on(Hex::new(0, -2))));
+        assert_eq!(
+            Some(path.len()),
+            shortest_distance(test_map.start, test_map.goal, &test_map.map_geometry)
+        );
+    }
+
+    #[test]
+    fn unreachable_goals_have_no_path() {
+        let sealed = TestMap::parse(SEALED_MAP);
+        assert_eq!(sealed.find_path(), None);
+
+        let walled = TestMap::parse(WALLED_MAP);
+        let wall = standing_on(Hex::ZERO);
+        assert_eq!(
+            find_path(
+                walled.start,
+                wall,
+                &walled.map_geometry,
+                &TerrainManifest::new()
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn already_being_at_the_goal_is_an_empty_path() {
+        let test_map = TestMap::parse(WALLED_MAP);
+
+        assert_eq!(
+            find_path(
+                test_map.start,
+                test_map.start,
+                &test_map.map_geometry,
+                &TerrainManifest::new()
+            ),
+            Some(Vec::new())
+        );
+    }
+
+    #[test]
+    fn paths_never_cross_impassable_tiles() {
+        let test_map = TestMap::parse(WALLED_MAP);
+        let map_geometry = &test_map.map_geometry;
+        let open_tiles: Vec<VoxelPos> = map_geometry
+            .all_hexes()
+            .filter(|&&hex| map_geometry.is_passable(hex))
+            .map(|&hex| standing_on(hex))
+            .collect();
+
+        for &start in &open_tiles {
+            for &goal in &open_tiles {
+                let path = find_path(start, goal, map_geometry, &TerrainManifest::new()).unwrap();
+
+                assert_walkable(start, &path, map_geometry);
+                assert!(path
+                    .iter()
+                    .all(|voxel_pos| map_geometry.is_passable(voxel_pos.hex)));
+                assert_eq!(
+                    Some(path.len()),
+                    shortest_distance(start, goal, map_geometry)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn slow_terrain_is_avoided() {
+        let mut map_geometry = MapGeometry::new(&mut World::new(), 1);
+        let mud = Id::from_name("mud".to_string());
+        let mut terrain_manifest = TerrainManifest::new();
+        terrain_manifest.insert(
+            "mud".to_string(),
+            TerrainData {
+                walking_speed: 0.25,
+                ..default()
+            },
+        );
+        map_geometry.update_terrain_id(Hex::ZERO, mud);
+
+        let start = standing_on(Hex::new(-1, 0));
+        let goal = standing_on(Hex::new(1, 0));
+        let path = find_path(start, goal, &map_geometry, &terrain_manifest).unwrap();
+
+        // Walking around the mud takes an extra step, but is much quicker
+        assert_eq!(path.len(), 3);
+        assert!(!path.contains(&standing_on(Hex::ZERO)));
+        assert_walkable(start, &path, &map_geometry);
+    }
+}
@@ -6,16 +6,65 @@ use derive_more::{Add, AddAssign, Sub, SubAssign};
 use hexx::{shapes::hexagon, Direction, Hex};
 use serde::{Deserialize, Serialize};
 use std::{
+    cmp::Ordering,
     fmt::Formatter,
     ops::{Add, AddAssign, Div, Mul, Sub, SubAssign},
 };
 
 use super::{Facing, MAP_LAYOUT};
 
+/// How large a height difference units can cross in a single step.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Reflect, FromReflect)]
+#[serde(deny_unknown_fields)]
+pub struct TraversalConfig {
+    /// The largest height difference that units can climb up, or walk down, in a single step.
+    pub max_climb: Height,
+    /// The largest height difference that units can jump down in a single step.
+    ///
+    /// Drops that are larger than `max_climb` but no larger than this can only be traversed downwards.
+    pub max_drop: Height,
+}
+
+impl TraversalConfig {
+    /// The default thresholds for in-game movement.
+    pub const IN_GAME: Self = Self {
+        max_climb: Height::MAX_STEP,
+        max_drop: Height(2.),
+    };
+
+    /// Are these thresholds usable?
+    ///
+    /// Units must be able to climb at least one step, and can always jump down anything they can climb.
+    pub fn is_valid(&self) -> bool {
+        self.max_climb.0.is_finite()
+            && self.max_drop.0.is_finite()
+            && self.max_climb >= Height::ONE
+            && self.max_drop >= self.max_climb
+    }
+
+    /// The largest number of whole steps that units can climb.
+    pub(crate) fn climb_steps(&self) -> u8 {
+        DiscreteHeight::from(self.max_climb).0
+    }
+
+    /// The largest number of whole steps that units can jump down.
+    pub(crate) fn drop_steps(&self) -> u8 {
+        DiscreteHeight::from(self.max_drop.max(self.max_climb)).0
+    }
+}
+
+impl Default for TraversalConfig {
+    fn default() -> Self {
+        Self::IN_GAME
+    }
+}
+
 /// The discretized height of this tile
 ///
 /// The minimum height is 0.
-#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Default, Serialize, Deserialize)]
+#[derive(
+    Clone, Copy, Debug, PartialEq, PartialOrd, Default, Serialize, Deserialize, Reflect, FromReflect,
+)]
 pub struct Height(pub f32);
 
 impl Display for Height {
@@ -34,7 +83,7 @@ impl Height {
     /// The maximum allowable height
     pub(crate) const MAX: Height = Height(255.);
 
-    /// The maximum height difference that units can traverse in a single step.
+    /// The maximum height difference that units can traverse in a single step, unless the [`TraversalConfig`] says otherwise.
     pub(crate) const MAX_STEP: Height = Height::ONE;
 
     /// The thickness of all terrain topper models in world coordinates.
@@ -191,6 +240,7 @@ impl Div<f32> for Height {
     PartialOrd,
     Ord,
     Reflect,
+    FromReflect,
 )]
 pub struct DiscreteHeight(pub u8);
 
@@ -260,7 +310,21 @@ impl Display for DiscreteHeight {
 }
 
 /// A voxel position in the game world.
-#[derive(Component, Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize, Default)]
+#[derive(
+    Component,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    Default,
+    Reflect,
+    FromReflect,
+)]
+#[reflect_value(Component, PartialEq, Hash, Serialize, Deserialize, Default)]
 pub struct VoxelPos {
     /// The discretized x and z coordinates of the voxel
     pub hex: Hex,
@@ -268,6 +332,21 @@ pub struct VoxelPos {
     pub height: DiscreteHeight,
 }
 
+/// Voxels are ordered by their hex coordinates, then by height.
+///
+/// This order has no spatial meaning, but allows collections of voxels to be processed deterministically.
+impl Ord for VoxelPos {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.hex.x, self.hex.y, self.height).cmp(&(other.hex.x, other.hex.y, other.height))
+    }
+}
+
+impl PartialOrd for VoxelPos {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl Add for VoxelPos {
     type Output = VoxelPos;
 
@@ -470,6 +549,7 @@ impl VoxelPos {
     Sub,
     AddAssign,
     SubAssign,
+    FromReflect,
 )]
 pub struct Volume(pub f32);
 
@@ -11,7 +11,8 @@ use super::MAP_LAYOUT;
 /// The hex direction that this entity is facing.
 ///
 /// Stored as a component on each entity with a grid-aligned rotation.
-#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Deref, DerefMut)]
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Deref, DerefMut, Reflect, FromReflect)]
+#[reflect_value(PartialEq)]
 pub(crate) struct Facing {
     /// The desired direction.
     ///
@@ -81,7 +82,7 @@ impl Display for Facing {
 }
 
 /// The direction of a [`Facing`] rotation
-#[derive(Clone, Copy, PartialEq, Eq, Debug, Display)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Display, Reflect, FromReflect)]
 pub(crate) enum RotationDirection {
     /// Counterclockwise
     Left,
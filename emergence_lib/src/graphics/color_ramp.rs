@@ -0,0 +1,272 @@
+//! Maps values between 0 and 1 to colors, for visualizing scalar fields.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A continuous range of colors, defined by a sorted list of color stops.
+///
+/// Colors between stops are interpolated in the Oklab color space,
+/// so that perceived lightness changes smoothly along the ramp.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "Vec<(f32, Color)>", into = "Vec<(f32, Color)>")]
+pub(crate) struct ColorRamp {
+    /// The position of each stop along the ramp, and its color.
+    ///
+    /// Positions are between 0 and 1, sorted from lowest to highest.
+    stops: Vec<(f32, Color)>,
+}
+
+/// A [`ColorRamp`] must have at least one color stop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct EmptyColorRampError;
+
+impl std::fmt::Display for EmptyColorRampError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "a color ramp must have at least one color stop")
+    }
+}
+
+impl std::error::Error for EmptyColorRampError {}
+
+impl TryFrom<Vec<(f32, Color)>> for ColorRamp {
+    type Error = EmptyColorRampError;
+
+    fn try_from(stops: Vec<(f32, Color)>) -> Result<Self, Self::Error> {
+        ColorRamp::new(stops)
+    }
+}
+
+impl From<ColorRamp> for Vec<(f32, Color)> {
+    fn from(ramp: ColorRamp) -> Self {
+        ramp.stops
+    }
+}
+
+impl ColorRamp {
+    /// Creates a new ramp from `(position, color)` stops.
+    ///
+    /// Positions are clamped to [0, 1] and sorted, so stops can be provided in any order.
+    pub(crate) fn new(
+        stops: impl IntoIterator<Item = (f32, Color)>,
+    ) -> Result<Self, EmptyColorRampError> {
+        let mut stops: Vec<(f32, Color)> = stops
+            .into_iter()
+            .map(|(position, color)| (position.clamp(0., 1.), color))
+            .collect();
+
+        if stops.is_empty() {
+            return Err(EmptyColorRampError);
+        }
+
+        stops.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        Ok(ColorRamp { stops })
+    }
+
+    /// A ramp that runs evenly from `low` to `high`.
+    pub(crate) fn linear(low: Color, high: Color) -> Self {
+        ColorRamp {
+            stops: vec![(0., low), (1., high)],
+        }
+    }
+
+    /// A ramp that runs from `low` through `neutral` at the midpoint to `high`.
+    ///
+    /// Use this for quantities that can be either positive or negative.
+    pub(crate) fn diverging(low: Color, neutral: Color, high: Color) -> Self {
+        ColorRamp {
+            stops: vec![(0., low), (0.5, neutral), (1., high)],
+        }
+    }
+
+    /// An approximation of the viridis ramp, which runs from dark purple to yellow.
+    ///
+    /// Lightness increases steadily along the ramp, so it remains readable for colorblind players.
+    pub(crate) fn viridis(alpha: f32) -> Self {
+        let stops = [
+            (0.00, [0.267, 0.005, 0.329]),
+            (0.25, [0.231, 0.322, 0.545]),
+            (0.50, [0.129, 0.569, 0.549]),
+            (0.75, [0.369, 0.788, 0.384]),
+            (1.00, [0.992, 0.906, 0.145]),
+        ];
+
+        ColorRamp {
+            stops: stops
+                .into_iter()
+                .map(|(position, [r, g, b])| (position, Color::rgba(r, g, b, alpha)))
+                .collect(),
+        }
+    }
+
+    /// The same ramp, running in the opposite direction.
+    pub(crate) fn reversed(&self) -> Self {
+        ColorRamp {
+            stops: self
+                .stops
+                .iter()
+                .rev()
+                .map(|&(position, color)| (1. - position, color))
+                .collect(),
+        }
+    }
+
+    /// Returns the color at position `t` along the ramp.
+    ///
+    /// Values outside of [0, 1] are clamped, and NaN is treated as 0.
+    pub(crate) fn sample(&self, t: f32) -> Color {
+        let t = if t.is_nan() { 0. } else { t.clamp(0., 1.) };
+
+        // The first stop at or after `t`
+        let upper = self.stops.partition_point(|&(position, _)| position < t);
+        if upper == 0 {
+            return self.stops[0].1;
+        }
+        if upper == self.stops.len() {
+            return self.stops[upper - 1].1;
+        }
+
+        let (low_position, low_color) = self.stops[upper - 1];
+        let (high_position, high_color) = self.stops[upper];
+        let fraction = (t - low_position) / (high_position - low_position);
+
+        mix_oklab(low_color, high_color, fraction)
+    }
+
+    /// Samples `n` evenly spaced colors from the start to the end of the ramp.
+    pub(crate) fn colors(&self, n: usize) -> Vec<Color> {
+        match n {
+            0 => Vec::new(),
+            1 => vec![self.sample(0.)],
+            _ => (0..n)
+                .map(|i| self.sample(i as f32 / (n - 1) as f32))
+                .collect(),
+        }
+    }
+}
+
+/// Interpolates between `a` and `b` in the Oklab color space.
+///
+/// Alpha is interpolated linearly.
+fn mix_oklab(a: Color, b: Color, t: f32) -> Color {
+    let [a_l, a_a, a_b, a_alpha] = to_oklab(a);
+    let [b_l, b_a, b_b, b_alpha] = to_oklab(b);
+    let lerp = |start: f32, end: f32| start + (end - start) * t;
+
+    from_oklab([
+        lerp(a_l, b_l),
+        lerp(a_a, b_a),
+        lerp(a_b, b_b),
+        lerp(a_alpha, b_alpha),
+    ])
+}
+
+/// Converts a color to Oklab coordinates, followed by alpha.
+fn to_oklab(color: Color) -> [f32; 4] {
+    let [r, g, b, alpha] = color.as_linear_rgba_f32();
+
+    let l = (0.412_221_46 * r + 0.536_332_55 * g + 0.051_445_995 * b).cbrt();
+    let m = (0.211_903_5 * r + 0.680_699_5 * g + 0.107_396_96 * b).cbrt();
+    let s = (0.088_302_46 * r + 0.281_718_85 * g + 0.629_978_7 * b).cbrt();
+
+    [
+        0.210_454_26 * l + 0.793_617_8 * m - 0.004_072_047 * s,
+        1.977_998_5 * l - 2.428_592_2 * m + 0.450_593_7 * s,
+        0.025_904_037 * l + 0.782_771_77 * m - 0.808_675_77 * s,
+        alpha,
+    ]
+}
+
+/// Converts Oklab coordinates, followed by alpha, back to a color.
+///
+/// Colors that fall outside of the sRGB gamut are clamped to it.
+fn from_oklab([lightness, a, b, alpha]: [f32; 4]) -> Color {
+    let l = (lightness + 0.396_337_78 * a + 0.215_803_76 * b).powi(3);
+    let m = (lightness - 0.105_561_346 * a - 0.063_854_17 * b).powi(3);
+    let s = (lightness - 0.089_484_18 * a - 1.291_485_5 * b).powi(3);
+
+    Color::rgba_linear(
+        (4.076_741_7 * l - 3.307_711_6 * m + 0.230_969_94 * s).clamp(0., 1.),
+        (-1.268_438 * l + 2.609_757_4 * m - 0.341_319_38 * s).clamp(0., 1.),
+        (-0.004_196_086_3 * l - 0.703_418_6 * m + 1.707_614_7 * s).clamp(0., 1.),
+        alpha,
+    )
+    .as_rgba()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Asserts that two colors are equal, up to rounding error.
+    fn assert_close(actual: Color, expected: Color) {
+        let actual = actual.as_rgba_f32();
+        let expected = expected.as_rgba_f32();
+        for (a, e) in actual.iter().zip(expected.iter()) {
+            assert!((a - e).abs() < 1e-3, "{actual:?} != {expected:?}");
+        }
+    }
+
+    #[test]
+    fn stops_are_returned_exactly() {
+        let ramp = ColorRamp::viridis(1.);
+        for &(position, color) in &ramp.stops {
+            assert_close(ramp.sample(position), color);
+        }
+    }
+
+    #[test]
+    fn colors_between_stops_are_interpolated() {
+        let ramp =
+            ColorRamp::new([(0., Color::BLACK), (0.5, Color::RED), (1., Color::WHITE)]).unwrap();
+
+        // Halfway between two stops is the Oklab midpoint of those stops
+        assert_close(ramp.sample(0.25), mix_oklab(Color::BLACK, Color::RED, 0.5));
+        assert_close(ramp.sample(0.75), mix_oklab(Color::RED, Color::WHITE, 0.5));
+
+        // Lightness increases steadily along a black to white ramp
+        let grays = ColorRamp::linear(Color::BLACK, Color::WHITE).colors(8);
+        for pair in grays.windows(2) {
+            assert!(to_oklab(pair[0])[0] < to_oklab(pair[1])[0]);
+        }
+    }
+
+    #[test]
+    fn alpha_is_interpolated_linearly() {
+        let ramp = ColorRamp::linear(Color::rgba(1., 1., 1., 0.), Color::rgba(1., 1., 1., 1.));
+        assert!((ramp.sample(0.3).a() - 0.3).abs() < 1e-5);
+    }
+
+    #[test]
+    fn values_outside_the_ramp_are_clamped() {
+        let ramp = ColorRamp::linear(Color::BLUE, Color::RED);
+
+        assert_close(ramp.sample(-1.), Color::BLUE);
+        assert_close(ramp.sample(f32::NEG_INFINITY), Color::BLUE);
+        assert_close(ramp.sample(f32::NAN), Color::BLUE);
+        assert_close(ramp.sample(2.), Color::RED);
+        assert_close(ramp.sample(f32::INFINITY), Color::RED);
+    }
+
+    #[test]
+    fn stops_are_sorted_and_validated() {
+        let ramp = ColorRamp::new([(1., Color::RED), (0., Color::BLUE)]).unwrap();
+        assert_eq!(ramp, ColorRamp::linear(Color::BLUE, Color::RED));
+        assert_eq!(ramp.reversed(), ColorRamp::linear(Color::RED, Color::BLUE));
+
+        assert_eq!(ColorRamp::new([]), Err(EmptyColorRampError));
+        assert!(serde_json::from_str::<ColorRamp>("[]").is_err());
+    }
+
+    #[test]
+    fn ramps_round_trip_through_json() {
+        let ramp = ColorRamp::viridis(0.7);
+        let json = serde_json::to_string(&ramp).unwrap();
+        assert_eq!(serde_json::from_str::<ColorRamp>(&json).unwrap(), ramp);
+    }
+
+    #[test]
+    fn single_stop_ramps_are_constant() {
+        let ramp = ColorRamp::new([(0.3, Color::GREEN)]).unwrap();
+        assert_eq!(ramp.colors(3), vec![Color::GREEN; 3]);
+    }
+}
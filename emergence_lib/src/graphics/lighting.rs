@@ -70,11 +70,13 @@ impl CelestialBody {
 }
 
 /// This component signals that this Entity is the primary celestial body for lighting.
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Reflect, FromReflect, Default)]
+#[reflect(Component)]
 pub(crate) struct Sun;
 
 /// This component signals that this Entity is the secondary celestial body for lighting.
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Reflect, FromReflect, Default)]
+#[reflect(Component)]
 pub(crate) struct Moon;
 
 /// Spawns a directional light source to illuminate the scene
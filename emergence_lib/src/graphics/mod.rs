@@ -5,11 +5,17 @@ use bevy::prelude::*;
 use crate::{asset_management::AssetState, world_gen::WorldGenState};
 
 use self::{
-    atmosphere::AtmospherePlugin, lighting::LightingPlugin, litter::render_litter_piles,
-    overlay::OverlayPlugin, structures::remove_ghostly_shadows, water::WaterRenderingPlugin,
+    atmosphere::AtmospherePlugin,
+    lighting::LightingPlugin,
+    litter::render_litter_piles,
+    overlay::OverlayPlugin,
+    structures::remove_ghostly_shadows,
+    units::{spread_crowded_units, SubTileOffsets},
+    water::WaterRenderingPlugin,
 };
 
 mod atmosphere;
+pub(crate) mod color_ramp;
 pub(crate) mod lighting;
 mod litter;
 pub(crate) mod overlay;
@@ -33,7 +39,8 @@ impl Plugin for GraphicsPlugin {
             .add_plugin(AtmospherePlugin)
             .add_plugin(WaterRenderingPlugin)
             .add_plugin(OverlayPlugin)
-            .add_system(render_litter_piles.in_set(GraphicsSet))
+            .init_resource::<SubTileOffsets>()
+            .add_systems((render_litter_piles, spread_crowded_units).in_set(GraphicsSet))
             // Run these after Update to avoid panics due to despawned entities
             .add_systems(
                 (inherit_materials, remove_ghostly_shadows).in_base_set(CoreSet::PostUpdate),
@@ -2,10 +2,12 @@
 //!
 //! UI elements generated for / by this work belong in the `ui` module instead.
 
+use std::fmt::Display;
+
 use crate::{
     self as emergence_lib,
     geometry::Volume,
-    graphics::palette::infovis::{NEUTRAL_INFOVIS_COLOR, OVERLAY_ALPHA},
+    graphics::palette::infovis::{water_flux_ramp, water_table_ramp, OVERLAY_ALPHA},
     light::{shade::ReceivedLight, Illuminance},
     water::FlowVelocity,
 };
@@ -15,29 +17,33 @@ use bevy::{
     utils::HashMap,
 };
 use emergence_macros::IterableEnum;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     asset_management::manifest::Id,
     enum_iter::IterableEnum,
     geometry::{Height, MapGeometry, VoxelPos},
-    graphics::palette::infovis::{WATER_TABLE_COLOR_HIGH, WATER_TABLE_COLOR_LOW},
     player_interaction::{selection::ObjectInteraction, InteractionSystem},
     signals::{SignalKind, SignalStrength, SignalType, Signals},
+    simulation::sim_resources::{SimResource, SimResourceAppExt},
     terrain::{terrain_assets::TerrainHandles, terrain_manifest::Terrain},
     water::{PreviousWaterVolume, WaterDepth, WaterVolume},
 };
 
-use super::GraphicsSet;
+use super::{color_ramp::ColorRamp, GraphicsSet};
 
 /// Systems and reources for communicating the state of the world to the player.
 pub(super) struct OverlayPlugin;
 
 impl Plugin for OverlayPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<TileOverlay>()
+        app.init_resource::<OverlayColors>()
+            .init_resource::<TileOverlay>()
+            .register_sim_resource::<TileOverlay>()
             .add_systems(
                 (
-                    set_overlay_material,
+                    bake_overlay_colors,
+                    set_overlay_material.after(bake_overlay_colors),
                     set_overlay_height,
                     display_player_selection
                         .after(InteractionSystem::SelectTiles)
@@ -58,25 +64,359 @@ impl Plugin for OverlayPlugin {
 pub(crate) struct TileOverlay {
     /// The type of signal that is currently being visualized.
     pub(crate) overlay_type: OverlayType,
-    /// The materials used to visualize the signal strength.
+    /// The materials and legends used by each scalar overlay, baked from the [`OverlayColors`].
     ///
     /// Note that we cannot simply store a `Vec<Color>` here,
     /// because we need to be able to display the entire gradients of signal strength simultaneously.
-    signal_color_ramps: HashMap<SignalKind, Vec<Handle<StandardMaterial>>>,
-    /// The materials used to visualize the distance to the water table.
-    water_table_color_ramp: Vec<Handle<StandardMaterial>>,
+    scalar_ramps: HashMap<ScalarOverlay, BakedRamp>,
+    /// The recent range of values for each scalar overlay that uses [`Normalization::Dynamic`].
+    dynamic_ranges: HashMap<ScalarOverlay, DynamicRange>,
     /// The materials used to visualize light levels
     light_level_color_ramp: HashMap<Illuminance, Handle<StandardMaterial>>,
-    /// The materials used to visualize the net change in water volume.
-    flux_color_ramp: Vec<Handle<StandardMaterial>>,
     /// The materials used to visualize vector fields.
     vector_field_materials: HashMap<DiscretizedVector, Handle<StandardMaterial>>,
-    /// The images to be used to display the gradient in order to create a legend.
-    signal_legends: HashMap<SignalKind, Handle<Image>>,
-    /// The image used to display the gradient for the water table.
-    water_table_legend: Handle<Image>,
-    /// The image used to display the gradient for the net change in water volume.
-    flux_legend: Handle<Image>,
+    /// The range of values shown by the current overlay, if it visualizes a single quantity.
+    pub(crate) value_range: Option<OverlayRange>,
+}
+
+/// The smallest and largest values currently displayed by an overlay.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) struct OverlayRange {
+    /// The smallest displayed value.
+    pub(crate) min: f32,
+    /// The largest displayed value.
+    pub(crate) max: f32,
+}
+
+impl OverlayRange {
+    /// Computes the range of the sampled `values`.
+    ///
+    /// Non-finite values are ignored. Returns `None` if there are no finite values.
+    pub(crate) fn from_values(values: impl IntoIterator<Item = f32>) -> Option<Self> {
+        let mut range: Option<OverlayRange> = None;
+
+        for value in values.into_iter().filter(|value| value.is_finite()) {
+            range = Some(match range {
+                None => OverlayRange {
+                    min: value,
+                    max: value,
+                },
+                Some(OverlayRange { min, max }) => OverlayRange {
+                    min: min.min(value),
+                    max: max.max(value),
+                },
+            });
+        }
+
+        range
+    }
+}
+
+impl Display for OverlayRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.3} to {:.3}", self.min, self.max)
+    }
+}
+
+/// The range of values used by [`Normalization::Dynamic`], which follows the values shown without flickering.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub(crate) struct DynamicRange {
+    /// The current range, if any values have been seen yet.
+    range: Option<OverlayRange>,
+}
+
+impl DynamicRange {
+    /// The current range, if any values have been seen yet.
+    pub(crate) fn range(&self) -> Option<OverlayRange> {
+        self.range
+    }
+
+    /// Updates the range to follow the `observed` range of values.
+    ///
+    /// The range grows immediately to cover every observed value,
+    /// but only shrinks to fit them once more than `hysteresis` of its width is unused.
+    pub(crate) fn update(&mut self, observed: Option<OverlayRange>, hysteresis: f32) {
+        let Some(observed) = observed else { return };
+        let Some(current) = self.range else {
+            self.range = Some(observed);
+            return;
+        };
+
+        let grown = OverlayRange {
+            min: current.min.min(observed.min),
+            max: current.max.max(observed.max),
+        };
+        let width = grown.max - grown.min;
+        let unused = width - (observed.max - observed.min);
+
+        self.range = Some(if unused > hysteresis * width {
+            observed
+        } else {
+            grown
+        });
+    }
+}
+
+/// How the values shown by a scalar overlay are mapped onto its [`ColorRamp`].
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub(crate) enum Normalization {
+    /// Values from `min` to `max` span the whole ramp.
+    Fixed {
+        /// The value shown at the start of the ramp.
+        min: f32,
+        /// The value shown at the end of the ramp.
+        max: f32,
+    },
+    /// Values from 0 to `max` span the whole ramp on a logarithmic scale, so that small values are still visible.
+    Logarithmic {
+        /// The value shown at the end of the ramp.
+        max: f32,
+    },
+    /// The ramp spans the values currently shown, tracked by a [`DynamicRange`].
+    Dynamic {
+        /// The fraction of the range that must be unused before it shrinks.
+        hysteresis: f32,
+    },
+}
+
+impl Normalization {
+    /// Maps `value` to a position between 0 and 1 along the color ramp.
+    ///
+    /// The `dynamic_range` is only used by [`Normalization::Dynamic`].
+    pub(crate) fn normalize(&self, value: f32, dynamic_range: Option<OverlayRange>) -> f32 {
+        let normalized = match *self {
+            Normalization::Fixed { min, max } => (value - min) / (max - min),
+            Normalization::Logarithmic { max } => value.max(0.).ln_1p() / max.ln_1p(),
+            Normalization::Dynamic { .. } => match dynamic_range {
+                Some(OverlayRange { min, max }) if max > min => (value - min) / (max - min),
+                // Every value is the same, so there is nothing to distinguish
+                _ => 0.5,
+            },
+        };
+
+        normalized.clamp(0., 1.)
+    }
+}
+
+/// An overlay that shows a single quantity for each tile using a [`ColorRamp`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub(crate) enum ScalarOverlay {
+    /// The strength of one kind of signal.
+    Signal(SignalKind),
+    /// The distance to the water table.
+    DepthToWaterTable,
+    /// The height of the water table.
+    HeightOfWaterTable,
+    /// The net increase or decrease in water volume.
+    NetWater,
+}
+
+impl ScalarOverlay {
+    /// Every scalar overlay.
+    pub(crate) fn all() -> impl Iterator<Item = ScalarOverlay> {
+        SignalKind::variants().map(ScalarOverlay::Signal).chain([
+            ScalarOverlay::DepthToWaterTable,
+            ScalarOverlay::HeightOfWaterTable,
+            ScalarOverlay::NetWater,
+        ])
+    }
+
+    /// The name used to configure this overlay in the [`OverlayColors`].
+    pub(crate) fn name(&self) -> String {
+        match self {
+            ScalarOverlay::Signal(signal_kind) => {
+                format!("signal_{}", format!("{signal_kind:?}").to_lowercase())
+            }
+            ScalarOverlay::DepthToWaterTable => "depth_to_water_table".to_string(),
+            ScalarOverlay::HeightOfWaterTable => "height_of_water_table".to_string(),
+            ScalarOverlay::NetWater => "net_water".to_string(),
+        }
+    }
+}
+
+/// The color ramp and normalization used by a single scalar overlay.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub(crate) struct ScalarOverlayConfig {
+    /// The name of the ramp in [`OverlayColors::ramps`].
+    pub(crate) ramp: String,
+    /// How values are mapped onto the ramp.
+    pub(crate) normalization: Normalization,
+}
+
+/// The color ramps available to overlays, and which ramp each scalar overlay uses.
+///
+/// Overlays refer to ramps by name, so replacing a ramp (such as for a colorblind-friendly palette)
+/// changes every overlay that uses it.
+/// The overlay materials are rebuilt whenever this resource changes.
+#[derive(Resource, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub(crate) struct OverlayColors {
+    /// The color ramps, by name.
+    pub(crate) ramps: HashMap<String, ColorRamp>,
+    /// The settings for each scalar overlay, keyed by [`ScalarOverlay::name`].
+    pub(crate) overlays: HashMap<String, ScalarOverlayConfig>,
+}
+
+/// A problem with the [`OverlayColors`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) enum OverlayColorsError {
+    /// No settings were provided for the overlay with this name.
+    MissingOverlay(String),
+    /// An overlay refers to a ramp that does not exist.
+    MissingRamp {
+        /// The name of the overlay.
+        overlay: String,
+        /// The name of the missing ramp.
+        ramp: String,
+    },
+}
+
+impl Display for OverlayColorsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OverlayColorsError::MissingOverlay(overlay) => {
+                write!(f, "no color ramp is configured for the {overlay} overlay")
+            }
+            OverlayColorsError::MissingRamp { overlay, ramp } => {
+                write!(
+                    f,
+                    "the {overlay} overlay uses the {ramp} color ramp, which does not exist"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for OverlayColorsError {}
+
+impl Default for OverlayColors {
+    fn default() -> Self {
+        let mut ramps = HashMap::new();
+        let mut overlays = HashMap::new();
+
+        for signal_kind in SignalKind::variants() {
+            let overlay = ScalarOverlay::Signal(signal_kind);
+            ramps.insert(overlay.name(), signal_kind.color_ramp());
+            overlays.insert(
+                overlay.name(),
+                ScalarOverlayConfig {
+                    ramp: overlay.name(),
+                    normalization: Normalization::Logarithmic {
+                        max: TileOverlay::MAX_SIGNAL_STRENGTH,
+                    },
+                },
+            );
+        }
+
+        ramps.insert("water_table".to_string(), water_table_ramp());
+        // Higher water tables are wetter, so this runs the other way
+        ramps.insert(
+            "water_table_height".to_string(),
+            water_table_ramp().reversed(),
+        );
+        ramps.insert("water_flux".to_string(), water_flux_ramp());
+        ramps.insert("viridis".to_string(), ColorRamp::viridis(OVERLAY_ALPHA));
+
+        overlays.insert(
+            ScalarOverlay::DepthToWaterTable.name(),
+            ScalarOverlayConfig {
+                ramp: "water_table".to_string(),
+                normalization: Normalization::Fixed {
+                    min: 0.,
+                    max: TileOverlay::MAX_DEPTH_TO_WATER_TABLE,
+                },
+            },
+        );
+        overlays.insert(
+            ScalarOverlay::HeightOfWaterTable.name(),
+            ScalarOverlayConfig {
+                ramp: "water_table_height".to_string(),
+                normalization: Normalization::Dynamic { hysteresis: 0.25 },
+            },
+        );
+        overlays.insert(
+            ScalarOverlay::NetWater.name(),
+            ScalarOverlayConfig {
+                ramp: "water_flux".to_string(),
+                normalization: Normalization::Fixed {
+                    min: -TileOverlay::MAX_FLUX.0,
+                    max: TileOverlay::MAX_FLUX.0,
+                },
+            },
+        );
+
+        OverlayColors { ramps, overlays }
+    }
+}
+
+impl OverlayColors {
+    /// The ramp and normalization used by the provided `overlay`.
+    fn get(
+        &self,
+        overlay: ScalarOverlay,
+    ) -> Result<(&ColorRamp, Normalization), OverlayColorsError> {
+        let name = overlay.name();
+        let config = self
+            .overlays
+            .get(&name)
+            .ok_or_else(|| OverlayColorsError::MissingOverlay(name.clone()))?;
+        let ramp = self
+            .ramps
+            .get(&config.ramp)
+            .ok_or_else(|| OverlayColorsError::MissingRamp {
+                overlay: name,
+                ramp: config.ramp.clone(),
+            })?;
+
+        Ok((ramp, config.normalization))
+    }
+
+    /// Checks that every scalar overlay is configured, and uses a ramp that exists.
+    pub(crate) fn validate(&self) -> Result<(), Vec<OverlayColorsError>> {
+        let errors: Vec<OverlayColorsError> = ScalarOverlay::all()
+            .filter_map(|overlay| self.get(overlay).err())
+            .collect();
+
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(errors),
+        }
+    }
+}
+
+/// A [`ColorRamp`] converted into materials for the overlay, along with an image for its legend.
+#[derive(Debug)]
+struct BakedRamp {
+    /// How values are mapped onto the ramp.
+    normalization: Normalization,
+    /// One material for each of [`TileOverlay::N_COLORS`] evenly spaced colors along the ramp.
+    materials: Vec<Handle<StandardMaterial>>,
+    /// The image used to display the ramp in the legend.
+    legend: Handle<Image>,
+}
+
+impl BakedRamp {
+    /// Creates the materials and legend for `ramp`.
+    fn new(
+        ramp: &ColorRamp,
+        normalization: Normalization,
+        material_assets: &mut Assets<StandardMaterial>,
+        image_assets: &mut Assets<Image>,
+    ) -> Self {
+        let colors = ramp.colors(TileOverlay::N_COLORS);
+
+        BakedRamp {
+            normalization,
+            materials: generate_color_ramp(&colors, material_assets),
+            legend: image_assets.add(generate_legend(&colors, TileOverlay::LEGEND_WIDTH)),
+        }
+    }
+
+    /// The material for a `normalized` value between 0 and 1.
+    fn material(&self, normalized: f32) -> Handle<StandardMaterial> {
+        let color_index: usize = (normalized * TileOverlay::N_COLORS as f32) as usize;
+        // Avoid indexing out of bounds by clamping to the last color in the ramp
+        self.materials[color_index.min(TileOverlay::N_COLORS - 1)].clone_weak()
+    }
 }
 
 /// The type of information that is being visualized by the overlay.
@@ -102,10 +442,44 @@ pub(crate) enum OverlayType {
 }
 
 impl OverlayType {
+    /// The overlays that can be switched on and off by name, rather than by picking a signal type.
+    pub(crate) const TOGGLEABLE: [OverlayType; 6] = [
+        OverlayType::StrongestSignal,
+        OverlayType::DepthToWaterTable,
+        OverlayType::HeightOfWaterTable,
+        OverlayType::VelocityOfWaterTable,
+        OverlayType::NetWater,
+        OverlayType::LightLevel,
+    ];
+
     /// Returns true if this overlay type is `None`.
     pub(crate) const fn is_none(&self) -> bool {
         matches!(self, Self::None)
     }
+
+    /// The overlay to show after `overlay` is toggled while this one is shown.
+    ///
+    /// Toggling the overlay that is already shown turns it off, and toggling any other overlay replaces it.
+    pub(crate) fn toggled(self, overlay: OverlayType) -> OverlayType {
+        match self == overlay {
+            true => OverlayType::None,
+            false => overlay,
+        }
+    }
+
+    /// The scalar overlay being shown, if this overlay shows a single quantity with a color ramp.
+    pub(crate) fn scalar(&self) -> Option<ScalarOverlay> {
+        match self {
+            OverlayType::Single(signal_type) => Some(ScalarOverlay::Signal((*signal_type).into())),
+            OverlayType::DepthToWaterTable => Some(ScalarOverlay::DepthToWaterTable),
+            OverlayType::HeightOfWaterTable => Some(ScalarOverlay::HeightOfWaterTable),
+            OverlayType::NetWater => Some(ScalarOverlay::NetWater),
+            OverlayType::None
+            | OverlayType::StrongestSignal
+            | OverlayType::VelocityOfWaterTable
+            | OverlayType::LightLevel => None,
+        }
+    }
 }
 
 impl From<Option<SignalType>> for OverlayType {
@@ -118,53 +492,7 @@ impl From<Option<SignalType>> for OverlayType {
 }
 
 impl FromWorld for TileOverlay {
-    #[allow(clippy::identity_op)]
     fn from_world(world: &mut World) -> Self {
-        let mut color_ramps = HashMap::new();
-        let mut legends = HashMap::new();
-
-        // Signals
-        for kind in SignalKind::variants() {
-            let material_assets: &mut Assets<StandardMaterial> =
-                &mut world.resource_mut::<Assets<StandardMaterial>>();
-
-            let colors =
-                generate_color_gradient(kind.color_low(), kind.color_high(), Self::N_COLORS);
-            let color_ramp = generate_color_ramp(&colors, material_assets);
-            color_ramps.insert(kind, color_ramp);
-
-            let legend_image = generate_legend(&colors, Self::LEGEND_WIDTH);
-            let mut image_assets = world.resource_mut::<Assets<Image>>();
-            let legend_image_handle = image_assets.add(legend_image);
-            legends.insert(kind, legend_image_handle);
-        }
-
-        // Water table
-        let water_table_colors = generate_color_gradient(
-            WATER_TABLE_COLOR_LOW,
-            WATER_TABLE_COLOR_HIGH,
-            Self::N_COLORS,
-        );
-        let material_assets: &mut Assets<StandardMaterial> =
-            &mut world.resource_mut::<Assets<StandardMaterial>>();
-        let water_table_color_ramp = generate_color_ramp(&water_table_colors, material_assets);
-        let water_table_legend_image = generate_legend(&water_table_colors, Self::LEGEND_WIDTH);
-        let mut image_assets = world.resource_mut::<Assets<Image>>();
-        let water_table_legend = image_assets.add(water_table_legend_image);
-
-        // Flux
-        let flux_colors = generate_color_bigradient(
-            WATER_TABLE_COLOR_HIGH,
-            WATER_TABLE_COLOR_LOW,
-            Self::N_COLORS,
-        );
-        let material_assets: &mut Assets<StandardMaterial> =
-            &mut world.resource_mut::<Assets<StandardMaterial>>();
-        let flux_color_ramp = generate_color_ramp(&flux_colors, material_assets);
-        let flux_legend_image = generate_legend(&flux_colors, Self::LEGEND_WIDTH);
-        let mut image_assets = world.resource_mut::<Assets<Image>>();
-        let flux_legend = image_assets.add(flux_legend_image);
-
         let material_assets: &mut Assets<StandardMaterial> =
             &mut world.resource_mut::<Assets<StandardMaterial>>();
 
@@ -182,69 +510,29 @@ impl FromWorld for TileOverlay {
         }
 
         // Vector fields
-        let material_assets: &mut Assets<StandardMaterial> =
-            &mut world.resource_mut::<Assets<StandardMaterial>>();
         let vector_field_materials = generate_vector_field_materials(material_assets);
 
         Self {
             overlay_type: OverlayType::None,
-            signal_color_ramps: color_ramps,
-            water_table_color_ramp,
-            flux_color_ramp,
+            // These are baked from the `OverlayColors` by `bake_overlay_colors`
+            scalar_ramps: HashMap::new(),
+            dynamic_ranges: HashMap::new(),
             light_level_color_ramp,
             vector_field_materials,
-            signal_legends: legends,
-            water_table_legend,
-            flux_legend,
+            value_range: None,
         }
     }
 }
 
-/// Create a linearly interpolated color gradient between the two given colors.
-fn generate_color_gradient(color_low: Color, color_high: Color, n_steps: usize) -> Vec<Color> {
-    let mut colors = Vec::with_capacity(n_steps);
-    for i in 0..n_steps {
-        // Linearly interpolate the colors in the color ramp between SIGNAL_OVERLAY_LOW and SIGNAL_OVERLAY_HIGH
-        // Make sure to use HSLA colorspace to avoid weird artifacts
-        let t = i as f32 / (n_steps - 1) as f32;
-        let Color::Hsla { hue: low_hue, saturation: low_saturation, lightness: low_lightness, alpha: low_alpha } = color_low else {
-         panic!("Expected HSLA color for `color_low`");
-    };
-
-        let Color::Hsla { hue: high_hue, saturation: high_saturation, lightness: high_lightness, alpha: high_alpha } = color_high else {
-        panic!("Expected HSLA color for `color_high`");
-    };
-
-        let hue = low_hue * (1.0 - t) + high_hue * t;
-        let saturation = low_saturation * (1.0 - t) + high_saturation * t;
-        let lightness = low_lightness * (1.0 - t) + high_lightness * t;
-        let alpha = low_alpha * (1.0 - t) + high_alpha * t;
+/// The chosen overlay and its materials are kept, but the ranges of displayed values describe the current map.
+impl SimResource for TileOverlay {
+    const NAME: &'static str = "tile_overlay";
+    const PERSIST: bool = false;
 
-        let color = Color::hsla(hue, saturation, lightness, alpha);
-        colors.push(color);
+    fn reset_to_default(&mut self) {
+        self.dynamic_ranges.clear();
+        self.value_range = None;
     }
-    colors
-}
-
-/// Create a linearly interpolated color gradient between the two given colors, with a neutral white in the middle.
-fn generate_color_bigradient(color_low: Color, color_high: Color, n_steps: usize) -> Vec<Color> {
-    let mut colors = Vec::with_capacity(n_steps);
-    let half_way = n_steps / 2;
-
-    // Generate the first half of the gradient
-    colors.extend(generate_color_gradient(
-        color_low,
-        NEUTRAL_INFOVIS_COLOR,
-        half_way,
-    ));
-
-    // Generate the second half of the gradient
-    colors.extend(generate_color_gradient(
-        NEUTRAL_INFOVIS_COLOR,
-        color_high,
-        n_steps - half_way,
-    ));
-    colors
 }
 
 /// Generates a color ramp of [`StandardMaterial`]s based on the given color gradient.
@@ -352,6 +640,23 @@ impl TileOverlay {
     /// The width of the legend image.
     pub(crate) const LEGEND_WIDTH: u32 = 32;
 
+    /// Gets the material that should be used to show `value` on the provided scalar `overlay`.
+    ///
+    /// If this is `None`, the overlay has no color ramp configured.
+    fn get_scalar_material(
+        &self,
+        overlay: ScalarOverlay,
+        value: f32,
+    ) -> Option<Handle<StandardMaterial>> {
+        let baked_ramp = self.scalar_ramps.get(&overlay)?;
+        let dynamic_range = self
+            .dynamic_ranges
+            .get(&overlay)
+            .and_then(DynamicRange::range);
+
+        Some(baked_ramp.material(baked_ramp.normalization.normalize(value, dynamic_range)))
+    }
+
     /// Gets the material that should be used to visualize the given signal strength, if any.
     ///
     /// If this is `None`, then the signal strength is too weak to be visualized and the tile should be invisible.
@@ -365,21 +670,7 @@ impl TileOverlay {
             return None;
         }
 
-        // At MAX_SIGNAL_STRENGTH, we want to fetch the last color in the ramp.
-        // At 0, we want to fetch the first color in the ramp.
-
-        // The scale is logarithmic, so that small nuances are still pretty visible
-        // By adding 1 to the signal strength, we avoid taking the log of 0
-        // This produces a value in the range [0, 1] for all signal strengths that we care about.
-        let normalized_strength =
-            signal_strength.value().ln_1p() / Self::MAX_SIGNAL_STRENGTH.ln_1p();
-
-        // Now that strength is normalized, we can scale it to the number of colors in the ramp
-        // Which should give us a nice distribution of colors that uses the entire range.
-        let color_index: usize = (normalized_strength * (Self::N_COLORS as f32)) as usize;
-        // Avoid indexing out of bounds by clamping to the maximum value in the case of extremely strong signals
-        let color_index = color_index.min(Self::N_COLORS - 1);
-        Some(self.signal_color_ramps[&signal_kind][color_index].clone_weak())
+        self.get_scalar_material(ScalarOverlay::Signal(signal_kind), signal_strength.value())
     }
 
     /// Gets the material that should be used to visualize the depth to the water table.
@@ -389,31 +680,17 @@ impl TileOverlay {
         &self,
         depth_to_water_table: WaterDepth,
     ) -> Option<Handle<StandardMaterial>> {
-        let normalized_depth = match depth_to_water_table {
-            WaterDepth::Dry => 1.,
+        match depth_to_water_table {
+            // Dry tiles are shown as the deepest possible water table
+            WaterDepth::Dry => self
+                .scalar_ramps
+                .get(&ScalarOverlay::DepthToWaterTable)
+                .map(|baked_ramp| baked_ramp.material(1.)),
             WaterDepth::Underground(depth) => {
-                depth.0.min(Self::MAX_DEPTH_TO_WATER_TABLE) / Self::MAX_DEPTH_TO_WATER_TABLE
+                self.get_scalar_material(ScalarOverlay::DepthToWaterTable, depth.0)
             }
-            WaterDepth::Flooded(..) => return None,
-        };
-
-        let color_index: usize = (normalized_depth * (Self::N_COLORS as f32)) as usize;
-        // Avoid indexing out of bounds by clamping to the maximum value in the case of extremely strong signals
-        let color_index = color_index.min(Self::N_COLORS - 1);
-        Some(self.water_table_color_ramp[color_index].clone_weak())
-    }
-
-    /// Gets the material that should be used to visualize the flow of water with the provided `volume_per_second`.
-    fn get_water_flux_material(&self, volume_per_second: Volume) -> Handle<StandardMaterial> {
-        let normalized_volume = volume_per_second / Self::MAX_FLUX;
-        // How far along the color ramp we should be
-        // Divide by 2 then add 0.5 to shift the range from [-1, 1] to [0, 1]
-        let color_value = normalized_volume / 2. + 0.5;
-        let clamped_color_value = color_value.clamp(0., 1.);
-
-        // Avoid indexing out of bounds by clamping to the maximum value in the case of extreme water volumes
-        let color_index: usize = (clamped_color_value * Self::N_COLORS as f32) as usize;
-        self.flux_color_ramp[color_index.min(Self::N_COLORS - 1)].clone_weak()
+            WaterDepth::Flooded(..) => None,
+        }
     }
 
     /// Gets the material that should be used to visualize the flow of water with the provided `flow_velocity`.
@@ -450,28 +727,64 @@ impl TileOverlay {
             .map(|material| material.clone_weak())
     }
 
-    /// Gets the handle to the image that should be used to display the legend.
-    pub(crate) fn signal_legend_image_handle(&self, signal_kind: SignalKind) -> Handle<Image> {
-        self.signal_legends[&signal_kind].clone_weak()
+    /// Gets the handle to the image that should be used to display the legend for the provided scalar `overlay`.
+    pub(crate) fn legend_image_handle(&self, overlay: ScalarOverlay) -> Handle<Image> {
+        self.scalar_ramps
+            .get(&overlay)
+            .map(|baked_ramp| baked_ramp.legend.clone_weak())
+            .unwrap_or_default()
     }
+}
 
-    /// Gets the handle to the material that should be used to display the legend for the water table.
-    pub(crate) fn water_table_legend_image_handle(&self) -> Handle<Image> {
-        self.water_table_legend.clone_weak()
+/// Rebuilds the materials and legends for each scalar overlay whenever the [`OverlayColors`] change.
+fn bake_overlay_colors(
+    overlay_colors: Res<OverlayColors>,
+    mut tile_overlay: ResMut<TileOverlay>,
+    mut material_assets: ResMut<Assets<StandardMaterial>>,
+    mut image_assets: ResMut<Assets<Image>>,
+) {
+    if !overlay_colors.is_changed() {
+        return;
     }
 
-    /// Gets the handle to the material that should be used to display the legend for the water flux.
-    pub(crate) fn flux_legend_image_handle(&self) -> Handle<Image> {
-        self.flux_legend.clone_weak()
+    // Misconfigured overlays are left blank, rather than crashing the game
+    if let Err(errors) = overlay_colors.validate() {
+        for error in errors {
+            warn!("{error}");
+        }
     }
+
+    tile_overlay.scalar_ramps = ScalarOverlay::all()
+        .filter_map(|overlay| {
+            let (ramp, normalization) = overlay_colors.get(overlay).ok()?;
+            let baked_ramp =
+                BakedRamp::new(ramp, normalization, &mut material_assets, &mut image_assets);
+            Some((overlay, baked_ramp))
+        })
+        .collect();
+    tile_overlay.dynamic_ranges.clear();
+}
+
+/// Is a point with these normalized device coordinates inside the camera's view?
+fn is_within_view(ndc: Vec3) -> bool {
+    ndc.x.abs() <= 1. && ndc.y.abs() <= 1. && (0. ..=1.).contains(&ndc.z)
 }
 
 /// Sets the material for the currently visualized map overlay.
+///
+/// The [`TileOverlay::value_range`] only covers tiles that are on screen.
+/// When there is no camera, every tile counts.
 fn set_overlay_material(
     mut overlay_query: Query<
-        (&VoxelPos, &mut Handle<StandardMaterial>, &mut Visibility),
+        (
+            &VoxelPos,
+            &GlobalTransform,
+            &mut Handle<StandardMaterial>,
+            &mut Visibility,
+        ),
         With<Overlay>,
     >,
+    camera_query: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
     terrain_query: Query<&ReceivedLight, With<Id<Terrain>>>,
     water_depth_query: Query<&WaterDepth>,
     water_volume_query: Query<(&WaterVolume, &PreviousWaterVolume)>,
@@ -479,19 +792,42 @@ fn set_overlay_material(
     flow_velocity_query: Query<&FlowVelocity>,
     signals: Res<Signals>,
     map_geometry: Res<MapGeometry>,
-    tile_overlay: Res<TileOverlay>,
+    mut tile_overlay: ResMut<TileOverlay>,
     fixed_time: Res<FixedTime>,
 ) {
     if tile_overlay.overlay_type == OverlayType::None {
+        // Avoid triggering change detection every frame
+        if tile_overlay.value_range.is_some() {
+            tile_overlay.value_range = None;
+        }
         return;
     }
 
-    for (&voxel_pos, mut overlay_material, mut overlay_visibility) in overlay_query.iter_mut() {
+    let maybe_camera = camera_query.get_single().ok();
+
+    // The values displayed on each tile on screen, for overlays that visualize a single quantity
+    let mut sampled_values = Vec::new();
+
+    for (&voxel_pos, overlay_transform, mut overlay_material, mut overlay_visibility) in
+        overlay_query.iter_mut()
+    {
+        let on_screen = maybe_camera.is_none_or(|(camera, camera_transform)| {
+            camera
+                .world_to_ndc(camera_transform, overlay_transform.translation())
+                .is_some_and(is_within_view)
+        });
+        let mut sample = |value: f32| {
+            if on_screen {
+                sampled_values.push(value);
+            }
+        };
+
         let maybe_material = match tile_overlay.overlay_type {
             OverlayType::None => None,
             OverlayType::Single(signal_type) => {
                 // We must look at the voxel above the terrain to get the signal strength, as those are the voxels that units can walk in
                 let signal_strength = signals.get(signal_type, voxel_pos.above());
+                sample(signal_strength.value());
                 let signal_kind = signal_type.into();
                 tile_overlay.get_signal_material(signal_kind, signal_strength)
             }
@@ -505,6 +841,9 @@ fn set_overlay_material(
             OverlayType::DepthToWaterTable => {
                 let terrain_entity = map_geometry.get_terrain(voxel_pos.hex).unwrap();
                 let water_depth = *water_depth_query.get(terrain_entity).unwrap();
+                if let WaterDepth::Underground(depth) = water_depth {
+                    sample(depth.0);
+                }
 
                 tile_overlay.get_water_table_material(water_depth)
             }
@@ -513,14 +852,10 @@ fn set_overlay_material(
                 let water_depth = *water_depth_query.get(terrain_entity).unwrap();
                 let terrain_height = terrain_pos_query.get(terrain_entity).unwrap().height();
                 let water_table_height = water_depth.water_table_height(terrain_height);
+                sample(water_table_height.0);
 
-                // FIXME: use a dedicated color ramp for this, rather than hacking it
-                // We subtract here to ensure that blue == wet and red == dry
-                let inverted_height = WaterDepth::Underground(
-                    Height(TileOverlay::MAX_DEPTH_TO_WATER_TABLE) - water_table_height,
-                );
-
-                tile_overlay.get_water_table_material(inverted_height)
+                tile_overlay
+                    .get_scalar_material(ScalarOverlay::HeightOfWaterTable, water_table_height.0)
             }
             OverlayType::VelocityOfWaterTable => {
                 let terrain_entity = map_geometry.get_terrain(voxel_pos.hex).unwrap();
@@ -535,8 +870,9 @@ fn set_overlay_material(
 
                 let net_water = *current_water_volume - previous_water_volume.0;
                 let volume_per_second = net_water.volume() / fixed_time.period.as_secs_f32();
+                sample(volume_per_second.0);
 
-                Some(tile_overlay.get_water_flux_material(volume_per_second))
+                tile_overlay.get_scalar_material(ScalarOverlay::NetWater, volume_per_second.0)
             }
             OverlayType::LightLevel => {
                 let terrain_entity = map_geometry.get_terrain(voxel_pos.hex).unwrap();
@@ -556,6 +892,36 @@ fn set_overlay_material(
             }
         };
     }
+
+    let value_range = OverlayRange::from_values(sampled_values);
+
+    // Dynamic ranges follow the values shown, and are used to color the next frame
+    if let Some(overlay) = tile_overlay.overlay_type.scalar() {
+        let normalization = tile_overlay
+            .scalar_ramps
+            .get(&overlay)
+            .map(|baked_ramp| baked_ramp.normalization);
+
+        if let Some(Normalization::Dynamic { hysteresis }) = normalization {
+            let previous = tile_overlay
+                .dynamic_ranges
+                .get(&overlay)
+                .copied()
+                .unwrap_or_default();
+            let mut dynamic_range = previous;
+            dynamic_range.update(value_range, hysteresis);
+
+            // Avoid triggering change detection every frame
+            if dynamic_range != previous {
+                tile_overlay.dynamic_ranges.insert(overlay, dynamic_range);
+            }
+        }
+    }
+
+    // Avoid triggering change detection every frame
+    if tile_overlay.value_range != value_range {
+        tile_overlay.value_range = value_range;
+    }
 }
 
 /// Sets the overlay of the tile based on the player's selection.
@@ -822,3 +1188,155 @@ fn set_overlay_height(
         transform.translation.y = desired_height.into_world_pos();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlay_range_matches_sampled_values() {
+        let values = [0.5, -2., 3.25, 0.];
+        let range = OverlayRange::from_values(values).unwrap();
+
+        assert_eq!(range.min, -2.);
+        assert_eq!(range.max, 3.25);
+        assert_eq!(range.to_string(), "-2.000 to 3.250");
+    }
+
+    #[test]
+    fn toggling_an_overlay_twice_turns_it_off() {
+        for overlay in OverlayType::TOGGLEABLE {
+            let shown = OverlayType::None.toggled(overlay);
+            assert_eq!(shown, overlay);
+            assert_eq!(shown.toggled(overlay), OverlayType::None);
+        }
+
+        assert_eq!(
+            OverlayType::LightLevel.toggled(OverlayType::NetWater),
+            OverlayType::NetWater
+        );
+    }
+
+    #[test]
+    fn only_points_inside_the_view_are_on_screen() {
+        assert!(is_within_view(Vec3::new(0., 0., 0.5)));
+        assert!(is_within_view(Vec3::new(-1., 1., 0.)));
+        assert!(!is_within_view(Vec3::new(1.5, 0., 0.5)));
+        assert!(!is_within_view(Vec3::new(0., -1.1, 0.5)));
+        // Behind the camera
+        assert!(!is_within_view(Vec3::new(0., 0., -0.5)));
+    }
+
+    #[test]
+    fn overlay_range_ignores_non_finite_values() {
+        let range = OverlayRange::from_values([f32::NAN, 1., f32::INFINITY]);
+        assert_eq!(range, Some(OverlayRange { min: 1., max: 1. }));
+
+        assert_eq!(OverlayRange::from_values([f32::NAN]), None);
+        assert_eq!(OverlayRange::from_values([]), None);
+    }
+
+    #[test]
+    fn fixed_and_logarithmic_normalization() {
+        let fixed = Normalization::Fixed { min: -2., max: 2. };
+        assert_eq!(fixed.normalize(-2., None), 0.);
+        assert_eq!(fixed.normalize(0., None), 0.5);
+        assert_eq!(fixed.normalize(2., None), 1.);
+        // Values outside the range are clamped
+        assert_eq!(fixed.normalize(10., None), 1.);
+
+        let logarithmic = Normalization::Logarithmic { max: 1e3 };
+        assert_eq!(logarithmic.normalize(0., None), 0.);
+        assert_eq!(logarithmic.normalize(1e3, None), 1.);
+        // Small values take up more of the ramp
+        assert!(logarithmic.normalize(10., None) > 0.3);
+    }
+
+    #[test]
+    fn dynamic_ranges_follow_the_values_without_flickering() {
+        /// The range of a field whose values run from `min` to `max`.
+        fn field(min: f32, max: f32) -> Option<OverlayRange> {
+            OverlayRange::from_values([min, (min + max) / 2., max])
+        }
+
+        let mut dynamic_range = DynamicRange::default();
+        let hysteresis = 0.25;
+
+        dynamic_range.update(field(0., 10.), hysteresis);
+        assert_eq!(dynamic_range.range(), field(0., 10.));
+
+        // Small fluctuations inside the range leave it alone
+        dynamic_range.update(field(1., 9.), hysteresis);
+        assert_eq!(dynamic_range.range(), field(0., 10.));
+        dynamic_range.update(field(0.5, 10.), hysteresis);
+        assert_eq!(dynamic_range.range(), field(0., 10.));
+
+        // New extremes are included immediately
+        dynamic_range.update(field(0., 12.), hysteresis);
+        assert_eq!(dynamic_range.range(), field(0., 12.));
+
+        // Once the values cover much less of the range, it shrinks to fit them
+        dynamic_range.update(field(4., 8.), hysteresis);
+        assert_eq!(dynamic_range.range(), field(4., 8.));
+
+        // Frames without any values do not reset the range
+        dynamic_range.update(None, hysteresis);
+        assert_eq!(dynamic_range.range(), field(4., 8.));
+
+        let dynamic = Normalization::Dynamic { hysteresis };
+        assert_eq!(dynamic.normalize(6., dynamic_range.range()), 0.5);
+        // A flat field is shown in the middle of the ramp
+        assert_eq!(dynamic.normalize(3., field(3., 3.)), 0.5);
+    }
+
+    #[test]
+    fn every_overlay_uses_an_existing_ramp() {
+        let overlay_colors = OverlayColors::default();
+        assert_eq!(overlay_colors.validate(), Ok(()));
+
+        // Every overlay type that shows a scalar has its own configuration
+        for overlay in ScalarOverlay::all() {
+            assert!(overlay_colors.overlays.contains_key(&overlay.name()));
+        }
+    }
+
+    #[test]
+    fn missing_ramps_are_reported() {
+        let mut overlay_colors = OverlayColors::default();
+        overlay_colors.ramps.remove("water_flux");
+        overlay_colors
+            .overlays
+            .remove(&ScalarOverlay::DepthToWaterTable.name());
+
+        let errors = overlay_colors.validate().unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert!(errors.contains(&OverlayColorsError::MissingRamp {
+            overlay: ScalarOverlay::NetWater.name(),
+            ramp: "water_flux".to_string(),
+        }));
+        assert!(errors.contains(&OverlayColorsError::MissingOverlay(
+            ScalarOverlay::DepthToWaterTable.name()
+        )));
+    }
+
+    #[test]
+    fn ramps_can_be_swapped_by_name() {
+        let mut overlay_colors = OverlayColors::default();
+        let viridis = overlay_colors.ramps["viridis"].clone();
+        // Replacing a ramp changes every overlay that uses it
+        overlay_colors
+            .ramps
+            .insert("water_table".to_string(), viridis.clone());
+
+        let (ramp, _) = overlay_colors
+            .get(ScalarOverlay::DepthToWaterTable)
+            .unwrap();
+        assert_eq!(ramp, &viridis);
+
+        let json = serde_json::to_string(&overlay_colors).unwrap();
+        assert_eq!(
+            serde_json::from_str::<OverlayColors>(&json).unwrap(),
+            overlay_colors
+        );
+    }
+}
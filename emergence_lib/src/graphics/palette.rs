@@ -4,7 +4,7 @@
 pub(crate) mod infovis {
     use bevy::prelude::Color;
 
-    use crate::{light::Illuminance, signals::SignalKind};
+    use crate::{graphics::color_ramp::ColorRamp, light::Illuminance, signals::SignalKind};
 
     /// The alpha value used for selection/hovering/other UI overlay
     pub(crate) const OVERLAY_ALPHA: f32 = 0.7;
@@ -139,6 +139,26 @@ pub(crate) mod infovis {
                 OVERLAY_ALPHA,
             )
         }
+
+        /// The color ramp used to show the strength of this kind of signal.
+        ///
+        /// This passes through a saturated version of the signal's hue on its way from dark to light.
+        pub(crate) fn color_ramp(&self) -> ColorRamp {
+            ColorRamp::new([
+                (0., self.color_low()),
+                (
+                    0.5,
+                    Color::hsla(
+                        self.hue(),
+                        (Self::SIGNAL_SATURATION_LOW + Self::SIGNAL_SATURATION_HIGH) / 2.,
+                        (Self::SIGNAL_LIGHTNESS_LOW + Self::SIGNAL_LIGHTNESS_HIGH) / 2.,
+                        OVERLAY_ALPHA,
+                    ),
+                ),
+                (1., self.color_high()),
+            ])
+            .unwrap()
+        }
     }
 
     /// The color used to represent a "neutral" value in bicolor gradients.
@@ -149,6 +169,20 @@ pub(crate) mod infovis {
     /// The color used to indicate that water is near the surface.
     pub(crate) const WATER_TABLE_COLOR_LOW: Color = Color::hsla(195., 0.7, 0.2, OVERLAY_ALPHA);
 
+    /// The color ramp used to show how far the water table is below the surface, from shallow to deep.
+    pub(crate) fn water_table_ramp() -> ColorRamp {
+        ColorRamp::linear(WATER_TABLE_COLOR_LOW, WATER_TABLE_COLOR_HIGH)
+    }
+
+    /// The color ramp used to show the net change in water, from drying out to getting wetter.
+    pub(crate) fn water_flux_ramp() -> ColorRamp {
+        ColorRamp::diverging(
+            WATER_TABLE_COLOR_HIGH,
+            NEUTRAL_INFOVIS_COLOR,
+            WATER_TABLE_COLOR_LOW,
+        )
+    }
+
     impl Illuminance {
         /// The color used to describe the illuminance of a tile.
         pub(crate) fn info_vis_color(&self) -> Color {
@@ -175,7 +209,7 @@ pub(crate) mod environment {
         /// The color of the sky for this weather.
         pub(crate) const fn sky_color(&self) -> Color {
             match self {
-                Weather::Clear => Color::hsl(209., 0.7, 0.8),
+                Weather::Clear | Weather::Still => Color::hsl(209., 0.7, 0.8),
                 Weather::Cloudy => Color::hsl(209., 0.3, 0.6),
                 Weather::Rainy => Color::hsl(209., 0.3, 0.5),
             }
@@ -1 +1,316 @@
 //! Graphics and animation code for units.
+
+use std::f32::consts::TAU;
+
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+
+use crate::{
+    asset_management::manifest::Id,
+    geometry::{VoxelPos, MAP_LAYOUT},
+    units::unit_manifest::Unit,
+};
+
+/// Controls how units that share a tile are spread out within it.
+///
+/// This only changes where units are drawn: their [`VoxelPos`] is untouched.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub(crate) struct SubTileOffsets {
+    /// The number of evenly spaced spots around the center of each tile.
+    slots: u32,
+    /// The distance from the center of the tile to each spot.
+    radius: f32,
+}
+
+impl Default for SubTileOffsets {
+    fn default() -> Self {
+        SubTileOffsets::new(6, 0.3)
+    }
+}
+
+impl SubTileOffsets {
+    /// Creates a new set of offsets.
+    ///
+    /// The `radius` is clamped so that units are never drawn over the edge of their tile.
+    pub(crate) fn new(slots: u32, radius: f32) -> Self {
+        SubTileOffsets {
+            slots: slots.max(1),
+            radius: radius.clamp(0., Self::max_radius()),
+        }
+    }
+
+    /// The distance from the center of a tile to the middle of its edges.
+    fn max_radius() -> f32 {
+        MAP_LAYOUT.hex_size.x.min(MAP_LAYOUT.hex_size.y) * 3f32.sqrt() / 2.
+    }
+
+    /// The slot that `entity` uses unless another unit on the same tile already has it.
+    ///
+    /// This depends only on the entity, so it is the same every run.
+    fn preferred_slot(&self, entity: Entity) -> u32 {
+        // SplitMix64, to spread out consecutive entity indexes
+        let mut hash = entity.to_bits().wrapping_add(0x9E37_79B9_7F4A_7C15);
+        hash = (hash ^ (hash >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        hash = (hash ^ (hash >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        hash ^= hash >> 31;
+
+        (hash % self.slots as u64) as u32
+    }
+
+    /// The offset from the center of the tile to the provided `slot`.
+    fn slot_offset(&self, slot: u32) -> Vec3 {
+        let angle = TAU * slot as f32 / self.slots as f32;
+        Vec3::new(self.radius * angle.cos(), 0., self.radius * angle.sin())
+    }
+
+    /// Assigns an offset to each of the `units`.
+    ///
+    /// A unit that has its tile to itself is drawn in the center.
+    /// Units on the same tile get distinct slots wherever there are enough to go around.
+    /// Units are handled in [`Entity`] order, so the result does not depend on iteration order.
+    pub(crate) fn assign(
+        &self,
+        units: impl IntoIterator<Item = (Entity, VoxelPos)>,
+    ) -> HashMap<Entity, Vec3> {
+        let mut units: Vec<(Entity, VoxelPos)> = units.into_iter().collect();
+        units.sort_by_key(|(entity, _)| entity.to_bits());
+
+        let mut occupants: HashMap<VoxelPos, usize> = HashMap::new();
+        for (_, voxel_pos) in &units {
+            *occupants.entry(*voxel_pos).or_default() += 1;
+        }
+
+        let mut taken: HashMap<VoxelPos, HashSet<u32>> = HashMap::new();
+        let mut offsets = HashMap::new();
+
+        for (entity, voxel_pos) in units {
+            if occupants[&voxel_pos] == 1 {
+                offsets.insert(entity, Vec3::ZERO);
+                continue;
+            }
+
+            let taken_here = taken.entry(voxel_pos).or_default();
+            let preferred = self.preferred_slot(entity);
+            // Probe for the next free slot, falling back to sharing once the tile is full
+            let slot = (0..self.slots)
+                .map(|i| (preferred + i) % self.slots)
+                .find(|slot| !taken_here.contains(slot))
+                .unwrap_or(preferred);
+
+            taken_here.insert(slot);
+            offsets.insert(entity, self.slot_offset(slot));
+        }
+
+        offsets
+    }
+}
+
+/// Draws each unit at its offset within its tile, so that units sharing a tile do not overlap.
+///
+/// Slots are only reassigned on tiles that a unit has entered or left since the last run,
+/// unless the [`SubTileOffsets`] themselves changed.
+/// Status icons are children of the unit, so they move along with it.
+pub(super) fn spread_crowded_units(
+    offsets: Res<SubTileOffsets>,
+    mut last_positions: Local<HashMap<Entity, VoxelPos>>,
+    moved_query: Query<(Entity, &VoxelPos), (With<Id<Unit>>, Changed<VoxelPos>)>,
+    mut removed_units: RemovedComponents<Id<Unit>>,
+    mut unit_query: Query<(Entity, &VoxelPos, &mut Transform), With<Id<Unit>>>,
+) {
+    let mut changed_tiles: HashSet<VoxelPos> = HashSet::new();
+    for (entity, &voxel_pos) in moved_query.iter() {
+        changed_tiles.insert(voxel_pos);
+        if let Some(previous) = last_positions.insert(entity, voxel_pos) {
+            changed_tiles.insert(previous);
+        }
+    }
+    for entity in removed_units.iter() {
+        if let Some(previous) = last_positions.remove(&entity) {
+            changed_tiles.insert(previous);
+        }
+    }
+
+    let reassign_everything = offsets.is_changed();
+    if changed_tiles.is_empty() && !reassign_everything {
+        return;
+    }
+
+    let assigned = offsets.assign(
+        unit_query
+            .iter()
+            .filter(|(_, voxel_pos, _)| reassign_everything || changed_tiles.contains(*voxel_pos))
+            .map(|(entity, &voxel_pos, _)| (entity, voxel_pos)),
+    );
+
+    for (entity, voxel_pos, mut transform) in unit_query.iter_mut() {
+        let Some(offset) = assigned.get(&entity) else {
+            continue;
+        };
+
+        let translation = voxel_pos.inside_voxel() + *offset;
+        // Avoid triggering change detection needlessly
+        if transform.translation != translation {
+            transform.translation = translation;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Spawns a unit at `voxel_pos`, drawn at the center of its tile.
+    fn spawn_unit(app: &mut App, voxel_pos: VoxelPos) -> Entity {
+        app.world
+            .spawn((
+                Id::<Unit>::from_name("ant".to_string()),
+                voxel_pos,
+                Transform::from_translation(voxel_pos.inside_voxel()),
+            ))
+            .id()
+    }
+
+    /// An app that spreads out crowded units.
+    fn app() -> App {
+        let mut app = App::new();
+        app.init_resource::<SubTileOffsets>()
+            .add_system(spread_crowded_units);
+        app
+    }
+
+    #[test]
+    fn units_sharing_a_tile_are_drawn_apart() {
+        let mut app = app();
+        let a = spawn_unit(&mut app, VoxelPos::ZERO);
+        let b = spawn_unit(&mut app, VoxelPos::ZERO);
+        app.update();
+
+        let translation_of = |entity| app.world.get::<Transform>(entity).unwrap().translation;
+        assert_ne!(translation_of(a), translation_of(b));
+
+        // Both stay on top of the same tile
+        assert_eq!(translation_of(a).y, VoxelPos::ZERO.inside_voxel().y);
+        assert_eq!(translation_of(b).y, VoxelPos::ZERO.inside_voxel().y);
+    }
+
+    #[test]
+    fn offsets_are_deterministic() {
+        let offsets = SubTileOffsets::default();
+        let units: Vec<(Entity, VoxelPos)> = (0..20)
+            .map(|i| (Entity::from_raw(i), VoxelPos::ZERO))
+            .collect();
+
+        let forwards = offsets.assign(units.clone());
+        let backwards = offsets.assign(units.into_iter().rev());
+        assert_eq!(forwards, backwards);
+    }
+
+    #[test]
+    fn offsets_are_distinct_until_slots_run_out() {
+        let offsets = SubTileOffsets::new(6, 0.3);
+        let units = (0..6).map(|i| (Entity::from_raw(i * 17), VoxelPos::ZERO));
+
+        let assigned = offsets.assign(units);
+        for (a, offset_a) in assigned.iter() {
+            for (b, offset_b) in assigned.iter() {
+                if a != b {
+                    assert_ne!(offset_a, offset_b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn offsets_stay_inside_the_tile() {
+        let offsets = SubTileOffsets::new(8, 100.);
+        assert!(offsets.radius <= SubTileOffsets::max_radius());
+
+        let units = (0..50).map(|i| (Entity::from_raw(i), VoxelPos::ZERO));
+        for offset in offsets.assign(units).values() {
+            assert_eq!(offset.y, 0.);
+            assert!(offset.length() <= SubTileOffsets::max_radius() + 1e-4);
+        }
+    }
+
+    #[test]
+    fn lone_units_are_drawn_in_the_center() {
+        let offsets = SubTileOffsets::default();
+        let units = [
+            (Entity::from_raw(0), VoxelPos::ZERO),
+            (Entity::from_raw(1), VoxelPos::from_xy(1, 0)),
+            (Entity::from_raw(2), VoxelPos::from_xy(1, 0)),
+        ];
+
+        let assigned = offsets.assign(units);
+        assert_eq!(assigned[&Entity::from_raw(0)], Vec3::ZERO);
+        assert_ne!(assigned[&Entity::from_raw(1)], Vec3::ZERO);
+        assert_ne!(assigned[&Entity::from_raw(2)], Vec3::ZERO);
+    }
+
+    #[test]
+    fn units_left_alone_move_back_to_the_center() {
+        let mut app = app();
+        let a = spawn_unit(&mut app, VoxelPos::ZERO);
+        let b = spawn_unit(&mut app, VoxelPos::ZERO);
+        app.update();
+        assert_ne!(
+            app.world.get::<Transform>(a).unwrap().translation,
+            VoxelPos::ZERO.inside_voxel()
+        );
+
+        app.world.despawn(b);
+        app.update();
+        assert_eq!(
+            app.world.get::<Transform>(a).unwrap().translation,
+            VoxelPos::ZERO.inside_voxel()
+        );
+    }
+
+    #[test]
+    fn only_tiles_that_changed_are_reassigned() {
+        let mut app = app();
+        let elsewhere = VoxelPos::from_xy(3, 0);
+        let untouched = spawn_unit(&mut app, elsewhere);
+        let mover = spawn_unit(&mut app, VoxelPos::ZERO);
+        spawn_unit(&mut app, VoxelPos::ZERO);
+        app.update();
+
+        // Moved by something else, such as an animation
+        let displaced = Vec3::new(10., 0., 10.);
+        app.world
+            .get_mut::<Transform>(untouched)
+            .unwrap()
+            .translation = displaced;
+
+        *app.world.get_mut::<VoxelPos>(mover).unwrap() = VoxelPos::from_xy(-1, 0);
+        app.update();
+
+        assert_eq!(
+            app.world.get::<Transform>(untouched).unwrap().translation,
+            displaced
+        );
+        assert_eq!(
+            app.world.get::<Transform>(mover).unwrap().translation,
+            VoxelPos::from_xy(-1, 0).inside_voxel()
+        );
+    }
+
+    #[test]
+    fn changing_the_slot_count_moves_units() {
+        let mut app = app();
+        let unit = spawn_unit(&mut app, VoxelPos::ZERO);
+        spawn_unit(&mut app, VoxelPos::ZERO);
+        app.update();
+        let before = app.world.get::<Transform>(unit).unwrap().translation;
+
+        // A single slot at radius zero puts every unit in the center
+        app.insert_resource(SubTileOffsets::new(1, 0.));
+        app.update();
+        let after = app.world.get::<Transform>(unit).unwrap().translation;
+
+        assert_ne!(before, after);
+        assert_eq!(after, VoxelPos::ZERO.inside_voxel());
+    }
+}
@@ -1,6 +1,9 @@
 //! Storage of multiple items with a capacity.
 
-use bevy::prelude::warn;
+use bevy::{
+    prelude::warn,
+    reflect::{FromReflect, Reflect},
+};
 use itertools::rev;
 use serde::{Deserialize, Serialize};
 
@@ -17,7 +20,7 @@ use super::{
 };
 
 /// An inventory to store multiple types of items.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Reflect, FromReflect)]
 pub struct Inventory {
     /// Is this inventory reserved for a single item type?
     ///
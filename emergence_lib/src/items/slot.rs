@@ -1,5 +1,6 @@
 //! A container for a single item type, with a capacity.
 
+use bevy::reflect::{FromReflect, Reflect};
 use rand::{distributions::Uniform, prelude::Distribution, Rng};
 use serde::{Deserialize, Serialize};
 
@@ -13,7 +14,7 @@ use super::{
 };
 
 /// Multiple items of the same type.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Reflect, FromReflect)]
 pub struct ItemSlot {
     /// The unique identifier of the item that occupies the slot.
     item_id: Id<Item>,
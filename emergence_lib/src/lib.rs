@@ -54,6 +54,27 @@ pub mod testing {
         app
     }
 
+    /// The asset storage that the simulation needs, but no renderer and no game logic.
+    pub fn headless_app() -> App {
+        let mut app = minimal_app();
+        app.add_plugin(AssetPlugin::default())
+            .add_plugin(crate::asset_management::AssetManagementPlugin)
+            .add_asset::<Image>()
+            .add_asset::<Mesh>()
+            .add_asset::<Scene>()
+            .add_asset::<StandardMaterial>();
+        app
+    }
+
+    /// The game logic and simulation, with the asset storage they need but no renderer.
+    ///
+    /// Unlike [`simulation_app`], this can be built in unit tests to inspect the resources and schedules that the plugins add.
+    pub fn headless_simulation_app(gen_config: GenerationConfig) -> App {
+        let mut app = headless_app();
+        app.add_plugin(SimulationPlugin { gen_config });
+        app
+    }
+
     /// Test users interacting with the app
     pub fn interaction_app(gen_config: GenerationConfig) -> App {
         let mut app = simulation_app(gen_config);
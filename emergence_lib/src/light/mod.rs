@@ -9,9 +9,10 @@ use serde::{Deserialize, Serialize};
 use crate as emergence_lib;
 
 use crate::simulation::{
+    phases::{SimulationAppExt, TickPhase},
+    sim_resources::{SimResource, SimResourceAppExt},
     time::{InGameTime, TimeOfDay},
     weather::{CurrentWeather, Weather},
-    SimulationSet,
 };
 
 use self::shade::{compute_received_light, compute_shade};
@@ -23,19 +24,30 @@ pub(super) struct LightPlugin;
 
 impl Plugin for LightPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<TotalLight>().add_systems(
-            (compute_light, compute_shade, compute_received_light)
-                .chain()
-                .in_set(SimulationSet)
-                .in_schedule(CoreSchedule::FixedUpdate),
-        );
+        app.init_resource::<TotalLight>()
+            .register_sim_resource::<TotalLight>()
+            .add_simulation_systems(
+                TickPhase::Perception,
+                (compute_light, compute_shade, compute_received_light).chain(),
+            );
     }
 }
 
 /// The total current amount of light available.
-#[derive(Resource, Default, Debug)]
+#[derive(Resource, Default, Debug, Reflect)]
+#[reflect(Resource)]
 pub(crate) struct TotalLight(Illuminance);
 
+/// The light is recomputed from the time of day and weather on every tick.
+impl SimResource for TotalLight {
+    const NAME: &'static str = "total_light";
+    const PERSIST: bool = false;
+
+    fn reset_to_default(&mut self) {
+        *self = TotalLight::default();
+    }
+}
+
 impl Display for TotalLight {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.0)
@@ -55,6 +67,8 @@ impl Display for TotalLight {
     Serialize,
     Deserialize,
     IterableEnum,
+    Reflect,
+    FromReflect,
 )]
 pub enum Illuminance {
     /// The tile is in complete darkness.
@@ -88,7 +102,7 @@ fn compute_light(
         Illuminance::Dark
     } else {
         match current_weather.get() {
-            Weather::Clear => Illuminance::BrightlyLit,
+            Weather::Clear | Weather::Still => Illuminance::BrightlyLit,
             Weather::Cloudy => Illuminance::DimlyLit,
             Weather::Rainy => Illuminance::DimlyLit,
         }
@@ -12,7 +12,8 @@ use super::{Illuminance, TotalLight};
 use std::fmt::Display;
 
 /// The amount of shade on a tile.
-#[derive(Component, Clone, Debug, Default)]
+#[derive(Component, Clone, Debug, Default, Reflect, FromReflect)]
+#[reflect(Component)]
 #[allow(clippy::enum_variant_names)]
 pub(crate) enum Shade {
     /// This tile is not shaded.
@@ -59,7 +60,8 @@ impl Display for Shade {
 }
 
 /// The amount of light currently received by a tile.
-#[derive(Component, Clone, Debug, Default)]
+#[derive(Component, Clone, Debug, Default, Reflect, FromReflect)]
+#[reflect(Component)]
 pub(crate) struct ReceivedLight(pub(crate) Illuminance);
 
 impl Display for ReceivedLight {
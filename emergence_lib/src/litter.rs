@@ -2,9 +2,9 @@
 
 use std::f32::consts::TAU;
 
-use bevy::utils::Duration;
+use bevy::utils::{Duration, HashMap};
 use bevy::{ecs::system::Command, prelude::*};
-use hexx::Direction;
+use hexx::{Direction, Hex};
 use rand::thread_rng;
 use rand_distr::{Distribution, Normal};
 
@@ -14,6 +14,7 @@ use crate::items::inventory::InventoryState;
 use crate::items::item_manifest::Item;
 use crate::items::ItemCount;
 use crate::terrain::terrain_assets::TerrainHandles;
+use crate::units::actions::CurrentAction;
 use crate::{
     crafting::{inventories::StorageInventory, item_tags::ItemKind},
     geometry::{DiscreteHeight, Height, MapGeometry, VoxelPos},
@@ -41,7 +42,7 @@ struct LitterBundle {
 /// Items that are littered without a container.
 ///
 /// This component is tracked on a per-tile basis.
-#[derive(Component, Clone, Debug, Deref, DerefMut)]
+#[derive(Component, Clone, Debug, Deref, DerefMut, Reflect, FromReflect)]
 pub(crate) struct Litter {
     /// The items that are littered on the ground.
     pub(crate) contents: StorageInventory,
@@ -64,7 +65,7 @@ impl Litter {
 }
 
 /// Is this litter currently floating?
-#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Reflect, FromReflect)]
 pub(crate) struct Floating(bool);
 
 impl Litter {
@@ -122,6 +123,95 @@ pub(super) fn clear_empty_litter(query: Query<(Entity, &Litter)>, mut commands:
     }
 }
 
+/// Merges piles of the same item that are stacked on the same tile, so that dropped items don't each need their own entity.
+///
+/// Piles are filled from the bottom up, without exceeding the item's stack size: any excess is left in the next pile up.
+/// Units that were about to pick up from a pile that was merged away are sent to the pile that absorbed it instead.
+pub(super) fn merge_litter(
+    mut litter_query: Query<(Entity, &VoxelPos, &mut Litter)>,
+    mut unit_query: Query<&mut CurrentAction>,
+    item_manifest: Res<ItemManifest>,
+    mut map_geometry: ResMut<MapGeometry>,
+    mut commands: Commands,
+) {
+    let mut piles: HashMap<(Hex, Id<Item>), Vec<(VoxelPos, Entity)>> = HashMap::new();
+    for (entity, &voxel_pos, litter) in litter_query.iter() {
+        // Empty litter is cleaned up separately
+        if litter.contents.is_empty() {
+            continue;
+        }
+
+        for item_slot in litter.contents.iter() {
+            piles
+                .entry((voxel_pos.hex, item_slot.item_id()))
+                .or_default()
+                .push((voxel_pos, entity));
+        }
+    }
+
+    let mut merged_into: HashMap<Entity, Entity> = HashMap::new();
+    for ((_, item_id), mut pile) in piles {
+        if pile.len() < 2 {
+            continue;
+        }
+
+        pile.sort();
+        let stack_size = item_manifest.get(item_id).stack_size;
+        let mut remaining: u32 = pile
+            .iter()
+            .map(|&(_, entity)| litter_query.get(entity).unwrap().2.item_count(item_id))
+            .sum();
+        let mut surviving_entity = pile[0].1;
+
+        for (voxel_pos, entity) in pile {
+            if remaining > 0 {
+                let count = remaining.min(stack_size);
+                remaining -= count;
+                surviving_entity = entity;
+
+                // Avoid triggering change detection for piles that are already settled
+                let (_, _, mut litter) = litter_query.get_mut(entity).unwrap();
+                if litter.item_count(item_id) != count {
+                    litter.contents = StorageInventory::new(1, None);
+                    litter
+                        .contents
+                        .add_item_all_or_nothing(&ItemCount { item_id, count }, &item_manifest)
+                        .unwrap();
+                }
+            } else {
+                map_geometry.remove_litter(voxel_pos);
+                commands.entity(entity).despawn_recursive();
+                merged_into.insert(entity, surviving_entity);
+            }
+        }
+    }
+
+    if merged_into.is_empty() {
+        return;
+    }
+
+    for mut current_action in unit_query.iter_mut() {
+        for (&old_entity, &new_entity) in merged_into.iter() {
+            current_action.retarget_pickup(old_entity, new_entity);
+        }
+    }
+}
+
+/// Displays larger piles of litter with a larger model.
+pub(super) fn set_litter_scenes(
+    mut query: Query<(&Litter, &mut Handle<Scene>), Changed<Litter>>,
+    terrain_handles: Option<Res<TerrainHandles>>,
+) {
+    // Litter without visuals, such as in tests, has nothing to update
+    let Some(terrain_handles) = terrain_handles else { return };
+
+    for (litter, mut scene) in query.iter_mut() {
+        if let Some(scene_handle) = terrain_handles.litter_models.get(&litter.contents.state()) {
+            scene.set_if_neq(scene_handle.clone_weak());
+        }
+    }
+}
+
 /// Make litter in tiles submerged by water float (and stop it from floating when there's no water).
 pub(super) fn make_litter_float(
     mut query: Query<(&mut Floating, &mut VoxelPos), With<Litter>>,
@@ -158,9 +248,11 @@ pub(super) fn make_litter_float(
 }
 
 /// The direction and time remaining for a piece of litter to drift with the current.
-#[derive(Component, Default)]
+#[derive(Component, Default, Reflect, FromReflect)]
+#[reflect(Component)]
 pub(super) struct Drift {
     /// The direction the litter is drifting.
+    #[reflect(ignore)]
     pub(super) direction: Option<Direction>,
     /// The time remaining for the litter to drift.
     pub(super) timer: Timer,
@@ -316,12 +408,12 @@ impl Command for SpawnLitterCommand {
 
         let litter = Litter::new(self.item, item_manifest);
 
-        let terrain_handles = world.resource::<TerrainHandles>();
-        let litter_models = &terrain_handles.litter_models;
-        let scene = litter_models
-            .get(&InventoryState::Partial)
-            .unwrap()
-            .clone_weak();
+        // Litter without visuals, such as in tests, uses the default scene
+        let scene = world
+            .get_resource::<TerrainHandles>()
+            .and_then(|terrain_handles| terrain_handles.litter_models.get(&InventoryState::Partial))
+            .map(|scene| scene.clone_weak())
+            .unwrap_or_default();
 
         let scene_bundle = SceneBundle {
             scene,
@@ -347,3 +439,158 @@ impl Command for SpawnLitterCommand {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        asset_management::manifest::Manifest, geometry::Facing, items::item_manifest::ItemData,
+        units::actions::UnitAction,
+    };
+
+    /// The only kind of item in the test manifest.
+    fn leaf() -> Id<Item> {
+        Id::from_name("leaf".to_string())
+    }
+
+    /// A world with a small map, and an item manifest where leaves stack up to 20.
+    fn litter_world() -> World {
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 1);
+        world.insert_resource(map_geometry);
+
+        let mut item_manifest: ItemManifest = Manifest::new();
+        item_manifest.insert(
+            "leaf".to_string(),
+            ItemData {
+                stack_size: 20,
+                compostable: true,
+                fluid: false,
+                buoyant: true,
+                seed: None,
+            },
+        );
+        world.insert_resource(item_manifest);
+
+        world
+    }
+
+    /// Spawns a pile of `count` leaves on the center tile, `height` voxels above the ground.
+    fn spawn_pile(world: &mut World, height: u8, count: u32) -> Entity {
+        let voxel_pos = VoxelPos {
+            hex: Hex::ZERO,
+            height: DiscreteHeight(height),
+        };
+
+        let mut contents = StorageInventory::new(1, None);
+        contents
+            .add_item_all_or_nothing(
+                &ItemCount {
+                    item_id: leaf(),
+                    count,
+                },
+                world.resource::<ItemManifest>(),
+            )
+            .unwrap();
+
+        let entity = world.spawn((Litter { contents }, voxel_pos)).id();
+        let actual_pos = world
+            .resource_mut::<MapGeometry>()
+            .drop_litter(voxel_pos, entity);
+        assert_eq!(actual_pos, voxel_pos);
+
+        entity
+    }
+
+    /// Runs [`merge_litter`] once.
+    fn merge(world: &mut World) {
+        let mut schedule = Schedule::new();
+        schedule.add_system(merge_litter);
+        schedule.run(world);
+    }
+
+    /// The number of leaves in each remaining pile, from the bottom up.
+    fn pile_counts(world: &mut World) -> Vec<u32> {
+        let mut piles: Vec<(VoxelPos, u32)> = world
+            .query::<(&VoxelPos, &Litter)>()
+            .iter(world)
+            .map(|(&voxel_pos, litter)| (voxel_pos, litter.item_count(leaf())))
+            .collect();
+        piles.sort();
+
+        piles.into_iter().map(|(_, count)| count).collect()
+    }
+
+    #[test]
+    fn co_located_piles_merge_into_one() {
+        let mut world = litter_world();
+        let piles: Vec<Entity> = (1..=10)
+            .map(|height| spawn_pile(&mut world, height, 1))
+            .collect();
+
+        merge(&mut world);
+
+        assert_eq!(pile_counts(&mut world), vec![10]);
+        assert!(world.get_entity(piles[0]).is_some());
+
+        // Merged piles are removed from the spatial index
+        let map_geometry = world.resource::<MapGeometry>();
+        for height in 2..=10 {
+            let voxel_pos = VoxelPos {
+                hex: Hex::ZERO,
+                height: DiscreteHeight(height),
+            };
+            assert!(map_geometry.is_voxel_clear(voxel_pos).is_ok());
+        }
+    }
+
+    #[test]
+    fn piles_over_the_stack_size_are_split() {
+        let mut world = litter_world();
+        for height in 1..=10 {
+            spawn_pile(&mut world, height, 3);
+        }
+
+        merge(&mut world);
+
+        assert_eq!(pile_counts(&mut world), vec![20, 10]);
+
+        // Settled piles are left alone
+        merge(&mut world);
+        assert_eq!(pile_counts(&mut world), vec![20, 10]);
+    }
+
+    #[test]
+    fn units_picking_up_from_merged_piles_are_redirected() {
+        let mut world = litter_world();
+        let bottom_pile = spawn_pile(&mut world, 1, 1);
+        let top_pile = spawn_pile(&mut world, 2, 1);
+
+        let unit_pos = VoxelPos::from_xy(-1, 0);
+        let pile_pos = VoxelPos {
+            hex: Hex::ZERO,
+            height: DiscreteHeight(2),
+        };
+        let facing = Facing {
+            direction: unit_pos.hex.main_direction_to(pile_pos.hex),
+        };
+        let unit = world
+            .spawn(CurrentAction::pickup(
+                ItemKind::Single(leaf()),
+                top_pile,
+                &facing,
+                unit_pos,
+                pile_pos,
+            ))
+            .id();
+
+        merge(&mut world);
+
+        assert!(world.get_entity(top_pile).is_none());
+        let current_action = world.get::<CurrentAction>(unit).unwrap();
+        assert!(matches!(
+            current_action.action(),
+            UnitAction::PickUp { output_entity, .. } if *output_entity == bottom_pile
+        ));
+    }
+}
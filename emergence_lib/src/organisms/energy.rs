@@ -9,12 +9,17 @@ use rand::Rng;
 use serde::{Deserialize, Serialize};
 
 use crate::asset_management::manifest::Id;
+use crate::simulation::sim_resources::SimResource;
 use crate::structures::structure_manifest::Structure;
+use crate::units::unit_manifest::Unit;
 use crate::{geometry::VoxelPos, structures::commands::StructureCommandsExt};
 
 /// The amount of energy available to an organism.
 /// If they run out, they die.
-#[derive(Debug, Clone, PartialEq, Component, Resource, Serialize, Deserialize)]
+#[derive(
+    Debug, Clone, PartialEq, Component, Resource, Serialize, Deserialize, Reflect, FromReflect,
+)]
+#[reflect(Component)]
 pub struct EnergyPool {
     /// The current amount of stored energy.
     current: Energy,
@@ -90,6 +95,8 @@ impl Default for EnergyPool {
     SubAssign,
     Serialize,
     Deserialize,
+    Reflect,
+    FromReflect,
 )]
 pub struct Energy(pub f32);
 
@@ -188,13 +195,43 @@ pub(crate) enum StartingEnergy {
     NotAnOrganism,
 }
 
+/// Scales how quickly units lose [`Energy`] over time.
+///
+/// Only the drain is scaled: units whose energy regenerates are unaffected.
+/// This is set from the [`Difficulty`](crate::world_gen::Difficulty) of the world when it is generated.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Resource)]
+pub struct UnitEnergyDrain {
+    /// The multiplier applied to the energy drain of every unit.
+    pub multiplier: f32,
+}
+
+impl Default for UnitEnergyDrain {
+    fn default() -> Self {
+        UnitEnergyDrain { multiplier: 1. }
+    }
+}
+
+/// The drain is derived from the world's difficulty, so it is not saved.
+impl SimResource for UnitEnergyDrain {
+    const NAME: &'static str = "unit_energy_drain";
+    const PERSIST: bool = false;
+}
+
 /// Steadily depletes [`Energy`] over time.
-pub(super) fn consume_energy(fixed_time: Res<FixedTime>, mut energy_query: Query<&mut EnergyPool>) {
+pub(super) fn consume_energy(
+    fixed_time: Res<FixedTime>,
+    unit_energy_drain: Res<UnitEnergyDrain>,
+    mut energy_query: Query<(&mut EnergyPool, Option<&Id<Unit>>)>,
+) {
     let delta_time = fixed_time.period.as_secs_f32();
 
-    for mut energy_pool in energy_query.iter_mut() {
+    for (mut energy_pool, maybe_unit) in energy_query.iter_mut() {
         // Note that regen rates are almost always negative.
-        let regen_rate = energy_pool.regen_per_second;
+        let mut regen_rate = energy_pool.regen_per_second;
+        if maybe_unit.is_some() && regen_rate < Energy(0.) {
+            regen_rate = regen_rate * unit_energy_drain.multiplier;
+        }
         let current = energy_pool.current();
 
         energy_pool.set_current(current + regen_rate * delta_time);
@@ -215,3 +252,33 @@ pub(super) fn kill_organisms_when_out_of_energy(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unit_energy_drain_only_scales_units() {
+        let mut app = App::new();
+        app.insert_resource(FixedTime::new_from_secs(1.))
+            .insert_resource(UnitEnergyDrain { multiplier: 0.5 })
+            .add_system(consume_energy);
+
+        let energy_pool = EnergyPool::new_full(Energy(10.), Energy(-2.));
+        let unit = app
+            .world
+            .spawn((
+                energy_pool.clone(),
+                Id::<Unit>::from_name("ant".to_string()),
+            ))
+            .id();
+        let plant = app.world.spawn(energy_pool).id();
+
+        app.update();
+
+        let unit_energy = app.world.get::<EnergyPool>(unit).unwrap().current();
+        assert_eq!(unit_energy, Energy(9.));
+        let plant_energy = app.world.get::<EnergyPool>(plant).unwrap().current();
+        assert_eq!(plant_energy, Energy(8.));
+    }
+}
@@ -11,7 +11,10 @@ use crate::{
     items::item_manifest::ItemManifest,
     litter::Litter,
     player_interaction::clipboard::ClipboardData,
-    simulation::time::{Days, TimePool},
+    simulation::{
+        time::{Days, TimePool},
+        ChanceAudit,
+    },
     structures::{commands::StructureCommandsExt, structure_manifest::StructureManifest},
     units::{
         unit_assets::UnitHandles,
@@ -28,7 +31,7 @@ use super::{
 /// How this organism can grow, change and transform over time.
 ///
 /// This represents a local view of the graph.
-#[derive(Component, Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Component, Debug, Clone, Serialize, Deserialize, PartialEq, Reflect, FromReflect)]
 pub struct Lifecycle {
     /// The forms that this organism can turn into, and their triggering conditions.
     life_paths: Vec<LifePath>,
@@ -130,7 +133,7 @@ impl From<RawLifecycle> for Lifecycle {
 /// A path from one organism to another form.
 ///
 /// Units will transform once all of their non-`None` conditions are met.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Reflect, FromReflect)]
 pub struct LifePath {
     /// The form that this organism will take once all of the conditions are met.
     pub new_form: OrganismId,
@@ -276,12 +279,13 @@ pub(super) fn transform_when_lifecycle_complete(
 
 /// Items with [`ItemTag::Seed`](crate::crafting::item_tags::ItemTag) that are dropped on the ground will be consumed and transformed into a new organism.
 pub(super) fn sprout_seeds(
-    mut litter_query: Query<(&VoxelPos, &mut Litter)>,
+    mut litter_query: Query<(Entity, &VoxelPos, &mut Litter)>,
     item_manifest: Res<ItemManifest>,
     structure_manifest: Res<StructureManifest>,
     unit_manifest: Res<UnitManifest>,
     unit_handles: Res<UnitHandles>,
     map_geometry: Res<MapGeometry>,
+    mut chance_audit: ChanceAudit,
     mut commands: Commands,
 ) {
     // TODO: add germination conditions, and vary this based on the seed type.
@@ -290,9 +294,20 @@ pub(super) fn sprout_seeds(
 
     let rng = &mut rand::thread_rng();
 
-    for (&voxel_pos, mut litter) in litter_query.iter_mut() {
+    for (litter_entity, &voxel_pos, mut litter) in litter_query.iter_mut() {
         // Roll to see if any seeds will sprout for this tile this tick.
-        if rng.gen::<f32>() > SEED_SPROUT_CHANCE {
+        let roll = rng.gen::<f32>();
+        let sprouted = roll <= SEED_SPROUT_CHANCE;
+        crate::record_chance!(
+            audit: chance_audit,
+            system: "sprout_seeds",
+            probability: SEED_SPROUT_CHANCE,
+            rolled: roll,
+            outcome: sprouted,
+            subject: Some(litter_entity),
+        );
+
+        if !sprouted {
             continue;
         }
 
@@ -5,13 +5,16 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     asset_management::manifest::Id,
-    simulation::SimulationSet,
+    simulation::{
+        phases::{SimulationAppExt, TickPhase},
+        sim_resources::SimResourceAppExt,
+    },
     structures::structure_manifest::{Structure, StructureManifest},
     units::unit_manifest::{Unit, UnitManifest},
 };
 
 use self::{
-    energy::{consume_energy, kill_organisms_when_out_of_energy, EnergyPool},
+    energy::{consume_energy, kill_organisms_when_out_of_energy, EnergyPool, UnitEnergyDrain},
     lifecycle::{sprout_seeds, transform_when_lifecycle_complete, Lifecycle, RawLifecycle},
     oxygen::{manage_oxygen, Oxygen, OxygenPool},
     vegetative_reproduction::vegetative_spread,
@@ -23,7 +26,7 @@ pub mod oxygen;
 pub mod vegetative_reproduction;
 
 /// The [`Id`] of an organism.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Reflect, FromReflect)]
 pub enum OrganismId {
     /// Represents a [`Structure`].
     Structure(Id<Structure>),
@@ -152,7 +155,8 @@ impl From<RawOrganismVariety> for OrganismVariety {
 }
 
 /// A living part of the game ecosystem.
-#[derive(Component, Default)]
+#[derive(Component, Default, Reflect, FromReflect)]
+#[reflect(Component)]
 pub struct Organism;
 
 /// Controls the behavior of living organisms
@@ -160,17 +164,18 @@ pub struct OrganismPlugin;
 
 impl Plugin for OrganismPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            (
-                consume_energy,
-                kill_organisms_when_out_of_energy,
-                transform_when_lifecycle_complete,
-                vegetative_spread,
-                sprout_seeds,
-                manage_oxygen,
-            )
-                .in_set(SimulationSet)
-                .in_schedule(CoreSchedule::FixedUpdate),
-        );
+        app.init_resource::<UnitEnergyDrain>()
+            .register_sim_resource::<UnitEnergyDrain>()
+            .add_simulation_systems(
+                TickPhase::Resolution,
+                (
+                    consume_energy,
+                    kill_organisms_when_out_of_energy,
+                    transform_when_lifecycle_complete,
+                    vegetative_spread,
+                    sprout_seeds,
+                    manage_oxygen,
+                ),
+            );
     }
 }
@@ -20,7 +20,9 @@ use super::Organism;
 
 /// The amount of oxygen available to an organism.
 /// If they run out, they die.
-#[derive(Debug, Clone, PartialEq, Component, Resource, Serialize, Deserialize)]
+#[derive(
+    Debug, Clone, PartialEq, Component, Resource, Serialize, Deserialize, Reflect, FromReflect,
+)]
 pub struct OxygenPool {
     /// The current amount of stored oxygen.
     current: Oxygen,
@@ -80,6 +82,8 @@ impl Display for OxygenPool {
     SubAssign,
     Serialize,
     Deserialize,
+    Reflect,
+    FromReflect,
 )]
 pub struct Oxygen(pub f32);
 
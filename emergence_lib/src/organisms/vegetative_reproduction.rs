@@ -20,7 +20,7 @@ use crate::{
 use super::energy::{Energy, EnergyPool, StartingEnergy};
 
 /// A component that allows an organism to spread to nearby tiles.
-#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+#[derive(Component, Debug, Clone, Serialize, Deserialize, Reflect, FromReflect)]
 pub struct VegetativeReproduction {
     /// The minimum time remaining until this organism can spread again.
     timer: Timer,
@@ -15,6 +15,7 @@ use leafwing_input_manager::prelude::ActionState;
 use crate::geometry::DiscreteHeight;
 use crate::geometry::MapGeometry;
 use crate::geometry::VoxelPos;
+use crate::units::trace::trace_followed_unit;
 use crate::units::unit_manifest::Unit;
 use crate::world_gen::WorldGenState;
 
@@ -38,6 +39,8 @@ impl Plugin for CameraPlugin {
                     .before(set_camera_inclination)
                     .before(rotate_camera),
             )
+            .add_system(toggle_camera_follow.before(set_camera_focus))
+            .add_system(trace_followed_unit.after(toggle_camera_follow))
             .add_system(
                 set_camera_focus
                     // Allow users to break out of CameraMode::Follow by moving the camera manually
@@ -64,7 +67,12 @@ const STARTING_DISTANCE_FROM_ORIGIN: f32 = 30.;
 const MAX_FRAME_TIME: f32 = 1. / 20.;
 
 /// Spawns a [`Camera3dBundle`] and associated camera components.
-fn setup_camera(mut commands: Commands) {
+fn setup_camera(mut commands: Commands, existing_camera_query: Query<(), With<CameraFocus>>) {
+    // The world is complete again after being regenerated, but we should keep the existing camera
+    if !existing_camera_query.is_empty() {
+        return;
+    }
+
     let focus = CameraFocus::default();
     let settings = CameraSettings::default();
 
@@ -142,6 +150,12 @@ pub(crate) struct CameraSettings {
     inclination_speed: Speed,
     /// How much should dragging the mouse rotate the camera?
     drag_ratio: f32,
+    /// How far can a followed unit move away from the focus before the camera starts to move?
+    follow_deadzone: f32,
+    /// How quickly does the camera catch up to a followed unit, in seconds?
+    ///
+    /// After this much time, the distance left to close will have shrunk by a factor of e.
+    follow_time_constant: f32,
 }
 
 impl Default for CameraSettings {
@@ -158,6 +172,8 @@ impl Default for CameraSettings {
             inclination: Rotation::from_radians(0.5 * PI / 2.),
             inclination_speed: Speed::new(0.5, 1.0, 2.0),
             drag_ratio: 0.05,
+            follow_deadzone: 0.2,
+            follow_time_constant: 0.2,
         }
     }
 }
@@ -316,28 +332,72 @@ fn zoom(
     focus.distance = (focus.distance + delta_zoom).clamp(settings.min_zoom, settings.max_zoom);
 }
 
+/// Toggles whether the camera follows the selected unit.
+///
+/// Follow mode can only be entered when a unit is selected.
+fn toggle_camera_follow(
+    actions: Res<ActionState<PlayerAction>>,
+    selection: Res<CurrentSelection>,
+    mut camera_query: Query<&mut CameraSettings, With<Camera3d>>,
+) {
+    if !actions.just_pressed(PlayerAction::ToggleCameraFollow) {
+        return;
+    }
+
+    let Ok(mut settings) = camera_query.get_single_mut() else { return };
+
+    settings.camera_mode = match (settings.camera_mode, &*selection) {
+        (CameraMode::Free, CurrentSelection::Unit(_)) => CameraMode::FollowUnit,
+        _ => CameraMode::Free,
+    };
+}
+
 /// Sets the tile that the camera is  camera's focus.
 fn set_camera_focus(
     actions: Res<ActionState<PlayerAction>>,
     selection: Res<CurrentSelection>,
     unit_query: Query<&Transform>,
     mut camera_query: Query<(&mut CameraFocus, &mut CameraSettings), With<Camera3d>>,
+    time: Res<Time>,
 ) {
     let Ok((mut focus, mut settings)) = camera_query.get_single_mut() else { return; };
 
-    // Snap to selected object
-    if actions.pressed(PlayerAction::CenterCameraOnSelection)
-        || settings.camera_mode == CameraMode::FollowUnit
-    {
+    if settings.camera_mode == CameraMode::FollowUnit {
+        // If the unit we're following was deselected or has died, go back to free camera mode,
+        // leaving the camera where it is
+        let maybe_unit_transform = match &*selection {
+            CurrentSelection::Unit(entity) => unit_query.get(*entity).ok(),
+            _ => None,
+        };
+
+        let Some(unit_transform) = maybe_unit_transform else {
+            settings.camera_mode = CameraMode::Free;
+            return;
+        };
+
+        focus.translation = smooth_follow(
+            focus.translation,
+            unit_transform.translation,
+            settings.follow_deadzone,
+            settings.follow_time_constant,
+            time.delta_seconds(),
+        );
+
+        // Also rotate the camera to match the orientation of the unit we're following
+        let euler = unit_transform.rotation.to_euler(EulerRot::YXZ);
+        let angle_around_y = euler.0;
+        settings.facing = Rotation::from_radians(angle_around_y);
+    } else if actions.pressed(PlayerAction::CenterCameraOnSelection) {
+        // Snap to selected object
         let tile_to_snap_to = match &*selection {
             CurrentSelection::Voxels(selected_voxels) => Some(VoxelPos {
                 hex: selected_voxels.center(),
                 height: DiscreteHeight::ZERO,
             }),
-            CurrentSelection::Unit(entity) => {
-                let unit_transform = unit_query.get(*entity).unwrap();
-                Some(VoxelPos::from_world_pos(unit_transform.translation))
-            }
+            CurrentSelection::Unit(entity) => unit_query
+                .get(*entity)
+                .ok()
+                .map(|unit_transform| VoxelPos::from_world_pos(unit_transform.translation)),
             CurrentSelection::None => None,
         };
 
@@ -345,20 +405,35 @@ fn set_camera_focus(
             focus.translation = target.top_of_tile();
         }
     }
+}
 
-    // Also rotate the camera to match the orientation of the unit we're following
-    if settings.camera_mode == CameraMode::FollowUnit {
-        if let CurrentSelection::Unit(entity) = &*selection {
-            let unit_transform = unit_query.get(*entity).unwrap();
-            let quat = unit_transform.rotation;
-            let euler = quat.to_euler(EulerRot::YXZ);
-            let angle_around_y = euler.0;
-            settings.facing = Rotation::from_radians(angle_around_y);
-        } else {
-            // If we don't have a unit selected, go back to free camera mode
-            settings.camera_mode = CameraMode::Free;
-        }
+/// Moves the camera's focus from `current` towards the `target` that it is following.
+///
+/// The target can move up to `deadzone` away from the focus without moving the camera.
+/// Beyond that, the remaining gap shrinks by a factor of e every `time_constant` seconds.
+/// As this is exponential smoothing, the result does not depend on how `delta_seconds` is split up between frames.
+fn smooth_follow(
+    current: Vec3,
+    target: Vec3,
+    deadzone: f32,
+    time_constant: f32,
+    delta_seconds: f32,
+) -> Vec3 {
+    let offset = target - current;
+    let distance = offset.length();
+    if distance <= deadzone {
+        return current;
     }
+
+    // Only close the part of the gap that lies outside of the deadzone
+    let excess = offset * (1. - deadzone / distance);
+    let fraction_closed = if time_constant > 0. {
+        1. - (-delta_seconds / time_constant).exp()
+    } else {
+        1.
+    };
+
+    current + excess * fraction_closed
 }
 
 /// Pan the camera
@@ -463,3 +538,108 @@ fn compute_camera_transform(
 
     transform
 }
+
+#[cfg(test)]
+mod tests {
+    use leafwing_input_manager::axislike::DualAxisData;
+
+    use super::*;
+
+    /// Spawns a camera in follow mode, tracking a newly spawned unit.
+    fn following_app() -> (App, Entity, Entity) {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .init_resource::<ActionState<PlayerAction>>()
+            .add_system(pan_camera.before(set_camera_focus))
+            .add_system(set_camera_focus);
+
+        let unit = app.world.spawn(Transform::from_xyz(5., 0., 5.)).id();
+        app.insert_resource(CurrentSelection::Unit(unit));
+
+        let camera = app
+            .world
+            .spawn((
+                Camera3d::default(),
+                Transform::default(),
+                CameraFocus::default(),
+                CameraSettings {
+                    camera_mode: CameraMode::FollowUnit,
+                    ..Default::default()
+                },
+            ))
+            .id();
+
+        (app, camera, unit)
+    }
+
+    #[test]
+    fn follow_converges_on_stationary_target() {
+        let target = Vec3::new(10., 0., 0.);
+        let time_constant = 0.5;
+        let mut focus = Vec3::ZERO;
+
+        // One time constant, split across many frames
+        for _ in 0..30 {
+            focus = smooth_follow(focus, target, 0., time_constant, time_constant / 30.);
+        }
+        let expected_remaining = 10. * (-1f32).exp();
+        assert!((target.distance(focus) - expected_remaining).abs() < 1e-3);
+
+        // Five time constants later, we should be within 1% of the starting gap
+        for _ in 0..150 {
+            focus = smooth_follow(focus, target, 0., time_constant, time_constant / 30.);
+        }
+        assert!(target.distance(focus) < 0.1);
+    }
+
+    #[test]
+    fn follow_is_framerate_independent() {
+        let target = Vec3::new(3., 1., -4.);
+
+        let one_step = smooth_follow(Vec3::ZERO, target, 0.5, 0.2, 0.1);
+        let mut many_steps = Vec3::ZERO;
+        for _ in 0..10 {
+            many_steps = smooth_follow(many_steps, target, 0.5, 0.2, 0.01);
+        }
+
+        assert!(one_step.distance(many_steps) < 1e-4);
+    }
+
+    #[test]
+    fn small_movements_inside_deadzone_are_ignored() {
+        let current = Vec3::ZERO;
+        let target = Vec3::new(0.1, 0., 0.);
+
+        assert_eq!(smooth_follow(current, target, 0.2, 0.2, 1.0), current);
+    }
+
+    #[test]
+    fn manual_pan_cancels_follow() {
+        let (mut app, camera, _unit) = following_app();
+
+        let mut actions = app.world.resource_mut::<ActionState<PlayerAction>>();
+        actions.press(PlayerAction::Pan);
+        actions.action_data_mut(PlayerAction::Pan).axis_pair = Some(DualAxisData::new(1., 0.));
+
+        app.update();
+
+        let settings = app.world.get::<CameraSettings>(camera).unwrap();
+        assert_eq!(settings.camera_mode, CameraMode::Free);
+    }
+
+    #[test]
+    fn death_of_followed_unit_returns_to_free_camera() {
+        let (mut app, camera, unit) = following_app();
+        app.update();
+
+        let focus_before_death = app.world.get::<CameraFocus>(camera).unwrap().translation;
+        app.world.despawn(unit);
+        app.update();
+
+        let settings = app.world.get::<CameraSettings>(camera).unwrap();
+        assert_eq!(settings.camera_mode, CameraMode::Free);
+
+        let focus_after_death = app.world.get::<CameraFocus>(camera).unwrap().translation;
+        assert_eq!(focus_before_death, focus_after_death);
+    }
+}
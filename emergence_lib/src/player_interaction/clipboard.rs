@@ -38,7 +38,8 @@ impl Plugin for ClipboardPlugin {
 }
 
 /// Stores the currently active tool that the player is using.
-#[derive(Default, Resource, Debug)]
+#[derive(Default, Resource, Debug, Reflect)]
+#[reflect(Resource)]
 pub(crate) enum Tool {
     /// Terraform terrain.
     Terraform(TerraformingTool),
@@ -73,7 +74,7 @@ impl Tool {
 }
 
 /// The data copied via the clipboard for a single structure.
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Reflect, FromReflect)]
 pub(crate) struct ClipboardData {
     /// The identity of the structure.
     pub(crate) structure_id: Id<Structure>,
@@ -0,0 +1,705 @@
+//! Player-written rules that respond automatically to the state of the colony.
+//!
+//! Each rule pairs a [`Condition`] over [`ColonyMetrics`] with a [`RuleAction`].
+//! Rules are checked in order every time the census updates the metrics, as part of the simulation tick,
+//! and each rule waits for its cooldown to elapse before it can fire again.
+
+use std::{fmt::Display, path::Path};
+
+use bevy::{prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asset_management::manifest::Id,
+    crafting::{production_queue::ProductionQueue, recipe::Recipe},
+    simulation::{
+        sim_resources::{SimResource, SimResourceAppExt},
+        time::InGameTime,
+    },
+    structures::structure_manifest::Structure,
+    utils::memory::MemoryUsage,
+};
+
+/// Stores the [`ColonyRules`] and the [`ColonyMetrics`] they respond to.
+///
+/// The rules are applied by [`apply_colony_rules`], right after each census records new metrics.
+pub(super) struct ColonyRulesPlugin;
+
+impl Plugin for ColonyRulesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ColonyRules>()
+            .init_resource::<ColonyMetrics>()
+            .register_sim_resource::<ColonyRules>()
+            .register_sim_resource::<ColonyMetrics>();
+    }
+}
+
+/// Named measurements of the colony, such as population counts and item totals.
+///
+/// These are recorded alongside the census shown in the production statistics panel.
+#[derive(Resource, Debug, Default, Clone, PartialEq)]
+pub struct ColonyMetrics {
+    /// The current value of each metric, by name
+    values: HashMap<String, f32>,
+}
+
+impl ColonyMetrics {
+    /// Returns the current value of the metric called `name`, if it has been recorded.
+    pub fn get(&self, name: &str) -> Option<f32> {
+        self.values.get(name).copied()
+    }
+
+    /// Records the current `value` of the metric called `name`.
+    pub fn set(&mut self, name: impl Into<String>, value: f32) {
+        self.values.insert(name.into(), value);
+    }
+}
+
+/// The metrics are recorded again by the next census.
+impl SimResource for ColonyMetrics {
+    const NAME: &'static str = "colony_metrics";
+    const PERSIST: bool = false;
+
+    fn reset_to_default(&mut self) {
+        self.values.clear();
+    }
+
+    fn measure_memory(&self) -> Option<MemoryUsage> {
+        Some(MemoryUsage::of_hash_map(&self.values))
+    }
+}
+
+/// How a metric is compared to a fixed value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Comparison {
+    /// The metric is strictly less than the value.
+    LessThan,
+    /// The metric is less than or equal to the value.
+    LessOrEqual,
+    /// The metric is strictly greater than the value.
+    GreaterThan,
+    /// The metric is greater than or equal to the value.
+    GreaterOrEqual,
+    /// The metric is exactly equal to the value.
+    Equal,
+    /// The metric is not equal to the value.
+    NotEqual,
+}
+
+impl Comparison {
+    /// Compares the `metric` to the `value`.
+    pub fn holds(self, metric: f32, value: f32) -> bool {
+        match self {
+            Comparison::LessThan => metric < value,
+            Comparison::LessOrEqual => metric <= value,
+            Comparison::GreaterThan => metric > value,
+            Comparison::GreaterOrEqual => metric >= value,
+            Comparison::Equal => metric == value,
+            Comparison::NotEqual => metric != value,
+        }
+    }
+}
+
+/// A test of the colony's state that decides whether a rule should fire.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Condition {
+    /// Compares the metric called `metric` to a fixed `value`.
+    ///
+    /// Metrics that have not been recorded never satisfy a comparison.
+    Compare {
+        /// The name of the metric.
+        metric: String,
+        /// How the metric is compared.
+        comparison: Comparison,
+        /// The value the metric is compared to.
+        value: f32,
+    },
+    /// Holds while the policy flag with this name is set.
+    Flag(String),
+    /// Holds if every condition holds, including when there are none.
+    All(Vec<Condition>),
+    /// Holds if at least one condition holds.
+    Any(Vec<Condition>),
+    /// Holds if the condition does not.
+    Not(Box<Condition>),
+}
+
+impl Condition {
+    /// Does this condition hold, given the current `metrics` and policy `flags`?
+    pub fn evaluate(&self, metrics: &ColonyMetrics, flags: &HashMap<String, bool>) -> bool {
+        match self {
+            Condition::Compare {
+                metric,
+                comparison,
+                value,
+            } => metrics
+                .get(metric)
+                .is_some_and(|current| comparison.holds(current, *value)),
+            Condition::Flag(flag) => flags.get(flag).copied().unwrap_or_default(),
+            Condition::All(conditions) => conditions
+                .iter()
+                .all(|condition| condition.evaluate(metrics, flags)),
+            Condition::Any(conditions) => conditions
+                .iter()
+                .any(|condition| condition.evaluate(metrics, flags)),
+            Condition::Not(condition) => !condition.evaluate(metrics, flags),
+        }
+    }
+}
+
+/// What happens when a rule fires.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RuleAction {
+    /// Adds an order to the [`ProductionQueue`] of every structure of this kind.
+    QueueProduction {
+        /// The name of the structure.
+        structure: String,
+        /// The name of the recipe to craft.
+        recipe: String,
+        /// The number of times to craft the recipe.
+        count: u32,
+    },
+    /// Sets or clears a policy flag, which other rules can check with [`Condition::Flag`].
+    SetFlag {
+        /// The name of the flag.
+        flag: String,
+        /// The new value of the flag.
+        value: bool,
+    },
+    /// Writes a message to the log.
+    Log(String),
+}
+
+/// A single condition-action rule.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColonyRule {
+    /// A human-readable name for the rule.
+    pub name: String,
+    /// When the rule should fire.
+    pub condition: Condition,
+    /// What the rule does when it fires.
+    pub action: RuleAction,
+    /// The minimum number of in-game days between firings.
+    ///
+    /// This must be positive and finite, so that a rule cannot fire on every census.
+    pub cooldown_days: f32,
+    /// The in-game day on which the rule last fired.
+    #[serde(default)]
+    last_fired: Option<f32>,
+}
+
+impl ColonyRule {
+    /// Creates a rule that has never fired.
+    pub fn new(
+        name: impl Into<String>,
+        condition: Condition,
+        action: RuleAction,
+        cooldown_days: f32,
+    ) -> Self {
+        ColonyRule {
+            name: name.into(),
+            condition,
+            action,
+            cooldown_days,
+            last_fired: None,
+        }
+    }
+
+    /// Has the cooldown for this rule elapsed by `elapsed_days`?
+    fn is_ready(&self, elapsed_days: f32) -> bool {
+        self.last_fired
+            .is_none_or(|last_fired| elapsed_days - last_fired >= self.cooldown_days)
+    }
+
+    /// Checks that the cooldown is positive and finite.
+    fn validate(&self) -> Result<(), ColonyRulesError> {
+        if self.cooldown_days.is_finite() && self.cooldown_days > 0. {
+            Ok(())
+        } else {
+            Err(ColonyRulesError::InvalidCooldown {
+                rule: self.name.clone(),
+                cooldown_days: self.cooldown_days,
+            })
+        }
+    }
+}
+
+/// The player's list of colony rules, checked in order.
+///
+/// Rules can be written by hand in JSON, and are saved along with their cooldowns.
+#[derive(Resource, Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColonyRules {
+    /// The rules, in the order they are checked
+    rules: Vec<ColonyRule>,
+    /// The policy flags set by [`RuleAction::SetFlag`]
+    #[serde(default)]
+    flags: HashMap<String, bool>,
+}
+
+/// The rules themselves are kept for the next map, but their flags and cooldowns describe the current colony.
+impl SimResource for ColonyRules {
+    const NAME: &'static str = "colony_rules";
+    const PERSIST: bool = true;
+
+    fn reset_to_default(&mut self) {
+        self.flags.clear();
+        for rule in &mut self.rules {
+            rule.last_fired = None;
+        }
+    }
+
+    fn measure_memory(&self) -> Option<MemoryUsage> {
+        Some(MemoryUsage::of_vec(&self.rules) + MemoryUsage::of_hash_map(&self.flags))
+    }
+}
+
+/// An error encountered while loading [`ColonyRules`].
+#[derive(Debug)]
+pub enum ColonyRulesError {
+    /// The file could not be read.
+    Io(std::io::Error),
+    /// The file is not valid JSON, or does not describe a list of rules.
+    Parse(serde_json::Error),
+    /// A rule's cooldown was zero, negative or not a number.
+    InvalidCooldown {
+        /// The name of the rule.
+        rule: String,
+        /// The invalid cooldown, in days.
+        cooldown_days: f32,
+    },
+}
+
+impl Display for ColonyRulesError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ColonyRulesError::Io(error) => write!(f, "could not read the colony rules: {error}"),
+            ColonyRulesError::Parse(error) => {
+                write!(f, "could not parse the colony rules: {error}")
+            }
+            ColonyRulesError::InvalidCooldown {
+                rule,
+                cooldown_days,
+            } => write!(
+                f,
+                "the cooldown of rule {rule} must be a positive number of days, but was {cooldown_days}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ColonyRulesError {}
+
+impl From<std::io::Error> for ColonyRulesError {
+    fn from(error: std::io::Error) -> Self {
+        ColonyRulesError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for ColonyRulesError {
+    fn from(error: serde_json::Error) -> Self {
+        ColonyRulesError::Parse(error)
+    }
+}
+
+impl ColonyRules {
+    /// Loads the rules from the JSON file at `path`.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ColonyRulesError> {
+        let contents = std::fs::read_to_string(path)?;
+        ColonyRules::from_json(&contents)
+    }
+
+    /// Parses the rules from a JSON string, and checks that they are valid.
+    pub fn from_json(json: &str) -> Result<Self, ColonyRulesError> {
+        let rules: ColonyRules = serde_json::from_str(json)?;
+        for rule in &rules.rules {
+            rule.validate()?;
+        }
+
+        Ok(rules)
+    }
+
+    /// Adds a rule to the end of the list.
+    ///
+    /// Fails if the rule's cooldown is not positive and finite.
+    pub fn push(&mut self, rule: ColonyRule) -> Result<(), ColonyRulesError> {
+        rule.validate()?;
+        self.rules.push(rule);
+        Ok(())
+    }
+
+    /// Removes and returns the rule at `index`, if there is one.
+    pub fn remove(&mut self, index: usize) -> Option<ColonyRule> {
+        (index < self.rules.len()).then(|| self.rules.remove(index))
+    }
+
+    /// The rules, in the order they are checked.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = &ColonyRule> {
+        self.rules.iter()
+    }
+
+    /// Is the policy flag called `flag` set?
+    pub fn flag(&self, flag: &str) -> bool {
+        self.flags.get(flag).copied().unwrap_or_default()
+    }
+
+    /// Checks each rule in order, and returns the actions of every rule that fires.
+    ///
+    /// Flags are set as soon as their rule fires, so later rules in the list see the new value.
+    pub fn evaluate(&mut self, metrics: &ColonyMetrics, elapsed_days: f32) -> Vec<RuleAction> {
+        let mut fired = Vec::new();
+
+        for rule in &mut self.rules {
+            if !rule.is_ready(elapsed_days) || !rule.condition.evaluate(metrics, &self.flags) {
+                continue;
+            }
+
+            rule.last_fired = Some(elapsed_days);
+            if let RuleAction::SetFlag { flag, value } = &rule.action {
+                self.flags.insert(flag.clone(), *value);
+            }
+            fired.push(rule.action.clone());
+        }
+
+        fired
+    }
+}
+
+/// Checks the [`ColonyRules`] against the latest [`ColonyMetrics`], and carries out any actions.
+///
+/// Production orders are dropped when a structure's [`ProductionQueue`] is already full,
+/// so a rule whose condition stays true cannot grow the queue without bound.
+pub(crate) fn apply_colony_rules(
+    mut colony_rules: ResMut<ColonyRules>,
+    metrics: Res<ColonyMetrics>,
+    in_game_time: Res<InGameTime>,
+    mut queue_query: Query<(&Id<Structure>, &mut ProductionQueue)>,
+) {
+    for action in colony_rules.evaluate(&metrics, in_game_time.elapsed_days()) {
+        match action {
+            RuleAction::QueueProduction {
+                structure,
+                recipe,
+                count,
+            } => {
+                let structure_id = Id::<Structure>::from_name(structure);
+                let recipe_id = Id::<Recipe>::from_name(recipe);
+                for (&id, mut production_queue) in queue_query.iter_mut() {
+                    if id == structure_id && !production_queue.push(recipe_id, count) {
+                        debug!("The production queue of a {structure_id:?} is full");
+                    }
+                }
+            }
+            // Flags are set while the rules are evaluated
+            RuleAction::SetFlag { .. } => (),
+            RuleAction::Log(message) => info!("{message}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::simulation::time::advance_in_game_time;
+
+    use super::*;
+
+    /// Metrics with a single `value` for the metric called `name`.
+    fn metrics(name: &str, value: f32) -> ColonyMetrics {
+        let mut metrics = ColonyMetrics::default();
+        metrics.set(name, value);
+        metrics
+    }
+
+    /// A condition comparing the `ants` metric to `value`.
+    fn ants(comparison: Comparison, value: f32) -> Condition {
+        Condition::Compare {
+            metric: "ants".to_string(),
+            comparison,
+            value,
+        }
+    }
+
+    /// A rule that logs `message` whenever `condition` holds, at most once per `cooldown_days`.
+    fn log_rule(condition: Condition, message: &str, cooldown_days: f32) -> ColonyRule {
+        ColonyRule::new(
+            message,
+            condition,
+            RuleAction::Log(message.to_string()),
+            cooldown_days,
+        )
+    }
+
+    #[test]
+    fn comparisons() {
+        let cases = [
+            (Comparison::LessThan, [true, false, false]),
+            (Comparison::LessOrEqual, [true, true, false]),
+            (Comparison::GreaterThan, [false, false, true]),
+            (Comparison::GreaterOrEqual, [false, true, true]),
+            (Comparison::Equal, [false, true, false]),
+            (Comparison::NotEqual, [true, false, true]),
+        ];
+
+        for (comparison, expected) in cases {
+            for (metric, expected) in [4., 5., 6.].into_iter().zip(expected) {
+                assert_eq!(comparison.holds(metric, 5.), expected, "{comparison:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn missing_metrics_never_match() {
+        let no_metrics = ColonyMetrics::default();
+        let flags = HashMap::default();
+
+        assert!(!ants(Comparison::LessThan, 5.).evaluate(&no_metrics, &flags));
+        assert!(!ants(Comparison::NotEqual, 5.).evaluate(&no_metrics, &flags));
+    }
+
+    #[test]
+    fn combinators() {
+        let metrics = metrics("ants", 5.);
+        let flags = HashMap::default();
+        let yes = || ants(Comparison::Equal, 5.);
+        let no = || ants(Comparison::Equal, 6.);
+
+        assert!(Condition::All(vec![yes(), yes()]).evaluate(&metrics, &flags));
+        assert!(!Condition::All(vec![yes(), no()]).evaluate(&metrics, &flags));
+        assert!(Condition::All(Vec::new()).evaluate(&metrics, &flags));
+
+        assert!(Condition::Any(vec![no(), yes()]).evaluate(&metrics, &flags));
+        assert!(!Condition::Any(vec![no(), no()]).evaluate(&metrics, &flags));
+        assert!(!Condition::Any(Vec::new()).evaluate(&metrics, &flags));
+
+        assert!(Condition::Not(Box::new(no())).evaluate(&metrics, &flags));
+        assert!(!Condition::Not(Box::new(yes())).evaluate(&metrics, &flags));
+
+        // Combinators nest
+        let nested = Condition::Any(vec![no(), Condition::All(vec![yes(), yes()])]);
+        assert!(nested.evaluate(&metrics, &flags));
+    }
+
+    #[test]
+    fn flags_are_visible_to_later_rules() {
+        let mut rules = ColonyRules::default();
+        rules
+            .push(ColonyRule::new(
+                "alarm",
+                ants(Comparison::LessThan, 3.),
+                RuleAction::SetFlag {
+                    flag: "famine".to_string(),
+                    value: true,
+                },
+                1.,
+            ))
+            .unwrap();
+        rules
+            .push(log_rule(
+                Condition::Flag("famine".to_string()),
+                "famine",
+                1.,
+            ))
+            .unwrap();
+
+        let fired = rules.evaluate(&metrics("ants", 2.), 0.);
+        assert_eq!(fired.len(), 2);
+        assert!(rules.flag("famine"));
+    }
+
+    #[test]
+    fn cooldowns_suppress_repeated_firing() {
+        let mut rules = ColonyRules::default();
+        rules
+            .push(log_rule(ants(Comparison::GreaterThan, 0.), "ants!", 1.))
+            .unwrap();
+        let metrics = metrics("ants", 1.);
+
+        assert_eq!(rules.evaluate(&metrics, 0.).len(), 1);
+        assert!(rules.evaluate(&metrics, 0.5).is_empty());
+        assert!(rules.evaluate(&metrics, 0.99).is_empty());
+        assert_eq!(rules.evaluate(&metrics, 1.).len(), 1);
+    }
+
+    #[test]
+    fn rules_round_trip_through_json() {
+        let mut rules = ColonyRules::default();
+        rules
+            .push(log_rule(
+                Condition::All(vec![
+                    ants(Comparison::GreaterOrEqual, 10.),
+                    Condition::Not(Box::new(Condition::Flag("paused".to_string()))),
+                ]),
+                "lots of ants",
+                2.,
+            ))
+            .unwrap();
+        // Cooldowns are saved too
+        rules.evaluate(&metrics("ants", 10.), 3.);
+
+        let json = serde_json::to_string(&rules).unwrap();
+        assert_eq!(ColonyRules::from_json(&json).unwrap(), rules);
+    }
+
+    #[test]
+    fn rules_can_be_written_by_hand() {
+        let json = r#"{
+            "rules": [{
+                "name": "more mulch",
+                "condition": { "Compare": { "metric": "items.mulch", "comparison": "LessThan", "value": 5.0 } },
+                "action": { "QueueProduction": { "structure": "leuco_chunk", "recipe": "mulch", "count": 2 } },
+                "cooldown_days": 1.0
+            }]
+        }"#;
+
+        let rules = ColonyRules::from_json(json).unwrap();
+        assert_eq!(rules.iter().len(), 1);
+        assert!(matches!(
+            ColonyRules::from_json("{ \"rules\": 3 }"),
+            Err(ColonyRulesError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn cooldowns_must_be_positive_and_finite() {
+        let mut rules = ColonyRules::default();
+        for cooldown_days in [0., -1., f32::NAN, f32::INFINITY] {
+            assert!(matches!(
+                rules.push(log_rule(
+                    ants(Comparison::GreaterThan, 0.),
+                    "spam",
+                    cooldown_days
+                )),
+                Err(ColonyRulesError::InvalidCooldown { .. })
+            ));
+        }
+        assert_eq!(rules.iter().len(), 0);
+
+        // Rules without a cooldown cannot be loaded either
+        let json = r#"{
+            "rules": [{
+                "name": "spam",
+                "condition": { "Flag": "always" },
+                "action": { "Log": "spam" }
+            }]
+        }"#;
+        assert!(ColonyRules::from_json(json).is_err());
+    }
+
+    #[test]
+    fn queued_production_is_capped() {
+        let mut app = App::new();
+        app.insert_resource(metrics("ants", 1.))
+            .init_resource::<InGameTime>()
+            .add_system(apply_colony_rules);
+
+        let mut rules = ColonyRules::default();
+        rules
+            .push(ColonyRule::new(
+                "always",
+                ants(Comparison::GreaterThan, 0.),
+                RuleAction::QueueProduction {
+                    structure: "leuco_chunk".to_string(),
+                    recipe: "mulch".to_string(),
+                    count: 1,
+                },
+                // Fires every time in this test, as in-game time never advances
+                f32::MIN_POSITIVE,
+            ))
+            .unwrap();
+        app.insert_resource(rules);
+        let structure = app
+            .world
+            .spawn((
+                Id::<Structure>::from_name("leuco_chunk".to_string()),
+                ProductionQueue::default(),
+            ))
+            .id();
+
+        for _ in 0..2 * ProductionQueue::MAX_ORDERS {
+            // Forget when the rule last fired, so that it fires again
+            let mut rules = app.world.resource_mut::<ColonyRules>();
+            rules.rules[0].last_fired = None;
+            app.update();
+        }
+
+        let queue = app.world.get::<ProductionQueue>(structure).unwrap();
+        assert_eq!(queue.iter().len(), ProductionQueue::MAX_ORDERS);
+    }
+
+    #[test]
+    fn rules_queue_production_once_per_cooldown() {
+        let mut app = App::new();
+        // Each update advances the clock by half a day
+        let seconds_per_day = InGameTime::default().seconds_per_day();
+        app.init_resource::<ColonyMetrics>()
+            .init_resource::<InGameTime>()
+            .insert_resource(FixedTime::new_from_secs(seconds_per_day / 2.))
+            .add_systems((advance_in_game_time, apply_colony_rules).chain());
+
+        let mut rules = ColonyRules::default();
+        rules
+            .push(ColonyRule::new(
+                "restock",
+                Condition::Compare {
+                    metric: "items.mulch".to_string(),
+                    comparison: Comparison::LessThan,
+                    value: 5.,
+                },
+                RuleAction::QueueProduction {
+                    structure: "leuco_chunk".to_string(),
+                    recipe: "mulch".to_string(),
+                    count: 1,
+                },
+                1.,
+            ))
+            .unwrap();
+        app.insert_resource(rules);
+
+        let structure = app
+            .world
+            .spawn((
+                Id::<Structure>::from_name("leuco_chunk".to_string()),
+                ProductionQueue::default(),
+            ))
+            .id();
+        // Other kinds of structures are left alone
+        let other = app
+            .world
+            .spawn((
+                Id::<Structure>::from_name("acacia".to_string()),
+                ProductionQueue::default(),
+            ))
+            .id();
+
+        /// The number of orders queued at `entity`.
+        fn queued(app: &App, entity: Entity) -> usize {
+            app.world
+                .get::<ProductionQueue>(entity)
+                .unwrap()
+                .iter()
+                .len()
+        }
+
+        // Plenty of mulch: nothing happens
+        app.world
+            .resource_mut::<ColonyMetrics>()
+            .set("items.mulch", 10.);
+        app.update();
+        assert_eq!(queued(&app, structure), 0);
+
+        // Running low: one order is queued
+        app.world
+            .resource_mut::<ColonyMetrics>()
+            .set("items.mulch", 2.);
+        app.update();
+        assert_eq!(queued(&app, structure), 1);
+
+        // Still low, but the rule is cooling down
+        app.update();
+        assert_eq!(queued(&app, structure), 1);
+
+        // Once the cooldown has elapsed, another order is queued
+        app.update();
+        assert_eq!(queued(&app, structure), 2);
+        assert_eq!(queued(&app, other), 0);
+    }
+}
@@ -15,6 +15,7 @@ use crate::world_gen::WorldGenState;
 
 pub(crate) mod camera;
 pub(crate) mod clipboard;
+pub mod colony_rules;
 pub(crate) mod picking;
 pub(crate) mod selection;
 
@@ -30,6 +31,7 @@ impl Plugin for InteractionPlugin {
             .add_plugin(picking::PickingPlugin)
             .add_plugin(selection::SelectionPlugin)
             .add_plugin(clipboard::ClipboardPlugin)
+            .add_plugin(colony_rules::ColonyRulesPlugin)
             .configure_set(PlayerModifiesWorld.run_if(in_state(WorldGenState::Complete)));
 
         #[cfg(feature = "debug_tools")]
@@ -106,6 +108,8 @@ pub(crate) enum PlayerAction {
     RotateClipboardRight,
     /// Snaps the camera to the selected object
     CenterCameraOnSelection,
+    /// Toggles whether the camera follows the selected unit
+    ToggleCameraFollow,
     /// Drag the camera with the cursor
     DragCamera,
     /// Move the camera from side to side
@@ -134,6 +138,10 @@ pub(crate) enum PlayerAction {
     ToggleWaterTableOverlay,
     /// Show / hide the light overlay
     ToggleLightOverlay,
+    /// Copies a bug report describing the current world
+    CopyBugReport,
+    /// Opens or closes the encyclopedia
+    ToggleEncyclopedia,
 }
 
 impl PlayerAction {
@@ -159,6 +167,7 @@ impl PlayerAction {
             RotateClipboardLeft => UserInput::modified(Modifier::Shift, KeyCode::R),
             RotateClipboardRight => KeyCode::R.into(),
             CenterCameraOnSelection => KeyCode::L.into(),
+            ToggleCameraFollow => KeyCode::C.into(),
             DragCamera => MouseButton::Middle.into(),
             Pan => VirtualDPad::wasd().into(),
             MoveCursor => VirtualDPad::arrow_keys().into(),
@@ -175,6 +184,8 @@ impl PlayerAction {
             ToggleStrongestSignalOverlay => KeyCode::F3.into(),
             ToggleWaterTableOverlay => KeyCode::F4.into(),
             ToggleLightOverlay => KeyCode::F5.into(),
+            CopyBugReport => KeyCode::F12.into(),
+            ToggleEncyclopedia => KeyCode::F6.into(),
         }
     }
 
@@ -206,6 +217,7 @@ impl PlayerAction {
             RotateClipboardLeft => DPadLeft.into(),
             RotateClipboardRight => DPadRight.into(),
             CenterCameraOnSelection => GamepadButtonType::LeftThumb.into(),
+            ToggleCameraFollow => UserInput::chord([camera_modifier, Start]),
             DragCamera => GamepadButtonType::RightThumb.into(),
             Pan => DualAxis::left_stick().into(),
             MoveCursor => DualAxis::right_stick().into(),
@@ -221,6 +233,8 @@ impl PlayerAction {
             ToggleStrongestSignalOverlay => UserInput::chord([infovis_modifier, DPadRight]),
             ToggleWaterTableOverlay => UserInput::chord([infovis_modifier, DPadDown]),
             ToggleLightOverlay => UserInput::chord([infovis_modifier, DPadUp]),
+            CopyBugReport => UserInput::chord([infovis_modifier, Start]),
+            ToggleEncyclopedia => UserInput::chord([infovis_modifier, Select]),
         }
     }
 
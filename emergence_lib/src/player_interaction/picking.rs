@@ -5,7 +5,12 @@ use bevy_mod_raycast::{DefaultRaycastingPlugin, RaycastMethod, RaycastSource, Ra
 use leafwing_input_manager::prelude::ActionState;
 
 use super::{InteractionSystem, PlayerAction};
-use crate::{asset_management::manifest::Id, geometry::VoxelPos, units::unit_manifest::Unit};
+use crate::{
+    asset_management::manifest::Id,
+    geometry::VoxelPos,
+    simulation::sim_resources::{SimResource, SimResourceAppExt},
+    units::unit_manifest::Unit,
+};
 
 /// Controls raycasting.
 pub(super) struct PickingPlugin;
@@ -13,6 +18,8 @@ pub(super) struct PickingPlugin;
 impl Plugin for PickingPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<CursorPos>()
+            .register_sim_resource::<CursorPos>()
+            .init_resource::<PointerOverUi>()
             .add_plugin(DefaultRaycastingPlugin::<PickableVoxel>::default())
             .add_plugin(DefaultRaycastingPlugin::<Unit>::default())
             .add_system(
@@ -21,8 +28,10 @@ impl Plugin for PickingPlugin {
                     .in_base_set(CoreSet::First),
             )
             .add_system(move_cursor_manually.in_base_set(CoreSet::PreUpdate))
-            .add_system(
-                update_cursor_pos
+            .add_system(update_pointer_over_ui.before(InteractionSystem::ComputeCursorPos))
+            .add_systems(
+                (update_cursor_pos, suppress_picking_over_ui)
+                    .chain()
                     .in_set(InteractionSystem::ComputeCursorPos)
                     .after(InteractionSystem::MoveCamera),
             );
@@ -30,7 +39,8 @@ impl Plugin for PickingPlugin {
 }
 
 /// The position of the mouse cursor and what it is hovering over.
-#[derive(Resource, Default, Debug, Clone, Copy)]
+#[derive(Resource, Default, Debug, Clone, Copy, Reflect)]
+#[reflect(Resource)]
 pub(crate) struct CursorPos {
     /// The voxel that the cursor is pointing at, if any.
     voxel_pos: Option<VoxelPos>,
@@ -42,6 +52,19 @@ pub(crate) struct CursorPos {
     hovered_unit: Option<Entity>,
 }
 
+/// The hovered voxel and unit belong to the current map, so they are forgotten when a new map is generated.
+impl SimResource for CursorPos {
+    const NAME: &'static str = "cursor_pos";
+    const PERSIST: bool = false;
+
+    fn reset_to_default(&mut self) {
+        *self = CursorPos {
+            screen_pos: self.screen_pos,
+            ..Default::default()
+        };
+    }
+}
+
 impl CursorPos {
     /// Creates a new [`CursorPos`] with the given tile position.
     #[cfg(test)]
@@ -70,6 +93,39 @@ impl CursorPos {
     }
 }
 
+/// Is the cursor over a UI element that should block interaction with the world beneath it?
+///
+/// UI nodes opt in to this by having an [`Interaction`] component.
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct PointerOverUi(pub(crate) bool);
+
+/// Updates [`PointerOverUi`] based on the [`Interaction`] state of UI nodes.
+fn update_pointer_over_ui(
+    interaction_query: Query<&Interaction, With<Node>>,
+    mut pointer_over_ui: ResMut<PointerOverUi>,
+) {
+    let over_ui = interaction_query
+        .iter()
+        .any(|interaction| *interaction != Interaction::None);
+
+    if pointer_over_ui.0 != over_ui {
+        pointer_over_ui.0 = over_ui;
+    }
+}
+
+/// Stops tiles and units under UI panels from being hovered, and therefore selected.
+///
+/// The screen position is kept, so that cursor-anchored UI continues to work.
+fn suppress_picking_over_ui(
+    pointer_over_ui: Res<PointerOverUi>,
+    mut cursor_pos: ResMut<CursorPos>,
+) {
+    if pointer_over_ui.0 {
+        cursor_pos.voxel_pos = None;
+        cursor_pos.hovered_unit = None;
+    }
+}
+
 /// Updates the raycast with the cursor position
 ///
 /// This system was adapted from <https://github.com/aevyrie/bevy_mod_raycast/blob/79012e4c7b12896ccfed09a129d163726d3a6516/examples/mouse_picking.rs#L45>
@@ -155,3 +211,40 @@ fn move_cursor_manually(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An app that only tracks whether the pointer is over the UI.
+    fn pointer_app() -> App {
+        let mut app = App::new();
+        app.init_resource::<PointerOverUi>()
+            .insert_resource(CursorPos::new(VoxelPos::ZERO))
+            .add_systems((update_pointer_over_ui, suppress_picking_over_ui).chain());
+        app
+    }
+
+    #[test]
+    fn hovering_a_panel_blocks_picking() {
+        let mut app = pointer_app();
+        app.world.spawn((Node::default(), Interaction::Hovered));
+        app.update();
+
+        assert!(app.world.resource::<PointerOverUi>().0);
+        assert_eq!(app.world.resource::<CursorPos>().maybe_voxel_pos(), None);
+    }
+
+    #[test]
+    fn panels_that_are_not_hovered_do_not_block_picking() {
+        let mut app = pointer_app();
+        app.world.spawn((Node::default(), Interaction::None));
+        app.update();
+
+        assert!(!app.world.resource::<PointerOverUi>().0);
+        assert_eq!(
+            app.world.resource::<CursorPos>().maybe_voxel_pos(),
+            Some(VoxelPos::ZERO)
+        );
+    }
+}
@@ -10,6 +10,7 @@ use leafwing_input_manager::prelude::ActionState;
 use crate::geometry::MapGeometry;
 use crate::geometry::VoxelObject;
 use crate::geometry::VoxelPos;
+use crate::simulation::sim_resources::{SimResource, SimResourceAppExt};
 
 use crate as emergence_lib;
 
@@ -22,8 +23,10 @@ pub(super) struct SelectionPlugin;
 impl Plugin for SelectionPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<CurrentSelection>()
+            .register_sim_resource::<CurrentSelection>()
             .init_resource::<SelectionState>()
             .init_resource::<HoveredTiles>()
+            .register_sim_resource::<HoveredTiles>()
             .add_system(
                 set_selection
                     .in_set(InteractionSystem::SelectTiles)
@@ -39,7 +42,7 @@ impl Plugin for SelectionPlugin {
 }
 
 /// The set of voxels that are currently selected
-#[derive(Debug, Default, Clone, PartialEq, Eq, Deref, DerefMut)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deref, DerefMut, Reflect, FromReflect)]
 pub(crate) struct SelectedVoxels {
     /// The underlying set of voxels
     selected: HashSet<VoxelPos>,
@@ -156,6 +159,16 @@ pub(crate) struct HoveredTiles {
     hovered: HashSet<Hex>,
 }
 
+/// The hovered tiles are on the current map, so they are cleared when a new map is generated.
+impl SimResource for HoveredTiles {
+    const NAME: &'static str = "hovered_tiles";
+    const PERSIST: bool = false;
+
+    fn reset_to_default(&mut self) {
+        self.hovered.clear();
+    }
+}
+
 impl HoveredTiles {
     /// Updates the set of hovered actions based on the current cursor position and player inputs.
     fn update(&mut self, hovered_tile: VoxelPos, selection_state: &SelectionState) {
@@ -255,7 +268,8 @@ fn update_selection_radius(
 }
 
 /// The game object(s) currently selected for inspection.
-#[derive(Resource, Debug, Default)]
+#[derive(Resource, Debug, Default, Reflect)]
+#[reflect(Resource)]
 pub(crate) enum CurrentSelection {
     /// One or more tile is selected.
     ///
@@ -268,6 +282,16 @@ pub(crate) enum CurrentSelection {
     None,
 }
 
+/// The selection refers to entities on the current map, so it is cleared when a new map is generated.
+impl SimResource for CurrentSelection {
+    const NAME: &'static str = "current_selection";
+    const PERSIST: bool = false;
+
+    fn reset_to_default(&mut self) {
+        *self = CurrentSelection::None;
+    }
+}
+
 impl CurrentSelection {
     /// Returns the set of terrain tiles that should be affected by actions.
     pub(crate) fn relevant_tiles(&self, cursor_pos: &CursorPos) -> SelectedVoxels {
@@ -15,15 +15,21 @@ use crate::water::WaterDepth;
 use bevy::{prelude::*, utils::HashMap};
 use core::ops::{Add, AddAssign, Mul, Sub, SubAssign};
 use emergence_macros::IterableEnum;
+use hexx::{Direction, Hex};
 use itertools::Itertools;
 use rand::seq::SliceRandom;
 use rayon::prelude::*;
+use std::hash::{Hash, Hasher};
 use std::ops::{Div, DivAssign, MulAssign};
 
 use crate::asset_management::manifest::Id;
-use crate::geometry::{Facing, Height, MapGeometry, VoxelPos};
-use crate::simulation::SimulationSet;
+use crate::geometry::{Facing, Height, MapGeometry, VoxelPos, MAP_LAYOUT};
+use crate::simulation::phases::{SimulationAppExt, TickPhase};
+use crate::simulation::sim_resources::{SimResource, SimResourceAppExt};
+use crate::simulation::weather::Wind;
 use crate::units::goals::Goal;
+use crate::utils::collections::{ordered_iter, StableHashMap};
+use crate::utils::memory::{MemoryFootprint, MemoryUsage};
 
 /// The fraction of signals in each cell that will move to each of 6 neighbors each frame.
 ///
@@ -39,13 +45,14 @@ pub(crate) struct SignalsPlugin;
 
 impl Plugin for SignalsPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<Signals>().add_systems(
-            (emit_signals, diffuse_signals, degrade_signals)
-                .chain()
-                .in_set(ManageSignals)
-                .in_set(SimulationSet)
-                .in_schedule(CoreSchedule::FixedUpdate),
-        );
+        app.init_resource::<Signals>()
+            .register_sim_resource::<Signals>()
+            .add_simulation_systems(
+                TickPhase::Perception,
+                (emit_signals, diffuse_signals, degrade_signals)
+                    .chain()
+                    .in_set(ManageSignals),
+            );
     }
 }
 
@@ -54,10 +61,15 @@ impl Plugin for SignalsPlugin {
 pub(crate) struct ManageSignals;
 
 /// The central resource that tracks all signals.
-#[derive(Resource, Debug, Default)]
+///
+/// Diffusion sums floating point values in the order that the maps are iterated,
+/// so they use a [`StableHashMap`] to get the same results in every run.
+#[derive(Resource, Debug, Default, Clone, Reflect)]
+#[reflect(Resource)]
 pub struct Signals {
     /// The spatialized map for each signal
-    maps: HashMap<SignalType, SignalMap>,
+    #[reflect(ignore)]
+    maps: StableHashMap<SignalType, SignalMap>,
 }
 
 impl Signals {
@@ -78,6 +90,22 @@ impl Signals {
             .any(|signal_type| self.get(*signal_type, voxel_pos) > SignalStrength::ZERO)
     }
 
+    /// Returns each [`VoxelPos`] where `signal_type` is at least as strong as `threshold`, along with its strength.
+    ///
+    /// The positions are returned in an arbitrary order.
+    pub fn positions_at_least(
+        &self,
+        signal_type: SignalType,
+        threshold: SignalStrength,
+    ) -> impl Iterator<Item = (VoxelPos, SignalStrength)> + '_ {
+        self.maps
+            .get(&signal_type)
+            .into_iter()
+            .flat_map(|map| map.current.iter())
+            .filter(move |(_, &strength)| strength >= threshold)
+            .map(|(&voxel_pos, &strength)| (voxel_pos, strength))
+    }
+
     /// Adds `signal_strength` of `signal_type` at `voxel_pos`.
     pub fn add_signal(
         &mut self,
@@ -131,37 +159,92 @@ impl Signals {
         strongest_signal.map(|signal_type| (signal_type, strongest_strength))
     }
 
-    /// Returns the adjacent, empty tile position that contains the highest sum signal strength that can be used to meet the provided `goal`.
+    /// Returns the direction of steepest increase of `signal_type` at `voxel_pos`, along with the slope in that direction.
+    ///
+    /// The slope is measured in signal strength per world unit.
+    /// If the signal is flat at this location, [`None`] will be returned instead.
+    ///
+    /// See [`SignalMap::gradient`] for details on how this is estimated.
+    pub fn gradient(
+        &self,
+        voxel_pos: VoxelPos,
+        signal_type: SignalType,
+        map_geometry: &MapGeometry,
+    ) -> Option<(Direction, f32)> {
+        self.maps
+            .get(&signal_type)?
+            .gradient(voxel_pos, map_geometry)
+    }
+
+    /// Computes [`Signals::gradient`] for each of the provided `positions`.
+    ///
+    /// The signal map is only looked up once, making this cheaper than repeated calls when processing many units at once.
+    /// The returned [`Vec`] has the same length and ordering as `positions`.
+    pub fn gradients(
+        &self,
+        positions: &[VoxelPos],
+        signal_type: SignalType,
+        map_geometry: &MapGeometry,
+    ) -> Vec<Option<(Direction, f32)>> {
+        match self.maps.get(&signal_type) {
+            Some(signal_map) => positions
+                .iter()
+                .map(|&voxel_pos| signal_map.gradient(voxel_pos, map_geometry))
+                .collect(),
+            None => vec![None; positions.len()],
+        }
+    }
+
+    /// Returns the adjacent, empty tile that best continues uphill along the signals relevant to `goal`.
+    ///
+    /// The direction of travel blends the [`gradient`](Self::gradient) of each of these signals
+    /// with the direction the unit is `facing`.
+    /// A `momentum` of 0 follows the gradient exactly, while values closer to 1 favor continuing straight ahead,
+    /// which smooths out the zig-zags caused by noisy signals.
+    /// Only tiles with a stronger signal than `voxel_pos` are considered, so units never head downhill.
     ///
     /// If no suitable tile exists, [`None`] will be returned instead.
     pub(crate) fn upstream(
         &self,
         voxel_pos: VoxelPos,
         goal: &Goal,
+        facing: Direction,
+        momentum: f32,
         item_manifest: &ItemManifest,
         map_geometry: &MapGeometry,
     ) -> Option<VoxelPos> {
-        let mut best_choice: Option<VoxelPos> = None;
-        let mut best_score = SignalStrength::ZERO;
-
-        for (possible_tile, current_score) in
-            self.relevant_neighboring_signals(voxel_pos, goal, item_manifest, map_geometry)
-        {
-            if current_score > best_score {
-                best_score = current_score;
-                best_choice = Some(possible_tile);
-            }
-        }
-
-        if let Some(best_tile_pos) = best_choice {
-            if best_tile_pos == voxel_pos {
-                None
-            } else {
-                best_choice
-            }
-        } else {
-            None
-        }
+        let samples =
+            self.relevant_neighboring_signals(voxel_pos, goal, item_manifest, map_geometry);
+        let center_strength = *samples.get(&voxel_pos)?;
+
+        // Gradients add, so the gradient of the summed signals is the sum of their gradients
+        let gradient: Vec2 = Self::relevant_signal_types(goal, item_manifest)
+            .into_iter()
+            .filter_map(|signal_type| self.gradient(voxel_pos, signal_type, map_geometry))
+            .map(|(direction, slope)| {
+                hex_offset(Hex::ZERO, Hex::ZERO.neighbor(direction)).normalize() * slope
+            })
+            .sum();
+        let momentum = momentum.clamp(0., 1.);
+        let heading = gradient.normalize_or_zero() * (1. - momentum)
+            + hex_offset(Hex::ZERO, Hex::ZERO.neighbor(facing)).normalize() * momentum;
+
+        Direction::ALL_DIRECTIONS
+            .into_iter()
+            .filter_map(|direction| {
+                let neighbor = map_geometry.walkable_neighbor_in_direction(voxel_pos, direction)?;
+                let strength = *samples.get(&neighbor)?;
+                (strength > center_strength).then_some((neighbor, strength))
+            })
+            // Ties, such as when the signal is too flat to have a gradient, go to the strongest signal
+            .max_by(|(a, a_strength), (b, b_strength)| {
+                let a_alignment = heading.dot(hex_offset(voxel_pos.hex, a.hex).normalize());
+                let b_alignment = heading.dot(hex_offset(voxel_pos.hex, b.hex).normalize());
+                a_alignment
+                    .total_cmp(&b_alignment)
+                    .then(a_strength.value().total_cmp(&b_strength.value()))
+            })
+            .map(|(neighbor, _)| neighbor)
     }
 
     /// Returns the adjacent, empty tile position that contains the lowest sum signal strength that can be used to meet the provided `goal`.
@@ -197,6 +280,29 @@ impl Signals {
         }
     }
 
+    /// Returns the types of signal that units follow to meet the provided `goal`.
+    fn relevant_signal_types(goal: &Goal, item_manifest: &ItemManifest) -> Vec<SignalType> {
+        match goal {
+            // Does not follow any signal
+            Goal::Wander { .. } => Vec::new(),
+            // Follows gradient of water depth instead of signal
+            Goal::Breathe => Vec::new(),
+            Goal::Fetch(item_kind)
+            | Goal::Eat(item_kind)
+            | Goal::Store(item_kind)
+            | Goal::Deliver(item_kind)
+            | Goal::Remove(item_kind) => SignalType::item_signal_types(
+                *item_kind,
+                item_manifest,
+                goal.delivery_mode().unwrap(),
+                goal.purpose(),
+            ),
+            Goal::Work(structure_id) => vec![SignalType::Work(*structure_id)],
+            Goal::Avoid(unit_id) => vec![SignalType::Unit(*unit_id)],
+            Goal::Demolish(structure_id) => vec![SignalType::Demolish(*structure_id)],
+        }
+    }
+
     /// Returns the strength of goal-relevant signals in neighboring tiles.
     fn relevant_neighboring_signals(
         &self,
@@ -205,48 +311,20 @@ impl Signals {
         item_manifest: &ItemManifest,
         map_geometry: &MapGeometry,
     ) -> HashMap<VoxelPos, SignalStrength> {
-        match goal {
-            // Does not follow any signal
-            Goal::Wander { .. } => HashMap::new(),
-            // Follows gradient of water depth instead of signal
-            Goal::Breathe => HashMap::new(),
-            Goal::Fetch(item_kind)
-            | Goal::Eat(item_kind)
-            | Goal::Store(item_kind)
-            | Goal::Deliver(item_kind)
-            | Goal::Remove(item_kind) => {
-                let relevant_signal_types = SignalType::item_signal_types(
-                    *item_kind,
-                    item_manifest,
-                    goal.delivery_mode().unwrap(),
-                    goal.purpose(),
-                );
-                let mut total_signals = HashMap::new();
-
-                for signal_type in relevant_signal_types {
-                    let signals = self.neighboring_signals(signal_type, voxel_pos, map_geometry);
-                    for (voxel_pos, signal_strength) in signals {
-                        if let Some(existing_signal_strength) = total_signals.get_mut(&voxel_pos) {
-                            *existing_signal_strength += signal_strength;
-                        } else {
-                            total_signals.insert(voxel_pos, signal_strength);
-                        }
-                    }
+        let mut total_signals = HashMap::new();
+
+        for signal_type in Self::relevant_signal_types(goal, item_manifest) {
+            let signals = self.neighboring_signals(signal_type, voxel_pos, map_geometry);
+            for (voxel_pos, signal_strength) in signals {
+                if let Some(existing_signal_strength) = total_signals.get_mut(&voxel_pos) {
+                    *existing_signal_strength += signal_strength;
+                } else {
+                    total_signals.insert(voxel_pos, signal_strength);
                 }
-                total_signals
-            }
-            Goal::Work(structure_id) => {
-                self.neighboring_signals(SignalType::Work(*structure_id), voxel_pos, map_geometry)
             }
-            Goal::Avoid(unit_id) => {
-                self.neighboring_signals(SignalType::Unit(*unit_id), voxel_pos, map_geometry)
-            }
-            Goal::Demolish(structure_id) => self.neighboring_signals(
-                SignalType::Demolish(*structure_id),
-                voxel_pos,
-                map_geometry,
-            ),
         }
+
+        total_signals
     }
 
     /// Returns the signal strength of the type `signal_type` in `voxel_pos` and its 6 surrounding neighbors.
@@ -268,7 +346,22 @@ impl Signals {
 
     /// Diffuses signals from one cell into the next
     pub fn diffuse(&mut self, map_geometry: &MapGeometry, diffusion_fraction: f32) {
+        self.diffuse_with_wind(map_geometry, diffusion_fraction, Wind::CALM);
+    }
+
+    /// Diffuses signals from one cell into the next, while the `wind` carries some of them downwind.
+    ///
+    /// The amount carried by the wind comes out of the same budget as ordinary diffusion,
+    /// so the total amount of signal leaving each tile is unchanged.
+    /// When the wind is calm, this is exactly equivalent to [`Signals::diffuse`].
+    pub fn diffuse_with_wind(
+        &mut self,
+        map_geometry: &MapGeometry,
+        diffusion_fraction: f32,
+        wind: Wind,
+    ) {
         assert!((0.0..=1.0 / 6.0).contains(&diffusion_fraction));
+        assert!((0.0..=1.0).contains(&wind.strength));
 
         self.maps
             .par_iter_mut()
@@ -280,18 +373,51 @@ impl Signals {
                 {
                     let amount_to_send_to_each_neighbor = *original_strength * diffusion_fraction;
 
-                    for neighbor in map_geometry.walkable_neighbors(occupied_tile) {
+                    let isotropic_amount = if wind.is_calm() {
+                        amount_to_send_to_each_neighbor
+                    } else {
+                        amount_to_send_to_each_neighbor * (1. - wind.strength)
+                    };
+
+                    // Signals flow against the direction of travel, so units can always follow them back to their source
+                    for neighbor in map_geometry.walkable_predecessors(occupied_tile) {
                         signal_map
                             .pending_addition
-                            .push((neighbor, amount_to_send_to_each_neighbor));
+                            .push((neighbor, isotropic_amount));
+                    }
+
+                    // Signal is never sent off the edge of the map, so none of it leaks away there
+                    let neighbors_on_map = Direction::ALL_DIRECTIONS
+                        .into_iter()
+                        .filter(|&direction| {
+                            map_geometry.is_valid(occupied_tile.hex.neighbor(direction))
+                        })
+                        .count();
+                    let mut amount_sent = isotropic_amount * neighbors_on_map as f32;
+
+                    if !wind.is_calm()
+                        && map_geometry.is_valid(occupied_tile.hex.neighbor(wind.direction))
+                    {
+                        let downwind_amount =
+                            amount_to_send_to_each_neighbor * (6.0 * wind.strength);
+                        amount_sent += downwind_amount;
+
+                        // Just like ordinary diffusion, signal blown into an impassable tile is lost
+                        if let Some(downwind) = map_geometry
+                            .walkable_predecessor_in_direction(occupied_tile, wind.direction)
+                        {
+                            signal_map
+                                .pending_addition
+                                .push((downwind, downwind_amount));
+                        }
                     }
-                    signal_map.pending_removal.push((
-                        occupied_tile,
-                        // Signal that goes out of bounds or into an impassable tile is lost
-                        // This is both a simplification and a performance optimization
-                        // But it also has a gameplay effect: it makes circuitous routes less efficient
-                        amount_to_send_to_each_neighbor * 6.0,
-                    ));
+
+                    // Signal that goes into an impassable tile is lost
+                    // This is both a simplification and a performance optimization
+                    // But it also has a gameplay effect: it makes circuitous routes less efficient
+                    signal_map
+                        .pending_removal
+                        .push((occupied_tile, amount_sent));
                 }
 
                 // We cannot do this in one step, as we need to avoid bizarre iteration order dependencies
@@ -307,6 +433,17 @@ impl Signals {
         keys.shuffle(&mut rng);
         keys.pop().copied()
     }
+
+    /// Feeds the strength of every signal into the `hasher`, sorted by signal type and position.
+    ///
+    /// Used to compute [`simulation_checksum`](crate::simulation::checksum::simulation_checksum).
+    pub(crate) fn hash_strengths(&self, hasher: &mut impl Hasher) {
+        for (signal_type, signal_map) in ordered_iter(&self.maps) {
+            for (voxel_pos, strength) in ordered_iter(&signal_map.current) {
+                (signal_type, voxel_pos, strength.0.to_bits()).hash(hasher);
+            }
+        }
+    }
 }
 
 /// All of the signals on a single tile.
@@ -355,17 +492,49 @@ impl LocalSignals {
     }
 }
 
+impl MemoryFootprint for Signals {
+    fn memory_usage(&self) -> MemoryUsage {
+        self.maps
+            .values()
+            .map(SignalMap::memory_usage)
+            .fold(MemoryUsage::of_hash_map(&self.maps), |total, usage| {
+                total + usage
+            })
+    }
+}
+
+impl SimResource for Signals {
+    const NAME: &'static str = "signals";
+    const PERSIST: bool = true;
+
+    fn reset_to_default(&mut self) {
+        *self = Signals::default();
+    }
+
+    fn measure_memory(&self) -> Option<MemoryUsage> {
+        Some(MemoryFootprint::memory_usage(self))
+    }
+}
+
 /// Stores the [`SignalStrength`] of the given [`SignalType`] at each [`VoxelPos`].
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 struct SignalMap {
     /// The current amount of signal at each location.
-    current: HashMap<VoxelPos, SignalStrength>,
+    current: StableHashMap<VoxelPos, SignalStrength>,
     /// The amount of signal that will be added to each location at the end of the frame.
     pending_addition: Vec<(VoxelPos, SignalStrength)>,
     /// The amount of signal that will be removed from each location at the end of the frame.
     pending_removal: Vec<(VoxelPos, SignalStrength)>,
 }
 
+impl MemoryFootprint for SignalMap {
+    fn memory_usage(&self) -> MemoryUsage {
+        MemoryUsage::of_hash_map(&self.current)
+            + MemoryUsage::of_vec(&self.pending_addition)
+            + MemoryUsage::of_vec(&self.pending_removal)
+    }
+}
+
 impl SignalMap {
     /// Returns the signal strength at the given [`VoxelPos`].
     ///
@@ -386,6 +555,48 @@ impl SignalMap {
             .or_insert(SignalStrength::ZERO)
     }
 
+    /// Estimates the direction of steepest increase in signal strength at `voxel_pos`, and the slope in that direction.
+    ///
+    /// The gradient is fit to the difference in strength between `voxel_pos` and each of its walkable neighbors,
+    /// as described in [`fit_gradient`].
+    /// Neighbors that cannot be walked to are left out, as signals cannot diffuse there either.
+    ///
+    /// Returns [`None`] if the slope is below [`SignalMap::FLAT_GRADIENT`].
+    fn gradient(
+        &self,
+        voxel_pos: VoxelPos,
+        map_geometry: &MapGeometry,
+    ) -> Option<(Direction, f32)> {
+        let gradient = fit_gradient(
+            voxel_pos,
+            self.get(voxel_pos).value(),
+            map_geometry
+                .walkable_neighbors(voxel_pos)
+                .map(|neighbor| (neighbor, self.get(neighbor).value())),
+        );
+
+        let slope = gradient.length();
+        if slope < Self::FLAT_GRADIENT {
+            return None;
+        }
+
+        let direction = Direction::ALL_DIRECTIONS
+            .into_iter()
+            .max_by(|a, b| {
+                let a = hex_offset(Hex::ZERO, Hex::ZERO.neighbor(*a));
+                let b = hex_offset(Hex::ZERO, Hex::ZERO.neighbor(*b));
+                a.normalize()
+                    .dot(gradient)
+                    .total_cmp(&b.normalize().dot(gradient))
+            })
+            .unwrap();
+
+        Some((direction, slope))
+    }
+
+    /// Slopes smaller than this are treated as a flat field by [`SignalMap::gradient`].
+    const FLAT_GRADIENT: f32 = 1e-6;
+
     /// Adds the `signal_strength` to the signal at `voxel_pos`.
     fn add_signal(&mut self, voxel_pos: VoxelPos, signal_strength: SignalStrength) {
         *self.get_mut(voxel_pos) += signal_strength
@@ -422,7 +633,7 @@ impl SignalMap {
 }
 
 /// The variety of signal.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Reflect, FromReflect)]
 pub enum SignalType {
     /// Take this item away from here.
     Push(ItemKind),
@@ -560,7 +771,7 @@ impl From<SignalType> for SignalKind {
 /// How strong a signal is.
 ///
 /// This has a minimum value of 0.
-#[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd, Reflect, FromReflect)]
 pub struct SignalStrength(f32);
 
 impl SignalStrength {
@@ -640,7 +851,8 @@ impl DivAssign<f32> for SignalStrength {
 /// The component that causes a game object to emit a signal.
 ///
 /// This can change over time, and multiple signals may be emitted at once.
-#[derive(Default, Component, Debug, Clone)]
+#[derive(Default, Component, Debug, Clone, Reflect, FromReflect)]
+#[reflect(Component)]
 pub(crate) struct Emitter {
     /// The list of signals to emit at a provided
     pub(crate) signals: Vec<(SignalType, SignalStrength)>,
@@ -723,8 +935,8 @@ fn emit_signals(
 }
 
 /// Spreads signals between tiles.
-fn diffuse_signals(mut signals: ResMut<Signals>, map_geometry: Res<MapGeometry>) {
-    signals.diffuse(&map_geometry, DIFFUSION_FRACTION);
+fn diffuse_signals(mut signals: ResMut<Signals>, map_geometry: Res<MapGeometry>, wind: Res<Wind>) {
+    signals.diffuse_with_wind(&map_geometry, DIFFUSION_FRACTION, *wind);
 }
 
 /// Degrades signals, allowing them to approach an asymptotically constant level.
@@ -762,8 +974,46 @@ fn degrade_signals(mut signals: ResMut<Signals>) {
     });
 }
 
+/// The world-space offset from the center of the tile at `from` to the center of the tile at `to`.
+fn hex_offset(from: Hex, to: Hex) -> Vec2 {
+    MAP_LAYOUT.hex_to_world_pos(to) - MAP_LAYOUT.hex_to_world_pos(from)
+}
+
+/// Estimates the gradient of a field at `center` from its value there and at some of its `neighbors`.
+///
+/// This is the least-squares fit of a linear field to the differences between each neighbor and the center,
+/// so missing neighbors (such as at the edge of the map) are left out without biasing the result.
+/// The returned vector points uphill, and its length is the slope in units per world unit.
+fn fit_gradient(
+    center: VoxelPos,
+    center_value: f32,
+    neighbors: impl IntoIterator<Item = (VoxelPos, f32)>,
+) -> Vec2 {
+    let mut second_moment = Mat2::ZERO;
+    let mut weighted_differences = Vec2::ZERO;
+
+    for (neighbor, value) in neighbors {
+        let offset = hex_offset(center.hex, neighbor.hex);
+        second_moment += Mat2::from_cols(offset * offset.x, offset * offset.y);
+        weighted_differences += offset * (value - center_value);
+    }
+
+    // When the neighbors all lie along a single line, only the slope along that line can be recovered
+    if second_moment.determinant().abs() <= f32::EPSILON {
+        let total_squared_distance = second_moment.x_axis.x + second_moment.y_axis.y;
+        return if total_squared_distance > 0. {
+            weighted_differences / total_squared_distance
+        } else {
+            Vec2::ZERO
+        };
+    }
+
+    second_moment.inverse() * weighted_differences
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::geometry::DiscreteHeight;
     use crate::items::item_manifest::ItemData;
 
     use super::*;
@@ -791,6 +1041,23 @@ mod tests {
         manifest
     }
 
+    #[test]
+    fn sparse_signal_maps_use_less_memory() {
+        let mut sparse = SignalMap::default();
+        *sparse.get_mut(VoxelPos::ZERO) = SignalStrength::new(1.);
+
+        let mut dense = SignalMap::default();
+        for hex in hexx::shapes::hexagon(Hex::ZERO, 10) {
+            *dense.get_mut(VoxelPos {
+                hex,
+                ..VoxelPos::ZERO
+            }) = SignalStrength::new(1.);
+        }
+
+        assert!(sparse.bytes() * 100 < dense.bytes());
+        assert!(sparse.memory_usage().allocated_bytes < dense.memory_usage().allocated_bytes);
+    }
+
     #[test]
     fn pending_additions_are_applied() {
         let mut signal_map = SignalMap::default();
@@ -875,6 +1142,8 @@ mod tests {
             signals.upstream(
                 VoxelPos::ZERO.above(),
                 &Goal::Store(test_item()),
+                Direction::Top,
+                0.,
                 &item_manifest,
                 &map_geometry
             ),
@@ -884,6 +1153,8 @@ mod tests {
             signals.upstream(
                 VoxelPos::ZERO.above(),
                 &Goal::Fetch(test_item()),
+                Direction::Top,
+                0.,
                 &item_manifest,
                 &map_geometry
             ),
@@ -893,6 +1164,8 @@ mod tests {
             signals.upstream(
                 VoxelPos::ZERO.above(),
                 &Goal::Work(WorkplaceId::structure(test_structure())),
+                Direction::Top,
+                0.,
                 &item_manifest,
                 &map_geometry
             ),
@@ -902,6 +1175,8 @@ mod tests {
             signals.upstream(
                 VoxelPos::ZERO.above(),
                 &Goal::default(),
+                Direction::Top,
+                0.,
                 &item_manifest,
                 &map_geometry
             ),
@@ -926,6 +1201,8 @@ mod tests {
             signals.upstream(
                 VoxelPos::ZERO.above(),
                 &Goal::Store(test_item()),
+                Direction::Top,
+                0.,
                 &item_manifest,
                 &map_geometry
             ),
@@ -954,6 +1231,8 @@ mod tests {
             signals.upstream(
                 VoxelPos::ZERO.above(),
                 &Goal::Fetch(test_item()),
+                Direction::Top,
+                0.,
                 &item_manifest,
                 &map_geometry
             ),
@@ -983,6 +1262,8 @@ mod tests {
             signals.upstream(
                 VoxelPos::ZERO.above(),
                 &Goal::Store(test_item()),
+                Direction::Top,
+                0.,
                 &item_manifest,
                 &map_geometry
             ),
@@ -1005,6 +1286,8 @@ mod tests {
             .upstream(
                 VoxelPos::ZERO.above(),
                 &Goal::Store(test_item()),
+                Direction::Top,
+                0.,
                 &item_manifest,
                 &map_geometry
             )
@@ -1032,12 +1315,264 @@ mod tests {
             .upstream(
                 VoxelPos::ZERO.above(),
                 &Goal::Store(test_item()),
+                Direction::Top,
+                0.,
                 &item_manifest,
                 &map_geometry
             )
             .is_some());
     }
 
+    /// Estimates the gradient at `voxel_pos` of a linear field with the provided `slope`.
+    fn linear_field_gradient(slope: Vec2, voxel_pos: VoxelPos, map_geometry: &MapGeometry) -> Vec2 {
+        let value_at = |voxel_pos: VoxelPos| slope.dot(MAP_LAYOUT.hex_to_world_pos(voxel_pos.hex));
+        let neighbors = map_geometry
+            .walkable_neighbors(voxel_pos)
+            .map(|neighbor| (neighbor, value_at(neighbor)));
+
+        fit_gradient(voxel_pos, value_at(voxel_pos), neighbors)
+    }
+
+    #[test]
+    fn gradient_follows_linear_field() {
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 3);
+        let slope = Vec2::new(0.3, -0.4);
+
+        let gradient = linear_field_gradient(slope, VoxelPos::ZERO.above(), &map_geometry);
+        assert!((gradient - slope).length() < 1e-4);
+    }
+
+    #[test]
+    fn gradient_is_zero_in_flat_field() {
+        let neighbors = Direction::ALL_DIRECTIONS
+            .into_iter()
+            .map(|direction| (VoxelPos::ZERO.neighbor(direction), 1.));
+
+        assert_eq!(fit_gradient(VoxelPos::ZERO, 1., neighbors), Vec2::ZERO);
+        assert_eq!(fit_gradient(VoxelPos::ZERO, 1., []), Vec2::ZERO);
+    }
+
+    #[test]
+    fn gradient_is_exact_at_the_edge_of_the_map() {
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 3);
+        let slope = Vec2::new(-0.2, 0.5);
+
+        // Tiles on the edge of the map are missing some of their neighbors
+        for voxel_pos in map_geometry.walkable_voxels() {
+            if map_geometry.walkable_neighbors(voxel_pos).count() == 6 {
+                continue;
+            }
+
+            let gradient = linear_field_gradient(slope, voxel_pos, &map_geometry);
+            assert!((gradient - slope).length() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn gradient_uses_the_slope_along_a_single_neighbor() {
+        let gradient = fit_gradient(
+            VoxelPos::ZERO,
+            1.,
+            [(VoxelPos::ZERO.neighbor(Direction::Top), 3.)],
+        );
+        let offset = hex_offset(Hex::ZERO, Hex::ZERO.neighbor(Direction::Top));
+
+        assert!((gradient.length() - 2. / offset.length()).abs() < 1e-4);
+        assert!(gradient.normalize().dot(offset.normalize()) > 0.999);
+    }
+
+    #[test]
+    fn signal_gradient_points_uphill() {
+        let mut signals = Signals::default();
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 3);
+        let signal_type = SignalType::Contains(test_item());
+
+        let uphill = Direction::TopLeft;
+        let slope = hex_offset(Hex::ZERO, Hex::ZERO.neighbor(uphill)).normalize() * 0.5;
+        for voxel_pos in map_geometry.walkable_voxels() {
+            let world_pos = MAP_LAYOUT.hex_to_world_pos(voxel_pos.hex);
+            // Offset to keep all signals positive
+            signals.add_signal(
+                signal_type,
+                voxel_pos,
+                SignalStrength::new(10. + slope.dot(world_pos)),
+            );
+        }
+
+        let (direction, magnitude) = signals
+            .gradient(VoxelPos::ZERO.above(), signal_type, &map_geometry)
+            .unwrap();
+        assert_eq!(direction, uphill);
+        assert!((magnitude - 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn signal_gradient_is_none_in_flat_field() {
+        let mut signals = Signals::default();
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 2);
+        let signal_type = SignalType::Contains(test_item());
+
+        // No signal at all
+        assert_eq!(
+            signals.gradient(VoxelPos::ZERO.above(), signal_type, &map_geometry),
+            None
+        );
+
+        // A signal of the same strength everywhere
+        for voxel_pos in map_geometry.walkable_voxels() {
+            signals.add_signal(signal_type, voxel_pos, SignalStrength::new(1.));
+        }
+        assert_eq!(
+            signals.gradient(VoxelPos::ZERO.above(), signal_type, &map_geometry),
+            None
+        );
+    }
+
+    #[test]
+    fn batched_gradients_match_individual_gradients() {
+        use crate::utils::collections::ordered;
+
+        let mut signals = Signals::default();
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 2);
+        let signal_type = SignalType::Contains(test_item());
+
+        let positions: Vec<VoxelPos> = ordered(map_geometry.walkable_voxels()).collect();
+        assert_eq!(
+            signals.gradients(&positions, signal_type, &map_geometry),
+            vec![None; positions.len()]
+        );
+
+        signals.add_signal(signal_type, VoxelPos::ZERO.above(), SignalStrength::new(1.));
+        signals.diffuse(&map_geometry, 0.1);
+
+        let batched = signals.gradients(&positions, signal_type, &map_geometry);
+        assert_eq!(batched.len(), positions.len());
+        assert!(batched.iter().any(Option::is_some));
+        for (voxel_pos, gradient) in positions.iter().zip(batched) {
+            assert_eq!(
+                gradient,
+                signals.gradient(*voxel_pos, signal_type, &map_geometry)
+            );
+        }
+    }
+
+    #[test]
+    fn upstream_follows_the_gradient_without_momentum() {
+        let mut signals = Signals::default();
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 3);
+        let structure_id = test_structure();
+        let goal = Goal::Work(WorkplaceId::Structure(structure_id));
+        let item_manifest = test_manifest();
+
+        let uphill = Direction::BottomRight;
+        let slope = hex_offset(Hex::ZERO, Hex::ZERO.neighbor(uphill)).normalize();
+        for voxel_pos in map_geometry.walkable_voxels() {
+            let world_pos = MAP_LAYOUT.hex_to_world_pos(voxel_pos.hex);
+            signals.add_signal(
+                SignalType::Work(WorkplaceId::Structure(structure_id)),
+                voxel_pos,
+                SignalStrength::new(10. + slope.dot(world_pos)),
+            );
+        }
+
+        let start = VoxelPos::ZERO.above();
+        for facing in Direction::ALL_DIRECTIONS {
+            assert_eq!(
+                signals.upstream(start, &goal, facing, 0., &item_manifest, &map_geometry),
+                Some(start.neighbor(uphill))
+            );
+        }
+
+        // Momentum never sends units downhill, even when they are facing that way
+        assert_eq!(
+            signals
+                .upstream(
+                    start,
+                    &goal,
+                    uphill.const_neg(),
+                    1.,
+                    &item_manifest,
+                    &map_geometry
+                )
+                .map(|voxel_pos| hex_offset(start.hex, voxel_pos.hex).dot(slope) > 0.),
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn momentum_reduces_zig_zagging_on_noisy_signals() {
+        use crate::utils::collections::ordered;
+        use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+        let mut signals = Signals::default();
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 20);
+        let structure_id = test_structure();
+        let goal = Goal::Work(WorkplaceId::Structure(structure_id));
+        let item_manifest = test_manifest();
+        let mut rng = SmallRng::seed_from_u64(42);
+
+        // Halfway between two hex directions, where units are most prone to zig-zagging
+        let slope = (hex_offset(Hex::ZERO, Hex::ZERO.neighbor(Direction::Top))
+            + hex_offset(Hex::ZERO, Hex::ZERO.neighbor(Direction::TopRight)))
+        .normalize()
+            * 0.5;
+        for voxel_pos in ordered(map_geometry.walkable_voxels()) {
+            let world_pos = MAP_LAYOUT.hex_to_world_pos(voxel_pos.hex);
+            let noise = rng.gen_range(-0.5..0.5);
+            signals.add_signal(
+                SignalType::Work(WorkplaceId::Structure(structure_id)),
+                voxel_pos,
+                SignalStrength::new(100. + slope.dot(world_pos) + noise),
+            );
+        }
+
+        // Start at the bottom of the slope
+        let start = ordered(map_geometry.walkable_voxels())
+            .min_by(|a, b| {
+                let a = slope.dot(MAP_LAYOUT.hex_to_world_pos(a.hex));
+                let b = slope.dot(MAP_LAYOUT.hex_to_world_pos(b.hex));
+                a.total_cmp(&b)
+            })
+            .unwrap();
+
+        let count_turns = |momentum: f32| {
+            let mut voxel_pos = start;
+            let mut facing = Direction::Top;
+            let mut turns = 0;
+
+            while let Some(next) = signals.upstream(
+                voxel_pos,
+                &goal,
+                facing,
+                momentum,
+                &item_manifest,
+                &map_geometry,
+            ) {
+                let direction = Direction::ALL_DIRECTIONS
+                    .into_iter()
+                    .find(|&direction| voxel_pos.hex.neighbor(direction) == next.hex)
+                    .unwrap();
+                if direction != facing {
+                    turns += 1;
+                }
+
+                facing = direction;
+                voxel_pos = next;
+            }
+
+            turns
+        };
+
+        assert!(count_turns(0.6) < count_turns(0.));
+    }
+
     #[test]
     fn item_signal_types_are_correct() {
         let item_kind = test_item();
@@ -1080,4 +1615,238 @@ mod tests {
             vec![SignalType::Pull(item_kind), SignalType::Stores(item_kind)]
         );
     }
+
+    fn total_strength(signals: &Signals, signal_type: SignalType) -> f32 {
+        signals.maps[&signal_type]
+            .current
+            .values()
+            .map(|strength| strength.value())
+            .sum()
+    }
+
+    fn centroid(signals: &Signals, signal_type: SignalType) -> Vec2 {
+        let weighted_sum: Vec2 = signals.maps[&signal_type]
+            .current
+            .iter()
+            .map(|(voxel_pos, strength)| {
+                MAP_LAYOUT.hex_to_world_pos(voxel_pos.hex) * strength.value()
+            })
+            .sum();
+
+        weighted_sum / total_strength(signals, signal_type)
+    }
+
+    #[test]
+    fn signals_lead_around_ledges() {
+        let mut signals = Signals::default();
+        let mut world = World::new();
+        let mut map_geometry = MapGeometry::new(&mut world, 1);
+        let signal_type = SignalType::Contains(test_item());
+
+        // The source sits on a plateau, with a ledge down to one side and a slope beside it
+        let hexes: Vec<Hex> = map_geometry.all_hexes().copied().collect();
+        for hex in hexes {
+            map_geometry.update_height(hex, DiscreteHeight(2));
+        }
+        let below_ledge = Hex::new(1, 0);
+        let slope = Hex::new(1, -1);
+        map_geometry.update_height(below_ledge, DiscreteHeight::ZERO);
+        map_geometry.update_height(slope, DiscreteHeight::ONE);
+
+        let standing_on = |hex: Hex| VoxelPos {
+            hex,
+            height: map_geometry.get_height(hex).unwrap().above(),
+        };
+        let source = standing_on(Hex::ZERO);
+        signals.add_signal(signal_type, source, SignalStrength(1.));
+
+        // Units below the ledge cannot climb it, so the signal does not spread straight down
+        signals.diffuse(&map_geometry, 0.1);
+        assert!(signals.get(signal_type, standing_on(slope)) > SignalStrength::ZERO);
+        assert_eq!(
+            signals.get(signal_type, standing_on(below_ledge)),
+            SignalStrength::ZERO
+        );
+
+        // Instead, it reaches the bottom of the ledge by way of the slope
+        signals.diffuse(&map_geometry, 0.1);
+        assert!(signals.get(signal_type, standing_on(below_ledge)) > SignalStrength::ZERO);
+        let strongest_neighbor = map_geometry
+            .walkable_neighbors(standing_on(below_ledge))
+            .max_by(|a, b| {
+                signals
+                    .get(signal_type, *a)
+                    .partial_cmp(&signals.get(signal_type, *b))
+                    .unwrap()
+            });
+        assert_eq!(strongest_neighbor, Some(standing_on(slope)));
+    }
+
+    #[test]
+    fn calm_wind_matches_isotropic_diffusion() {
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 4);
+        let signal_type = SignalType::Contains(test_item());
+
+        let mut isotropic = Signals::default();
+        isotropic.add_signal(signal_type, VoxelPos::ZERO.above(), SignalStrength(1.));
+        for neighbor in map_geometry.walkable_neighbors(VoxelPos::ZERO.above()) {
+            isotropic.add_signal(signal_type, neighbor, SignalStrength(0.3));
+        }
+        // The order of insertion changes the order in which floating point values are summed.
+        // Cloning keeps the order identical, so the results can be compared bit-for-bit.
+        let mut calm = isotropic.clone();
+        let calm_wind = Wind {
+            direction: Direction::Bottom,
+            strength: 0.,
+        };
+
+        for _ in 0..10 {
+            isotropic.diffuse(&map_geometry, 0.1);
+            calm.diffuse_with_wind(&map_geometry, 0.1, calm_wind);
+        }
+
+        assert_eq!(
+            isotropic.maps[&signal_type].current,
+            calm.maps[&signal_type].current
+        );
+    }
+
+    #[test]
+    fn diffusion_is_reproducible() {
+        use crate::utils::collections::ordered;
+
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 4);
+
+        // Built separately, rather than cloned, so each has its own hash map
+        let build_signals = || {
+            let mut signals = Signals::default();
+            for (i, voxel_pos) in ordered(map_geometry.walkable_voxels()).enumerate() {
+                let signal_type = if i % 2 == 0 {
+                    SignalType::Contains(test_item())
+                } else {
+                    SignalType::Work(WorkplaceId::Structure(test_structure()))
+                };
+                signals.add_signal(signal_type, voxel_pos, SignalStrength(0.1 * i as f32));
+            }
+            signals
+        };
+
+        let checksum = |signals: &Signals| {
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            signals.hash_strengths(&mut hasher);
+            hasher.finish()
+        };
+
+        let mut first = build_signals();
+        let mut second = build_signals();
+        for _ in 0..10 {
+            first.diffuse(&map_geometry, 0.1);
+            second.diffuse(&map_geometry, 0.1);
+        }
+
+        assert_eq!(checksum(&first), checksum(&second));
+    }
+
+    #[test]
+    fn wind_conserves_total_signal() {
+        let mut signals = Signals::default();
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 10);
+        let signal_type = SignalType::Contains(test_item());
+        let wind = Wind {
+            direction: Direction::TopRight,
+            strength: 0.5,
+        };
+
+        signals.add_signal(signal_type, VoxelPos::ZERO.above(), SignalStrength(1.));
+        // Few enough steps that no signal reaches the edge of the map
+        for _ in 0..5 {
+            signals.diffuse_with_wind(&map_geometry, 0.1, wind);
+        }
+
+        assert!((total_strength(&signals, signal_type) - 1.).abs() < 1e-5);
+    }
+
+    #[test]
+    fn wind_shifts_centroid_downwind() {
+        let mut signals = Signals::default();
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 10);
+        let signal_type = SignalType::Contains(test_item());
+        let diffusion_fraction = 0.1;
+        let n_steps = 5;
+        let wind = Wind {
+            direction: Direction::Bottom,
+            strength: 0.5,
+        };
+
+        signals.add_signal(signal_type, VoxelPos::ZERO.above(), SignalStrength(1.));
+        for _ in 0..n_steps {
+            signals.diffuse_with_wind(&map_geometry, diffusion_fraction, wind);
+        }
+
+        // Isotropic diffusion is symmetric, so only the advected fraction moves the centroid
+        let step = MAP_LAYOUT.hex_to_world_pos(Hex::ZERO.neighbor(wind.direction));
+        let expected = step * (n_steps as f32 * 6. * diffusion_fraction * wind.strength);
+
+        let observed = centroid(&signals, signal_type);
+        assert!(
+            (observed - expected).length() < 1e-3,
+            "Expected centroid at {expected}, found {observed}"
+        );
+        assert!(observed.dot(step) > 0.);
+    }
+
+    #[test]
+    fn signals_do_not_leak_off_the_map() {
+        let mut signals = Signals::default();
+        let mut world = World::new();
+        let map_geometry = MapGeometry::new(&mut world, 1);
+        let signal_type = SignalType::Contains(test_item());
+        let edge = VoxelPos {
+            hex: Hex::new(1, 0),
+            height: DiscreteHeight::ONE,
+        };
+
+        signals.add_signal(signal_type, edge, SignalStrength(1.));
+        // Long enough for the signal to spread evenly across this tiny map
+        for _ in 0..100 {
+            signals.diffuse(&map_geometry, DIFFUSION_FRACTION);
+        }
+
+        assert!((total_strength(&signals, signal_type) - 1.).abs() < 1e-5);
+        for strength in signals.maps[&signal_type].current.values() {
+            assert!(strength.value().is_finite());
+            assert!(strength.value() >= 0.);
+        }
+    }
+
+    #[test]
+    fn signals_decay_to_zero() {
+        let mut world = World::new();
+        world.init_resource::<Signals>();
+        let signal_type = SignalType::Contains(test_item());
+        world.resource_mut::<Signals>().add_signal(
+            signal_type,
+            VoxelPos::ZERO.above(),
+            SignalStrength(1.),
+        );
+
+        let mut schedule = Schedule::new();
+        schedule.add_system(degrade_signals);
+
+        schedule.run(&mut world);
+        let strength = total_strength(world.resource::<Signals>(), signal_type);
+        assert!(strength > 0. && strength < 1.);
+
+        // Decay is exponential, so this takes a while
+        for _ in 0..10_000 {
+            schedule.run(&mut world);
+        }
+        assert!(world.resource::<Signals>().maps[&signal_type]
+            .current
+            .is_empty());
+    }
 }
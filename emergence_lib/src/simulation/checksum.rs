@@ -0,0 +1,159 @@
+//! A fingerprint of the simulation state, used to check that runs are reproducible.
+
+use bevy::prelude::*;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use crate::asset_management::manifest::Id;
+use crate::geometry::VoxelPos;
+use crate::signals::Signals;
+use crate::structures::structure_manifest::Structure;
+use crate::terrain::terrain_manifest::Terrain;
+use crate::units::unit_manifest::Unit;
+
+/// Hashes the terrain, organisms and signals in the `world` into a single number.
+///
+/// Everything is visited in sorted order, so the result does not depend on the order in which entities were spawned.
+/// Two worlds with different checksums have diverged.
+pub fn simulation_checksum(world: &mut World) -> u64 {
+    let mut hasher = DefaultHasher::new();
+
+    sorted_positions::<Terrain>(world).hash(&mut hasher);
+    sorted_positions::<Structure>(world).hash(&mut hasher);
+    sorted_positions::<Unit>(world).hash(&mut hasher);
+
+    if let Some(signals) = world.get_resource::<Signals>() {
+        signals.hash_strengths(&mut hasher);
+    }
+
+    hasher.finish()
+}
+
+/// Collects the position and type of every entity with an `Id<T>`, sorted by position.
+fn sorted_positions<T: Send + Sync + 'static>(world: &mut World) -> Vec<(VoxelPos, Id<T>)> {
+    let mut query = world.query::<(&VoxelPos, &Id<T>)>();
+    let mut positions: Vec<(VoxelPos, Id<T>)> = query
+        .iter(world)
+        .map(|(&voxel_pos, &id)| (voxel_pos, id))
+        .collect();
+    positions.sort();
+    positions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crafting::item_tags::ItemKind;
+    use crate::signals::{SignalStrength, SignalType};
+    use crate::simulation::sim_resources::SimResources;
+    use crate::testing::headless_simulation_app;
+    use crate::world_gen::GenerationConfig;
+
+    /// How each simulation resource keeps iteration order from changing the outcome of the simulation.
+    ///
+    /// Every resource registered with [`SimResources`] must have an entry here,
+    /// including those registered by the interaction and UI plugins.
+    const DETERMINISM_AUDIT: &[(&str, &str)] = &[
+        (
+            "map_geometry",
+            "Its indexes are looked up by key; simulation code that loops over them uses `ordered` or only accumulates order-independent results",
+        ),
+        (
+            "signals",
+            "Diffusion sums floats in iteration order, so the maps are `StableHashMap`s",
+        ),
+        (
+            "traffic",
+            "Visits are looked up by key, and only summed as integers",
+        ),
+        ("food_deliveries", "Holds no collections"),
+        (
+            "entity_traces",
+            "Debugging output only; never read by the simulation",
+        ),
+        ("in_game_time", "Holds no collections"),
+        ("rng", "Holds no collections"),
+        (
+            "signal_thresholds",
+            "Thresholds are visited with `ordered_iter`, and events are sent in sorted order",
+        ),
+        ("total_light", "Holds no collections"),
+        ("current_weather", "Holds no collections"),
+        ("wind", "Holds no collections"),
+        ("weather_chances", "Holds no collections"),
+        ("unit_energy_drain", "Holds no collections"),
+        ("ocean", "Holds no collections"),
+        ("current_selection", "Player input only; never read by the simulation"),
+        ("hovered_tiles", "Player input only; never read by the simulation"),
+        ("cursor_pos", "Player input only; never read by the simulation"),
+        (
+            "census",
+            "Counts are accumulated in sorted groups, and only displayed",
+        ),
+        (
+            "item_count",
+            "Counts are looked up by key, and only summed as integers",
+        ),
+        (
+            "colony_metrics",
+            "Metrics are looked up by name; colony rules never iterate over them",
+        ),
+        (
+            "colony_rules",
+            "Rules are stored in a `Vec` and checked in order; flags are looked up by name",
+        ),
+        ("tile_overlay", "Display only; never read by the simulation"),
+    ];
+
+    #[test]
+    fn every_sim_resource_is_audited() {
+        let app = headless_simulation_app(GenerationConfig::testing());
+
+        for name in app.world.resource::<SimResources>().names() {
+            assert!(
+                DETERMINISM_AUDIT
+                    .iter()
+                    .any(|(audited, _)| *audited == name),
+                "{name} is missing from the determinism audit"
+            );
+        }
+    }
+
+    #[test]
+    fn checksum_ignores_spawn_order() {
+        let units: Vec<(VoxelPos, Id<Unit>)> = (0..10)
+            .map(|i| {
+                (
+                    VoxelPos::from_xy(i, 0),
+                    Id::from_name(format!("unit_{}", i % 3)),
+                )
+            })
+            .collect();
+
+        let mut forwards = World::new();
+        forwards.spawn_batch(units.clone());
+        let mut backwards = World::new();
+        backwards.spawn_batch(units.into_iter().rev());
+
+        assert_eq!(
+            simulation_checksum(&mut forwards),
+            simulation_checksum(&mut backwards)
+        );
+    }
+
+    #[test]
+    fn checksum_detects_changed_signals() {
+        let signal_type = SignalType::Push(ItemKind::Single(Id::from_name("acorn".to_string())));
+
+        let mut world = World::new();
+        world.init_resource::<Signals>();
+        let empty = simulation_checksum(&mut world);
+
+        world.resource_mut::<Signals>().add_signal(
+            signal_type,
+            VoxelPos::ZERO,
+            SignalStrength::new(1.),
+        );
+        assert_ne!(simulation_checksum(&mut world), empty);
+    }
+}
@@ -0,0 +1,561 @@
+//! Public events that report key moments in the simulation.
+//!
+//! Other plugins can observe the simulation by reading these events with an [`EventReader`],
+//! without needing to modify or order themselves relative to any simulation system.
+//!
+//! Each occurrence is sent exactly once, during the tick in which it happened:
+//!
+//! | Event                      | Sent during                                            |
+//! |----------------------------|--------------------------------------------------------|
+//! | [`SignalThresholdCrossed`] | the perception phase, after signals have been updated  |
+//! | [`TerrainChanged`]         | the end of the tick, when terrain commands are applied |
+//! | [`OrganismSpawned`]        | the bookkeeping phase                                  |
+//! | [`OrganismDied`]           | the bookkeeping phase                                  |
+//! | [`TimeOfDayChanged`]       | the bookkeeping phase, after the in-game time advances |
+//!
+//! Organisms are compared against those seen on the previous tick,
+//! so organisms that are added or removed outside of the simulation are reported on the next tick.
+//!
+//! Unlike other Bevy events, these are cleared once per tick, at the start of the bookkeeping phase,
+//! rather than once per frame.
+//! As a result, simulation systems in later phases see the events from earlier phases of the same tick,
+//! while events from the bookkeeping phase and the end of the tick are seen by earlier phases on the following tick,
+//! however many frames pass in between.
+//! Each event is kept for two ticks, so readers outside of the simulation see every event
+//! as long as they run at least once every other tick.
+
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use hexx::Hex;
+
+use crate::asset_management::manifest::Id;
+use crate::geometry::VoxelPos;
+use crate::organisms::Organism;
+use crate::signals::{ManageSignals, SignalStrength, SignalType, Signals};
+use crate::simulation::phases::{SimulationAppExt, TickPhase};
+use crate::simulation::sim_resources::{SimResource, SimResourceAppExt};
+use crate::simulation::time::{advance_in_game_time, InGameTime, TimeOfDay};
+use crate::terrain::terrain_manifest::Terrain;
+use crate::utils::collections::{ordered, ordered_iter};
+use crate::utils::memory::{MemoryFootprint, MemoryUsage};
+
+/// Registers the public simulation events, and the systems that send them.
+pub(super) struct SimulationEventsPlugin;
+
+impl Plugin for SimulationEventsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_simulation_events()
+            .init_resource::<SignalThresholds>()
+            .register_sim_resource::<SignalThresholds>()
+            .add_simulation_system(
+                TickPhase::Perception,
+                send_signal_threshold_events.after(ManageSignals),
+            )
+            .add_simulation_systems(
+                TickPhase::Bookkeeping,
+                (
+                    send_organism_events,
+                    send_time_of_day_events.after(advance_in_game_time),
+                )
+                    .after(UpdateSimulationEvents),
+            );
+    }
+}
+
+/// The systems that clear old simulation events, once per tick.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+struct UpdateSimulationEvents;
+
+/// An [`App`] extension trait to add events that are cleared once per tick.
+trait SimulationEventsAppExt {
+    /// Adds the event `T`, which is cleared at the start of [`TickPhase::Bookkeeping`] rather than every frame.
+    fn add_simulation_event<T: Event>(&mut self) -> &mut Self;
+
+    /// Adds every public simulation event.
+    fn add_simulation_events(&mut self) -> &mut Self {
+        self.add_simulation_event::<OrganismSpawned>()
+            .add_simulation_event::<OrganismDied>()
+            .add_simulation_event::<TerrainChanged>()
+            .add_simulation_event::<SignalThresholdCrossed>()
+            .add_simulation_event::<TimeOfDayChanged>()
+    }
+}
+
+impl SimulationEventsAppExt for App {
+    fn add_simulation_event<T: Event>(&mut self) -> &mut Self {
+        // Unlike `add_event`, this does not add `Events::update_system` to `CoreSet::First`
+        self.init_resource::<Events<T>>().add_simulation_system(
+            TickPhase::Bookkeeping,
+            Events::<T>::update_system.in_set(UpdateSimulationEvents),
+        )
+    }
+}
+
+/// A new organism was added to the world.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OrganismSpawned {
+    /// The organism's entity.
+    pub entity: Entity,
+}
+
+/// An organism was removed from the world, either because it died or because it was demolished.
+///
+/// The entity has already been despawned when this event is read.
+/// This is sent on the tick after the organism was removed, even if it was removed between ticks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OrganismDied {
+    /// The organism's former entity.
+    pub entity: Entity,
+}
+
+/// The type of terrain at a tile changed.
+///
+/// Terrain is changed with [`TerrainCommandsExt::set_terrain`](crate::terrain::commands::TerrainCommandsExt::set_terrain).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TerrainChanged {
+    /// The location of the tile.
+    pub hex: Hex,
+    /// The type of terrain before the change.
+    pub old: Id<Terrain>,
+    /// The type of terrain after the change.
+    pub new: Id<Terrain>,
+}
+
+/// A signal rose to meet the threshold set for it in [`SignalThresholds`].
+///
+/// This is only sent again once the signal has dropped below the threshold at that position.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SignalThresholdCrossed {
+    /// The type of signal.
+    pub signal_type: SignalType,
+    /// Where the threshold was crossed.
+    pub voxel_pos: VoxelPos,
+    /// The strength of the signal at `voxel_pos`.
+    pub strength: SignalStrength,
+}
+
+/// The [`TimeOfDay`] changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeOfDayChanged {
+    /// The new time of day.
+    pub time_of_day: TimeOfDay,
+}
+
+/// The signal strengths that trigger a [`SignalThresholdCrossed`] event.
+///
+/// No signals are watched by default, as checking a signal requires scanning every position where it is present.
+#[derive(Resource, Debug, Default, Reflect)]
+#[reflect(Resource)]
+pub struct SignalThresholds {
+    /// The threshold for each watched signal type.
+    #[reflect(ignore)]
+    thresholds: HashMap<SignalType, SignalStrength>,
+    /// The positions where each watched signal met its threshold when last checked.
+    #[reflect(ignore)]
+    above_threshold: HashMap<SignalType, HashSet<VoxelPos>>,
+}
+
+impl MemoryFootprint for SignalThresholds {
+    fn memory_usage(&self) -> MemoryUsage {
+        self.above_threshold
+            .values()
+            .map(MemoryUsage::of_hash_set)
+            .fold(
+                MemoryUsage::of_hash_map(&self.thresholds)
+                    + MemoryUsage::of_hash_map(&self.above_threshold),
+                |total, usage| total + usage,
+            )
+    }
+}
+
+/// The watched thresholds are kept, but which positions were above them refers to the current map.
+impl SimResource for SignalThresholds {
+    const NAME: &'static str = "signal_thresholds";
+    const PERSIST: bool = false;
+
+    fn reset_to_default(&mut self) {
+        self.above_threshold.clear();
+    }
+
+    fn measure_memory(&self) -> Option<MemoryUsage> {
+        Some(self.memory_usage())
+    }
+}
+
+impl SignalThresholds {
+    /// Sends a [`SignalThresholdCrossed`] event whenever `signal_type` rises to at least `threshold`.
+    ///
+    /// This replaces any existing threshold for `signal_type`.
+    pub fn watch(&mut self, signal_type: SignalType, threshold: SignalStrength) {
+        self.thresholds.insert(signal_type, threshold);
+        self.above_threshold.remove(&signal_type);
+    }
+
+    /// Stops watching `signal_type`.
+    pub fn unwatch(&mut self, signal_type: SignalType) {
+        self.thresholds.remove(&signal_type);
+        self.above_threshold.remove(&signal_type);
+    }
+
+    /// The threshold for `signal_type`, if it is being watched.
+    pub fn get(&self, signal_type: SignalType) -> Option<SignalStrength> {
+        self.thresholds.get(&signal_type).copied()
+    }
+}
+
+/// Sends [`OrganismSpawned`] and [`OrganismDied`] events, by comparing the organisms against those seen on the previous tick.
+///
+/// [`RemovedComponents`] is cleared every frame, so it would miss organisms removed in frames without a tick.
+fn send_organism_events(
+    organism_query: Query<Entity, With<Organism>>,
+    mut previous_organisms: Local<HashSet<Entity>>,
+    mut spawned_events: EventWriter<OrganismSpawned>,
+    mut died_events: EventWriter<OrganismDied>,
+) {
+    let organisms: HashSet<Entity> = organism_query.iter().collect();
+
+    spawned_events.send_batch(
+        ordered(organisms.difference(&previous_organisms))
+            .map(|&entity| OrganismSpawned { entity }),
+    );
+    died_events.send_batch(
+        ordered(previous_organisms.difference(&organisms)).map(|&entity| OrganismDied { entity }),
+    );
+
+    *previous_organisms = organisms;
+}
+
+/// Sends a [`TimeOfDayChanged`] event whenever the time of day is different from the previous tick.
+fn send_time_of_day_events(
+    in_game_time: Res<InGameTime>,
+    mut previous_time_of_day: Local<Option<TimeOfDay>>,
+    mut events: EventWriter<TimeOfDayChanged>,
+) {
+    let time_of_day = in_game_time.time_of_day();
+
+    // The time of day does not change when the simulation starts
+    if previous_time_of_day.is_some_and(|previous| previous != time_of_day) {
+        events.send(TimeOfDayChanged { time_of_day });
+    }
+
+    *previous_time_of_day = Some(time_of_day);
+}
+
+/// Sends a [`SignalThresholdCrossed`] event for each position where a watched signal has newly met its threshold.
+fn send_signal_threshold_events(
+    signals: Res<Signals>,
+    mut signal_thresholds: ResMut<SignalThresholds>,
+    mut events: EventWriter<SignalThresholdCrossed>,
+) {
+    let SignalThresholds {
+        thresholds,
+        above_threshold,
+    } = &mut *signal_thresholds;
+
+    for (&signal_type, &threshold) in ordered_iter(thresholds.iter()) {
+        let previously_above = above_threshold.entry(signal_type).or_default();
+        let currently_above: HashMap<VoxelPos, SignalStrength> =
+            signals.positions_at_least(signal_type, threshold).collect();
+
+        let newly_above = currently_above
+            .iter()
+            .filter(|(voxel_pos, _)| !previously_above.contains(*voxel_pos));
+
+        events.send_batch(ordered_iter(newly_above).map(|(&voxel_pos, &strength)| {
+            SignalThresholdCrossed {
+                signal_type,
+                voxel_pos,
+                strength,
+            }
+        }));
+
+        *previously_above = currently_above.into_keys().collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::time::fixed_timestep::run_fixed_update_schedule;
+
+    use super::*;
+    use crate::geometry::DiscreteHeight;
+    use crate::simulation::phases::configure_tick_phases;
+
+    /// Counts every simulation event, as an example of how other plugins can observe the simulation.
+    struct SubscriberPlugin;
+
+    impl Plugin for SubscriberPlugin {
+        fn build(&self, app: &mut App) {
+            app.init_resource::<EventCounts>()
+                .add_system(count_events.in_base_set(CoreSet::PostUpdate));
+        }
+    }
+
+    /// The number of times each event has been read.
+    #[derive(Resource, Debug, Default)]
+    struct EventCounts {
+        /// The number of [`OrganismSpawned`] events.
+        spawned: usize,
+        /// The number of [`OrganismDied`] events.
+        died: usize,
+        /// The number of [`TerrainChanged`] events.
+        terrain_changed: usize,
+        /// The number of [`SignalThresholdCrossed`] events.
+        thresholds_crossed: usize,
+        /// Each [`TimeOfDayChanged`] event, in the order they were read.
+        times_of_day: Vec<TimeOfDay>,
+    }
+
+    /// Records each event in [`EventCounts`].
+    fn count_events(
+        mut counts: ResMut<EventCounts>,
+        mut spawned: EventReader<OrganismSpawned>,
+        mut died: EventReader<OrganismDied>,
+        mut terrain_changed: EventReader<TerrainChanged>,
+        mut thresholds_crossed: EventReader<SignalThresholdCrossed>,
+        mut time_of_day_changed: EventReader<TimeOfDayChanged>,
+    ) {
+        counts.spawned += spawned.iter().count();
+        counts.died += died.iter().count();
+        counts.terrain_changed += terrain_changed.iter().count();
+        counts.thresholds_crossed += thresholds_crossed.iter().count();
+        counts
+            .times_of_day
+            .extend(time_of_day_changed.iter().map(|event| event.time_of_day));
+    }
+
+    /// An app with the simulation events and a [`SubscriberPlugin`], but no simulation systems.
+    fn subscriber_app() -> App {
+        let mut app = App::new();
+        app.add_simulation_events()
+            .init_resource::<SignalThresholds>()
+            .init_resource::<Signals>()
+            .add_plugin(SubscriberPlugin);
+        app
+    }
+
+    /// A signal type used for testing.
+    fn test_signal() -> SignalType {
+        SignalType::Unit(Id::from_name("ant".to_string()))
+    }
+
+    /// A voxel on the ground at `hex`.
+    fn voxel(hex: Hex) -> VoxelPos {
+        VoxelPos {
+            hex,
+            height: DiscreteHeight::ZERO,
+        }
+    }
+
+    #[test]
+    fn organism_events_are_sent_exactly_once() {
+        let mut app = subscriber_app();
+        app.add_system(send_organism_events);
+
+        let organisms: Vec<Entity> = (0..3).map(|_| app.world.spawn(Organism).id()).collect();
+        // Entities that are not organisms are ignored
+        app.world.spawn_empty();
+
+        app.update();
+        assert_eq!(app.world.resource::<EventCounts>().spawned, 3);
+
+        app.update();
+        app.update();
+        assert_eq!(app.world.resource::<EventCounts>().spawned, 3);
+        assert_eq!(app.world.resource::<EventCounts>().died, 0);
+
+        app.world.despawn(organisms[0]);
+        app.world.despawn(organisms[1]);
+
+        app.update();
+        app.update();
+        let counts = app.world.resource::<EventCounts>();
+        assert_eq!(counts.spawned, 3);
+        assert_eq!(counts.died, 2);
+    }
+
+    #[test]
+    fn time_of_day_changes_are_sent_once_per_change() {
+        let mut app = subscriber_app();
+        let seconds_per_day = InGameTime::default().seconds_per_day();
+        // Each update advances the time by a quarter of a day
+        app.init_resource::<InGameTime>()
+            .insert_resource(FixedTime::new_from_secs(seconds_per_day / 4.))
+            .add_systems((advance_in_game_time, send_time_of_day_events).chain());
+
+        // Two full days
+        for _ in 0..8 {
+            app.update();
+        }
+
+        assert_eq!(
+            app.world.resource::<EventCounts>().times_of_day,
+            vec![
+                TimeOfDay::Night,
+                TimeOfDay::Day,
+                TimeOfDay::Night,
+                TimeOfDay::Day
+            ]
+        );
+    }
+
+    #[test]
+    fn signal_thresholds_are_reported_when_first_crossed() {
+        let mut app = subscriber_app();
+        app.add_system(send_signal_threshold_events);
+        app.world
+            .resource_mut::<SignalThresholds>()
+            .watch(test_signal(), SignalStrength::new(10.));
+
+        // Several crossings in the same tick are all reported
+        let mut signals = app.world.resource_mut::<Signals>();
+        signals.add_signal(test_signal(), voxel(Hex::ZERO), SignalStrength::new(15.));
+        signals.add_signal(
+            test_signal(),
+            voxel(Hex::new(1, 0)),
+            SignalStrength::new(20.),
+        );
+        // Weak signals are not
+        signals.add_signal(
+            test_signal(),
+            voxel(Hex::new(0, 1)),
+            SignalStrength::new(5.),
+        );
+
+        app.update();
+        assert_eq!(app.world.resource::<EventCounts>().thresholds_crossed, 2);
+
+        // Remaining above the threshold is not a new crossing
+        app.update();
+        assert_eq!(app.world.resource::<EventCounts>().thresholds_crossed, 2);
+
+        app.world.resource_mut::<Signals>().add_signal(
+            test_signal(),
+            voxel(Hex::new(0, 1)),
+            SignalStrength::new(5.),
+        );
+        app.update();
+        assert_eq!(app.world.resource::<EventCounts>().thresholds_crossed, 3);
+
+        // Unwatched signals are never reported
+        app.world
+            .resource_mut::<SignalThresholds>()
+            .unwatch(test_signal());
+        app.world.resource_mut::<Signals>().add_signal(
+            test_signal(),
+            voxel(Hex::new(-1, 0)),
+            SignalStrength::new(50.),
+        );
+        app.update();
+        assert_eq!(app.world.resource::<EventCounts>().thresholds_crossed, 3);
+    }
+
+    /// The events seen by a system in [`TickPhase::Decision`], in the order they were read.
+    #[derive(Resource, Debug, Default)]
+    struct DecisionLog(Vec<&'static str>);
+
+    /// Records the events that a simulation system in the [`TickPhase::Decision`] phase can see.
+    fn record_decision_events(
+        mut log: ResMut<DecisionLog>,
+        mut thresholds_crossed: EventReader<SignalThresholdCrossed>,
+        mut spawned: EventReader<OrganismSpawned>,
+    ) {
+        log.0
+            .extend(thresholds_crossed.iter().map(|_| "threshold_crossed"));
+        log.0.extend(spawned.iter().map(|_| "spawned"));
+    }
+
+    /// An app that runs one simulation tick for each call to [`tick_then_idle`], with the systems in `configure` added.
+    fn ticking_app(configure: impl FnOnce(&mut App)) -> App {
+        let mut app = subscriber_app();
+        app.init_resource::<DecisionLog>()
+            .init_resource::<Time>()
+            .insert_resource(FixedTime::new_from_secs(1.))
+            .add_system(run_fixed_update_schedule.in_base_set(CoreSet::FixedUpdate))
+            .edit_schedule(CoreSchedule::FixedUpdate, configure_tick_phases);
+        configure(&mut app);
+        app
+    }
+
+    /// Runs a frame with a single simulation tick, followed by `idle_frames` frames without one.
+    fn tick_then_idle(app: &mut App, idle_frames: usize) {
+        app.world
+            .resource_mut::<FixedTime>()
+            .tick(Duration::from_secs(1));
+        app.update();
+
+        for _ in 0..idle_frames {
+            app.update();
+        }
+    }
+
+    #[test]
+    fn events_follow_the_tick_phase_order() {
+        let mut app = ticking_app(|app| {
+            app.add_simulation_system(TickPhase::Perception, send_signal_threshold_events)
+                .add_simulation_system(TickPhase::Decision, record_decision_events)
+                .add_simulation_system(
+                    TickPhase::Bookkeeping,
+                    send_organism_events.after(UpdateSimulationEvents),
+                );
+        });
+        app.world
+            .resource_mut::<SignalThresholds>()
+            .watch(test_signal(), SignalStrength::new(1.));
+
+        app.world.spawn(Organism);
+        app.world.resource_mut::<Signals>().add_signal(
+            test_signal(),
+            voxel(Hex::ZERO),
+            SignalStrength::new(2.),
+        );
+
+        // Perception events are visible to the decision phase of the same tick,
+        // but bookkeeping events are not seen until the next tick,
+        // even when several frames pass between ticks
+        tick_then_idle(&mut app, 3);
+        assert_eq!(
+            app.world.resource::<DecisionLog>().0,
+            vec!["threshold_crossed"]
+        );
+
+        tick_then_idle(&mut app, 3);
+        assert_eq!(
+            app.world.resource::<DecisionLog>().0,
+            vec!["threshold_crossed", "spawned"]
+        );
+
+        // Readers outside of the simulation see each event exactly once
+        let counts = app.world.resource::<EventCounts>();
+        assert_eq!(counts.thresholds_crossed, 1);
+        assert_eq!(counts.spawned, 1);
+    }
+
+    #[test]
+    fn organisms_removed_between_ticks_are_reported() {
+        let mut app = ticking_app(|app| {
+            app.add_simulation_system(
+                TickPhase::Bookkeeping,
+                send_organism_events.after(UpdateSimulationEvents),
+            );
+        });
+
+        let organism = app.world.spawn(Organism).id();
+        tick_then_idle(&mut app, 0);
+        assert_eq!(app.world.resource::<EventCounts>().spawned, 1);
+
+        // Removed during a frame without a tick, as when the player demolishes a structure
+        app.world.despawn(organism);
+        for _ in 0..3 {
+            app.update();
+        }
+
+        tick_then_idle(&mut app, 3);
+        tick_then_idle(&mut app, 3);
+        assert_eq!(app.world.resource::<EventCounts>().died, 1);
+    }
+}
@@ -2,29 +2,131 @@
 //!
 //! All plugins in this module should work without rendering.
 
-use crate::asset_management::AssetState;
+use crate::asset_management::manifest::Id;
+use crate::asset_management::{AssetState, AssetsToLoad};
+use crate::construction::demolition::MarkedForDemolition;
+use crate::construction::ghosts::{Ghost, GhostHandles, Preview};
+use crate::construction::terraform::TerraformingAction;
+use crate::construction::zoning::CleanMeUp;
 use crate::construction::ConstructionPlugin;
+use crate::crafting::inventories::{
+    CraftingState, InputInventory, OutputInventory, StorageInventory,
+};
+use crate::crafting::production_queue::ProductionQueue;
+use crate::crafting::recipe::{ActiveRecipe, Recipe};
+use crate::crafting::workers::WorkersPresent;
 use crate::crafting::CraftingPlugin;
-use crate::geometry::sync_rotation_to_facing;
-use crate::light::LightPlugin;
-use crate::organisms::OrganismPlugin;
-use crate::signals::SignalsPlugin;
+use crate::geometry::{sync_rotation_to_facing, DiscreteHeight, Facing, MapGeometry, VoxelPos};
+use crate::graphics::lighting::{Moon, Sun};
+use crate::items::item_manifest::Item;
+use crate::light::shade::{ReceivedLight, Shade};
+use crate::light::{LightPlugin, TotalLight};
+use crate::litter::{Drift, Floating, Litter};
+use crate::organisms::energy::{Energy, EnergyPool, UnitEnergyDrain};
+use crate::organisms::lifecycle::Lifecycle;
+use crate::organisms::oxygen::OxygenPool;
+use crate::organisms::vegetative_reproduction::VegetativeReproduction;
+use crate::organisms::{Organism, OrganismPlugin};
+use crate::player_interaction::clipboard::Tool;
+use crate::player_interaction::picking::CursorPos;
+use crate::player_interaction::selection::CurrentSelection;
+use crate::signals::{Emitter, Signals, SignalsPlugin};
+use crate::simulation::events::{SignalThresholds, SimulationEventsPlugin};
+use crate::simulation::phases::configure_tick_phases;
+#[cfg(feature = "probability_audit")]
+use crate::simulation::phases::{SimulationAppExt, TickPhase};
 use crate::simulation::rng::GlobalRng;
-use crate::simulation::time::TemporalPlugin;
-use crate::simulation::weather::WeatherPlugin;
-use crate::structures::StructuresPlugin;
+use crate::simulation::sim_resources::{SimResourceAppExt, SimResources};
+use crate::simulation::time::{Days, InGameTime, TemporalPlugin};
+use crate::simulation::weather::{CurrentWeather, WeatherChances, WeatherPlugin, Wind};
+use crate::structures::adjacency::AdjacencyBonus;
+use crate::structures::logistic_buildings::{AbsorbsItems, ReleasesItems};
+use crate::structures::structure_assets::StructureHandles;
+use crate::structures::structure_manifest::Structure;
+use crate::structures::{Footprint, Landmark, StructuresPlugin};
+use crate::terrain::terrain_assets::TerrainHandles;
+use crate::terrain::terrain_manifest::Terrain;
 use crate::terrain::TerrainPlugin;
-use crate::units::UnitsPlugin;
-use crate::water::WaterPlugin;
+use crate::units::actions::CurrentAction;
+use crate::units::age::Age;
+use crate::units::deliveries::FoodDeliveries;
+use crate::units::goals::Goal;
+use crate::units::impatience::ImpatiencePool;
+use crate::units::item_interaction::UnitInventory;
+use crate::units::trace::EntityTraces;
+use crate::units::traffic::TrafficMap;
+use crate::units::unit_assets::UnitHandles;
+use crate::units::unit_manifest::Unit;
+use crate::units::{Nest, UnitsPlugin};
+use crate::utils::memory::MemoryReport;
+use crate::water::emitters::WaterEmitter;
+use crate::water::ocean::Ocean;
+use crate::water::water_dynamics::{SoilWaterEvaporationRate, SoilWaterFlowRate};
+use crate::water::{
+    FlowVelocity, PreviousWaterVolume, SoilWaterCapacity, WaterConfig, WaterDepth, WaterPlugin,
+    WaterVolume,
+};
 use crate::world_gen::{GenerationConfig, GenerationPlugin, WorldGenState};
 use bevy::core::FrameCount;
 use bevy::ecs::schedule::{LogLevel, ScheduleBuildSettings};
+#[cfg(not(feature = "probability_audit"))]
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
+#[cfg(not(feature = "probability_audit"))]
+use std::marker::PhantomData;
 
+pub mod checksum;
+pub mod events;
+pub(crate) mod phases;
+#[cfg(feature = "probability_audit")]
+pub mod probability_audit;
 pub mod rng;
+pub mod sim_resources;
+pub mod soak;
 pub mod time;
 pub mod weather;
 
+/// Records a random decision for later inspection.
+///
+/// The `probability_audit` feature is disabled, so nothing is recorded,
+/// and the audit types are not referenced at all.
+#[cfg(not(feature = "probability_audit"))]
+#[macro_export]
+macro_rules! record_chance {
+    (
+        audit: $audit:expr,
+        system: $system:expr,
+        probability: $probability:expr,
+        rolled: $rolled:expr,
+        outcome: $outcome:expr,
+        subject: $subject:expr $(,)?
+    ) => {{
+        let _ = &mut $audit;
+        // Type-checked just as strictly as when the feature is enabled
+        let _: (
+            &'static str,
+            f32,
+            f32,
+            bool,
+            Option<::bevy::ecs::entity::Entity>,
+        ) = ($system, $probability, $rolled, $outcome, $subject);
+    }};
+}
+
+/// A system parameter used to record random decisions with [`record_chance!`].
+///
+/// The `probability_audit` feature is disabled, so this does not access any data.
+#[cfg(not(feature = "probability_audit"))]
+#[derive(SystemParam)]
+pub struct ChanceAudit<'w> {
+    /// Ties the parameter to the world's lifetime, like the real audit parameter.
+    #[system_param(ignore)]
+    _marker: PhantomData<&'w ()>,
+}
+
+#[cfg(feature = "probability_audit")]
+pub use probability_audit::ChanceAudit;
+
 /// All of the code needed to make the simulation run
 pub struct SimulationPlugin {
     /// Configuration settings for world generation
@@ -34,7 +136,10 @@ pub struct SimulationPlugin {
 impl Plugin for SimulationPlugin {
     fn build(&self, app: &mut App) {
         info!("Building simulation plugin...");
+        register_emergence_types(app);
+
         app.insert_resource(GlobalRng::new(self.gen_config.seed))
+            .register_sim_resource::<GlobalRng>()
             .add_system(sync_rotation_to_facing)
             .edit_schedule(CoreSchedule::FixedUpdate, |schedule| {
                 schedule.configure_set(
@@ -44,6 +149,7 @@ impl Plugin for SimulationPlugin {
                         .run_if(world_gen_ready)
                         .run_if(max_ticks_not_reached),
                 );
+                configure_tick_phases(schedule);
                 schedule.add_system(update_ticks_this_frame.run_if(max_ticks_not_reached));
 
                 schedule.set_build_settings(ScheduleBuildSettings {
@@ -65,13 +171,125 @@ impl Plugin for SimulationPlugin {
             .add_plugin(TemporalPlugin)
             .add_plugin(LightPlugin)
             .add_plugin(WaterPlugin)
-            .add_plugin(WeatherPlugin);
+            .add_plugin(WeatherPlugin)
+            .add_plugin(SimulationEventsPlugin)
+            .add_system(log_memory_report.in_schedule(OnEnter(WorldGenState::Complete)));
+
+        #[cfg(feature = "probability_audit")]
+        app.init_resource::<probability_audit::ChanceLog>()
+            .add_simulation_system(
+                TickPhase::Bookkeeping,
+                probability_audit::advance_audit_tick,
+            );
     }
 }
 
+/// Registers the gameplay types that support reflection with the [`AppTypeRegistry`].
+///
+/// This allows tools like the world inspector to read and modify these types without any type-specific code.
+/// Every component and resource added by the simulation plugins must be registered here:
+/// this is checked by the `every_simulation_component_is_registered` test.
+///
+/// The generic manifest resources are registered by their [`ManifestPlugin`](crate::asset_management::manifest::plugin::ManifestPlugin) instead.
+pub fn register_emergence_types(app: &mut App) {
+    // Identifiers and shared values
+    app.register_type::<Id<Unit>>()
+        .register_type::<Id<Structure>>()
+        .register_type::<Id<Terrain>>()
+        .register_type::<Id<Item>>()
+        .register_type::<Id<Recipe>>()
+        .register_type::<Option<Id<Item>>>()
+        .register_type::<DiscreteHeight>()
+        .register_type::<Days>()
+        .register_type::<Energy>();
+
+    // Components
+    app.register_type::<MarkedForDemolition>()
+        .register_type::<Ghost>()
+        .register_type::<Preview>()
+        .register_type::<TerraformingAction>()
+        .register_type::<CleanMeUp>()
+        .register_type::<CraftingState>()
+        .register_type::<InputInventory>()
+        .register_type::<OutputInventory>()
+        .register_type::<StorageInventory>()
+        .register_type::<ProductionQueue>()
+        .register_type::<ActiveRecipe>()
+        .register_type::<WorkersPresent>()
+        .register_type::<VoxelPos>()
+        .register_type::<Facing>()
+        .register_type::<Sun>()
+        .register_type::<Moon>()
+        .register_type::<ReceivedLight>()
+        .register_type::<Shade>()
+        .register_type::<Drift>()
+        .register_type::<Floating>()
+        .register_type::<Litter>()
+        .register_type::<Organism>()
+        .register_type::<EnergyPool>()
+        .register_type::<Lifecycle>()
+        .register_type::<OxygenPool>()
+        .register_type::<VegetativeReproduction>()
+        .register_type::<Emitter>()
+        .register_type::<Footprint>()
+        .register_type::<Landmark>()
+        .register_type::<AdjacencyBonus>()
+        .register_type::<AbsorbsItems>()
+        .register_type::<ReleasesItems>()
+        .register_type::<Age>()
+        .register_type::<ImpatiencePool>()
+        .register_type::<UnitInventory>()
+        .register_type::<CurrentAction>()
+        .register_type::<Goal>()
+        .register_type::<Nest>()
+        .register_type::<WaterVolume>()
+        .register_type::<PreviousWaterVolume>()
+        .register_type::<FlowVelocity>()
+        .register_type::<WaterDepth>()
+        .register_type::<SoilWaterCapacity>()
+        .register_type::<SoilWaterEvaporationRate>()
+        .register_type::<SoilWaterFlowRate>()
+        .register_type::<WaterEmitter>();
+
+    // Resources
+    app.register_type::<AssetsToLoad>()
+        .register_type::<GhostHandles>()
+        .register_type::<MapGeometry>()
+        .register_type::<TotalLight>()
+        .register_type::<Tool>()
+        .register_type::<CursorPos>()
+        .register_type::<CurrentSelection>()
+        .register_type::<Signals>()
+        .register_type::<TicksThisFrame>()
+        .register_type::<SignalThresholds>()
+        .register_type::<GlobalRng>()
+        .register_type::<SimResources>()
+        .register_type::<InGameTime>()
+        .register_type::<CurrentWeather>()
+        .register_type::<Wind>()
+        .register_type::<WeatherChances>()
+        .register_type::<UnitEnergyDrain>()
+        .register_type::<StructureHandles>()
+        .register_type::<TerrainHandles>()
+        .register_type::<UnitHandles>()
+        .register_type::<FoodDeliveries>()
+        .register_type::<EntityTraces>()
+        .register_type::<TrafficMap>()
+        .register_type::<MemoryReport>()
+        .register_type::<WaterConfig>()
+        .register_type::<Ocean>()
+        .register_type::<GenerationConfig>();
+}
+
+/// Logs the memory used by large resources once the world has been generated.
+fn log_memory_report(world: &World) {
+    let report = world.resource::<MemoryReport>();
+    info!("Memory usage:\n{}", report.display(world));
+}
+
 /// Controls whether or not the game is paused.
 #[derive(States, Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
-enum PauseState {
+pub(crate) enum PauseState {
     /// Game logic is running.
     #[default]
     Playing,
@@ -85,13 +303,14 @@ enum PauseState {
 /// - are run in [`CoreSchedule::FixedUpdate`]
 /// - only run in [`PauseState::Playing`]
 /// - only run in [`AssetState::FullyLoaded`]
+/// - belong to exactly one [`TickPhase`](phases::TickPhase), which orders them within each tick
 #[derive(SystemSet, PartialEq, Eq, Hash, Debug, Clone)]
 pub(crate) struct SimulationSet;
 
 /// Tracks how many ticks have passed this frame.
 // BLOCKED: this is a workaround for https://github.com/bevyengine/bevy/issues/8543.
 // Once that's fixed and released all this code should be removed.
-#[derive(Resource, Debug)]
+#[derive(Resource, Debug, Reflect)]
 struct TicksThisFrame {
     /// The number of ticks that have passed this frame.
     current: u8,
@@ -121,3 +340,105 @@ fn max_ticks_not_reached(frame_count: Res<FrameCount>, ticks: Res<TicksThisFrame
 fn world_gen_ready(world_gen_state: Res<State<WorldGenState>>) -> bool {
     world_gen_state.0 == WorldGenState::Complete || world_gen_state.0 == WorldGenState::BurningIn
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::schedule::Schedules;
+    use bevy::utils::HashSet;
+
+    use super::*;
+    use crate::geometry::MapGeometry;
+    use crate::simulation::sim_resources::{resource_type_ids, SimResources};
+    use crate::testing::{headless_app, headless_simulation_app};
+    use crate::utils::memory::MemoryFootprint;
+
+    #[test]
+    fn every_simulation_component_is_registered() {
+        let mut app = headless_simulation_app(GenerationConfig::testing());
+        // Components are only initialized once a system that uses them is
+        app.world
+            .resource_scope(|world, mut schedules: Mut<Schedules>| {
+                for (_, schedule) in schedules.iter_mut() {
+                    schedule.initialize(world).unwrap();
+                }
+            });
+
+        let type_registry = app.world.resource::<AppTypeRegistry>().clone();
+        let registry = type_registry.read();
+
+        let unregistered: Vec<&str> = app
+            .world
+            .components()
+            .iter()
+            .filter(|info| info.name().starts_with("emergence_lib::"))
+            .filter(|info| {
+                info.type_id()
+                    .is_none_or(|type_id| registry.get(type_id).is_none())
+            })
+            .map(|info| info.name())
+            .collect();
+        assert!(
+            unregistered.is_empty(),
+            "These types must be registered in `register_emergence_types`: {unregistered:?}"
+        );
+    }
+
+    #[test]
+    fn memory_report_covers_every_sim_resource() {
+        let mut app = headless_simulation_app(GenerationConfig::testing());
+        // The map is only created during world generation
+        let map_geometry = MapGeometry::new(&mut app.world, 5);
+        app.insert_resource(map_geometry);
+
+        let report = app.world.resource::<MemoryReport>();
+        let measurements = report.measure(&app.world);
+        assert_eq!(measurements.len(), report.names().count());
+
+        let measured: HashSet<&str> = measurements.iter().map(|(name, _)| *name).collect();
+        let registered: HashSet<&str> = app.world.resource::<SimResources>().names().collect();
+        assert_eq!(measured, registered);
+
+        let map_usage = app.world.resource::<MapGeometry>().memory_usage();
+        assert!(measurements.contains(&("map_geometry", map_usage)));
+        assert!(map_usage.used_bytes > 0);
+    }
+
+    /// Resources added by the [`SimulationPlugin`] that do not hold simulation state, and so are not registered.
+    ///
+    /// Assets, events and states are skipped separately.
+    const NOT_SIM_STATE: &[&str] = &[
+        "emergence_lib::construction::ghosts::GhostHandles",
+        "emergence_lib::simulation::TicksThisFrame",
+        "emergence_lib::water::WaterConfig",
+        "emergence_lib::world_gen::GenerationConfig",
+    ];
+
+    /// Is `type_name` one of Bevy's asset, event or state resources?
+    fn is_engine_resource(type_name: &str) -> bool {
+        [
+            "bevy_asset::",
+            "bevy_ecs::event::",
+            "bevy_ecs::schedule::state::",
+        ]
+        .iter()
+        .any(|prefix| type_name.starts_with(prefix))
+    }
+
+    #[test]
+    fn simulation_plugin_registers_its_resources() {
+        let mut app = headless_app();
+        let baseline = resource_type_ids(&app.world);
+        app.add_plugin(SimulationPlugin {
+            gen_config: GenerationConfig::testing(),
+        });
+
+        let unregistered: Vec<String> = app
+            .world
+            .resource::<SimResources>()
+            .unregistered(&app.world, &baseline)
+            .into_iter()
+            .filter(|type_name| !is_engine_resource(type_name))
+            .collect();
+        assert_eq!(unregistered, NOT_SIM_STATE);
+    }
+}
@@ -0,0 +1,416 @@
+//! Declares the order in which simulation systems run within a single tick.
+//!
+//! Every system in the [`SimulationSet`] belongs to exactly one [`TickPhase`],
+//! and the phases run one after another in the order they are declared.
+//! Register simulation systems with [`SimulationAppExt::add_simulation_systems`]
+//! (or [`SimulationAppExt::add_simulation_system`] for a single system),
+//! and use `.before` / `.after` only to order systems within a phase.
+//!
+//! The tests check these rules against the schedule built by the real simulation plugins.
+
+#[cfg(test)]
+use std::fmt::Display;
+
+use bevy::prelude::*;
+#[cfg(test)]
+use bevy::{
+    ecs::schedule::NodeId,
+    utils::{HashMap, HashSet},
+};
+use emergence_macros::IterableEnum;
+
+use crate as emergence_lib;
+#[cfg(test)]
+use crate::enum_iter::IterableEnum;
+
+use super::SimulationSet;
+
+/// A stage of a single simulation tick.
+///
+/// Phases run in the order they are declared here.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, IterableEnum)]
+pub(crate) enum TickPhase {
+    /// Organisms sense the world: light is computed and signals are emitted and spread.
+    ///
+    /// Emitters are refreshed at the start of this phase, before [`ManageSignals`](crate::signals::ManageSignals),
+    /// so the signals emitted reflect everything that happened in the previous tick.
+    Perception,
+    /// Units pick goals and the actions that will accomplish them.
+    Decision,
+    /// Units carry out actions, and structures craft and get built.
+    Action,
+    /// The world responds: water flows, organisms grow, starve and spread.
+    Resolution,
+    /// Time advances and derived state is updated for the next tick.
+    Bookkeeping,
+}
+
+/// Orders the [`TickPhase`]s within the [`SimulationSet`].
+pub(super) fn configure_tick_phases(schedule: &mut Schedule) {
+    schedule.configure_sets(
+        (
+            TickPhase::Perception,
+            TickPhase::Decision,
+            TickPhase::Action,
+            TickPhase::Resolution,
+            TickPhase::Bookkeeping,
+        )
+            .chain()
+            .in_set(SimulationSet),
+    );
+}
+
+/// An [`App`] extension trait to register simulation systems in a [`TickPhase`].
+pub(crate) trait SimulationAppExt {
+    /// Adds `system` to the fixed update schedule, in the provided `phase` of the [`SimulationSet`].
+    fn add_simulation_system<M>(
+        &mut self,
+        phase: TickPhase,
+        system: impl IntoSystemConfig<M>,
+    ) -> &mut Self;
+
+    /// Adds `systems` to the fixed update schedule, in the provided `phase` of the [`SimulationSet`].
+    fn add_simulation_systems<M>(
+        &mut self,
+        phase: TickPhase,
+        systems: impl IntoSystemConfigs<M>,
+    ) -> &mut Self;
+}
+
+impl SimulationAppExt for App {
+    fn add_simulation_system<M>(
+        &mut self,
+        phase: TickPhase,
+        system: impl IntoSystemConfig<M>,
+    ) -> &mut Self {
+        self.add_system(system.in_set(phase).in_schedule(CoreSchedule::FixedUpdate))
+    }
+
+    fn add_simulation_systems<M>(
+        &mut self,
+        phase: TickPhase,
+        systems: impl IntoSystemConfigs<M>,
+    ) -> &mut Self {
+        self.add_systems(systems.in_set(phase).in_schedule(CoreSchedule::FixedUpdate))
+    }
+}
+
+/// A way in which a schedule breaks the [`TickPhase`] rules.
+#[cfg(test)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum TickPhaseViolation {
+    /// A simulation system is not in any phase.
+    MissingPhase {
+        /// The name of the system.
+        system: String,
+    },
+    /// A system is in more than one phase.
+    MultiplePhases {
+        /// The name of the system.
+        system: String,
+        /// The phases the system is in.
+        phases: Vec<TickPhase>,
+    },
+    /// A dependency orders a later phase before an earlier one.
+    OutOfOrder {
+        /// The name of the system or set that runs first.
+        before: String,
+        /// The phase of `before`.
+        before_phase: TickPhase,
+        /// The name of the system or set that runs second.
+        after: String,
+        /// The phase of `after`.
+        after_phase: TickPhase,
+    },
+}
+
+#[cfg(test)]
+impl Display for TickPhaseViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TickPhaseViolation::MissingPhase { system } => {
+                write!(
+                    f,
+                    "{system} is a simulation system but is not in any tick phase"
+                )
+            }
+            TickPhaseViolation::MultiplePhases { system, phases } => {
+                write!(f, "{system} is in more than one tick phase: {phases:?}")
+            }
+            TickPhaseViolation::OutOfOrder {
+                before,
+                before_phase,
+                after,
+                after_phase,
+            } => write!(
+                f,
+                "{before} ({before_phase:?}) is ordered before {after} ({after_phase:?})"
+            ),
+        }
+    }
+}
+
+/// Checks that every simulation system in `schedule` is in exactly one [`TickPhase`],
+/// and that no dependency runs a later phase before an earlier one.
+///
+/// Systems count as simulation systems if they are in the [`SimulationSet`] or in any phase.
+/// Sets that are not phases take the phase of their ancestors,
+/// or failing that, of their member systems if those all share one phase.
+#[cfg(test)]
+pub(crate) fn check_tick_phases(schedule: &Schedule) -> Vec<TickPhaseViolation> {
+    let graph = schedule.graph();
+
+    let mut phase_ids: HashMap<NodeId, TickPhase> = HashMap::default();
+    let mut simulation_set_id = None;
+    for (id, set, _membership, _conditions) in graph.system_sets() {
+        if set == &SimulationSet as &dyn SystemSet {
+            simulation_set_id = Some(id);
+        }
+
+        for phase in TickPhase::variants() {
+            if set == &phase as &dyn SystemSet {
+                phase_ids.insert(id, phase);
+            }
+        }
+    }
+
+    let mut parents: HashMap<NodeId, Vec<NodeId>> = HashMap::default();
+    let mut children: HashMap<NodeId, Vec<NodeId>> = HashMap::default();
+    for (parent, child, _) in graph.hierarchy().graph().all_edges() {
+        parents.entry(child).or_default().push(parent);
+        children.entry(parent).or_default().push(child);
+    }
+
+    let name = |id: NodeId| -> String {
+        match id {
+            NodeId::System(_) => graph
+                .get_system_at(id)
+                .map(|system| system.name().to_string()),
+            NodeId::Set(_) => graph.get_set_at(id).map(|set| format!("{set:?}")),
+        }
+        .unwrap_or_else(|| format!("{id:?}"))
+    };
+
+    let mut violations = Vec::new();
+    let mut system_phases: HashMap<NodeId, TickPhase> = HashMap::default();
+
+    for (id, _system, _membership, _conditions) in graph.systems() {
+        let ancestors = ancestors(id, &parents);
+        let mut phases: Vec<TickPhase> = ancestors
+            .iter()
+            .filter_map(|ancestor| phase_ids.get(ancestor).copied())
+            .collect();
+        phases.sort();
+
+        match phases.len() {
+            0 => {
+                if simulation_set_id.is_some_and(|sim_id| ancestors.contains(&sim_id)) {
+                    violations.push(TickPhaseViolation::MissingPhase { system: name(id) });
+                }
+            }
+            1 => {
+                system_phases.insert(id, phases[0]);
+            }
+            _ => violations.push(TickPhaseViolation::MultiplePhases {
+                system: name(id),
+                phases,
+            }),
+        }
+    }
+
+    let phase_of = |id: NodeId| -> Option<TickPhase> {
+        if let Some(&phase) = phase_ids.get(&id).or_else(|| system_phases.get(&id)) {
+            return Some(phase);
+        }
+
+        let mut phases: HashSet<TickPhase> = ancestors(id, &parents)
+            .iter()
+            .filter_map(|ancestor| phase_ids.get(ancestor).copied())
+            .collect();
+
+        if phases.is_empty() {
+            phases = descendants(id, &children)
+                .iter()
+                .filter_map(|descendant| system_phases.get(descendant).copied())
+                .collect();
+        }
+
+        match phases.len() {
+            1 => phases.into_iter().next(),
+            _ => None,
+        }
+    };
+
+    let mut edges: Vec<(NodeId, NodeId)> = graph
+        .dependency()
+        .graph()
+        .all_edges()
+        .map(|(before, after, _)| (before, after))
+        .collect();
+    // Report violations in a stable order
+    edges.sort_by_key(|&(before, after)| (format!("{before:?}"), format!("{after:?}")));
+
+    for (before, after) in edges {
+        let (Some(before_phase), Some(after_phase)) = (phase_of(before), phase_of(after)) else {
+            continue;
+        };
+
+        if before_phase > after_phase {
+            violations.push(TickPhaseViolation::OutOfOrder {
+                before: name(before),
+                before_phase,
+                after: name(after),
+                after_phase,
+            });
+        }
+    }
+
+    violations
+}
+
+/// All nodes reachable by repeatedly following `edges` from `start`, excluding `start` itself.
+#[cfg(test)]
+fn reachable(start: NodeId, edges: &HashMap<NodeId, Vec<NodeId>>) -> HashSet<NodeId> {
+    let mut visited = HashSet::default();
+    let mut stack = vec![start];
+
+    while let Some(node) = stack.pop() {
+        for &next in edges.get(&node).into_iter().flatten() {
+            if visited.insert(next) {
+                stack.push(next);
+            }
+        }
+    }
+
+    visited
+}
+
+/// All of the sets that `id` is nested within.
+#[cfg(test)]
+fn ancestors(id: NodeId, parents: &HashMap<NodeId, Vec<NodeId>>) -> HashSet<NodeId> {
+    reachable(id, parents)
+}
+
+/// All of the systems and sets nested within `id`.
+#[cfg(test)]
+fn descendants(id: NodeId, children: &HashMap<NodeId, Vec<NodeId>>) -> HashSet<NodeId> {
+    reachable(id, children)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::headless_simulation_app;
+    use crate::world_gen::GenerationConfig;
+
+    /// A system that does nothing.
+    fn sense() {}
+
+    /// Another system that does nothing.
+    fn decide() {}
+
+    /// A third system that does nothing.
+    fn tidy_up() {}
+
+    /// A schedule with the tick phases configured.
+    fn phased_schedule() -> Schedule {
+        let mut schedule = Schedule::new();
+        configure_tick_phases(&mut schedule);
+        schedule
+    }
+
+    #[test]
+    fn phases_are_declared_in_canonical_order() {
+        let phases: Vec<TickPhase> = TickPhase::variants().collect();
+        let mut sorted = phases.clone();
+        sorted.sort();
+
+        assert_eq!(phases, sorted);
+        assert_eq!(phases.first(), Some(&TickPhase::Perception));
+        assert_eq!(phases.last(), Some(&TickPhase::Bookkeeping));
+    }
+
+    #[test]
+    fn conforming_schedule_has_no_violations() {
+        let mut schedule = phased_schedule();
+        schedule
+            .add_system(sense.in_set(TickPhase::Perception))
+            .add_system(decide.in_set(TickPhase::Decision).after(sense))
+            .add_system(tidy_up.in_set(TickPhase::Bookkeeping));
+
+        assert_eq!(check_tick_phases(&schedule), Vec::new());
+    }
+
+    #[test]
+    fn systems_outside_the_simulation_are_ignored() {
+        let mut schedule = phased_schedule();
+        schedule.add_system(sense);
+
+        assert_eq!(check_tick_phases(&schedule), Vec::new());
+    }
+
+    #[test]
+    fn simulation_systems_without_a_phase_are_reported_by_name() {
+        let mut schedule = phased_schedule();
+        schedule.add_system(sense.in_set(SimulationSet));
+
+        let violations = check_tick_phases(&schedule);
+        assert_eq!(violations.len(), 1);
+        assert!(matches!(
+            &violations[0],
+            TickPhaseViolation::MissingPhase { system } if system.ends_with("sense")
+        ));
+    }
+
+    #[test]
+    fn systems_in_two_phases_are_reported() {
+        let mut schedule = phased_schedule();
+        schedule.add_system(
+            sense
+                .in_set(TickPhase::Perception)
+                .in_set(TickPhase::Action),
+        );
+
+        let violations = check_tick_phases(&schedule);
+        assert_eq!(violations.len(), 1);
+        assert!(matches!(
+            &violations[0],
+            TickPhaseViolation::MultiplePhases { system, phases }
+                if system.ends_with("sense") && phases == &[TickPhase::Perception, TickPhase::Action]
+        ));
+    }
+
+    #[test]
+    fn simulation_plugins_follow_the_tick_phase_rules() {
+        let app = headless_simulation_app(GenerationConfig::testing());
+        let schedule = app
+            .get_schedule(CoreSchedule::FixedUpdate)
+            .expect("The simulation adds systems to the fixed update schedule");
+
+        let violations: Vec<String> = check_tick_phases(schedule)
+            .iter()
+            .map(|violation| violation.to_string())
+            .collect();
+        assert_eq!(violations, Vec::<String>::new());
+    }
+
+    #[test]
+    fn backwards_dependencies_are_reported() {
+        let mut schedule = phased_schedule();
+        schedule
+            .add_system(tidy_up.in_set(TickPhase::Bookkeeping))
+            .add_system(decide.in_set(TickPhase::Decision).after(tidy_up));
+
+        let violations = check_tick_phases(&schedule);
+        assert!(!violations.is_empty());
+        assert!(violations.iter().all(|violation| matches!(
+            violation,
+            TickPhaseViolation::OutOfOrder {
+                before_phase: TickPhase::Bookkeeping,
+                after_phase: TickPhase::Decision,
+                ..
+            }
+        )));
+        assert!(violations[0].to_string().contains("tidy_up"));
+    }
+}
@@ -0,0 +1,273 @@
+//! Records notable random decisions, so that surprising outcomes can be explained after the fact.
+//!
+//! This module only exists when the `probability_audit` feature is enabled.
+//! Add a [`ChanceAudit`] parameter to the system making the roll,
+//! and use the [`record_chance!`](crate::record_chance) macro to record it:
+//! both compile to nothing when the feature is disabled.
+
+use std::collections::VecDeque;
+use std::fmt::Display;
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+/// A single random decision made by the simulation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChanceRecord {
+    /// The name of the system that made the decision.
+    pub system: &'static str,
+    /// The simulation tick on which the decision was made.
+    pub tick: u64,
+    /// The chance that the roll would succeed, between 0 and 1.
+    pub probability: f32,
+    /// The random number that was drawn, between 0 and 1.
+    pub rolled: f32,
+    /// Did the roll succeed?
+    pub outcome: bool,
+    /// The entity that the decision was about, if any.
+    pub subject: Option<Entity>,
+}
+
+impl Display for ChanceRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let outcome = if self.outcome { "succeeded" } else { "failed" };
+        write!(
+            f,
+            "[tick {}] {}: rolled {:.3} against {:.3} and {outcome}",
+            self.tick, self.system, self.rolled, self.probability
+        )?;
+
+        if let Some(subject) = self.subject {
+            write!(f, " ({subject:?})")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A bounded ring buffer of [`ChanceRecord`]s.
+///
+/// Once full, the oldest records are discarded first.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct ChanceLog {
+    /// The stored records, from oldest to newest.
+    records: VecDeque<ChanceRecord>,
+    /// The maximum number of records that will be stored.
+    capacity: usize,
+    /// The current simulation tick.
+    current_tick: u64,
+}
+
+impl ChanceLog {
+    /// The number of records kept by default.
+    pub const DEFAULT_CAPACITY: usize = 1024;
+
+    /// Creates an empty log that stores at most `capacity` records.
+    pub fn new(capacity: usize) -> Self {
+        ChanceLog {
+            records: VecDeque::new(),
+            capacity,
+            current_tick: 0,
+        }
+    }
+
+    /// Adds a record to the log, discarding the oldest record if the log is full.
+    ///
+    /// The record's `tick` is overwritten with the current tick.
+    pub fn push(&mut self, mut record: ChanceRecord) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if self.records.len() == self.capacity {
+            self.records.pop_front();
+        }
+
+        record.tick = self.current_tick;
+        self.records.push_back(record);
+    }
+
+    /// All stored records, from oldest to newest.
+    pub fn records(&self) -> impl DoubleEndedIterator<Item = &ChanceRecord> {
+        self.records.iter()
+    }
+
+    /// The stored records about `subject`, from oldest to newest.
+    pub fn records_for(&self, subject: Entity) -> impl Iterator<Item = &ChanceRecord> {
+        self.records
+            .iter()
+            .filter(move |record| record.subject == Some(subject))
+    }
+
+    /// The number of stored records.
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Are there no stored records?
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Renders every stored record on its own line, suitable for including in bug reports.
+    pub fn dump(&self) -> String {
+        self.records
+            .iter()
+            .map(|record| record.to_string())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl Default for ChanceLog {
+    fn default() -> Self {
+        ChanceLog::new(ChanceLog::DEFAULT_CAPACITY)
+    }
+}
+
+/// A system parameter used to record random decisions in the [`ChanceLog`].
+///
+/// Prefer the [`record_chance!`](crate::record_chance) macro, which is removed entirely when auditing is disabled.
+#[derive(SystemParam)]
+pub struct ChanceAudit<'w> {
+    /// The log that records are added to, if it exists.
+    log: Option<ResMut<'w, ChanceLog>>,
+}
+
+impl ChanceAudit<'_> {
+    /// Adds a record to the [`ChanceLog`], if there is one.
+    pub fn record(&mut self, record: ChanceRecord) {
+        if let Some(log) = self.log.as_mut() {
+            log.push(record);
+        }
+    }
+}
+
+/// Advances the tick stored in new records.
+pub(super) fn advance_audit_tick(mut chance_log: ResMut<ChanceLog>) {
+    chance_log.current_tick += 1;
+}
+
+/// Records a random decision in the [`ChanceLog`], using the provided [`ChanceAudit`].
+///
+/// When the `probability_audit` feature is disabled, this expands to nothing
+/// beyond borrowing the provided values.
+#[macro_export]
+macro_rules! record_chance {
+    (
+        audit: $audit:expr,
+        system: $system:expr,
+        probability: $probability:expr,
+        rolled: $rolled:expr,
+        outcome: $outcome:expr,
+        subject: $subject:expr $(,)?
+    ) => {
+        $audit.record($crate::simulation::probability_audit::ChanceRecord {
+            system: $system,
+            tick: 0,
+            probability: $probability,
+            rolled: $rolled,
+            outcome: $outcome,
+            subject: $subject,
+        })
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A record with the provided `subject`.
+    fn record_about(subject: Option<Entity>) -> ChanceRecord {
+        ChanceRecord {
+            system: "test",
+            tick: 0,
+            probability: 0.5,
+            rolled: 0.25,
+            outcome: true,
+            subject,
+        }
+    }
+
+    #[test]
+    fn ring_buffer_caps_records() {
+        let mut log = ChanceLog::new(3);
+        for i in 0..5 {
+            log.current_tick = i;
+            log.push(record_about(None));
+        }
+
+        assert_eq!(log.len(), 3);
+        let ticks: Vec<u64> = log.records().map(|record| record.tick).collect();
+        assert_eq!(ticks, vec![2, 3, 4]);
+    }
+
+    #[test]
+    fn zero_capacity_stores_nothing() {
+        let mut log = ChanceLog::new(0);
+        log.push(record_about(None));
+        assert!(log.is_empty());
+    }
+
+    #[test]
+    fn can_filter_by_subject() {
+        let a = Entity::from_raw(1);
+        let b = Entity::from_raw(2);
+
+        let mut log = ChanceLog::new(10);
+        log.push(record_about(Some(a)));
+        log.push(record_about(Some(b)));
+        log.push(record_about(None));
+        log.push(record_about(Some(a)));
+
+        assert_eq!(log.records_for(a).count(), 2);
+        assert_eq!(log.records_for(b).count(), 1);
+    }
+
+    #[test]
+    fn macro_records_all_fields() {
+        let subject = Entity::from_raw(9001);
+
+        let roll = move |mut audit: ChanceAudit| {
+            record_chance!(
+                audit: audit,
+                system: "macro_records_all_fields",
+                probability: 0.05,
+                rolled: 0.01,
+                outcome: true,
+                subject: Some(subject),
+            );
+        };
+
+        let mut app = App::new();
+        app.init_resource::<ChanceLog>()
+            .add_systems((roll, advance_audit_tick).chain());
+        app.update();
+        app.update();
+
+        let log = app.world.resource::<ChanceLog>();
+        assert_eq!(log.records_for(subject).count(), 2);
+        let record = log.records_for(subject).last().unwrap();
+        assert_eq!(record.system, "macro_records_all_fields");
+        assert_eq!(record.probability, 0.05);
+        assert_eq!(record.rolled, 0.01);
+        assert!(record.outcome);
+        assert_eq!(record.tick, 1);
+    }
+
+    #[test]
+    fn recording_without_a_log_does_nothing() {
+        let mut app = App::new();
+        app.add_system(|mut audit: ChanceAudit| {
+            record_chance!(
+                audit: audit,
+                system: "recording_without_a_log_does_nothing",
+                probability: 0.5,
+                rolled: 0.5,
+                outcome: false,
+                subject: None,
+            );
+        });
+
+        app.update();
+    }
+}
@@ -6,9 +6,11 @@
 use bevy::prelude::*;
 use rand::{rngs::SmallRng, SeedableRng};
 
+use super::sim_resources::SimResource;
+
 /// A global source of entropy.
-#[derive(Debug, Clone, Resource, PartialEq, Eq, Deref, DerefMut)]
-pub(crate) struct GlobalRng(SmallRng);
+#[derive(Debug, Clone, Resource, PartialEq, Eq, Deref, DerefMut, Reflect)]
+pub(crate) struct GlobalRng(#[reflect(ignore)] SmallRng);
 
 impl GlobalRng {
     /// Creates a new seeded RNG
@@ -21,3 +23,9 @@ impl GlobalRng {
         &mut self.0
     }
 }
+
+/// The RNG is reseeded when a new map is generated, rather than reset.
+impl SimResource for GlobalRng {
+    const NAME: &'static str = "rng";
+    const PERSIST: bool = true;
+}
@@ -0,0 +1,287 @@
+//! A single registry of the resources that hold simulation state.
+//!
+//! Tearing down the map, saving the game and reporting memory usage all need to know which resources make up the simulation.
+//! Rather than each keeping their own list, they all read the [`SimResources`] registry.
+//! Register resources with [`SimResourceAppExt::register_sim_resource`] when building the plugin that adds them.
+
+use std::any::TypeId;
+
+use bevy::{prelude::*, utils::HashSet};
+
+use crate::utils::memory::{MemoryReport, MemoryReportAppExt, MemoryUsage};
+
+/// A resource that holds simulation state.
+pub trait SimResource: Resource {
+    /// The name of this resource, as used in reports.
+    const NAME: &'static str;
+
+    /// Should this resource be stored when the game is saved?
+    ///
+    /// Resources that can be derived from other state, or that only describe the player's view, should not be persisted.
+    const PERSIST: bool;
+
+    /// Discards any state that refers to the current map, before a new map is generated.
+    ///
+    /// Resources that are replaced outright during world generation do not need to do anything here.
+    fn reset_to_default(&mut self) {}
+
+    /// Estimates the memory used by this resource, if it is present.
+    ///
+    /// By default, this is just the size of the resource itself.
+    /// Resources that own collections should override this to include them.
+    fn measure_memory(&self) -> Option<MemoryUsage> {
+        let size = std::mem::size_of_val(self);
+        Some(MemoryUsage {
+            used_bytes: size,
+            allocated_bytes: size,
+        })
+    }
+}
+
+/// A single registered [`SimResource`].
+#[derive(Clone, Copy)]
+struct SimResourceEntry {
+    /// The [`SimResource::NAME`] of the resource.
+    name: &'static str,
+    /// The [`TypeId`] of the resource.
+    type_id: TypeId,
+    /// The [`SimResource::PERSIST`] flag of the resource.
+    persist: bool,
+    /// Calls [`SimResource::reset_to_default`] on the resource, if it exists.
+    reset: fn(&mut World),
+}
+
+/// The registry of every [`SimResource`].
+///
+/// Add entries with [`SimResourceAppExt::register_sim_resource`].
+#[derive(Resource, Default, Reflect)]
+#[reflect(Resource)]
+pub struct SimResources {
+    /// The registered resources, in the order they were registered.
+    #[reflect(ignore)]
+    entries: Vec<SimResourceEntry>,
+}
+
+impl SimResources {
+    /// The names of all registered resources.
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.entries.iter().map(|entry| entry.name)
+    }
+
+    /// The names of the registered resources that should be stored when the game is saved.
+    pub fn persistent(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.entries
+            .iter()
+            .filter(|entry| entry.persist)
+            .map(|entry| entry.name)
+    }
+
+    /// Resets every registered resource that is present in the `world`, in the order they were registered.
+    pub fn reset_all(world: &mut World) {
+        let Some(sim_resources) = world.get_resource::<SimResources>() else {
+            return;
+        };
+
+        let resets: Vec<fn(&mut World)> = sim_resources
+            .entries
+            .iter()
+            .map(|entry| entry.reset)
+            .collect();
+
+        for reset in resets {
+            reset(world);
+        }
+    }
+
+    /// Lists the type names of resources in the `world` that have not been registered.
+    ///
+    /// Resources whose types are in `ignored` are skipped, as is the bookkeeping for the registry itself.
+    /// Collect `ignored` with [`resource_type_ids`] before adding the plugins that should register their resources,
+    /// to check that those plugins have not forgotten any.
+    pub fn unregistered(&self, world: &World, ignored: &HashSet<TypeId>) -> Vec<String> {
+        let registered: HashSet<TypeId> = self.entries.iter().map(|entry| entry.type_id).collect();
+        let bookkeeping = [TypeId::of::<SimResources>(), TypeId::of::<MemoryReport>()];
+
+        let mut unregistered: Vec<String> = present_resources(world)
+            .filter(|(type_id, _)| {
+                !registered.contains(type_id)
+                    && !ignored.contains(type_id)
+                    && !bookkeeping.contains(type_id)
+            })
+            .map(|(_, name)| name.to_string())
+            .collect();
+
+        unregistered.sort();
+        unregistered
+    }
+}
+
+/// The types of every resource currently in the `world`.
+pub fn resource_type_ids(world: &World) -> HashSet<TypeId> {
+    present_resources(world)
+        .map(|(type_id, _)| type_id)
+        .collect()
+}
+
+/// The type and name of each resource currently in the `world`.
+fn present_resources(world: &World) -> impl Iterator<Item = (TypeId, &str)> + '_ {
+    world.components().iter().filter_map(|component_info| {
+        let type_id = component_info.type_id()?;
+        let is_present = world
+            .storages()
+            .resources
+            .get(component_info.id())
+            .is_some_and(|resource_data| resource_data.is_present());
+
+        is_present.then_some((type_id, component_info.name()))
+    })
+}
+
+/// An [`App`] extension trait to register [`SimResource`]s.
+pub trait SimResourceAppExt {
+    /// Adds the resource `R` to the [`SimResources`] registry, and to the [`MemoryReport`].
+    ///
+    /// This does not insert the resource itself.
+    fn register_sim_resource<R: SimResource>(&mut self) -> &mut Self;
+}
+
+impl SimResourceAppExt for App {
+    fn register_sim_resource<R: SimResource>(&mut self) -> &mut Self {
+        self.init_resource::<SimResources>();
+        let mut sim_resources = self.world.resource_mut::<SimResources>();
+        sim_resources.entries.push(SimResourceEntry {
+            name: R::NAME,
+            type_id: TypeId::of::<R>(),
+            persist: R::PERSIST,
+            reset: |world: &mut World| {
+                if let Some(mut resource) = world.get_resource_mut::<R>() {
+                    resource.reset_to_default();
+                }
+            },
+        });
+
+        self.register_memory_measurement(R::NAME, |world: &World| {
+            world
+                .get_resource::<R>()
+                .and_then(SimResource::measure_memory)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A resource that counts how often it has been reset.
+    #[derive(Resource, Default)]
+    struct Counter {
+        /// The number of times this has been reset.
+        resets: u32,
+    }
+
+    impl SimResource for Counter {
+        const NAME: &'static str = "counter";
+        const PERSIST: bool = true;
+
+        fn reset_to_default(&mut self) {
+            self.resets += 1;
+        }
+
+        fn measure_memory(&self) -> Option<MemoryUsage> {
+            Some(MemoryUsage {
+                used_bytes: 4,
+                allocated_bytes: 4,
+            })
+        }
+    }
+
+    /// A resource that describes the player's view, rather than the simulation.
+    #[derive(Resource, Default)]
+    struct Viewpoint;
+
+    impl SimResource for Viewpoint {
+        const NAME: &'static str = "viewpoint";
+        const PERSIST: bool = false;
+    }
+
+    /// A resource that a plugin forgot to register.
+    #[derive(Resource, Default)]
+    struct Forgotten;
+
+    /// A plugin that registers all but one of its resources.
+    struct ForgetfulPlugin;
+
+    impl Plugin for ForgetfulPlugin {
+        fn build(&self, app: &mut App) {
+            app.init_resource::<Counter>()
+                .init_resource::<Viewpoint>()
+                .init_resource::<Forgotten>()
+                .register_sim_resource::<Counter>()
+                .register_sim_resource::<Viewpoint>();
+        }
+    }
+
+    #[test]
+    fn registered_resources_are_listed() {
+        let mut app = App::new();
+        app.add_plugin(ForgetfulPlugin);
+
+        let sim_resources = app.world.resource::<SimResources>();
+        assert_eq!(
+            sim_resources.names().collect::<Vec<_>>(),
+            vec!["counter", "viewpoint"]
+        );
+        assert_eq!(
+            sim_resources.persistent().collect::<Vec<_>>(),
+            vec!["counter"]
+        );
+    }
+
+    #[test]
+    fn reset_all_resets_each_resource_once() {
+        let mut app = App::new();
+        app.add_plugin(ForgetfulPlugin);
+
+        SimResources::reset_all(&mut app.world);
+        assert_eq!(app.world.resource::<Counter>().resets, 1);
+
+        // Missing resources are skipped
+        app.world.remove_resource::<Viewpoint>();
+        SimResources::reset_all(&mut app.world);
+        assert_eq!(app.world.resource::<Counter>().resets, 2);
+    }
+
+    #[test]
+    fn memory_report_is_derived_from_the_registry() {
+        let mut app = App::new();
+        app.add_plugin(ForgetfulPlugin);
+
+        let report = app.world.resource::<MemoryReport>();
+        assert_eq!(
+            report.names().collect::<Vec<_>>(),
+            vec!["counter", "viewpoint"]
+        );
+
+        // Resources without collections are measured by their size alone
+        let measurements = report.measure(&app.world);
+        assert_eq!(measurements.len(), report.names().count());
+        assert_eq!(
+            measurements[1],
+            ("viewpoint", MemoryUsage::default()),
+            "Viewpoint has no fields"
+        );
+    }
+
+    #[test]
+    fn forgotten_resources_are_caught() {
+        let mut app = App::new();
+        let baseline = resource_type_ids(&app.world);
+        app.add_plugin(ForgetfulPlugin);
+
+        let sim_resources = app.world.resource::<SimResources>();
+        assert_eq!(
+            sim_resources.unregistered(&app.world, &baseline),
+            vec![std::any::type_name::<Forgotten>().to_string()]
+        );
+    }
+}
@@ -0,0 +1,468 @@
+//! Detects slow leaks and performance drift over very long simulation runs.
+//!
+//! Insert a [`SoakRecorder`] into a headless app, drive it for many ticks with [`run_soak`],
+//! then call [`SoakRecorder::report`] to check whether anything grew without bound.
+
+use std::{
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
+use bevy::{core::FrameCount, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{organisms::Organism, utils::memory::MemoryReport};
+
+/// Runs `n_ticks` simulation ticks in `app`, sampling it with the app's [`SoakRecorder`].
+///
+/// Ticks are run by calling [`CoreSchedule::FixedUpdate`] directly, rather than by updating the app,
+/// so no time is spent waiting for the fixed timestep and only the simulation itself is timed.
+/// Each tick is treated as a new frame, so the cap on ticks per frame never stalls the run.
+///
+/// # Panics
+///
+/// Panics if `app` does not contain a [`SoakRecorder`].
+pub fn run_soak(app: &mut App, n_ticks: u64) {
+    for _ in 0..n_ticks {
+        if let Some(mut frame_count) = app.world.get_resource_mut::<FrameCount>() {
+            frame_count.0 = frame_count.0.wrapping_add(1);
+        }
+
+        let start = Instant::now();
+        app.world.run_schedule(CoreSchedule::FixedUpdate);
+        let tick_duration = start.elapsed();
+
+        let should_sample = app
+            .world
+            .resource_mut::<SoakRecorder>()
+            .advance(tick_duration);
+        if should_sample {
+            record_soak_sample(&mut app.world);
+        }
+    }
+}
+
+/// A snapshot of the simulation, taken during a soak run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SoakSample {
+    /// The number of ticks since the soak run began.
+    pub tick: u64,
+    /// The total number of entities in the world.
+    pub entity_count: u32,
+    /// The bytes used by the resources in the [`MemoryReport`].
+    pub memory_bytes: usize,
+    /// The average time spent running each tick since the previous sample, in seconds.
+    pub seconds_per_tick: f32,
+    /// The number of living organisms.
+    pub population: usize,
+}
+
+/// A quantity that grew steadily over a soak run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Drift {
+    /// The number of entities grew while the population was stable.
+    EntityCount,
+    /// The memory footprint grew while the population was stable.
+    MemoryFootprint,
+    /// Ticks took longer and longer to simulate.
+    TickDuration,
+}
+
+impl Display for Drift {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let description = match self {
+            Drift::EntityCount => "the entity count grew while the population was stable",
+            Drift::MemoryFootprint => "the memory footprint grew while the population was stable",
+            Drift::TickDuration => "ticks took steadily longer to simulate",
+        };
+
+        write!(f, "{description}")
+    }
+}
+
+/// Decides whether a series of measurements is trending upwards.
+///
+/// A straight line is fit to the most recent `window` samples by least squares.
+/// The series is drifting if that line rises by more than `max_growth` of the series' baseline across the window,
+/// where the baseline is the mean of the first `window` samples.
+/// Measuring against the baseline rather than the recent level means that a steady leak is still caught
+/// long after it has inflated the measurement.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DriftDetector {
+    /// The number of samples to fit, and to average for the baseline.
+    pub window: usize,
+    /// The largest tolerated rise across the window, as a fraction of the baseline.
+    pub max_growth: f64,
+}
+
+impl Default for DriftDetector {
+    fn default() -> Self {
+        DriftDetector {
+            window: 100,
+            max_growth: 0.1,
+        }
+    }
+}
+
+impl DriftDetector {
+    /// Is the series of `(tick, value)` pairs trending upwards?
+    ///
+    /// Series with fewer than three points never count as drifting.
+    pub fn is_drifting(&self, series: &[(f64, f64)]) -> bool {
+        let recent = &series[series.len().saturating_sub(self.window)..];
+        if recent.len() < 3 {
+            return false;
+        }
+
+        let baseline = &series[..self.window.min(series.len())];
+        let baseline_mean = baseline.iter().map(|&(_, y)| y).sum::<f64>() / baseline.len() as f64;
+
+        let slope = linear_regression_slope(recent);
+        let span = recent[recent.len() - 1].0 - recent[0].0;
+
+        slope * span > self.max_growth * baseline_mean.abs().max(f64::EPSILON)
+    }
+}
+
+/// The slope of the least-squares line through `points`.
+///
+/// Returns 0 if all of the points share the same x value.
+fn linear_regression_slope(points: &[(f64, f64)]) -> f64 {
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|&(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|&(_, y)| y).sum::<f64>() / n;
+
+    let mut covariance = 0.;
+    let mut variance = 0.;
+    for &(x, y) in points {
+        covariance += (x - mean_x) * (y - mean_y);
+        variance += (x - mean_x) * (x - mean_x);
+    }
+
+    if variance == 0. {
+        0.
+    } else {
+        covariance / variance
+    }
+}
+
+/// The results of a soak run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SoakReport {
+    /// Every sample taken, in order.
+    pub samples: Vec<SoakSample>,
+    /// The quantities that were found to be drifting.
+    pub drifts: Vec<Drift>,
+}
+
+impl SoakReport {
+    /// Checks the `samples` for drift.
+    pub fn analyze(samples: Vec<SoakSample>, detector: DriftDetector) -> Self {
+        /// Extracts a single measurement from each sample.
+        fn series(samples: &[SoakSample], f: impl Fn(&SoakSample) -> f64) -> Vec<(f64, f64)> {
+            samples
+                .iter()
+                .map(|sample| (sample.tick as f64, f(sample)))
+                .collect()
+        }
+
+        let population_stable =
+            !detector.is_drifting(&series(&samples, |sample| sample.population as f64));
+
+        let mut drifts = Vec::new();
+        if population_stable
+            && detector.is_drifting(&series(&samples, |sample| sample.entity_count as f64))
+        {
+            drifts.push(Drift::EntityCount);
+        }
+        if population_stable
+            && detector.is_drifting(&series(&samples, |sample| sample.memory_bytes as f64))
+        {
+            drifts.push(Drift::MemoryFootprint);
+        }
+        if detector.is_drifting(&series(&samples, |sample| sample.seconds_per_tick as f64)) {
+            drifts.push(Drift::TickDuration);
+        }
+
+        SoakReport { samples, drifts }
+    }
+
+    /// Did the soak run finish without any drift?
+    pub fn passed(&self) -> bool {
+        self.drifts.is_empty()
+    }
+
+    /// The last tick that was sampled, if any.
+    pub fn last_tick(&self) -> Option<u64> {
+        self.samples.last().map(|sample| sample.tick)
+    }
+}
+
+impl Display for SoakReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let n_ticks = self.last_tick().unwrap_or_default();
+        writeln!(f, "{} samples over {n_ticks} ticks", self.samples.len())?;
+
+        if self.passed() {
+            write!(f, "No drift detected")
+        } else {
+            for drift in &self.drifts {
+                writeln!(f, "Drift: {drift}")?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Collects [`SoakSample`]s as the simulation runs.
+#[derive(Resource, Debug)]
+pub struct SoakRecorder {
+    /// How many ticks pass between samples.
+    sample_interval: u64,
+    /// The number of ticks since the soak run began.
+    tick: u64,
+    /// The samples taken so far.
+    samples: Vec<SoakSample>,
+    /// The time spent running ticks since the previous sample.
+    time_since_sample: Duration,
+}
+
+impl SoakRecorder {
+    /// Starts a fresh soak run.
+    pub fn new(sample_interval: u64) -> Self {
+        SoakRecorder {
+            sample_interval: sample_interval.max(1),
+            tick: 0,
+            samples: Vec::new(),
+            time_since_sample: Duration::ZERO,
+        }
+    }
+
+    /// Continues the soak run recorded in `report`, so a long run can be split into pieces.
+    ///
+    /// Tick numbers carry on from the last sample in the report.
+    pub fn resume(report: SoakReport, sample_interval: u64) -> Self {
+        SoakRecorder {
+            tick: report.last_tick().unwrap_or_default(),
+            samples: report.samples,
+            ..SoakRecorder::new(sample_interval)
+        }
+    }
+
+    /// Advances by one tick that took `tick_duration` to run, returning `true` if a sample should be taken.
+    fn advance(&mut self, tick_duration: Duration) -> bool {
+        self.time_since_sample += tick_duration;
+        self.tick += 1;
+        self.tick.is_multiple_of(self.sample_interval)
+    }
+
+    /// The samples taken so far.
+    pub fn samples(&self) -> &[SoakSample] {
+        &self.samples
+    }
+
+    /// Checks the samples taken so far for drift.
+    pub fn report(&self, detector: DriftDetector) -> SoakReport {
+        SoakReport::analyze(self.samples.clone(), detector)
+    }
+}
+
+/// Adds a [`SoakSample`] of the `world` to its [`SoakRecorder`].
+fn record_soak_sample(world: &mut World) {
+    let entity_count = world.entities().len();
+    let memory_bytes = world
+        .get_resource::<MemoryReport>()
+        .map(|report| {
+            report
+                .measure(world)
+                .iter()
+                .map(|(_name, usage)| usage.used_bytes)
+                .sum()
+        })
+        .unwrap_or_default();
+    let population = world
+        .query_filtered::<(), With<Organism>>()
+        .iter(world)
+        .count();
+
+    let mut recorder = world.resource_mut::<SoakRecorder>();
+    let seconds_per_tick =
+        recorder.time_since_sample.as_secs_f32() / recorder.sample_interval as f32;
+    recorder.time_since_sample = Duration::ZERO;
+
+    let tick = recorder.tick;
+    recorder.samples.push(SoakSample {
+        tick,
+        entity_count,
+        memory_bytes,
+        seconds_per_tick,
+        population,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+    use crate::simulation::{
+        phases::{configure_tick_phases, SimulationAppExt, TickPhase},
+        SimulationSet,
+    };
+
+    use super::*;
+
+    /// A sample where nothing interesting is happening.
+    fn sample(tick: u64) -> SoakSample {
+        SoakSample {
+            tick,
+            entity_count: 1000,
+            memory_bytes: 50_000,
+            seconds_per_tick: 1e-3,
+            population: 100,
+        }
+    }
+
+    /// A series of samples that fluctuate randomly around a constant level.
+    fn stable_samples(n: u64) -> Vec<SoakSample> {
+        let mut rng = SmallRng::seed_from_u64(0);
+
+        (0..n)
+            .map(|i| {
+                let mut sample = sample(i * 100);
+                sample.entity_count += rng.gen_range(0..50);
+                sample.memory_bytes += rng.gen_range(0..5_000);
+                sample.seconds_per_tick *= rng.gen_range(0.9..1.1);
+                sample.population += rng.gen_range(0..10);
+                sample
+            })
+            .collect()
+    }
+
+    #[test]
+    fn slope_of_a_line_is_recovered() {
+        let points: Vec<(f64, f64)> = (0..10).map(|x| (x as f64, 3. * x as f64 + 1.)).collect();
+        assert!((linear_regression_slope(&points) - 3.).abs() < 1e-9);
+        assert_eq!(linear_regression_slope(&[(1., 1.), (1., 5.)]), 0.);
+    }
+
+    #[test]
+    fn stable_noisy_series_passes() {
+        let report = SoakReport::analyze(stable_samples(500), DriftDetector::default());
+        assert!(report.passed(), "{report}");
+    }
+
+    #[test]
+    fn leaking_series_is_flagged() {
+        let mut samples = stable_samples(500);
+        for (i, sample) in samples.iter_mut().enumerate() {
+            // A couple of entities leak between each sample
+            sample.entity_count += 2 * i as u32;
+            sample.memory_bytes += 200 * i;
+        }
+
+        let report = SoakReport::analyze(samples, DriftDetector::default());
+        assert_eq!(
+            report.drifts,
+            vec![Drift::EntityCount, Drift::MemoryFootprint]
+        );
+    }
+
+    #[test]
+    fn growing_populations_are_not_leaks() {
+        let mut samples = stable_samples(500);
+        for (i, sample) in samples.iter_mut().enumerate() {
+            sample.population += i;
+            sample.entity_count += 2 * i as u32;
+        }
+
+        let report = SoakReport::analyze(samples, DriftDetector::default());
+        assert!(report.passed(), "{report}");
+    }
+
+    #[test]
+    fn slowing_ticks_are_flagged() {
+        let mut samples = stable_samples(500);
+        for (i, sample) in samples.iter_mut().enumerate() {
+            sample.seconds_per_tick += i as f32 * 1e-5;
+        }
+
+        let report = SoakReport::analyze(samples, DriftDetector::default());
+        assert_eq!(report.drifts, vec![Drift::TickDuration]);
+    }
+
+    #[test]
+    fn report_round_trips_through_json() {
+        let report = SoakReport {
+            samples: vec![sample(100), sample(200)],
+            drifts: vec![Drift::TickDuration],
+        };
+
+        let json = serde_json::to_string(&report).unwrap();
+        let parsed: SoakReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, report);
+    }
+
+    /// An app whose only simulation system spawns an entity every tick.
+    fn leaking_app(recorder: SoakRecorder) -> App {
+        let mut app = App::new();
+        app.insert_resource(recorder)
+            .edit_schedule(CoreSchedule::FixedUpdate, configure_tick_phases)
+            .add_simulation_system(TickPhase::Bookkeeping, |mut commands: Commands| {
+                commands.spawn_empty();
+            });
+        app
+    }
+
+    #[test]
+    fn runner_drives_the_fixed_update_schedule() {
+        let mut app = leaking_app(SoakRecorder::new(10));
+        run_soak(&mut app, 30);
+
+        let samples = app.world.resource::<SoakRecorder>().samples();
+        let ticks: Vec<u64> = samples.iter().map(|sample| sample.tick).collect();
+        let entity_counts: Vec<u32> = samples.iter().map(|sample| sample.entity_count).collect();
+        assert_eq!(ticks, vec![10, 20, 30]);
+        assert_eq!(entity_counts, vec![10, 20, 30]);
+        assert!(samples
+            .iter()
+            .all(|sample| sample.seconds_per_tick.is_finite() && sample.seconds_per_tick >= 0.));
+    }
+
+    #[test]
+    fn runner_ignores_the_tick_cap() {
+        let mut app = leaking_app(SoakRecorder::new(1));
+        app.init_resource::<FrameCount>()
+            .edit_schedule(CoreSchedule::FixedUpdate, |schedule| {
+                schedule.configure_set(
+                    SimulationSet.run_if(|frame_count: Res<FrameCount>| frame_count.is_changed()),
+                );
+            });
+        run_soak(&mut app, 5);
+
+        assert_eq!(app.world.entities().len(), 5);
+    }
+
+    #[test]
+    fn resuming_continues_tick_numbering() {
+        let mut app = leaking_app(SoakRecorder::new(10));
+        run_soak(&mut app, 25);
+
+        let first_half = app
+            .world
+            .resource::<SoakRecorder>()
+            .report(DriftDetector::default());
+        assert_eq!(first_half.last_tick(), Some(20));
+
+        let mut app = leaking_app(SoakRecorder::resume(first_half, 10));
+        run_soak(&mut app, 20);
+
+        let ticks: Vec<u64> = app
+            .world
+            .resource::<SoakRecorder>()
+            .samples()
+            .iter()
+            .map(|sample| sample.tick)
+            .collect();
+        assert_eq!(ticks, vec![10, 20, 30, 40]);
+    }
+}
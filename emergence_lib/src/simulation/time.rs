@@ -14,7 +14,9 @@ use crate::graphics::lighting::{Moon, Sun};
 use crate::organisms::lifecycle::Lifecycle;
 use crate::player_interaction::PlayerAction;
 
-use super::{PauseState, SimulationSet};
+use super::phases::{SimulationAppExt, TickPhase};
+use super::sim_resources::{SimResource, SimResourceAppExt};
+use super::PauseState;
 
 /// Introduces temporal variation into the environment.
 pub(crate) struct TemporalPlugin;
@@ -23,23 +25,23 @@ impl Plugin for TemporalPlugin {
     fn build(&self, app: &mut App) {
         app.add_state::<PauseState>()
             .insert_resource(FixedTime::new_from_secs(1.0 / 30.))
-            .add_systems(
+            .add_simulation_systems(
+                TickPhase::Bookkeeping,
                 (
                     advance_in_game_time,
                     move_celestial_bodies,
                     record_elapsed_time_for_lifecycles,
                 )
-                    .chain()
-                    .in_set(SimulationSet)
-                    .in_schedule(CoreSchedule::FixedUpdate),
+                    .chain(),
             )
             .add_system(pause_game)
-            .init_resource::<InGameTime>();
+            .init_resource::<InGameTime>()
+            .register_sim_resource::<InGameTime>();
     }
 }
 
 /// Stores the in game time.
-#[derive(Resource)]
+#[derive(Resource, Reflect)]
 pub struct InGameTime {
     /// How much time has elapsed, in units of in-game days.
     elapsed_time: Days,
@@ -52,6 +54,7 @@ pub struct InGameTime {
     Debug,
     Clone,
     Copy,
+    Default,
     Add,
     Sub,
     AddAssign,
@@ -60,6 +63,8 @@ pub struct InGameTime {
     PartialOrd,
     Serialize,
     Deserialize,
+    Reflect,
+    FromReflect,
 )]
 pub struct Days(pub f32);
 
@@ -164,6 +169,12 @@ impl Default for InGameTime {
     }
 }
 
+/// Time keeps running when a new map is generated.
+impl SimResource for InGameTime {
+    const NAME: &'static str = "in_game_time";
+    const PERSIST: bool = true;
+}
+
 /// Advances the in game time based on elapsed clock time when the game is not paused.
 pub fn advance_in_game_time(time: Res<FixedTime>, mut in_game_time: ResMut<InGameTime>) {
     let delta = Days(time.period.as_secs_f32() / in_game_time.seconds_per_day);
@@ -206,7 +217,7 @@ fn pause_game(
 }
 
 /// A [`Pool`] of [`Days`], which builds up and will eventually be filled (at which point some event will occur).
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Reflect, FromReflect)]
 pub struct TimePool {
     /// The current quantity of this pool.
     current: Days,
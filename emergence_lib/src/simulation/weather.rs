@@ -3,27 +3,33 @@
 use bevy::prelude::*;
 use derive_more::Display;
 use emergence_macros::IterableEnum;
+use hexx::Direction;
 use rand::rngs::ThreadRng;
 use rand::Rng;
 
 use crate as emergence_lib;
+use crate::simulation::phases::{SimulationAppExt, TickPhase};
+use crate::simulation::sim_resources::{SimResource, SimResourceAppExt};
 use crate::simulation::time::InGameTime;
+use crate::simulation::ChanceAudit;
 
 /// A plugin that handles weather.
 pub(crate) struct WeatherPlugin;
 
 impl Plugin for WeatherPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<CurrentWeather>().add_systems(
-            (set_daily_weather,)
-                .in_set(super::SimulationSet)
-                .in_schedule(CoreSchedule::FixedUpdate),
-        );
+        app.init_resource::<CurrentWeather>()
+            .init_resource::<Wind>()
+            .init_resource::<WeatherChances>()
+            .register_sim_resource::<CurrentWeather>()
+            .register_sim_resource::<Wind>()
+            .register_sim_resource::<WeatherChances>()
+            .add_simulation_system(TickPhase::Bookkeeping, set_daily_weather);
     }
 }
 
 /// The current weather.
-#[derive(Resource)]
+#[derive(Resource, Reflect)]
 pub struct CurrentWeather {
     /// The day that the weather was last updated.
     last_updated: u32,
@@ -40,6 +46,16 @@ impl Default for CurrentWeather {
     }
 }
 
+/// The weather is rolled once per day, counted from the start of the current map.
+impl SimResource for CurrentWeather {
+    const NAME: &'static str = "current_weather";
+    const PERSIST: bool = true;
+
+    fn reset_to_default(&mut self) {
+        *self = CurrentWeather::default();
+    }
+}
+
 impl CurrentWeather {
     /// Initializes this resource with the provided `weather`.
     #[cfg(test)]
@@ -57,10 +73,12 @@ impl CurrentWeather {
 }
 
 /// A type of weather.
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Display, IterableEnum)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Display, IterableEnum, Reflect, FromReflect)]
 pub(crate) enum Weather {
     /// A clear day.
     Clear,
+    /// A clear day without any wind.
+    Still,
     /// A cloudy day.
     Cloudy,
     /// A rainy day.
@@ -68,14 +86,23 @@ pub(crate) enum Weather {
 }
 
 impl Weather {
-    /// Chooses a random weather.
-    fn random(rng: &mut ThreadRng) -> Self {
-        match rng.gen_range(0..3) {
-            0 => Self::Clear,
-            1 => Self::Cloudy,
-            2 => Self::Rainy,
-            _ => unreachable!(),
-        }
+    /// Chooses a random weather, weighted by the provided `chances`.
+    fn random(
+        chances: &WeatherChances,
+        rng: &mut ThreadRng,
+        chance_audit: &mut ChanceAudit,
+    ) -> Self {
+        choose_weighted(
+            &[
+                (Self::Clear, chances.clear),
+                (Self::Still, chances.still),
+                (Self::Cloudy, chances.cloudy),
+                (Self::Rainy, chances.rainy),
+            ],
+            "daily_weather",
+            rng,
+            chance_audit,
+        )
     }
 
     /// The relative rate of precipitation for this kind of weather.
@@ -85,18 +112,329 @@ impl Weather {
     pub(crate) fn precipitation_rate(self) -> f32 {
         match self {
             Self::Clear => 0.,
+            Self::Still => 0.,
             Self::Cloudy => 0.0,
             Self::Rainy => 1.,
         }
     }
+
+    /// The strength of the [`Wind`] that accompanies this kind of weather.
+    pub(crate) fn wind_strength(self) -> f32 {
+        match self {
+            Self::Clear => 0.1,
+            Self::Still => 0.,
+            Self::Cloudy => 0.2,
+            Self::Rainy => 0.4,
+        }
+    }
+}
+
+/// The relative chance of each kind of [`Weather`] being rolled at the start of each day.
+///
+/// These are weights rather than probabilities: they must not be negative, and at least one must be positive.
+/// This is set from the [`Difficulty`](crate::world_gen::Difficulty) of the world when it is generated.
+#[derive(Resource, Debug, Clone, PartialEq, Reflect)]
+#[reflect(Resource)]
+pub struct WeatherChances {
+    /// The relative chance of a clear day.
+    pub clear: f32,
+    /// The relative chance of a clear day without any wind.
+    pub still: f32,
+    /// The relative chance of a cloudy day.
+    pub cloudy: f32,
+    /// The relative chance of a rainy day.
+    pub rainy: f32,
+}
+
+impl Default for WeatherChances {
+    /// Every kind of weather is equally likely.
+    fn default() -> Self {
+        WeatherChances {
+            clear: 1.,
+            still: 1.,
+            cloudy: 1.,
+            rainy: 1.,
+        }
+    }
+}
+
+/// The chances are derived from the world's difficulty, so they are not saved.
+impl SimResource for WeatherChances {
+    const NAME: &'static str = "weather_chances";
+    const PERSIST: bool = false;
+}
+
+/// The wind blowing across the map, which carries signals downwind as they diffuse.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct Wind {
+    /// The direction that the wind is blowing towards.
+    #[reflect(ignore)]
+    pub direction: Direction,
+    /// The fraction of each tile's diffusion budget that is carried downwind, between 0 and 1.
+    ///
+    /// At 0, signals diffuse equally in all directions.
+    pub strength: f32,
+}
+
+impl Wind {
+    /// No wind at all.
+    pub const CALM: Wind = Wind {
+        direction: Direction::Top,
+        strength: 0.,
+    };
+
+    /// Is the wind too weak to have any effect?
+    pub fn is_calm(&self) -> bool {
+        self.strength == 0.
+    }
+
+    /// An arrow pointing in the direction that the wind is blowing towards.
+    fn arrow(&self) -> char {
+        match self.direction {
+            Direction::TopRight => '↗',
+            Direction::Top => '↑',
+            Direction::TopLeft => '↖',
+            Direction::BottomLeft => '↙',
+            Direction::Bottom => '↓',
+            Direction::BottomRight => '↘',
+        }
+    }
+}
+
+impl Default for Wind {
+    fn default() -> Self {
+        Wind::CALM
+    }
+}
+
+/// The wind is rerolled along with the weather, so a new map starts out calm.
+impl SimResource for Wind {
+    const NAME: &'static str = "wind";
+    const PERSIST: bool = true;
+
+    fn reset_to_default(&mut self) {
+        *self = Wind::CALM;
+    }
+}
+
+impl std::fmt::Display for Wind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_calm() {
+            write!(f, "Calm")
+        } else {
+            write!(f, "{} {:.0}%", self.arrow(), self.strength * 100.)
+        }
+    }
 }
 
 /// Sets the weather for the day.
-fn set_daily_weather(in_game_time: Res<InGameTime>, mut current_weather: ResMut<CurrentWeather>) {
+///
+/// The wind picks up or dies down to match the new weather,
+/// and its direction shifts by at most one step so that it changes gradually.
+fn set_daily_weather(
+    in_game_time: Res<InGameTime>,
+    weather_chances: Res<WeatherChances>,
+    mut current_weather: ResMut<CurrentWeather>,
+    mut wind: ResMut<Wind>,
+    mut chance_audit: ChanceAudit,
+) {
     let current_day = in_game_time.elapsed_days() as u32;
     if current_weather.last_updated != current_day {
         current_weather.last_updated = current_day;
         let rng = &mut rand::thread_rng();
-        current_weather.weather = Weather::random(rng);
+        current_weather.weather = Weather::random(&weather_chances, rng, &mut chance_audit);
+
+        wind.strength = current_weather.weather.wind_strength();
+        wind.direction = choose_uniformly(
+            &[
+                wind.direction.counter_clockwise(),
+                wind.direction,
+                wind.direction.clockwise(),
+            ],
+            "wind_direction",
+            rng,
+            &mut chance_audit,
+        );
+    }
+}
+
+/// Chooses one of the `options` uniformly at random.
+///
+/// # Panics
+///
+/// Panics if `options` is empty.
+fn choose_uniformly<T: Copy>(
+    options: &[T],
+    system: &'static str,
+    rng: &mut ThreadRng,
+    chance_audit: &mut ChanceAudit,
+) -> T {
+    let weighted_options: Vec<(T, f32)> = options.iter().map(|&option| (option, 1.)).collect();
+    choose_weighted(&weighted_options, system, rng, chance_audit)
+}
+
+/// Chooses one of the `options` at random, in proportion to its weight.
+///
+/// The choice is made as a series of yes-or-no rolls, one per option,
+/// so that each roll can be recorded with [`record_chance!`](crate::record_chance).
+///
+/// # Panics
+///
+/// Panics if `options` is empty.
+fn choose_weighted<T: Copy>(
+    options: &[(T, f32)],
+    system: &'static str,
+    rng: &mut ThreadRng,
+    chance_audit: &mut ChanceAudit,
+) -> T {
+    let (&(last, _), rest) = options
+        .split_last()
+        .expect("There must be at least one option.");
+    let mut remaining_weight: f32 = options.iter().map(|(_, weight)| weight).sum();
+
+    for &(option, weight) in rest {
+        // Each remaining option gets its share of the remaining chance
+        let probability = weight / remaining_weight;
+        remaining_weight -= weight;
+        let roll = rng.gen::<f32>();
+        let chosen = roll < probability;
+        crate::record_chance!(
+            audit: *chance_audit,
+            system: system,
+            probability: probability,
+            rolled: roll,
+            outcome: chosen,
+            subject: None,
+        );
+
+        if chosen {
+            return option;
+        }
+    }
+
+    last
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enum_iter::IterableEnum;
+
+    #[test]
+    fn wind_strength_is_valid_for_all_weather() {
+        for weather in Weather::variants() {
+            assert!((0.0..=1.0).contains(&weather.wind_strength()));
+        }
+    }
+
+    #[test]
+    fn still_weather_is_calm() {
+        let wind = Wind {
+            direction: Direction::Top,
+            strength: Weather::Still.wind_strength(),
+        };
+
+        assert!(wind.is_calm());
+    }
+
+    #[test]
+    fn uniform_choices_are_fair() {
+        /// The number of times that each option was chosen.
+        #[derive(Resource, Default)]
+        struct Counts([u32; 4]);
+
+        let mut app = App::new();
+        app.init_resource::<Counts>().add_system(
+            |mut counts: ResMut<Counts>, mut chance_audit: ChanceAudit| {
+                let rng = &mut rand::thread_rng();
+                for _ in 0..4000 {
+                    let choice = choose_uniformly(
+                        &[0, 1, 2, 3],
+                        "uniform_choices_are_fair",
+                        rng,
+                        &mut chance_audit,
+                    );
+                    counts.0[choice] += 1;
+                }
+            },
+        );
+        app.update();
+
+        for count in app.world.resource::<Counts>().0 {
+            assert!((800..1200).contains(&count), "{count}");
+        }
+    }
+
+    #[test]
+    fn weighted_choices_skip_impossible_options() {
+        /// The number of times that each option was chosen.
+        #[derive(Resource, Default)]
+        struct Counts([u32; 3]);
+
+        let mut app = App::new();
+        app.init_resource::<Counts>().add_system(
+            |mut counts: ResMut<Counts>, mut chance_audit: ChanceAudit| {
+                let rng = &mut rand::thread_rng();
+                for _ in 0..3000 {
+                    let choice = choose_weighted(
+                        &[(0, 0.), (1, 3.), (2, 1.)],
+                        "weighted_choices_skip_impossible_options",
+                        rng,
+                        &mut chance_audit,
+                    );
+                    counts.0[choice] += 1;
+                }
+            },
+        );
+        app.update();
+
+        let [never, common, rare] = app.world.resource::<Counts>().0;
+        assert_eq!(never, 0);
+        assert!((2000..2500).contains(&common), "{common}");
+        assert!((500..1000).contains(&rare), "{rare}");
+    }
+
+    #[test]
+    fn weather_follows_its_chances() {
+        let mut app = App::new();
+        app.init_resource::<InGameTime>()
+            .init_resource::<Wind>()
+            .insert_resource(WeatherChances {
+                clear: 0.,
+                still: 0.,
+                cloudy: 0.,
+                rainy: 1.,
+            })
+            .insert_resource(CurrentWeather {
+                last_updated: u32::MAX,
+                weather: Weather::Clear,
+            })
+            .add_system(set_daily_weather);
+        app.update();
+
+        assert_eq!(app.world.resource::<CurrentWeather>().get(), Weather::Rainy);
+    }
+
+    #[cfg(feature = "probability_audit")]
+    #[test]
+    fn weather_rolls_are_recorded() {
+        use crate::simulation::probability_audit::ChanceLog;
+
+        let mut app = App::new();
+        app.init_resource::<ChanceLog>()
+            .init_resource::<InGameTime>()
+            .init_resource::<Wind>()
+            .init_resource::<WeatherChances>()
+            .insert_resource(CurrentWeather {
+                last_updated: u32::MAX,
+                weather: Weather::Clear,
+            })
+            .add_system(set_daily_weather);
+        app.update();
+
+        let chance_log = app.world.resource::<ChanceLog>();
+        for system in ["daily_weather", "wind_direction"] {
+            assert!(chance_log.records().any(|record| record.system == system));
+        }
     }
 }
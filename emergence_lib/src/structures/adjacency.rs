@@ -0,0 +1,332 @@
+//! Structures can be boosted (or hindered) by what is built or flowing next to them.
+//!
+//! Each structure variety may define a list of [`AdjacencyRule`]s in the structure manifest.
+//! The combined multiplier is cached in the [`AdjacencyBonus`] component,
+//! which is then read by the production systems.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+use crate::{
+    asset_management::manifest::Id,
+    geometry::{MapGeometry, VoxelPos},
+    terrain::terrain_manifest::Terrain,
+    water::WaterDepth,
+};
+
+use super::structure_manifest::{Structure, StructureManifest};
+
+/// A single entry in the adjacency table of a structure.
+///
+/// If any of the six neighboring tiles satisfies `neighbor`, the structure's production rate is multiplied by `multiplier`.
+///
+/// Each rule is applied at most once, no matter how many neighbors match it.
+/// When several rules match, their multipliers are multiplied together.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AdjacencyRule {
+    /// The condition that a neighboring tile must meet.
+    pub neighbor: NeighborCondition,
+    /// The factor that production is multiplied by when the condition is met.
+    pub multiplier: f32,
+}
+
+/// A condition that a tile adjacent to a structure can satisfy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NeighborCondition {
+    /// The tile has surface water on it.
+    Flooded,
+    /// The tile is occupied by a structure of this type.
+    Structure(Id<Structure>),
+    /// The tile is made of this type of terrain.
+    Terrain(Id<Terrain>),
+}
+
+/// The unprocessed equivalent of [`AdjacencyRule`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RawAdjacencyRule {
+    /// The condition that a neighboring tile must meet.
+    pub neighbor: RawNeighborCondition,
+    /// The factor that production is multiplied by when the condition is met.
+    pub multiplier: f32,
+}
+
+/// The unprocessed equivalent of [`NeighborCondition`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RawNeighborCondition {
+    /// The tile has surface water on it.
+    Flooded,
+    /// The tile is occupied by a structure of this type.
+    Structure(String),
+    /// The tile is made of this type of terrain.
+    Terrain(String),
+}
+
+impl From<RawAdjacencyRule> for AdjacencyRule {
+    fn from(raw: RawAdjacencyRule) -> Self {
+        assert!(
+            raw.multiplier.is_finite() && raw.multiplier >= 0.,
+            "Adjacency multipliers must be finite and non-negative, but {} was provided",
+            raw.multiplier
+        );
+
+        Self {
+            neighbor: raw.neighbor.into(),
+            multiplier: raw.multiplier,
+        }
+    }
+}
+
+impl From<RawNeighborCondition> for NeighborCondition {
+    fn from(raw: RawNeighborCondition) -> Self {
+        match raw {
+            RawNeighborCondition::Flooded => Self::Flooded,
+            RawNeighborCondition::Structure(name) => Self::Structure(Id::from_name(name)),
+            RawNeighborCondition::Terrain(name) => Self::Terrain(Id::from_name(name)),
+        }
+    }
+}
+
+/// The facts about a neighboring tile that [`NeighborCondition`]s are checked against.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct NeighborInfo {
+    /// The structure on this tile, if any.
+    pub(crate) structure: Option<Id<Structure>>,
+    /// The terrain type of this tile.
+    pub(crate) terrain: Option<Id<Terrain>>,
+    /// Is there surface water on this tile?
+    pub(crate) flooded: bool,
+}
+
+impl NeighborCondition {
+    /// Does the provided `neighbor` satisfy this condition?
+    pub(crate) fn matches(&self, neighbor: &NeighborInfo) -> bool {
+        match self {
+            NeighborCondition::Flooded => neighbor.flooded,
+            NeighborCondition::Structure(structure_id) => neighbor.structure == Some(*structure_id),
+            NeighborCondition::Terrain(terrain_id) => neighbor.terrain == Some(*terrain_id),
+        }
+    }
+}
+
+/// Computes the combined production multiplier for a structure with the provided `rules`.
+///
+/// Each rule contributes its multiplier once if at least one neighbor matches,
+/// and the contributions of all matching rules are multiplied together.
+pub(crate) fn combined_multiplier(rules: &[AdjacencyRule], neighbors: &[NeighborInfo]) -> f32 {
+    rules
+        .iter()
+        .filter(|rule| {
+            neighbors
+                .iter()
+                .any(|neighbor| rule.neighbor.matches(neighbor))
+        })
+        .map(|rule| rule.multiplier)
+        .product()
+}
+
+/// The cached production multiplier granted to a structure by its surroundings.
+///
+/// Read it with [`AdjacencyBonus::multiplier`], which keeps the value in a safe range.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect, FromReflect)]
+pub(crate) struct AdjacencyBonus(pub(crate) f32);
+
+impl AdjacencyBonus {
+    /// The largest multiplier that stacked rules can grant.
+    pub(crate) const MAX: f32 = 100.;
+
+    /// The production multiplier, clamped between 0 and [`AdjacencyBonus::MAX`].
+    ///
+    /// Invalid values are treated as no bonus at all.
+    pub(crate) fn multiplier(&self) -> f32 {
+        if self.0.is_nan() {
+            1.
+        } else {
+            self.0.clamp(0., AdjacencyBonus::MAX)
+        }
+    }
+}
+
+impl Default for AdjacencyBonus {
+    fn default() -> Self {
+        AdjacencyBonus(1.)
+    }
+}
+
+impl Display for AdjacencyBonus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "x{:.2}", self.0)
+    }
+}
+
+/// Re-evaluates the [`AdjacencyBonus`] of every structure that has adjacency rules.
+///
+/// The cached value is only written when it changes, so change detection can be used downstream.
+pub(crate) fn update_adjacency_bonuses(
+    mut structure_query: Query<(Entity, &Id<Structure>, &VoxelPos, &mut AdjacencyBonus)>,
+    structure_id_query: Query<&Id<Structure>>,
+    terrain_query: Query<(&Id<Terrain>, &WaterDepth)>,
+    structure_manifest: Res<StructureManifest>,
+    map_geometry: Res<MapGeometry>,
+) {
+    for (entity, structure_id, voxel_pos, mut adjacency_bonus) in structure_query.iter_mut() {
+        let rules = &structure_manifest.get(*structure_id).adjacency_rules;
+        if rules.is_empty() {
+            continue;
+        }
+
+        // A fixed-size array avoids allocating for every structure on every tick
+        let neighbors: [NeighborInfo; 6] =
+            map_geometry.adjacent_hexes(voxel_pos.hex).map(|maybe_hex| {
+                let mut info = NeighborInfo::default();
+                let Some(hex) = maybe_hex else { return info };
+
+                if let Ok(terrain_entity) = map_geometry.get_terrain(hex) {
+                    if let Ok((terrain_id, water_depth)) = terrain_query.get(terrain_entity) {
+                        info.terrain = Some(*terrain_id);
+                        info.flooded = matches!(water_depth, WaterDepth::Flooded(..));
+                    }
+                }
+
+                if let Ok(height) = map_geometry.get_height(hex) {
+                    let neighbor_pos = VoxelPos {
+                        hex,
+                        height: height.above(),
+                    };
+
+                    info.structure = map_geometry
+                        .get_structure(neighbor_pos)
+                        // Multi-tile structures should not boost themselves
+                        .filter(|&neighbor_entity| neighbor_entity != entity)
+                        .and_then(|neighbor_entity| structure_id_query.get(neighbor_entity).ok())
+                        .copied();
+                }
+
+                info
+            });
+
+        let new_bonus = AdjacencyBonus(combined_multiplier(rules, &neighbors));
+        if *adjacency_bonus != new_bonus {
+            *adjacency_bonus = new_bonus;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A neighboring tile covered in surface water.
+    fn flooded() -> NeighborInfo {
+        NeighborInfo {
+            flooded: true,
+            ..Default::default()
+        }
+    }
+
+    /// A neighboring tile occupied by the named structure.
+    fn next_to(structure: &str) -> NeighborInfo {
+        NeighborInfo {
+            structure: Some(Id::from_name(structure.to_string())),
+            ..Default::default()
+        }
+    }
+
+    /// Shorthand for constructing an [`AdjacencyRule`].
+    fn rule(neighbor: NeighborCondition, multiplier: f32) -> AdjacencyRule {
+        AdjacencyRule {
+            neighbor,
+            multiplier,
+        }
+    }
+
+    #[test]
+    fn no_rules_means_no_bonus() {
+        assert_eq!(combined_multiplier(&[], &[flooded(), next_to("leuco")]), 1.);
+    }
+
+    #[test]
+    fn unmatched_rules_have_no_effect() {
+        let rules = [rule(NeighborCondition::Flooded, 1.5)];
+        assert_eq!(combined_multiplier(&rules, &[next_to("leuco")]), 1.);
+    }
+
+    #[test]
+    fn rules_apply_once_regardless_of_matching_neighbors() {
+        let rules = [rule(NeighborCondition::Flooded, 1.5)];
+        assert_eq!(combined_multiplier(&rules, &[flooded()]), 1.5);
+        assert_eq!(
+            combined_multiplier(&rules, &[flooded(), flooded(), flooded()]),
+            1.5
+        );
+    }
+
+    #[test]
+    fn stacked_rules_multiply() {
+        let rules = [
+            rule(NeighborCondition::Flooded, 1.5),
+            rule(
+                NeighborCondition::Structure(Id::from_name("leuco".to_string())),
+                2.,
+            ),
+            rule(
+                NeighborCondition::Structure(Id::from_name("acacia".to_string())),
+                0.5,
+            ),
+        ];
+
+        assert_eq!(
+            combined_multiplier(&rules, &[flooded(), next_to("leuco")]),
+            3.
+        );
+        assert_eq!(
+            combined_multiplier(&rules, &[flooded(), next_to("leuco"), next_to("acacia")]),
+            1.5
+        );
+    }
+
+    #[test]
+    fn raw_conditions_resolve_to_ids() {
+        let raw = RawAdjacencyRule {
+            neighbor: RawNeighborCondition::Structure("leuco".to_string()),
+            multiplier: 2.,
+        };
+
+        let processed: AdjacencyRule = raw.into();
+        assert!(processed.neighbor.matches(&next_to("leuco")));
+        assert!(!processed.neighbor.matches(&next_to("acacia")));
+    }
+
+    #[test]
+    fn bonuses_are_clamped_when_read() {
+        assert_eq!(AdjacencyBonus(1.5).multiplier(), 1.5);
+        assert_eq!(AdjacencyBonus(-1.).multiplier(), 0.);
+        assert_eq!(AdjacencyBonus(f32::NAN).multiplier(), 1.);
+        assert_eq!(
+            AdjacencyBonus(f32::INFINITY).multiplier(),
+            AdjacencyBonus::MAX
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn negative_multipliers_are_rejected_on_load() {
+        let raw = RawAdjacencyRule {
+            neighbor: RawNeighborCondition::Flooded,
+            multiplier: -1.,
+        };
+
+        let _processed: AdjacencyRule = raw.into();
+    }
+
+    #[test]
+    #[should_panic]
+    fn nan_multipliers_are_rejected_on_load() {
+        let raw = RawAdjacencyRule {
+            neighbor: RawNeighborCondition::Flooded,
+            multiplier: f32::NAN,
+        };
+
+        let _processed: AdjacencyRule = raw.into();
+    }
+}
@@ -11,19 +11,21 @@ use crate::{
     geometry::{Facing, Height, MapGeometry, VoxelPos},
     items::item_manifest::ItemManifest,
     litter::Litter,
-    signals::{Emitter, SignalStrength, SignalType},
-    simulation::SimulationSet,
+    signals::{Emitter, ManageSignals, SignalStrength, SignalType},
+    simulation::phases::{SimulationAppExt, TickPhase},
     water::WaterDepth,
 };
 
 use super::Footprint;
 
 /// A building that spits out items.
-#[derive(Component)]
+#[derive(Component, Reflect, FromReflect, Default)]
+#[reflect(Component)]
 pub(crate) struct ReleasesItems;
 
 /// A building that takes in items.
-#[derive(Component)]
+#[derive(Component, Reflect, FromReflect, Default)]
+#[reflect(Component)]
 pub(crate) struct AbsorbsItems;
 
 /// Logic that controls how items are moved around by structures.
@@ -31,11 +33,11 @@ pub(super) struct LogisticsPlugin;
 
 impl Plugin for LogisticsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            (release_items, absorb_items, logistic_buildings_signals)
-                .in_set(SimulationSet)
-                .in_schedule(CoreSchedule::FixedUpdate),
-        );
+        app.add_simulation_systems(TickPhase::Action, (release_items, absorb_items))
+            .add_simulation_system(
+                TickPhase::Perception,
+                logistic_buildings_signals.before(ManageSignals),
+            );
     }
 }
 
@@ -25,9 +25,10 @@ use self::{
     structure_manifest::{RawStructureManifest, Structure},
 };
 
+pub mod adjacency;
 pub(crate) mod commands;
 pub(crate) mod logistic_buildings;
-mod structure_assets;
+pub(crate) mod structure_assets;
 pub mod structure_manifest;
 
 /// The systems that make structures tick.
@@ -92,7 +93,7 @@ impl StructureBundle {
 /// The set of tiles taken up by a structure.
 ///
 /// Structures are always "centered" on 0, 0, so these coordinates are relative to that.
-#[derive(Component, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Component, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Reflect, FromReflect)]
 pub struct Footprint {
     /// The set of tiles is taken up by this structure.
     pub(crate) set: HashSet<VoxelPos>,
@@ -178,7 +179,20 @@ impl Footprint {
 /// A special structure used to create interest in the game world.
 ///
 /// Landmarks cannot be created or destroyed by players.
-#[derive(Component, Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(
+    Component,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    Serialize,
+    Deserialize,
+    Reflect,
+    FromReflect,
+    Default,
+)]
+#[reflect(Component)]
 pub(crate) struct Landmark;
 
 #[cfg(test)]
@@ -10,7 +10,7 @@ use crate::{
 use bevy::{asset::LoadState, prelude::*, utils::HashMap};
 
 /// Stores material handles for the different tile types.
-#[derive(Resource)]
+#[derive(Resource, Reflect)]
 pub(crate) struct StructureHandles {
     /// The scene for each type of structure
     pub(crate) scenes: HashMap<Id<Structure>, Handle<Scene>>,
@@ -17,7 +17,10 @@ use bevy::{
 };
 use serde::{Deserialize, Serialize};
 
-use super::Footprint;
+use super::{
+    adjacency::{AdjacencyRule, RawAdjacencyRule},
+    Footprint,
+};
 
 /// The marker type for [`Id<Structure>`](super::Id).
 #[derive(Reflect, FromReflect, Clone, Copy, PartialEq, Eq)]
@@ -74,6 +77,8 @@ pub struct StructureData {
     pub can_walk_through: bool,
     /// Can units walk on top of this structure?
     pub can_walk_on_roof: bool,
+    /// Production modifiers granted by neighboring tiles.
+    pub adjacency_rules: Vec<AdjacencyRule>,
 }
 
 #[cfg(test)]
@@ -90,6 +95,7 @@ impl StructureData {
             root_zone: None,
             can_walk_through: true,
             can_walk_on_roof: false,
+            adjacency_rules: Vec::new(),
         }
     }
 
@@ -105,6 +111,7 @@ impl StructureData {
             root_zone: None,
             can_walk_through: true,
             can_walk_on_roof: false,
+            adjacency_rules: Vec::new(),
         }
     }
 
@@ -120,6 +127,7 @@ impl StructureData {
             root_zone: None,
             can_walk_through: false,
             can_walk_on_roof: false,
+            adjacency_rules: Vec::new(),
         }
     }
 }
@@ -147,6 +155,9 @@ pub struct RawStructureData {
     pub can_walk_through: bool,
     /// Can units walk on top of this structure?
     pub can_walk_on_roof: bool,
+    /// Production modifiers granted by neighboring tiles.
+    #[serde(default)]
+    pub adjacency_rules: Vec<RawAdjacencyRule>,
 }
 
 impl From<RawStructureData> for StructureData {
@@ -161,6 +172,7 @@ impl From<RawStructureData> for StructureData {
             root_zone: raw.root_zone,
             can_walk_through: raw.can_walk_through,
             can_walk_on_roof: raw.can_walk_on_roof,
+            adjacency_rules: raw.adjacency_rules.into_iter().map(Into::into).collect(),
         }
     }
 }
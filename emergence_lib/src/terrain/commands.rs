@@ -0,0 +1,211 @@
+//! Methods to use [`Commands`] to change terrain after the map has been generated.
+
+use bevy::{ecs::system::Command, prelude::*};
+use hexx::Hex;
+
+use crate::{
+    asset_management::manifest::Id,
+    geometry::MapGeometry,
+    simulation::events::TerrainChanged,
+    water::{
+        water_dynamics::{SoilWaterEvaporationRate, SoilWaterFlowRate},
+        SoilWaterCapacity,
+    },
+};
+
+use super::{
+    terrain_assets::TerrainHandles,
+    terrain_manifest::{Terrain, TerrainManifest},
+};
+
+/// An extension trait for [`Commands`] for changing terrain.
+pub trait TerrainCommandsExt {
+    /// Changes the type of the terrain at `hex` to `terrain_id`.
+    ///
+    /// The existing terrain entity is kept, and a [`TerrainChanged`] event is sent.
+    /// Has no effect if the terrain is already of that type, or if `hex` is not on the map.
+    fn set_terrain(&mut self, hex: Hex, terrain_id: Id<Terrain>);
+}
+
+impl TerrainCommandsExt for Commands<'_, '_> {
+    fn set_terrain(&mut self, hex: Hex, terrain_id: Id<Terrain>) {
+        self.add(SetTerrainCommand { hex, terrain_id });
+    }
+}
+
+/// A [`Command`] used to change the type of terrain at a tile.
+pub(crate) struct SetTerrainCommand {
+    /// The location of the tile to change.
+    pub(crate) hex: Hex,
+    /// The new type of terrain.
+    pub(crate) terrain_id: Id<Terrain>,
+}
+
+impl Command for SetTerrainCommand {
+    fn write(self, world: &mut World) {
+        let Ok(terrain_entity) = world.resource::<MapGeometry>().get_terrain(self.hex) else {
+            warn!(
+                "Tried to set the terrain at {:?}, outside of the map bounds.",
+                self.hex
+            );
+            return;
+        };
+        let mut current_terrain_id = world.get_mut::<Id<Terrain>>(terrain_entity).unwrap();
+        let old_terrain_id = *current_terrain_id;
+
+        if old_terrain_id == self.terrain_id {
+            return;
+        }
+
+        *current_terrain_id = self.terrain_id;
+
+        world
+            .resource_mut::<MapGeometry>()
+            .update_terrain_id(self.hex, self.terrain_id);
+
+        // Terrain without visuals or water data, such as in tests, only needs its type changed
+        if let Some(terrain_handles) = world.get_resource::<TerrainHandles>() {
+            let scene_handle = terrain_handles
+                .scenes
+                .get(&self.terrain_id)
+                .unwrap()
+                .clone_weak();
+            *world.get_mut::<Handle<Scene>>(terrain_entity).unwrap() = scene_handle;
+        }
+
+        if let Some(terrain_manifest) = world.get_resource::<TerrainManifest>() {
+            let terrain_data = terrain_manifest.get(self.terrain_id).clone();
+            let mut terrain_entity_mut = world.entity_mut(terrain_entity);
+            if let Some(mut capacity) = terrain_entity_mut.get_mut::<SoilWaterCapacity>() {
+                *capacity = terrain_data.soil_water_capacity;
+            }
+            if let Some(mut evaporation_rate) =
+                terrain_entity_mut.get_mut::<SoilWaterEvaporationRate>()
+            {
+                *evaporation_rate = terrain_data.soil_water_evaporation_rate;
+            }
+            if let Some(mut flow_rate) = terrain_entity_mut.get_mut::<SoilWaterFlowRate>() {
+                *flow_rate = terrain_data.soil_water_flow_rate;
+            }
+        }
+
+        if let Some(mut events) = world.get_resource_mut::<Events<TerrainChanged>>() {
+            events.send(TerrainChanged {
+                hex: self.hex,
+                old: old_terrain_id,
+                new: self.terrain_id,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::CommandQueue;
+
+    use super::*;
+    use crate::geometry::{DiscreteHeight, VoxelPos};
+    use crate::terrain::TerrainBundle;
+
+    /// A small headless world where every tile is `grassy`.
+    fn terrain_world() -> World {
+        let mut world = World::new();
+        world.init_resource::<Events<TerrainChanged>>();
+
+        let mut map_geometry = MapGeometry::new(&mut world, 1);
+        let hexes: Vec<Hex> = map_geometry.all_hexes().copied().collect();
+        for hex in hexes {
+            let voxel_pos = VoxelPos {
+                hex,
+                height: DiscreteHeight::ZERO,
+            };
+            let terrain_entity = map_geometry.get_terrain(hex).unwrap();
+            world
+                .entity_mut(terrain_entity)
+                .insert(TerrainBundle::minimal(grassy(), voxel_pos));
+            map_geometry.update_terrain_id(hex, grassy());
+        }
+        world.insert_resource(map_geometry);
+
+        world
+    }
+
+    /// A common terrain type.
+    fn grassy() -> Id<Terrain> {
+        Id::from_name("grassy".to_string())
+    }
+
+    /// A less common terrain type.
+    fn rocky() -> Id<Terrain> {
+        Id::from_name("rocky".to_string())
+    }
+
+    /// Applies [`TerrainCommandsExt::set_terrain`] to the `world`.
+    fn set_terrain(world: &mut World, hex: Hex, terrain_id: Id<Terrain>) {
+        let mut queue = CommandQueue::default();
+        Commands::new(&mut queue, world).set_terrain(hex, terrain_id);
+        queue.apply(world);
+    }
+
+    /// Returns all [`TerrainChanged`] events sent so far.
+    fn terrain_changed_events(world: &World) -> Vec<TerrainChanged> {
+        let events = world.resource::<Events<TerrainChanged>>();
+        events.get_reader().iter(events).copied().collect()
+    }
+
+    #[test]
+    fn set_terrain_changes_the_existing_tile() {
+        let mut world = terrain_world();
+        let hex = Hex::new(1, 0);
+        let terrain_entity = world.resource::<MapGeometry>().get_terrain(hex).unwrap();
+
+        set_terrain(&mut world, hex, rocky());
+
+        // The tile entity is reused
+        let map_geometry = world.resource::<MapGeometry>();
+        assert_eq!(map_geometry.get_terrain(hex), Ok(terrain_entity));
+        assert_eq!(map_geometry.get_terrain_id(hex), Ok(rocky()));
+        assert_eq!(world.get::<Id<Terrain>>(terrain_entity), Some(&rocky()));
+
+        assert_eq!(
+            terrain_changed_events(&world),
+            vec![TerrainChanged {
+                hex,
+                old: grassy(),
+                new: rocky()
+            }]
+        );
+
+        // Other tiles are untouched
+        let map_geometry = world.resource::<MapGeometry>();
+        assert_eq!(map_geometry.get_terrain_id(Hex::ZERO), Ok(grassy()));
+    }
+
+    #[test]
+    fn setting_terrain_off_the_map_does_nothing() {
+        let mut world = terrain_world();
+        let off_map = Hex::new(5, 0);
+        assert!(!world.resource::<MapGeometry>().is_valid(off_map));
+
+        set_terrain(&mut world, off_map, rocky());
+
+        let map_geometry = world.resource::<MapGeometry>();
+        assert!(map_geometry
+            .all_hexes()
+            .all(|&hex| map_geometry.get_terrain_id(hex) == Ok(grassy())));
+        assert!(terrain_changed_events(&world).is_empty());
+    }
+
+    #[test]
+    fn setting_the_same_terrain_does_nothing() {
+        let mut world = terrain_world();
+
+        set_terrain(&mut world, Hex::ZERO, grassy());
+
+        assert_eq!(
+            world.resource::<MapGeometry>().get_terrain_id(Hex::ZERO),
+            Ok(grassy())
+        );
+        assert!(terrain_changed_events(&world).is_empty());
+    }
+}
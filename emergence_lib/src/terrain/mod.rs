@@ -12,17 +12,18 @@ use crate::geometry::{MapGeometry, VoxelPos};
 use crate::light::shade::{ReceivedLight, Shade};
 use crate::player_interaction::picking::PickableVoxel;
 use crate::player_interaction::selection::ObjectInteraction;
-use crate::signals::Emitter;
-use crate::simulation::SimulationSet;
+use crate::signals::{Emitter, ManageSignals};
+use crate::simulation::phases::{SimulationAppExt, TickPhase};
 use crate::water::{WaterBundle, WaterSet};
 
 use self::terrain_assets::TerrainHandles;
 use self::terrain_manifest::{RawTerrainManifest, Terrain, TerrainManifest};
 use crate::litter::{
-    carry_floating_litter_with_current, clear_empty_litter, make_litter_float, set_litter_emitters,
-    LitterEmitters,
+    carry_floating_litter_with_current, clear_empty_litter, make_litter_float, merge_litter,
+    set_litter_emitters, set_litter_scenes, LitterEmitters,
 };
 
+pub mod commands;
 pub(crate) mod terrain_assets;
 pub mod terrain_manifest;
 
@@ -33,7 +34,8 @@ impl Plugin for TerrainPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugin(ManifestPlugin::<RawTerrainManifest>::new())
             .add_asset_collection::<TerrainHandles>()
-            .add_systems(
+            .add_simulation_systems(
+                TickPhase::Resolution,
                 (
                     respond_to_height_changes,
                     make_litter_float.after(respond_to_height_changes),
@@ -45,16 +47,24 @@ impl Plugin for TerrainPlugin {
                     // but we also want to clean up after because we may have condensed litter inventories by drifting
                     clear_empty_litter.before(carry_floating_litter_with_current),
                     clear_empty_litter.after(carry_floating_litter_with_current),
-                    set_litter_emitters
-                        .after(carry_floating_litter_with_current)
-                        .in_set(LitterEmitters),
-                )
-                    .in_set(SimulationSet)
-                    .in_schedule(CoreSchedule::FixedUpdate),
-            );
+                    // Drifting litter often washes up on top of other litter
+                    merge_litter.after(carry_floating_litter_with_current),
+                ),
+            )
+            .add_simulation_system(
+                TickPhase::Perception,
+                set_litter_emitters
+                    .in_set(LitterEmitters)
+                    .before(ManageSignals),
+            )
+            .add_simulation_system(TickPhase::Bookkeeping, set_litter_scenes);
     }
 }
 
+/// Marks a terrain tile that units can never walk onto, such as the border around the edge of the map.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImpassableTerrain;
+
 /// All of the components needed to define a piece of terrain.
 #[derive(Bundle)]
 pub(crate) struct TerrainBundle {
@@ -147,13 +157,22 @@ impl TerrainBundle {
 }
 
 /// Updates the game state appropriately whenever the height of a tile is changed.
+///
+/// Warns if terraforming has left a basin that units cannot escape from.
 fn respond_to_height_changes(
     mut terrain_query: Query<(Ref<VoxelPos>, &mut Transform, &Children), With<Id<Terrain>>>,
     mut column_query: Query<&mut Transform, (With<Parent>, Without<VoxelPos>)>,
     mut map_geometry: ResMut<MapGeometry>,
 ) {
+    let mut terraformed_hexes = Vec::new();
+
     for (voxel_pos, mut transform, children) in terrain_query.iter_mut() {
         if voxel_pos.is_changed() {
+            // Newly generated terrain is checked for basins once the whole map exists
+            if !voxel_pos.is_added() {
+                terraformed_hexes.push(voxel_pos.hex);
+            }
+
             // PERF: this is probably redundant, as long as we're careful about how the voxel pos of terrain can be mutated
             map_geometry.update_height(voxel_pos.hex, voxel_pos.height);
             let height = voxel_pos.height();
@@ -164,4 +183,12 @@ fn respond_to_height_changes(
             *column_transform = height.column_transform();
         }
     }
+
+    for basin in map_geometry.basins_near(terraformed_hexes) {
+        warn!(
+            "Terraforming created a basin of {} tiles around {} that units cannot escape from.",
+            basin.len(),
+            basin[0]
+        );
+    }
 }
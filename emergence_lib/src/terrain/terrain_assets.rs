@@ -13,7 +13,7 @@ use crate::{
 };
 
 /// Stores material handles for the different tile types.
-#[derive(Resource)]
+#[derive(Resource, Reflect)]
 pub(crate) struct TerrainHandles {
     /// The scene used for each type of terrain
     pub(crate) scenes: HashMap<Id<Terrain>, Handle<Scene>>,
@@ -24,9 +24,11 @@ pub(crate) struct TerrainHandles {
     /// The material of the column underneath each terrain topper
     pub(crate) column_material: Handle<StandardMaterial>,
     /// The materials used to display player interaction with terrain tiles
+    #[reflect(ignore)]
     pub(crate) interaction_materials: HashMap<ObjectInteraction, Handle<StandardMaterial>>,
     /// Models used to depict litter on tiles.
     // FIXME: move out of terrain handles
+    #[reflect(ignore)]
     pub(crate) litter_models: HashMap<InventoryState, Handle<Scene>>,
 }
 
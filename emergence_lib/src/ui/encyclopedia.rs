@@ -0,0 +1,937 @@
+//! An in-game encyclopedia, describing every kind of object that can be found in the world.
+//!
+//! Pages are generated from the manifests at runtime, so new kinds of objects are documented as soon as they are added.
+
+use bevy::prelude::*;
+use leafwing_abilities::prelude::Pool;
+use leafwing_input_manager::{plugin::InputManagerSystem, prelude::ActionState, Actionlike};
+
+use crate::{
+    asset_management::{manifest::Id, AssetState},
+    crafting::item_tags::ItemKind,
+    enum_iter::IterableEnum,
+    items::item_manifest::{Item, ItemManifest},
+    organisms::{OrganismId, OrganismVariety},
+    player_interaction::PlayerAction,
+    signals::{SignalKind, DIFFUSION_FRACTION},
+    structures::{
+        adjacency::NeighborCondition,
+        structure_manifest::{Structure, StructureKind, StructureManifest},
+    },
+    terrain::terrain_manifest::{Terrain, TerrainManifest},
+    units::unit_manifest::{Unit, UnitManifest},
+};
+
+use super::FiraSansFontFamily;
+
+/// Generates the [`Encyclopedia`] and displays it on request.
+pub(super) struct EncyclopediaPlugin;
+
+impl Plugin for EncyclopediaPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Encyclopedia>()
+            .init_resource::<EncyclopediaState>()
+            .add_startup_system(spawn_encyclopedia_panel)
+            .add_system(
+                capture_gameplay_input
+                    .in_base_set(CoreSet::PreUpdate)
+                    .after(InputManagerSystem::Update),
+            )
+            .add_system(build_encyclopedia.run_if(in_state(AssetState::FullyLoaded)))
+            .add_systems(
+                (
+                    toggle_encyclopedia,
+                    search_encyclopedia,
+                    follow_encyclopedia_links,
+                    update_encyclopedia_panel,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Identifies a single page of the [`Encyclopedia`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum EntryId {
+    /// A kind of unit.
+    Unit(Id<Unit>),
+    /// A kind of structure, including plants and fungi.
+    Structure(Id<Structure>),
+    /// A kind of terrain.
+    Terrain(Id<Terrain>),
+    /// A kind of item.
+    Item(Id<Item>),
+    /// A kind of signal.
+    Signal(SignalKind),
+}
+
+impl EntryId {
+    /// The name of the category that this entry is listed under.
+    fn category(&self) -> &'static str {
+        match self {
+            EntryId::Unit(_) => "Unit",
+            EntryId::Structure(_) => "Structure",
+            EntryId::Terrain(_) => "Terrain",
+            EntryId::Item(_) => "Item",
+            EntryId::Signal(_) => "Signal",
+        }
+    }
+}
+
+impl From<OrganismId> for EntryId {
+    fn from(organism_id: OrganismId) -> Self {
+        match organism_id {
+            OrganismId::Structure(structure_id) => EntryId::Structure(structure_id),
+            OrganismId::Unit(unit_id) => EntryId::Unit(unit_id),
+        }
+    }
+}
+
+/// A single page of the [`Encyclopedia`].
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct EncyclopediaPage {
+    /// The object that this page describes.
+    entry: EntryId,
+    /// The name of the object.
+    title: String,
+    /// A short description of the object.
+    description: String,
+    /// The name and value of each of the object's statistics.
+    stats: Vec<(String, String)>,
+    /// Other pages that are relevant to this one, and the text shown for each link.
+    links: Vec<(String, EntryId)>,
+}
+
+impl EncyclopediaPage {
+    /// Creates a new page with no stats or links.
+    fn new(entry: EntryId, title: impl Into<String>, description: impl Into<String>) -> Self {
+        EncyclopediaPage {
+            entry,
+            title: title.into(),
+            description: description.into(),
+            stats: Vec::new(),
+            links: Vec::new(),
+        }
+    }
+
+    /// Adds a statistic to this page.
+    fn stat(&mut self, name: &str, value: impl ToString) {
+        self.stats.push((name.to_string(), value.to_string()));
+    }
+
+    /// Adds a link to another page.
+    fn link(&mut self, label: &str, target: EntryId) {
+        let link = (label.to_string(), target);
+        if target != self.entry && !self.links.contains(&link) {
+            self.links.push(link);
+        }
+    }
+}
+
+/// Describes every kind of unit, structure, terrain, item and signal in the game.
+///
+/// This is rebuilt from the manifests whenever they change.
+#[derive(Resource, Debug, Default, Clone, PartialEq)]
+pub(crate) struct Encyclopedia {
+    /// All of the pages, sorted by category and then by title.
+    pages: Vec<EncyclopediaPage>,
+}
+
+impl Encyclopedia {
+    /// Generates a page for each object in the manifests, and for each kind of signal.
+    pub(crate) fn new(
+        unit_manifest: &UnitManifest,
+        structure_manifest: &StructureManifest,
+        terrain_manifest: &TerrainManifest,
+        item_manifest: &ItemManifest,
+    ) -> Self {
+        let mut pages = Vec::new();
+
+        for unit_id in unit_manifest.variants() {
+            pages.push(unit_page(unit_id, unit_manifest, item_manifest));
+        }
+
+        for structure_id in structure_manifest.variants() {
+            pages.push(structure_page(
+                structure_id,
+                structure_manifest,
+                terrain_manifest,
+                item_manifest,
+            ));
+        }
+
+        for terrain_id in terrain_manifest.variants() {
+            pages.push(terrain_page(terrain_id, terrain_manifest));
+        }
+
+        for item_id in item_manifest.variants() {
+            pages.push(item_page(
+                item_id,
+                item_manifest,
+                unit_manifest,
+                structure_manifest,
+            ));
+        }
+
+        for signal_kind in SignalKind::variants() {
+            pages.push(signal_page(signal_kind));
+        }
+
+        // Links to objects that are missing from the manifests cannot be followed
+        let entries: Vec<EntryId> = pages.iter().map(|page| page.entry).collect();
+        for page in &mut pages {
+            page.links.retain(|(_, target)| entries.contains(target));
+        }
+
+        pages.sort_by(|a, b| (a.entry.category(), &a.title).cmp(&(b.entry.category(), &b.title)));
+
+        Encyclopedia { pages }
+    }
+
+    /// Returns the page describing `entry`, if any.
+    pub(crate) fn page(&self, entry: EntryId) -> Option<&EncyclopediaPage> {
+        self.pages.iter().find(|page| page.entry == entry)
+    }
+
+    /// Returns the pages whose titles contain `query`, ignoring case.
+    ///
+    /// An empty query matches every page.
+    pub(crate) fn search(&self, query: &str) -> Vec<&EncyclopediaPage> {
+        let query = query.to_lowercase();
+        self.pages
+            .iter()
+            .filter(|page| page.title.to_lowercase().contains(&query))
+            .collect()
+    }
+}
+
+/// Adds the stats and links shared by all organisms to the `page`.
+fn describe_organism(
+    page: &mut EncyclopediaPage,
+    organism_variety: &OrganismVariety,
+    unit_manifest: Option<&UnitManifest>,
+    structure_manifest: Option<&StructureManifest>,
+) {
+    page.stat("Max energy", organism_variety.energy_pool.max());
+
+    let prototype = organism_variety.prototypical_form;
+    let prototype_name = match prototype {
+        OrganismId::Unit(unit_id) => unit_manifest
+            .filter(|manifest| manifest.data_map().contains_key(&unit_id))
+            .map(|manifest| manifest.name(unit_id)),
+        OrganismId::Structure(structure_id) => structure_manifest
+            .filter(|manifest| manifest.data_map().contains_key(&structure_id))
+            .map(|manifest| manifest.name(structure_id)),
+    };
+
+    if let Some(prototype_name) = prototype_name {
+        page.link(prototype_name, prototype.into());
+    }
+}
+
+/// Generates the page for a kind of unit.
+fn unit_page(
+    unit_id: Id<Unit>,
+    unit_manifest: &UnitManifest,
+    item_manifest: &ItemManifest,
+) -> EncyclopediaPage {
+    let unit_data = unit_manifest.get(unit_id);
+    let diet_kind = unit_data.diet.item_kind();
+    let mut page = EncyclopediaPage::new(
+        EntryId::Unit(unit_id),
+        unit_manifest.name(unit_id),
+        format!(
+            "A creature that wanders the world, carrying items and tending to structures. It eats {}.",
+            item_manifest.name_of_kind(diet_kind)
+        ),
+    );
+
+    page.stat("Diet", unit_data.diet.display(item_manifest));
+    page.stat("Lifespan", format!("{:.1} days", unit_data.max_age.0));
+    page.stat("Patience", unit_data.max_impatience);
+    describe_organism(
+        &mut page,
+        &unit_data.organism_variety,
+        Some(unit_manifest),
+        None,
+    );
+
+    if let ItemKind::Single(item_id) = diet_kind {
+        page.link(item_manifest.name(item_id), EntryId::Item(item_id));
+    }
+
+    page
+}
+
+/// Generates the page for a kind of structure.
+fn structure_page(
+    structure_id: Id<Structure>,
+    structure_manifest: &StructureManifest,
+    terrain_manifest: &TerrainManifest,
+    item_manifest: &ItemManifest,
+) -> EncyclopediaPage {
+    let structure_data = structure_manifest.get(structure_id);
+    let purpose = match &structure_data.kind {
+        StructureKind::Storage { .. } => "It stores items.",
+        StructureKind::Crafting { .. } => "It turns input items into output items.",
+        StructureKind::Path => "Units walk faster along it.",
+        StructureKind::Landmark => "It is a special feature of the world.",
+        StructureKind::Releaser => "It releases items into the world.",
+        StructureKind::Absorber => "It absorbs items from the world.",
+    };
+    let description = match structure_data.organism_variety {
+        Some(_) => format!("A living structure. {purpose}"),
+        None => format!("A structure. {purpose}"),
+    };
+
+    let mut page = EncyclopediaPage::new(
+        EntryId::Structure(structure_id),
+        structure_manifest.name(structure_id),
+        description,
+    );
+
+    if let StructureKind::Storage {
+        max_slot_count,
+        reserved_for,
+    } = &structure_data.kind
+    {
+        page.stat("Storage slots", max_slot_count);
+        if let Some(item_id) = reserved_for {
+            page.stat("Stores only", item_manifest.name(*item_id));
+            page.link(item_manifest.name(*item_id), EntryId::Item(*item_id));
+        }
+    }
+
+    page.stat("Tiles", structure_data.footprint.set.len());
+    page.stat("Max workers", structure_data.max_workers);
+    page.stat(
+        "Can walk through",
+        yes_or_no(structure_data.can_walk_through),
+    );
+    page.stat(
+        "Can walk on top",
+        yes_or_no(structure_data.can_walk_on_roof),
+    );
+
+    for rule in &structure_data.adjacency_rules {
+        let multiplier = format!("x{:.2} production", rule.multiplier);
+        match rule.neighbor {
+            NeighborCondition::Flooded => page.stat("Next to flooded tiles", multiplier),
+            NeighborCondition::Structure(neighbor_id) => {
+                let name = structure_manifest.name(neighbor_id);
+                page.stat(&format!("Next to {name}"), multiplier);
+                page.link(name, EntryId::Structure(neighbor_id));
+            }
+            NeighborCondition::Terrain(terrain_id) => {
+                let name = terrain_manifest.name(terrain_id);
+                page.stat(&format!("Next to {name}"), multiplier);
+                page.link(name, EntryId::Terrain(terrain_id));
+            }
+        }
+    }
+
+    if let Some(organism_variety) = &structure_data.organism_variety {
+        describe_organism(&mut page, organism_variety, None, Some(structure_manifest));
+    }
+
+    page
+}
+
+/// Generates the page for a kind of terrain.
+fn terrain_page(terrain_id: Id<Terrain>, terrain_manifest: &TerrainManifest) -> EncyclopediaPage {
+    let terrain_data = terrain_manifest.get(terrain_id);
+    let mut page = EncyclopediaPage::new(
+        EntryId::Terrain(terrain_id),
+        terrain_manifest.name(terrain_id),
+        "A kind of ground. It controls how quickly units walk across it, and how water soaks into it.",
+    );
+
+    page.stat(
+        "Walking speed",
+        format!("{:.2}x", terrain_data.walking_speed),
+    );
+    page.stat(
+        "Soil water capacity",
+        format!("{:.2}", terrain_data.soil_water_capacity.0),
+    );
+    page.stat(
+        "Soil water flow rate",
+        format!("{:.2}", terrain_data.soil_water_flow_rate.0),
+    );
+    page.stat(
+        "Soil water evaporation rate",
+        format!("{:.2}", terrain_data.soil_water_evaporation_rate.0),
+    );
+
+    page
+}
+
+/// Generates the page for a kind of item.
+fn item_page(
+    item_id: Id<Item>,
+    item_manifest: &ItemManifest,
+    unit_manifest: &UnitManifest,
+    structure_manifest: &StructureManifest,
+) -> EncyclopediaPage {
+    let item_data = item_manifest.get(item_id);
+
+    let seed_of = item_data.seed.and_then(|organism_id| match organism_id {
+        OrganismId::Unit(unit_id) => unit_manifest
+            .data_map()
+            .contains_key(&unit_id)
+            .then(|| unit_manifest.name(unit_id)),
+        OrganismId::Structure(structure_id) => structure_manifest
+            .data_map()
+            .contains_key(&structure_id)
+            .then(|| structure_manifest.name(structure_id)),
+    });
+
+    let description = match seed_of {
+        Some(name) => {
+            format!("An item that units can carry. If left on the ground, it grows into {name}.")
+        }
+        None => "An item that units can carry.".to_string(),
+    };
+
+    let mut page = EncyclopediaPage::new(
+        EntryId::Item(item_id),
+        item_manifest.name(item_id),
+        description,
+    );
+
+    page.stat("Stack size", item_data.stack_size);
+    page.stat("Compostable", yes_or_no(item_data.compostable));
+    page.stat("Fluid", yes_or_no(item_data.fluid));
+    page.stat("Floats", yes_or_no(item_data.buoyant));
+
+    if let (Some(organism_id), Some(name)) = (item_data.seed, seed_of) {
+        page.link(name, organism_id.into());
+    }
+
+    page
+}
+
+/// Generates the page for a kind of signal.
+fn signal_page(signal_kind: SignalKind) -> EncyclopediaPage {
+    let description = match signal_kind {
+        SignalKind::Push => "Asks units to take an item away from here.",
+        SignalKind::Pull => "Asks units to bring an item here.",
+        SignalKind::Work => "Asks units to work at a structure.",
+        SignalKind::Demolish => "Asks units to tear down a structure.",
+        SignalKind::Contains => {
+            "Lets units know that an item can be found here, in case they are looking for it."
+        }
+        SignalKind::Stores => {
+            "Lets units know that an item can be stored here, in case they are looking for somewhere to put it."
+        }
+        SignalKind::Unit => "Given off by units, so that others can avoid crowds.",
+    };
+
+    let mut page = EncyclopediaPage::new(
+        EntryId::Signal(signal_kind),
+        format!("{signal_kind:?} signal"),
+        description,
+    );
+
+    page.stat(
+        "Spreads to each neighbor",
+        format!("{:.0}% per tick", DIFFUSION_FRACTION * 100.),
+    );
+
+    page
+}
+
+/// Formats a boolean statistic.
+fn yes_or_no(value: bool) -> &'static str {
+    match value {
+        true => "Yes",
+        false => "No",
+    }
+}
+
+/// Regenerates the [`Encyclopedia`] whenever the manifests change.
+fn build_encyclopedia(
+    mut encyclopedia: ResMut<Encyclopedia>,
+    unit_manifest: Res<UnitManifest>,
+    structure_manifest: Res<StructureManifest>,
+    terrain_manifest: Res<TerrainManifest>,
+    item_manifest: Res<ItemManifest>,
+) {
+    if unit_manifest.is_changed()
+        || structure_manifest.is_changed()
+        || terrain_manifest.is_changed()
+        || item_manifest.is_changed()
+    {
+        *encyclopedia = Encyclopedia::new(
+            &unit_manifest,
+            &structure_manifest,
+            &terrain_manifest,
+            &item_manifest,
+        );
+    }
+}
+
+/// What the player is currently looking at in the encyclopedia.
+#[derive(Resource, Debug, Default)]
+struct EncyclopediaState {
+    /// Is the encyclopedia being displayed?
+    open: bool,
+    /// The text typed into the search box.
+    search: String,
+    /// The page being read, if any.
+    ///
+    /// When this is `None`, the pages matching the search are listed instead.
+    current: Option<EntryId>,
+}
+
+/// Marker component for the root node of the encyclopedia.
+#[derive(Component)]
+struct EncyclopediaPanel;
+
+/// A button that opens the page for the contained entry when clicked.
+#[derive(Component)]
+struct EncyclopediaLink(EntryId);
+
+/// Creates the hidden encyclopedia panel.
+fn spawn_encyclopedia_panel(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Percent(25.),
+                    top: Val::Percent(10.),
+                    ..default()
+                },
+                size: Size::new(Val::Percent(50.), Val::Percent(80.)),
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(8.)),
+                ..default()
+            },
+            background_color: Color::rgba(0., 0., 0., 0.9).into(),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        EncyclopediaPanel,
+    ));
+}
+
+/// Opens and closes the encyclopedia.
+fn toggle_encyclopedia(
+    actions: Res<ActionState<PlayerAction>>,
+    mut state: ResMut<EncyclopediaState>,
+) {
+    if actions.just_pressed(PlayerAction::ToggleEncyclopedia) {
+        state.open = !state.open;
+    }
+}
+
+/// Stops the keys typed into the search box from also controlling the game.
+///
+/// While the encyclopedia is open, every [`PlayerAction`] except [`PlayerAction::ToggleEncyclopedia`] is consumed,
+/// so it stays released until its keys are let go, even after the encyclopedia is closed.
+fn capture_gameplay_input(
+    mut actions: ResMut<ActionState<PlayerAction>>,
+    state: Res<EncyclopediaState>,
+) {
+    if !state.open {
+        return;
+    }
+
+    for action in PlayerAction::variants() {
+        if matches!(action, PlayerAction::ToggleEncyclopedia) {
+            continue;
+        }
+
+        actions.consume(action.clone());
+        let action_data = actions.action_data_mut(action);
+        action_data.value = 0.;
+        action_data.axis_pair = None;
+    }
+}
+
+/// Types into the search box while the encyclopedia is open.
+fn search_encyclopedia(
+    mut received_characters: EventReader<ReceivedCharacter>,
+    mut state: ResMut<EncyclopediaState>,
+) {
+    for event in received_characters.iter() {
+        if !state.open {
+            continue;
+        }
+
+        match event.char {
+            // Backspace
+            '\u{8}' => {
+                state.search.pop();
+            }
+            character if character.is_control() => continue,
+            character => state.search.push(character),
+        }
+
+        state.current = None;
+    }
+}
+
+/// Opens the linked page when a link is clicked.
+fn follow_encyclopedia_links(
+    link_query: Query<(&Interaction, &EncyclopediaLink), Changed<Interaction>>,
+    mut state: ResMut<EncyclopediaState>,
+) {
+    for (interaction, link) in link_query.iter() {
+        if *interaction == Interaction::Clicked {
+            state.current = Some(link.0);
+            state.search.clear();
+        }
+    }
+}
+
+/// Redraws the encyclopedia whenever what should be displayed changes.
+fn update_encyclopedia_panel(
+    mut commands: Commands,
+    mut panel_query: Query<(Entity, &mut Visibility), With<EncyclopediaPanel>>,
+    encyclopedia: Res<Encyclopedia>,
+    state: Res<EncyclopediaState>,
+    fonts: Res<FiraSansFontFamily>,
+) {
+    if !encyclopedia.is_changed() && !state.is_changed() {
+        return;
+    }
+
+    let (panel_entity, mut visibility) = panel_query.single_mut();
+    *visibility = match state.open {
+        true => Visibility::Visible,
+        false => Visibility::Hidden,
+    };
+
+    let heading_style = TextStyle {
+        font: fonts.regular.clone_weak(),
+        font_size: 28.,
+        color: Color::WHITE,
+    };
+    let text_style = TextStyle {
+        font_size: 20.,
+        ..heading_style.clone()
+    };
+    let link_style = TextStyle {
+        color: Color::rgb(0.6, 0.8, 1.),
+        ..text_style.clone()
+    };
+
+    let current_page = state.current.and_then(|entry| encyclopedia.page(entry));
+
+    commands.entity(panel_entity).despawn_descendants();
+    commands.entity(panel_entity).with_children(|parent| {
+        parent.spawn(TextBundle::from_section(
+            format!("Search: {}_", state.search),
+            text_style.clone(),
+        ));
+
+        match current_page {
+            Some(page) => {
+                parent.spawn(TextBundle::from_section(
+                    format!("{} ({})", page.title, page.entry.category()),
+                    heading_style,
+                ));
+
+                let mut body = format!("{}\n", page.description);
+                for (name, value) in &page.stats {
+                    body += &format!("\n{name}: {value}");
+                }
+                parent.spawn(TextBundle::from_section(body, text_style.clone()));
+
+                if !page.links.is_empty() {
+                    parent.spawn(TextBundle::from_section("See also:", text_style));
+                    for (label, target) in &page.links {
+                        spawn_link(parent, label.clone(), *target, &link_style);
+                    }
+                }
+            }
+            None => {
+                for page in encyclopedia.search(&state.search) {
+                    let label = format!("{} ({})", page.title, page.entry.category());
+                    spawn_link(parent, label, page.entry, &link_style);
+                }
+            }
+        }
+    });
+}
+
+/// Spawns a button labeled `label` that opens the page for `target`.
+fn spawn_link(parent: &mut ChildBuilder, label: String, target: EntryId, style: &TextStyle) {
+    parent
+        .spawn((ButtonBundle::default(), EncyclopediaLink(target)))
+        .with_children(|button| {
+            button.spawn(TextBundle::from_section(label, style.clone()));
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        items::item_manifest::ItemData,
+        structures::{adjacency::AdjacencyRule, structure_manifest::StructureData},
+        terrain::terrain_manifest::TerrainData,
+        units::{basic_needs::Diet, unit_manifest::UnitData},
+    };
+
+    /// The manifests used to generate the test encyclopedia.
+    struct TestManifests {
+        /// The kinds of unit.
+        units: UnitManifest,
+        /// The kinds of structure.
+        structures: StructureManifest,
+        /// The kinds of terrain.
+        terrain: TerrainManifest,
+        /// The kinds of item.
+        items: ItemManifest,
+    }
+
+    impl TestManifests {
+        /// An ant that eats leaves, and an acacia that grows best next to plain terrain.
+        fn new() -> Self {
+            let mut units = UnitManifest::new();
+            units.insert(
+                "ant".to_string(),
+                UnitData::simple("ant", Diet::simple("leaf")),
+            );
+
+            let mut terrain = TerrainManifest::new();
+            terrain.insert(
+                "plain".to_string(),
+                TerrainData {
+                    walking_speed: 1.5,
+                    ..default()
+                },
+            );
+            terrain.insert("rocky".to_string(), TerrainData::default());
+
+            let mut structures = StructureManifest::new();
+            let mut acacia = StructureData::organism("acacia");
+            acacia.organism_variety.as_mut().unwrap().prototypical_form =
+                OrganismId::Structure(Id::from_name("acacia".to_string()));
+            acacia.adjacency_rules.push(AdjacencyRule {
+                neighbor: NeighborCondition::Terrain(Id::from_name("plain".to_string())),
+                multiplier: 1.5,
+            });
+            structures.insert("acacia".to_string(), acacia);
+
+            let mut items = ItemManifest::new();
+            items.insert(
+                "leaf".to_string(),
+                ItemData {
+                    stack_size: 3,
+                    compostable: true,
+                    fluid: false,
+                    buoyant: true,
+                    seed: None,
+                },
+            );
+            items.insert(
+                "acacia_seed".to_string(),
+                ItemData {
+                    stack_size: 10,
+                    compostable: false,
+                    fluid: false,
+                    buoyant: false,
+                    seed: Some(OrganismId::Structure(Id::from_name("acacia".to_string()))),
+                },
+            );
+
+            TestManifests {
+                units,
+                structures,
+                terrain,
+                items,
+            }
+        }
+
+        /// Generates the encyclopedia from these manifests.
+        fn encyclopedia(&self) -> Encyclopedia {
+            Encyclopedia::new(&self.units, &self.structures, &self.terrain, &self.items)
+        }
+    }
+
+    /// Looks up the value of the statistic called `name` on the page for `entry`.
+    fn stat(encyclopedia: &Encyclopedia, entry: EntryId, name: &str) -> String {
+        encyclopedia
+            .page(entry)
+            .unwrap()
+            .stats
+            .iter()
+            .find(|(stat_name, _)| stat_name == name)
+            .unwrap_or_else(|| panic!("{name} is missing from {entry:?}"))
+            .1
+            .clone()
+    }
+
+    /// Follows the link labeled `label` from the page describing `from`.
+    fn follow_link<'a>(
+        encyclopedia: &'a Encyclopedia,
+        from: EntryId,
+        label: &str,
+    ) -> Option<&'a EncyclopediaPage> {
+        let (_, target) = encyclopedia
+            .page(from)?
+            .links
+            .iter()
+            .find(|(link_label, _)| link_label == label)?;
+
+        encyclopedia.page(*target)
+    }
+
+    #[test]
+    fn every_kind_has_a_page() {
+        let manifests = TestManifests::new();
+        let encyclopedia = manifests.encyclopedia();
+
+        let mut expected: Vec<EntryId> = Vec::new();
+        expected.extend(manifests.units.variants().into_iter().map(EntryId::Unit));
+        expected.extend(
+            manifests
+                .structures
+                .variants()
+                .into_iter()
+                .map(EntryId::Structure),
+        );
+        expected.extend(
+            manifests
+                .terrain
+                .variants()
+                .into_iter()
+                .map(EntryId::Terrain),
+        );
+        expected.extend(manifests.items.variants().into_iter().map(EntryId::Item));
+        expected.extend(SignalKind::variants().map(EntryId::Signal));
+
+        assert_eq!(encyclopedia.pages.len(), expected.len());
+        for entry in expected {
+            assert!(encyclopedia.page(entry).is_some(), "No page for {entry:?}");
+        }
+    }
+
+    #[test]
+    fn stats_match_the_manifests() {
+        let mut manifests = TestManifests::new();
+        let plain = EntryId::Terrain(Id::from_name("plain".to_string()));
+        let ant = EntryId::Unit(Id::from_name("ant".to_string()));
+        let leaf = EntryId::Item(Id::from_name("leaf".to_string()));
+
+        let encyclopedia = manifests.encyclopedia();
+        assert_eq!(stat(&encyclopedia, plain, "Walking speed"), "1.50x");
+        assert_eq!(stat(&encyclopedia, ant, "Lifespan"), "10.0 days");
+        assert_eq!(stat(&encyclopedia, leaf, "Stack size"), "3");
+        assert_eq!(
+            stat(
+                &encyclopedia,
+                EntryId::Signal(SignalKind::Push),
+                "Spreads to each neighbor"
+            ),
+            format!("{:.0}% per tick", DIFFUSION_FRACTION * 100.)
+        );
+
+        // Pages are regenerated from the current data
+        manifests.terrain.insert(
+            "plain".to_string(),
+            TerrainData {
+                walking_speed: 0.5,
+                ..default()
+            },
+        );
+        let encyclopedia = manifests.encyclopedia();
+        assert_eq!(stat(&encyclopedia, plain, "Walking speed"), "0.50x");
+    }
+
+    #[test]
+    fn search_matches_titles_ignoring_case() {
+        let encyclopedia = TestManifests::new().encyclopedia();
+
+        let titles = |query: &str| -> Vec<String> {
+            encyclopedia
+                .search(query)
+                .into_iter()
+                .map(|page| page.title.clone())
+                .collect()
+        };
+
+        // Results are grouped by category, and items are listed before structures
+        assert_eq!(titles("ACACIA"), vec!["acacia_seed", "acacia"]);
+        assert_eq!(titles("rock"), vec!["rocky"]);
+        assert!(titles("zebra").is_empty());
+        assert_eq!(titles("").len(), encyclopedia.pages.len());
+    }
+
+    #[test]
+    fn links_lead_to_related_pages() {
+        let encyclopedia = TestManifests::new().encyclopedia();
+        let acacia = EntryId::Structure(Id::from_name("acacia".to_string()));
+        let acacia_seed = EntryId::Item(Id::from_name("acacia_seed".to_string()));
+        let ant = EntryId::Unit(Id::from_name("ant".to_string()));
+
+        let plain = follow_link(&encyclopedia, acacia, "plain").unwrap();
+        assert_eq!(
+            plain.entry,
+            EntryId::Terrain(Id::from_name("plain".to_string()))
+        );
+
+        let leaf = follow_link(&encyclopedia, ant, "leaf").unwrap();
+        assert_eq!(leaf.entry, EntryId::Item(Id::from_name("leaf".to_string())));
+
+        let grown = follow_link(&encyclopedia, acacia_seed, "acacia").unwrap();
+        assert_eq!(grown.entry, acacia);
+
+        // Pages do not link to themselves, or to text that is not a link
+        assert!(follow_link(&encyclopedia, acacia, "acacia").is_none());
+        assert!(follow_link(&encyclopedia, ant, "rocky").is_none());
+    }
+
+    /// An app that only runs [`capture_gameplay_input`].
+    fn input_app(open: bool) -> App {
+        let mut app = App::new();
+        app.init_resource::<ActionState<PlayerAction>>()
+            .insert_resource(EncyclopediaState { open, ..default() })
+            .add_system(capture_gameplay_input);
+        app
+    }
+
+    /// Holds down the pause and encyclopedia keys.
+    fn press_keys(app: &mut App) {
+        let mut actions = app.world.resource_mut::<ActionState<PlayerAction>>();
+        actions.press(PlayerAction::TogglePause);
+        actions.press(PlayerAction::ClearZoning);
+        actions.press(PlayerAction::ToggleEncyclopedia);
+    }
+
+    #[test]
+    fn open_encyclopedia_captures_gameplay_input() {
+        let mut app = input_app(true);
+        press_keys(&mut app);
+        app.update();
+
+        let actions = app.world.resource::<ActionState<PlayerAction>>();
+        assert!(!actions.pressed(PlayerAction::TogglePause));
+        assert!(!actions.pressed(PlayerAction::ClearZoning));
+        // The encyclopedia must still be able to close itself
+        assert!(actions.pressed(PlayerAction::ToggleEncyclopedia));
+
+        // Keys that were typed into the search box do not fire once it closes
+        app.world.resource_mut::<EncyclopediaState>().open = false;
+        press_keys(&mut app);
+        app.update();
+        let actions = app.world.resource::<ActionState<PlayerAction>>();
+        assert!(!actions.pressed(PlayerAction::TogglePause));
+    }
+
+    #[test]
+    fn closed_encyclopedia_leaves_input_alone() {
+        let mut app = input_app(false);
+        press_keys(&mut app);
+        app.update();
+
+        let actions = app.world.resource::<ActionState<PlayerAction>>();
+        assert!(actions.pressed(PlayerAction::TogglePause));
+        assert!(actions.pressed(PlayerAction::ClearZoning));
+        assert!(actions.pressed(PlayerAction::ToggleEncyclopedia));
+    }
+}
@@ -0,0 +1,360 @@
+//! Places the root UI panels, and keeps them on screen and apart as the window and UI scale change.
+
+use bevy::{
+    math::Rect,
+    prelude::*,
+    window::{PrimaryWindow, WindowResized},
+};
+
+/// Positions the root UI panels whenever the window or UI scale changes.
+pub(super) struct UiLayoutPlugin;
+
+impl Plugin for UiLayoutPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<UiLayout>().add_system(apply_ui_layout);
+    }
+}
+
+/// One of the root UI panels, positioned by the [`UiLayout`].
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub(super) enum UiPanel {
+    /// The panel on the left side, with statistics and overlays.
+    Left,
+    /// The panel on the right side, with selection details and world info.
+    Right,
+}
+
+/// The corner of the window that a panel is attached to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Corner {
+    /// The top-left corner.
+    TopLeft,
+    /// The top-right corner.
+    TopRight,
+}
+
+/// Where and how large a single [`UiPanel`] should be.
+///
+/// All lengths are in UI units: logical pixels, before [`UiScale`] is applied.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct PanelAnchor {
+    /// The corner that the panel is attached to.
+    pub(super) corner: Corner,
+    /// The gap between the panel and the edges of the window, and between neighboring panels.
+    pub(super) margin: f32,
+    /// The width of the panel when there is enough room.
+    pub(super) width: f32,
+    /// The height of the panel, or [`None`] to fill the height of the window.
+    pub(super) height: Option<f32>,
+    /// The narrowest that the panel can be squeezed before it is hidden instead.
+    pub(super) min_width: f32,
+    /// Panels with a higher priority are placed first, and keep their full width.
+    pub(super) priority: u8,
+}
+
+/// The anchors of every [`UiPanel`].
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub(super) struct UiLayout {
+    /// The anchor of each panel.
+    anchors: Vec<(UiPanel, PanelAnchor)>,
+}
+
+impl Default for UiLayout {
+    fn default() -> Self {
+        UiLayout {
+            anchors: vec![
+                (
+                    UiPanel::Left,
+                    PanelAnchor {
+                        corner: Corner::TopLeft,
+                        margin: 8.,
+                        width: 200.,
+                        height: None,
+                        min_width: 120.,
+                        priority: 0,
+                    },
+                ),
+                (
+                    UiPanel::Right,
+                    PanelAnchor {
+                        corner: Corner::TopRight,
+                        margin: 8.,
+                        width: 400.,
+                        height: None,
+                        min_width: 200.,
+                        priority: 1,
+                    },
+                ),
+            ],
+        }
+    }
+}
+
+impl UiLayout {
+    /// Creates a layout from the provided anchors.
+    #[cfg(test)]
+    fn new(anchors: Vec<(UiPanel, PanelAnchor)>) -> Self {
+        UiLayout { anchors }
+    }
+
+    /// Computes the rectangle covered by each panel, in UI units, or [`None`] if the panel does not fit.
+    ///
+    /// `window_size` is in logical pixels, and is divided by `ui_scale` to get the space available to the UI.
+    /// Panels are placed in order of priority.
+    /// Each is narrowed so it keeps at least one margin away from the panels already placed,
+    /// and hidden if that would make it narrower than its `min_width`.
+    pub(super) fn resolve(&self, window_size: Vec2, ui_scale: f32) -> Vec<(UiPanel, Option<Rect>)> {
+        let available = window_size / ui_scale.max(f32::EPSILON);
+
+        let mut anchors: Vec<&(UiPanel, PanelAnchor)> = self.anchors.iter().collect();
+        anchors.sort_by_key(|(panel, anchor)| (std::cmp::Reverse(anchor.priority), *panel));
+
+        let mut placed: Vec<Rect> = Vec::new();
+        let mut resolved = Vec::with_capacity(anchors.len());
+
+        for (panel, anchor) in anchors {
+            let maybe_rect = Self::place(anchor, available, &placed);
+            if let Some(rect) = maybe_rect {
+                placed.push(rect);
+            }
+            resolved.push((*panel, maybe_rect));
+        }
+
+        resolved
+    }
+
+    /// Places a single panel in the `available` space, keeping clear of the `placed` panels.
+    fn place(anchor: &PanelAnchor, available: Vec2, placed: &[Rect]) -> Option<Rect> {
+        let margin = anchor.margin;
+        let max_height = available.y - 2. * margin;
+        let height = anchor.height.unwrap_or(max_height).min(max_height);
+
+        let top = margin;
+        let bottom = top + height;
+
+        // The horizontal extent that this panel may use
+        let mut left_limit = margin;
+        let mut right_limit = available.x - margin;
+        for rect in placed {
+            let overlaps_vertically = rect.min.y < bottom && top < rect.max.y;
+            if !overlaps_vertically {
+                continue;
+            }
+
+            if anchor.corner == Corner::TopLeft {
+                right_limit = right_limit.min(rect.min.x - margin);
+            } else {
+                left_limit = left_limit.max(rect.max.x + margin);
+            }
+        }
+
+        let width = anchor.width.min(right_limit - left_limit);
+        if width < anchor.min_width || height <= 0. {
+            return None;
+        }
+
+        let left = match anchor.corner {
+            Corner::TopLeft => left_limit,
+            Corner::TopRight => right_limit - width,
+        };
+
+        Some(Rect::new(left, top, left + width, bottom))
+    }
+}
+
+/// Moves and resizes the [`UiPanel`]s to match the [`UiLayout`].
+///
+/// This only does work when the window is resized, the [`UiScale`] or [`UiLayout`] changes, or a new panel is added.
+fn apply_ui_layout(
+    mut resize_events: EventReader<WindowResized>,
+    ui_scale: Res<UiScale>,
+    ui_layout: Res<UiLayout>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut panel_query: Query<(&UiPanel, &mut Style)>,
+    new_panel_query: Query<(), Added<UiPanel>>,
+) {
+    let resized = resize_events.iter().count() > 0;
+    if !resized && !ui_scale.is_changed() && !ui_layout.is_changed() && new_panel_query.is_empty() {
+        return;
+    }
+
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    let window_size = Vec2::new(window.width(), window.height());
+    let resolved = ui_layout.resolve(window_size, ui_scale.scale as f32);
+
+    for (panel, mut style) in panel_query.iter_mut() {
+        let Some((_, maybe_rect)) = resolved
+            .iter()
+            .find(|(resolved_panel, _)| resolved_panel == panel)
+        else {
+            continue;
+        };
+
+        match maybe_rect {
+            Some(rect) => {
+                style.display = Display::Flex;
+                style.position_type = PositionType::Absolute;
+                style.position = UiRect {
+                    left: Val::Px(rect.min.x),
+                    top: Val::Px(rect.min.y),
+                    ..default()
+                };
+                style.size = Size::new(Val::Px(rect.width()), Val::Px(rect.height()));
+            }
+            None => style.display = Display::None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::window::WindowResolution;
+
+    use super::*;
+
+    /// Window sizes from a small laptop up to a large monitor, in logical pixels.
+    const WINDOW_SIZES: [Vec2; 5] = [
+        Vec2::new(640., 480.),
+        Vec2::new(800., 600.),
+        Vec2::new(1280., 720.),
+        Vec2::new(1920., 1080.),
+        Vec2::new(3840., 2160.),
+    ];
+
+    /// The rectangles of the panels that fit.
+    fn visible_rects(resolved: &[(UiPanel, Option<Rect>)]) -> Vec<Rect> {
+        resolved.iter().filter_map(|(_, rect)| *rect).collect()
+    }
+
+    /// The rectangle of `panel`, if it is visible.
+    fn rect_of(resolved: &[(UiPanel, Option<Rect>)], panel: UiPanel) -> Option<Rect> {
+        resolved
+            .iter()
+            .find(|(resolved_panel, _)| *resolved_panel == panel)
+            .and_then(|(_, rect)| *rect)
+    }
+
+    #[test]
+    fn panels_never_overlap() {
+        let layout = UiLayout::default();
+
+        for window_size in WINDOW_SIZES {
+            for ui_scale in [0.5, 1., 1.5, 2.] {
+                let resolved = layout.resolve(window_size, ui_scale);
+                let rects = visible_rects(&resolved);
+                let available = window_size / ui_scale;
+
+                for (i, a) in rects.iter().enumerate() {
+                    assert!(a.min.cmpge(Vec2::ZERO).all() && a.max.cmple(available).all());
+                    for b in &rects[i + 1..] {
+                        assert!(
+                            a.intersect(*b).is_empty(),
+                            "{a:?} and {b:?} overlap in a {window_size} window at scale {ui_scale}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn large_windows_show_every_panel_at_full_width() {
+        let layout = UiLayout::default();
+        let resolved = layout.resolve(Vec2::new(1920., 1080.), 1.);
+
+        assert_eq!(rect_of(&resolved, UiPanel::Left).unwrap().width(), 200.);
+        assert_eq!(rect_of(&resolved, UiPanel::Right).unwrap().width(), 400.);
+    }
+
+    #[test]
+    fn low_priority_panels_are_squeezed_then_hidden() {
+        let layout = UiLayout::default();
+
+        // 8 + 150 + 8 + 400 + 8: the left panel is squeezed into the gap
+        let squeezed = layout.resolve(Vec2::new(574., 600.), 1.);
+        assert_eq!(rect_of(&squeezed, UiPanel::Left).unwrap().width(), 150.);
+        assert_eq!(rect_of(&squeezed, UiPanel::Right).unwrap().width(), 400.);
+
+        // There is no room for the left panel at all
+        let hidden = layout.resolve(Vec2::new(450., 600.), 1.);
+        assert_eq!(rect_of(&hidden, UiPanel::Left), None);
+        assert!(rect_of(&hidden, UiPanel::Right).is_some());
+    }
+
+    #[test]
+    fn panels_stick_to_their_corners() {
+        let layout = UiLayout::new(vec![(
+            UiPanel::Left,
+            PanelAnchor {
+                corner: Corner::TopRight,
+                margin: 10.,
+                width: 100.,
+                height: Some(50.),
+                min_width: 10.,
+                priority: 0,
+            },
+        )]);
+
+        let resolved = layout.resolve(Vec2::new(800., 600.), 1.);
+        assert_eq!(
+            rect_of(&resolved, UiPanel::Left),
+            Some(Rect::new(690., 10., 790., 60.))
+        );
+    }
+
+    #[test]
+    fn scale_changes_recompute_margins() {
+        let layout = UiLayout::default();
+        let window_size = Vec2::new(1920., 1080.);
+
+        let unscaled = rect_of(&layout.resolve(window_size, 1.), UiPanel::Right).unwrap();
+        let scaled = rect_of(&layout.resolve(window_size, 2.), UiPanel::Right).unwrap();
+
+        // Measured in window pixels, the gap to the right edge grows with the scale
+        let gap = |rect: Rect, ui_scale: f32| window_size.x - rect.max.x * ui_scale;
+        assert_eq!(gap(unscaled, 1.), 8.);
+        assert_eq!(gap(scaled, 2.), 16.);
+    }
+
+    #[test]
+    fn resizing_the_window_moves_panels() {
+        let mut app = App::new();
+        app.add_event::<WindowResized>()
+            .init_resource::<UiScale>()
+            .add_plugin(UiLayoutPlugin);
+
+        let window = app
+            .world
+            .spawn((
+                Window {
+                    resolution: WindowResolution::new(1280., 720.),
+                    ..default()
+                },
+                PrimaryWindow,
+            ))
+            .id();
+        let right_panel = app.world.spawn((UiPanel::Right, Style::default())).id();
+
+        app.update();
+        let style = app.world.get::<Style>(right_panel).unwrap();
+        assert_eq!(style.position.left, Val::Px(1280. - 8. - 400.));
+
+        app.world
+            .get_mut::<Window>(window)
+            .unwrap()
+            .resolution
+            .set(1920., 1080.);
+        app.world.send_event(WindowResized {
+            window,
+            width: 1920.,
+            height: 1080.,
+        });
+        app.update();
+
+        let style = app.world.get::<Style>(right_panel).unwrap();
+        assert_eq!(style.position.left, Val::Px(1920. - 8. - 400.));
+        assert_eq!(style.size.height, Val::Px(1080. - 16.));
+    }
+}
@@ -6,6 +6,8 @@ use crate::{
     structures::structure_manifest::Structure,
     ui::{
         cursor::CursorPlugin,
+        encyclopedia::EncyclopediaPlugin,
+        layout::{UiLayoutPlugin, UiPanel},
         overlay::OverlayMenuPlugin,
         production_statistics::ProductionStatisticsPlugin,
         select_structure::SelectStructurePlugin,
@@ -13,6 +15,7 @@ use crate::{
         selection_details::SelectionDetailsPlugin,
         status::{CraftingProgress, StatusPlugin},
         ui_assets::{Icons, UiElements},
+        world_info::WorldInfoPlugin,
     },
     units::{goals::GoalKind, unit_manifest::Unit},
 };
@@ -20,6 +23,8 @@ use bevy::prelude::*;
 use bevy_screen_diagnostics::{ScreenDiagnosticsPlugin, ScreenFrameDiagnosticsPlugin};
 
 mod cursor;
+mod encyclopedia;
+mod layout;
 mod overlay;
 mod production_statistics;
 mod select_structure;
@@ -28,6 +33,7 @@ mod selection_details;
 mod status;
 mod ui_assets;
 mod wheel_menu;
+mod world_info;
 
 /// The font handles for the `FiraSans` font family.
 ///
@@ -60,12 +66,15 @@ impl Plugin for UiPlugin {
         .add_plugin(ScreenDiagnosticsPlugin::default())
         .add_plugin(ScreenFrameDiagnosticsPlugin)
         .add_plugin(CursorPlugin)
+        .add_plugin(EncyclopediaPlugin)
+        .add_plugin(UiLayoutPlugin)
         .add_plugin(SelectionDetailsPlugin)
         .add_plugin(ProductionStatisticsPlugin)
         .add_plugin(StatusPlugin)
         .add_plugin(OverlayMenuPlugin)
         .add_plugin(SelectStructurePlugin)
-        .add_plugin(SelectTerraformingPlugin);
+        .add_plugin(SelectTerraformingPlugin)
+        .add_plugin(WorldInfoPlugin);
     }
 }
 
@@ -78,6 +87,8 @@ struct LeftPanel;
 struct RightPanel;
 
 /// Create the basic UI layout.
+///
+/// The panels are positioned by the [`UiLayout`](layout::UiLayout).
 fn setup_ui(mut commands: Commands) {
     // UI layout
     commands
@@ -102,6 +113,7 @@ fn setup_ui(mut commands: Commands) {
                     ..default()
                 },
                 LeftPanel,
+                UiPanel::Left,
             ));
 
             // UI panel on the right side
@@ -115,6 +127,7 @@ fn setup_ui(mut commands: Commands) {
                     ..default()
                 },
                 RightPanel,
+                UiPanel::Right,
             ));
         });
 }
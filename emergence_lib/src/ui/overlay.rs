@@ -2,7 +2,7 @@
 
 use crate::{
     asset_management::AssetState,
-    graphics::overlay::{OverlayType, TileOverlay},
+    graphics::overlay::{OverlayType, ScalarOverlay, TileOverlay},
     items::item_manifest::ItemManifest,
     player_interaction::PlayerAction,
     signals::{SignalKind, Signals},
@@ -21,8 +21,32 @@ pub(super) struct OverlayMenuPlugin;
 impl Plugin for OverlayMenuPlugin {
     fn build(&self, app: &mut App) {
         app.add_system(select_overlay)
+            .add_system(click_overlay_toggles)
             .add_startup_system(setup_overlay_menu)
-            .add_system(update_signal_type_display.run_if(in_state(AssetState::FullyLoaded)));
+            .add_system(update_signal_type_display.run_if(in_state(AssetState::FullyLoaded)))
+            .add_system(
+                highlight_shown_overlay
+                    .after(select_overlay)
+                    .after(click_overlay_toggles),
+            );
+    }
+}
+
+/// A row of the overlay menu that toggles the contained overlay when clicked.
+#[derive(Component, Debug)]
+struct OverlayToggle(OverlayType);
+
+/// The name of an overlay, as listed in the overlay menu.
+fn overlay_label(overlay_type: OverlayType) -> &'static str {
+    match overlay_type {
+        OverlayType::None => "No overlay",
+        OverlayType::Single(_) => "Signal",
+        OverlayType::StrongestSignal => "Strongest signal",
+        OverlayType::DepthToWaterTable => "Depth to water table",
+        OverlayType::HeightOfWaterTable => "Height of water table",
+        OverlayType::VelocityOfWaterTable => "Outgoing lateral water flow",
+        OverlayType::NetWater => "Net water flux",
+        OverlayType::LightLevel => "Light level",
     }
 }
 
@@ -33,6 +57,8 @@ struct OverlayMenu {
     signal_type_entity: Entity,
     /// The entity that stores the legend image.
     legend_entity: Entity,
+    /// The entity that displays the range of values shown by the overlay.
+    range_entity: Entity,
 }
 
 /// Controls the overlay that is currently being displayed based on UI interactions.
@@ -43,11 +69,9 @@ fn select_overlay(
     signals: Res<Signals>,
 ) {
     if player_actions.just_pressed(PlayerAction::ToggleStrongestSignalOverlay) {
-        if tile_overlay.overlay_type != OverlayType::StrongestSignal {
-            tile_overlay.overlay_type = OverlayType::StrongestSignal;
-        } else {
-            tile_overlay.overlay_type = OverlayType::None;
-        }
+        tile_overlay.overlay_type = tile_overlay
+            .overlay_type
+            .toggled(OverlayType::StrongestSignal);
     }
 
     if player_actions.just_pressed(PlayerAction::ToggleSignalOverlay) {
@@ -66,10 +90,48 @@ fn select_overlay(
     }
 
     if player_actions.just_pressed(PlayerAction::ToggleLightOverlay) {
-        tile_overlay.overlay_type = match tile_overlay.overlay_type {
-            OverlayType::LightLevel => OverlayType::None,
-            _ => OverlayType::LightLevel,
+        tile_overlay.overlay_type = tile_overlay.overlay_type.toggled(OverlayType::LightLevel);
+    }
+}
+
+/// Toggles overlays when their row in the overlay menu is clicked.
+fn click_overlay_toggles(
+    toggle_query: Query<(&Interaction, &OverlayToggle), Changed<Interaction>>,
+    mut tile_overlay: ResMut<TileOverlay>,
+) {
+    for (interaction, toggle) in toggle_query.iter() {
+        if *interaction == Interaction::Clicked {
+            tile_overlay.overlay_type = tile_overlay.overlay_type.toggled(toggle.0);
+        }
+    }
+}
+
+/// Highlights the row of the overlay that is shown, however it was turned on.
+fn highlight_shown_overlay(
+    tile_overlay: Res<TileOverlay>,
+    toggle_query: Query<(&OverlayToggle, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    if !tile_overlay.is_changed() {
+        return;
+    }
+
+    for (toggle, children) in toggle_query.iter() {
+        let color = match toggle.0 == tile_overlay.overlay_type {
+            true => Color::YELLOW,
+            false => Color::GRAY,
         };
+
+        for &child in children {
+            if let Ok(mut text) = text_query.get_mut(child) {
+                for section in &mut text.sections {
+                    // Avoid triggering change detection when nothing changed
+                    if section.style.color != color {
+                        section.style.color = color;
+                    }
+                }
+            }
+        }
     }
 }
 
@@ -86,9 +148,32 @@ fn setup_overlay_menu(
         color: Color::WHITE,
     };
 
+    // Every overlay that can be switched on by name, so players can see what is available
+    commands.entity(left_panel_entity).with_children(|parent| {
+        for overlay_type in OverlayType::TOGGLEABLE {
+            parent
+                .spawn((
+                    ButtonBundle {
+                        background_color: Color::NONE.into(),
+                        ..default()
+                    },
+                    OverlayToggle(overlay_type),
+                ))
+                .with_children(|button| {
+                    button.spawn(TextBundle::from_section(
+                        overlay_label(overlay_type),
+                        TextStyle {
+                            color: Color::GRAY,
+                            ..text_style.clone()
+                        },
+                    ));
+                });
+        }
+    });
+
     let signal_type_entity = commands
         .spawn(TextBundle {
-            text: Text::from_section("SIGNAL_TYPE".to_string(), text_style),
+            text: Text::from_section("SIGNAL_TYPE".to_string(), text_style.clone()),
             ..Default::default()
         })
         .id();
@@ -114,9 +199,18 @@ fn setup_overlay_menu(
         .id();
     commands.entity(left_panel_entity).add_child(legend_entity);
 
+    let range_entity = commands
+        .spawn(TextBundle {
+            text: Text::from_section(String::new(), text_style),
+            ..Default::default()
+        })
+        .id();
+    commands.entity(left_panel_entity).add_child(range_entity);
+
     commands.insert_resource(OverlayMenu {
         signal_type_entity,
         legend_entity,
+        range_entity,
     });
 }
 
@@ -132,6 +226,12 @@ fn update_signal_type_display(
     terrain_manifest: Res<TerrainManifest>,
     unit_manifest: Res<UnitManifest>,
 ) {
+    let mut range_text = text_query.get_mut(overlay_menu.range_entity).unwrap();
+    range_text.sections[0].value = match tile_overlay.value_range {
+        Some(range) => range.to_string(),
+        None => String::new(),
+    };
+
     let mut text = text_query.get_mut(overlay_menu.signal_type_entity).unwrap();
     let mut legend = image_query.get_mut(overlay_menu.legend_entity).unwrap();
     let font_size = 20.0;
@@ -139,7 +239,7 @@ fn update_signal_type_display(
     match &tile_overlay.overlay_type {
         OverlayType::None => {
             text.sections = vec![TextSection {
-                value: "No overlay".to_string(),
+                value: overlay_label(OverlayType::None).to_string(),
                 style: TextStyle {
                     font: fonts.regular.clone_weak(),
                     font_size,
@@ -166,7 +266,7 @@ fn update_signal_type_display(
                 },
             }];
 
-            legend.texture = tile_overlay.signal_legend_image_handle(signal_kind)
+            legend.texture = tile_overlay.legend_image_handle(ScalarOverlay::Signal(signal_kind))
         }
         OverlayType::StrongestSignal => {
             text.sections = vec![
@@ -216,7 +316,7 @@ fn update_signal_type_display(
         }
         OverlayType::DepthToWaterTable => {
             text.sections = vec![TextSection {
-                value: "Depth to water table".to_string(),
+                value: overlay_label(OverlayType::DepthToWaterTable).to_string(),
                 style: TextStyle {
                     font: fonts.regular.clone_weak(),
                     font_size,
@@ -224,11 +324,11 @@ fn update_signal_type_display(
                 },
             }];
 
-            legend.texture = tile_overlay.water_table_legend_image_handle();
+            legend.texture = tile_overlay.legend_image_handle(ScalarOverlay::DepthToWaterTable);
         }
         OverlayType::HeightOfWaterTable => {
             text.sections = vec![TextSection {
-                value: "Height of water table".to_string(),
+                value: overlay_label(OverlayType::HeightOfWaterTable).to_string(),
                 style: TextStyle {
                     font: fonts.regular.clone_weak(),
                     font_size,
@@ -236,11 +336,11 @@ fn update_signal_type_display(
                 },
             }];
 
-            legend.texture = tile_overlay.water_table_legend_image_handle();
+            legend.texture = tile_overlay.legend_image_handle(ScalarOverlay::HeightOfWaterTable);
         }
         OverlayType::VelocityOfWaterTable => {
             text.sections = vec![TextSection {
-                value: "Outgoing lateral water flow".to_string(),
+                value: overlay_label(OverlayType::VelocityOfWaterTable).to_string(),
                 style: TextStyle {
                     font: fonts.regular.clone_weak(),
                     font_size,
@@ -253,7 +353,7 @@ fn update_signal_type_display(
         }
         OverlayType::NetWater => {
             text.sections = vec![TextSection {
-                value: "Net water flux".to_string(),
+                value: overlay_label(OverlayType::NetWater).to_string(),
                 style: TextStyle {
                     font: fonts.regular.clone_weak(),
                     font_size,
@@ -261,11 +361,11 @@ fn update_signal_type_display(
                 },
             }];
 
-            legend.texture = tile_overlay.flux_legend_image_handle();
+            legend.texture = tile_overlay.legend_image_handle(ScalarOverlay::NetWater);
         }
         OverlayType::LightLevel => {
             text.sections = vec![TextSection {
-                value: "Light level".to_string(),
+                value: overlay_label(OverlayType::LightLevel).to_string(),
                 style: TextStyle {
                     font: fonts.regular.clone_weak(),
                     font_size,
@@ -278,3 +378,117 @@ fn update_signal_type_display(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An app that can run the overlay toggles, without a renderer.
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin::default())
+            .add_asset::<StandardMaterial>()
+            .init_resource::<TileOverlay>()
+            .add_system(click_overlay_toggles)
+            .add_system(highlight_shown_overlay.after(click_overlay_toggles));
+
+        for overlay_type in OverlayType::TOGGLEABLE {
+            app.world
+                .spawn((Interaction::None, OverlayToggle(overlay_type)))
+                .with_children(|parent| {
+                    parent.spawn(Text::from_section(
+                        overlay_label(overlay_type),
+                        TextStyle::default(),
+                    ));
+                });
+        }
+
+        app.update();
+        app
+    }
+
+    /// Simulates a click on the row for `overlay_type`.
+    fn click(app: &mut App, overlay_type: OverlayType) {
+        let mut query = app.world.query::<(&mut Interaction, &OverlayToggle)>();
+        for (mut interaction, toggle) in query.iter_mut(&mut app.world) {
+            *interaction = match toggle.0 == overlay_type {
+                true => Interaction::Clicked,
+                false => Interaction::None,
+            };
+        }
+        app.update();
+
+        // Release the button, as a real click would
+        for (mut interaction, _) in query.iter_mut(&mut app.world) {
+            *interaction = Interaction::None;
+        }
+        app.update();
+    }
+
+    /// The overlays whose rows are highlighted.
+    fn highlighted(app: &mut App) -> Vec<OverlayType> {
+        let mut query = app.world.query::<(&OverlayToggle, &Children)>();
+        let rows: Vec<(OverlayType, Entity)> = query
+            .iter(&app.world)
+            .map(|(toggle, children)| (toggle.0, children[0]))
+            .collect();
+
+        rows.into_iter()
+            .filter(|(_, text_entity)| {
+                app.world.get::<Text>(*text_entity).unwrap().sections[0]
+                    .style
+                    .color
+                    == Color::YELLOW
+            })
+            .map(|(overlay_type, _)| overlay_type)
+            .collect()
+    }
+
+    #[test]
+    fn the_menu_lists_every_toggleable_overlay() {
+        let mut app = app();
+        let mut query = app.world.query::<&OverlayToggle>();
+        let listed: Vec<OverlayType> = query.iter(&app.world).map(|toggle| toggle.0).collect();
+
+        assert_eq!(listed, OverlayType::TOGGLEABLE.to_vec());
+        assert!(highlighted(&mut app).is_empty());
+    }
+
+    #[test]
+    fn clicking_a_row_toggles_its_overlay() {
+        let mut app = app();
+
+        click(&mut app, OverlayType::NetWater);
+        assert_eq!(
+            app.world.resource::<TileOverlay>().overlay_type,
+            OverlayType::NetWater
+        );
+        assert_eq!(highlighted(&mut app), vec![OverlayType::NetWater]);
+
+        click(&mut app, OverlayType::NetWater);
+        assert_eq!(
+            app.world.resource::<TileOverlay>().overlay_type,
+            OverlayType::None
+        );
+        assert!(highlighted(&mut app).is_empty());
+    }
+
+    #[test]
+    fn hotkeys_and_clicks_stay_consistent() {
+        let mut app = app();
+
+        // Turned on by a hotkey...
+        app.world.resource_mut::<TileOverlay>().overlay_type = OverlayType::LightLevel;
+        app.update();
+        assert_eq!(highlighted(&mut app), vec![OverlayType::LightLevel]);
+
+        // ...and off again from the menu
+        click(&mut app, OverlayType::LightLevel);
+        assert_eq!(
+            app.world.resource::<TileOverlay>().overlay_type,
+            OverlayType::None
+        );
+        assert!(highlighted(&mut app).is_empty());
+    }
+}
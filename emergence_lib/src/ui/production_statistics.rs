@@ -1,16 +1,33 @@
 //! Displays information about population counts and production over time.
 
-use bevy::{prelude::*, utils::HashMap};
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
 
 use crate::{
     asset_management::manifest::Id,
     crafting::inventories::{InputInventory, OutputInventory, StorageInventory},
-    geometry::Volume,
+    geometry::{MapGeometry, Volume},
     items::item_manifest::{Item, ItemManifest},
     light::TotalLight,
     litter::Litter,
-    simulation::{time::InGameTime, weather::CurrentWeather},
-    units::{item_interaction::UnitInventory, unit_manifest::Unit},
+    organisms::{Organism, OrganismId},
+    player_interaction::colony_rules::{apply_colony_rules, ColonyMetrics, ColonyRules},
+    simulation::{
+        phases::{SimulationAppExt, TickPhase},
+        sim_resources::{SimResource, SimResourceAppExt},
+        time::InGameTime,
+        weather::{CurrentWeather, Wind},
+    },
+    structures::structure_manifest::{Structure, StructureManifest},
+    units::{
+        deliveries::FoodDeliveries,
+        item_interaction::UnitInventory,
+        traffic::TrafficMap,
+        unit_manifest::{Unit, UnitManifest},
+    },
+    utils::memory::MemoryUsage,
     water::WaterVolume,
     world_gen::WorldGenState,
 };
@@ -26,11 +43,28 @@ impl Plugin for ProductionStatisticsPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<Census>()
             .init_resource::<ItemCount>()
-            .add_systems(
-                (census, update_item_count).distributive_run_if(in_state(WorldGenState::Complete)),
+            .init_resource::<ColonyMetrics>()
+            .init_resource::<ColonyRules>()
+            .init_resource::<CollapsedCensusGroups>()
+            .register_sim_resource::<Census>()
+            .register_sim_resource::<ItemCount>()
+            .add_simulation_systems(
+                TickPhase::Bookkeeping,
+                (
+                    census,
+                    update_item_count,
+                    record_colony_metrics,
+                    apply_colony_rules,
+                )
+                    .chain()
+                    .distributive_run_if(census_is_due)
+                    .before(count_down_to_census),
             )
+            .add_simulation_system(TickPhase::Bookkeeping, count_down_to_census)
             .add_startup_system(spawn_production_statistics_menu)
-            .add_system(update_production_statistics.run_if(in_state(WorldGenState::Complete)));
+            .add_system(update_production_statistics.run_if(in_state(WorldGenState::Complete)))
+            .add_system(toggle_census_groups.before(update_census_rows))
+            .add_system(update_census_rows.run_if(in_state(WorldGenState::Complete)));
     }
 }
 
@@ -38,6 +72,18 @@ impl Plugin for ProductionStatisticsPlugin {
 #[derive(Component)]
 struct ProductionStats;
 
+/// Marker component for the node that holds one row per census group
+#[derive(Component)]
+struct CensusRows;
+
+/// A row of the census that collapses or expands the group with this column name when clicked
+#[derive(Component)]
+struct CensusRowToggle(String);
+
+/// The census groups whose subgroups are hidden, identified by their column name
+#[derive(Resource, Debug, Default)]
+struct CollapsedCensusGroups(HashSet<String>);
+
 /// Initializes the production statistics menu
 fn spawn_production_statistics_menu(
     mut commands: Commands,
@@ -67,10 +113,24 @@ fn spawn_production_statistics_menu(
         .insert(ProductionStats)
         .id();
 
+    let census_rows_entity = commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Column,
+                    ..default()
+                },
+                ..default()
+            },
+            CensusRows,
+        ))
+        .id();
+
     let left_panel_entity = left_panel_query.single();
     commands
         .entity(left_panel_entity)
-        .add_child(production_stats_entity);
+        .add_child(production_stats_entity)
+        .add_child(census_rows_entity);
 }
 
 /// Updates information about the production statistics to be displayed
@@ -78,6 +138,7 @@ fn update_production_statistics(
     mut query: Query<&mut Text, With<ProductionStats>>,
     in_game_time: Res<InGameTime>,
     current_weather: Res<CurrentWeather>,
+    wind: Res<Wind>,
     total_light: Res<TotalLight>,
     water_volume_query: Query<&WaterVolume>,
     census: Res<Census>,
@@ -93,29 +154,430 @@ fn update_production_statistics(
     let average_water_volume = total_water_volume / water_volume_query.iter().len() as f32;
 
     text.sections[0].value = format!("{}\n", *in_game_time);
-    text.sections[1].value = format!("Weather: {}\n", current_weather.get());
+    text.sections[1].value = format!("Weather: {}, wind: {}\n", current_weather.get(), *wind);
     text.sections[2].value = format!("Light: {}\n", *total_light);
     text.sections[3].value = format!("{average_water_volume} average volume of water per tile \n",);
-    text.sections[4].value = format!("{}\n", *census);
-    text.sections[5].value = format!("{}\n", item_count.display(&item_manifest));
+
+    // The census is only retaken every few ticks, so there is usually nothing new to show
+    if census.is_changed() || item_count.is_changed() {
+        text.sections[4].value = format!("{}\n", *census);
+        text.sections[5].value = format!("{}\n", item_count.display(&item_manifest));
+    }
 }
 
-/// Tracks the population of organisms
-#[derive(Debug, Resource, Default)]
+/// Collapses or expands a census group when its row is clicked.
+fn toggle_census_groups(
+    row_query: Query<(&Interaction, &CensusRowToggle), Changed<Interaction>>,
+    mut collapsed: ResMut<CollapsedCensusGroups>,
+) {
+    for (interaction, toggle) in row_query.iter() {
+        if *interaction == Interaction::Clicked && !collapsed.0.remove(&toggle.0) {
+            collapsed.0.insert(toggle.0.clone());
+        }
+    }
+}
+
+/// Redraws the rows of the population census whenever the census is retaken or a group is collapsed.
+fn update_census_rows(
+    mut commands: Commands,
+    rows_query: Query<Entity, With<CensusRows>>,
+    census: Res<Census>,
+    collapsed: Res<CollapsedCensusGroups>,
+    fonts: Res<FiraSansFontFamily>,
+) {
+    if !census.is_changed() && !collapsed.is_changed() {
+        return;
+    }
+
+    let Ok(rows_entity) = rows_query.get_single() else {
+        return;
+    };
+
+    let style = TextStyle {
+        font: fonts.regular.clone_weak(),
+        font_size: 24.,
+        color: Color::WHITE,
+    };
+
+    commands.entity(rows_entity).despawn_descendants();
+    commands.entity(rows_entity).with_children(|parent| {
+        for row in census.population.rows(&collapsed.0) {
+            let text = TextBundle::from_section(row.to_string(), style.clone());
+
+            if row.expandable {
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            background_color: Color::NONE.into(),
+                            ..default()
+                        },
+                        CensusRowToggle(row.column),
+                    ))
+                    .with_children(|button| {
+                        button.spawn(text);
+                    });
+            } else {
+                parent.spawn(text);
+            }
+        }
+    });
+}
+
+/// Tracks the population of organisms, and how they move around the map
+#[derive(Debug, Resource)]
 pub(crate) struct Census {
-    /// The total number of units of any kind
-    total_units: usize,
+    /// The number of living organisms, grouped by kind, variety and current form
+    population: CensusNode,
+    /// The Shannon entropy of unit traffic across tiles, in bits
+    ///
+    /// Lower values mean that units have converged on a few strong trails.
+    trail_entropy: f32,
+    /// The fraction of passable tiles that units have ever visited
+    exploration_coverage: f32,
+    /// The number of food items delivered per unit, per 1000 ticks
+    foraging_efficiency: f32,
+    /// The number of ticks until the next census is taken
+    ticks_until_next: u32,
+}
+
+impl Census {
+    /// The number of ticks between each census.
+    ///
+    /// Counting every organism and every passable tile is too slow to do on every tick.
+    const TICKS_BETWEEN_COUNTS: u32 = 20;
+}
+
+impl Default for Census {
+    fn default() -> Self {
+        Census {
+            population: CensusNode::new("Population"),
+            trail_entropy: 0.,
+            exploration_coverage: 0.,
+            foraging_efficiency: 0.,
+            ticks_until_next: 0,
+        }
+    }
+}
+
+/// The census is taken again from the organisms on the new map.
+impl SimResource for Census {
+    const NAME: &'static str = "census";
+    const PERSIST: bool = false;
+
+    fn reset_to_default(&mut self) {
+        *self = Census::default();
+    }
 }
 
 impl Display for Census {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Population: {}", self.total_units)
+        writeln!(f, "Trail entropy: {:.2} bits", self.trail_entropy)?;
+        writeln!(f, "Explored: {:.1}%", self.exploration_coverage * 100.)?;
+        write!(
+            f,
+            "Foraging: {:.2} food per ant per 1000 ticks",
+            self.foraging_efficiency
+        )
+    }
+}
+
+/// A group of organisms in the [`Census`], and the smaller groups it is divided into.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CensusNode {
+    /// The human-readable name of this group
+    label: String,
+    /// The number of organisms in this group, including all of its children
+    count: usize,
+    /// The subgroups of this group, sorted by label
+    children: Vec<CensusNode>,
+}
+
+impl CensusNode {
+    /// Creates an empty group with the provided `label`.
+    pub(crate) fn new(label: impl Into<String>) -> Self {
+        CensusNode {
+            label: label.into(),
+            count: 0,
+            children: Vec::new(),
+        }
+    }
+
+    /// Recounts this group, with one organism for each of the `paths`,
+    /// where each path lists the labels of its groups from outermost to innermost.
+    ///
+    /// Every existing group is kept, with a count of zero if it is now empty,
+    /// so that groups do not disappear and reappear as populations die out.
+    /// The tree is updated in place: only groups seen for the first time are allocated.
+    pub(crate) fn tally<P, L>(&mut self, paths: impl IntoIterator<Item = P>)
+    where
+        P: IntoIterator<Item = L>,
+        L: AsRef<str>,
+    {
+        self.clear_counts();
+
+        for path in paths {
+            self.add(path.into_iter());
+        }
+    }
+
+    /// Sets the count of this group and all of its subgroups to zero.
+    fn clear_counts(&mut self) {
+        self.count = 0;
+        for child in &mut self.children {
+            child.clear_counts();
+        }
+    }
+
+    /// Counts a single organism at the end of `path`, creating any missing groups.
+    fn add(&mut self, mut path: impl Iterator<Item = impl AsRef<str>>) {
+        self.count += 1;
+
+        let Some(label) = path.next() else {
+            return;
+        };
+        let label = label.as_ref();
+
+        let index = match self
+            .children
+            .binary_search_by(|child| child.label.as_str().cmp(label))
+        {
+            Ok(index) => index,
+            Err(index) => {
+                self.children.insert(index, CensusNode::new(label));
+                index
+            }
+        };
+
+        self.children[index].add(path);
+    }
+
+    /// Flattens the subgroups of this group into `(column name, count)` pairs, in a stable order.
+    ///
+    /// Column names join the labels of each group below this one with dots,
+    /// such as `unit.ant.ant`.
+    /// Labels are lowercased, and spaces are replaced with underscores.
+    pub(crate) fn columns(&self) -> Vec<(String, usize)> {
+        let mut columns = Vec::new();
+        for child in &self.children {
+            child.push_columns("", &mut columns);
+        }
+        columns
+    }
+
+    /// Adds the columns for this group and its subgroups to `columns`, prefixing their names with `prefix`.
+    fn push_columns(&self, prefix: &str, columns: &mut Vec<(String, usize)>) {
+        let name = self.column_name(prefix);
+
+        columns.push((name.clone(), self.count));
+        for child in &self.children {
+            child.push_columns(&name, columns);
+        }
+    }
+
+    /// The column name of this group, when its parent's column name is `prefix`.
+    fn column_name(&self, prefix: &str) -> String {
+        let label = self.label.to_lowercase().replace(' ', "_");
+        if prefix.is_empty() {
+            label
+        } else {
+            format!("{prefix}.{label}")
+        }
+    }
+
+    /// Lists the rows to display for this group and its subgroups, in the same order as [`columns`](Self::columns).
+    ///
+    /// Each row is identified by its column name, or by the label of this group for its own row.
+    /// The subgroups of groups whose identifier is in `collapsed` are left out.
+    fn rows(&self, collapsed: &HashSet<String>) -> Vec<CensusRow> {
+        let mut rows = Vec::new();
+        let root = self.column_name("");
+        let is_collapsed = collapsed.contains(&root);
+        rows.push(self.row(root, 0, is_collapsed));
+
+        if !is_collapsed {
+            for child in &self.children {
+                child.push_rows("", 1, collapsed, &mut rows);
+            }
+        }
+
+        rows
+    }
+
+    /// Adds the rows for this group and its visible subgroups to `rows`.
+    fn push_rows(
+        &self,
+        prefix: &str,
+        depth: usize,
+        collapsed: &HashSet<String>,
+        rows: &mut Vec<CensusRow>,
+    ) {
+        let column = self.column_name(prefix);
+        let is_collapsed = collapsed.contains(&column);
+        rows.push(self.row(column.clone(), depth, is_collapsed));
+
+        if !is_collapsed {
+            for child in &self.children {
+                child.push_rows(&column, depth + 1, collapsed, rows);
+            }
+        }
+    }
+
+    /// The row for this group alone.
+    fn row(&self, column: String, depth: usize, collapsed: bool) -> CensusRow {
+        CensusRow {
+            label: self.label.clone(),
+            count: self.count,
+            depth,
+            expandable: !self.children.is_empty(),
+            collapsed,
+            column,
+        }
+    }
+
+    /// Writes this group and its subgroups to `f`, indented by `depth`.
+    fn write_rows(&self, f: &mut std::fmt::Formatter<'_>, depth: usize) -> std::fmt::Result {
+        let indent = "  ".repeat(depth);
+        writeln!(f, "{indent}{}: {}", self.label, self.count)?;
+
+        for child in &self.children {
+            child.write_rows(f, depth + 1)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for CensusNode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.write_rows(f, 0)
     }
 }
 
-/// Counts the number of organisms
-fn census(mut census: ResMut<Census>, unit_query: Query<(), With<Id<Unit>>>) {
-    census.total_units = unit_query.iter().len();
+/// A single line of the census panel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CensusRow {
+    /// The human-readable name of the group
+    label: String,
+    /// The number of organisms in the group
+    count: usize,
+    /// How many groups this group is nested inside
+    depth: usize,
+    /// Does this group have subgroups that can be shown or hidden?
+    expandable: bool,
+    /// Are the subgroups of this group hidden?
+    collapsed: bool,
+    /// The column name of the group, used to remember whether it is collapsed
+    column: String,
+}
+
+impl Display for CensusRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let marker = match (self.expandable, self.collapsed) {
+            (false, _) => "  ",
+            (true, false) => "- ",
+            (true, true) => "+ ",
+        };
+
+        write!(
+            f,
+            "{}{marker}{}: {}",
+            "  ".repeat(self.depth),
+            self.label,
+            self.count
+        )
+    }
+}
+
+/// Counts the number of organisms, and summarizes where they have walked
+fn census(
+    mut census: ResMut<Census>,
+    organism_query: Query<AnyOf<(&Id<Unit>, &Id<Structure>)>, With<Organism>>,
+    unit_manifest: Res<UnitManifest>,
+    structure_manifest: Res<StructureManifest>,
+    traffic_map: Res<TrafficMap>,
+    food_deliveries: Res<FoodDeliveries>,
+    map_geometry: Res<MapGeometry>,
+) {
+    let name = |organism_id: OrganismId| match organism_id {
+        OrganismId::Unit(unit_id) => unit_manifest.name(unit_id).to_string(),
+        OrganismId::Structure(structure_id) => structure_manifest.name(structure_id).to_string(),
+    };
+
+    let paths = organism_query
+        .iter()
+        .filter_map(|ids| match ids {
+            (Some(&unit_id), _) => {
+                let variety = &unit_manifest.get(unit_id).organism_variety;
+                Some((
+                    "Units",
+                    variety.prototypical_form,
+                    OrganismId::Unit(unit_id),
+                ))
+            }
+            (None, Some(&structure_id)) => {
+                let variety = structure_manifest
+                    .get(structure_id)
+                    .organism_variety
+                    .as_ref()?;
+                Some((
+                    "Structures",
+                    variety.prototypical_form,
+                    OrganismId::Structure(structure_id),
+                ))
+            }
+            (None, None) => None,
+        })
+        .map(|(kind, variety, form)| [kind.to_string(), name(variety), name(form)]);
+
+    census.population.tally(paths);
+    census.trail_entropy = traffic_map.trail_entropy();
+    census.exploration_coverage = traffic_map.exploration_coverage(&map_geometry.walkable_voxels());
+    census.foraging_efficiency = food_deliveries.foraging_efficiency();
+}
+
+/// Is it time to take the next census?
+fn census_is_due(census: Res<Census>) -> bool {
+    census.ticks_until_next == 0
+}
+
+/// Counts down the ticks until the next census, starting again once one has been taken.
+fn count_down_to_census(mut census: ResMut<Census>) {
+    // The countdown is not shown anywhere, so the census only counts as changed when it is retaken
+    let census = census.bypass_change_detection();
+    census.ticks_until_next = match census.ticks_until_next {
+        0 => Census::TICKS_BETWEEN_COUNTS - 1,
+        n => n - 1,
+    };
+}
+
+/// Publishes the census and item counts as [`ColonyMetrics`], for colony rules to respond to.
+///
+/// Population metrics are named after the census columns, such as `units.ant.ant`,
+/// and item metrics are named `items.` followed by the item name.
+fn record_colony_metrics(
+    census: Res<Census>,
+    item_count: Res<ItemCount>,
+    item_manifest: Res<ItemManifest>,
+    mut colony_metrics: ResMut<ColonyMetrics>,
+) {
+    let mut metrics = ColonyMetrics::default();
+
+    metrics.set("population", census.population.count as f32);
+    for (name, count) in census.population.columns() {
+        metrics.set(name, count as f32);
+    }
+    metrics.set("trail_entropy", census.trail_entropy);
+    metrics.set("exploration_coverage", census.exploration_coverage);
+    metrics.set("foraging_efficiency", census.foraging_efficiency);
+
+    for (&item_id, &count) in item_count.map.iter() {
+        metrics.set(
+            format!("items.{}", item_manifest.name(item_id)),
+            count as f32,
+        );
+    }
+
+    *colony_metrics = metrics;
 }
 
 /// Counts the total number of items across all inventories of each type.
@@ -125,6 +587,20 @@ struct ItemCount {
     map: HashMap<Id<Item>, u32>,
 }
 
+/// The items are counted again by the next census.
+impl SimResource for ItemCount {
+    const NAME: &'static str = "item_count";
+    const PERSIST: bool = false;
+
+    fn reset_to_default(&mut self) {
+        self.map.clear();
+    }
+
+    fn measure_memory(&self) -> Option<MemoryUsage> {
+        Some(MemoryUsage::of_hash_map(&self.map))
+    }
+}
+
 impl ItemCount {
     /// Returns a human-readable string representation of the item count
     fn display(&self, item_manifest: &ItemManifest) -> String {
@@ -181,3 +657,183 @@ fn update_item_count(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tallies `paths` into a fresh tree.
+    fn tally(paths: &[&[&str]]) -> CensusNode {
+        let mut tree = CensusNode::new("Population");
+        tree.tally(paths.iter().map(|path| path.iter()));
+        tree
+    }
+
+    #[test]
+    fn children_are_sorted_by_label() {
+        let tree = tally(&[
+            &["Units", "Ant"],
+            &["Structures", "Acacia"],
+            &["Units", "Ant"],
+        ]);
+
+        let labels: Vec<&str> = tree
+            .children
+            .iter()
+            .map(|child| child.label.as_str())
+            .collect();
+        assert_eq!(labels, vec!["Structures", "Units"]);
+    }
+
+    #[test]
+    fn counts_roll_up_at_every_level() {
+        let tree = tally(&[
+            &["Units", "Ant", "Ant"],
+            &["Units", "Ant", "Ant"],
+            &["Units", "Ant", "Queen"],
+            &["Structures", "Acacia", "Acacia Seed"],
+        ]);
+
+        /// Checks that each group's count is the sum of its children's counts.
+        fn assert_rolled_up(node: &CensusNode) {
+            if !node.children.is_empty() {
+                let sum: usize = node.children.iter().map(|child| child.count).sum();
+                assert_eq!(node.count, sum, "{}", node.label);
+            }
+            node.children.iter().for_each(assert_rolled_up);
+        }
+
+        assert_eq!(tree.count, 4);
+        assert_rolled_up(&tree);
+    }
+
+    #[test]
+    fn columns_use_dotted_names() {
+        let tree = tally(&[
+            &["Units", "Ant", "Ant"],
+            &["Structures", "Acacia", "Acacia Seed"],
+        ]);
+
+        assert_eq!(
+            tree.columns(),
+            vec![
+                ("structures".to_string(), 1),
+                ("structures.acacia".to_string(), 1),
+                ("structures.acacia.acacia_seed".to_string(), 1),
+                ("units".to_string(), 1),
+                ("units.ant".to_string(), 1),
+                ("units.ant.ant".to_string(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn columns_are_stable_when_groups_die_out() {
+        let before = tally(&[&["Units", "Ant", "Ant"], &["Units", "Ant", "Queen"]]);
+        let mut after = before.clone();
+        after.tally([["Units", "Ant", "Ant"]]);
+
+        let names = |tree: &CensusNode| -> Vec<String> {
+            tree.columns().into_iter().map(|(name, _)| name).collect()
+        };
+        assert_eq!(names(&before), names(&after));
+        assert!(after
+            .columns()
+            .contains(&("units.ant.queen".to_string(), 0)));
+    }
+
+    #[test]
+    fn collapsed_groups_hide_their_subgroups() {
+        let tree = tally(&[
+            &["Units", "Ant", "Ant"],
+            &["Units", "Ant", "Queen"],
+            &["Structures", "Acacia", "Acacia Seed"],
+        ]);
+
+        let expanded = tree.rows(&HashSet::default());
+        let row_columns = |rows: &[CensusRow]| -> Vec<String> {
+            rows.iter().map(|row| row.column.clone()).collect()
+        };
+        let mut all_columns = vec!["population".to_string()];
+        all_columns.extend(tree.columns().into_iter().map(|(name, _)| name));
+        assert_eq!(row_columns(&expanded), all_columns);
+
+        let collapsed = tree.rows(&HashSet::from_iter(["units".to_string()]));
+        assert_eq!(
+            row_columns(&collapsed),
+            vec![
+                "population",
+                "structures",
+                "structures.acacia",
+                "structures.acacia.acacia_seed",
+                "units",
+            ]
+        );
+
+        let units_row = collapsed.last().unwrap();
+        assert!(units_row.expandable && units_row.collapsed);
+        assert_eq!(units_row.count, 2);
+        assert_eq!(units_row.to_string(), "  + Units: 2");
+    }
+
+    #[test]
+    fn counting_down_does_not_change_the_census() {
+        /// The number of frames on which the census was seen to change.
+        #[derive(Resource, Default)]
+        struct Changes(u32);
+
+        let mut app = App::new();
+        app.init_resource::<Census>()
+            .init_resource::<Changes>()
+            .add_system(count_down_to_census)
+            .add_system(
+                (|census: Res<Census>, mut changes: ResMut<Changes>| {
+                    if census.is_changed() {
+                        changes.0 += 1;
+                    }
+                })
+                .after(count_down_to_census),
+            );
+
+        for _ in 0..Census::TICKS_BETWEEN_COUNTS {
+            app.update();
+        }
+
+        // Only the insertion of the resource counts as a change
+        assert_eq!(app.world.resource::<Changes>().0, 1);
+    }
+
+    #[test]
+    fn census_is_taken_on_a_fixed_cadence() {
+        /// The number of times the census would have been taken.
+        #[derive(Resource, Default)]
+        struct Counts(u32);
+
+        let mut app = App::new();
+        app.init_resource::<Census>()
+            .init_resource::<Counts>()
+            .add_system(
+                (|mut counts: ResMut<Counts>| counts.0 += 1)
+                    .run_if(census_is_due)
+                    .before(count_down_to_census),
+            )
+            .add_system(count_down_to_census);
+
+        // The first census is taken right away
+        app.update();
+        assert_eq!(app.world.resource::<Counts>().0, 1);
+
+        for _ in 0..2 * Census::TICKS_BETWEEN_COUNTS {
+            app.update();
+        }
+        assert_eq!(app.world.resource::<Counts>().0, 3);
+    }
+
+    #[test]
+    fn insertion_order_does_not_matter() {
+        let forwards = tally(&[&["Units", "Ant"], &["Units", "Beetle"]]);
+        let backwards = tally(&[&["Units", "Beetle"], &["Units", "Ant"]]);
+
+        assert_eq!(forwards, backwards);
+    }
+}
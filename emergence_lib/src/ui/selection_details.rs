@@ -7,15 +7,11 @@ use crate::{
     crafting::recipe::RecipeManifest,
     geometry::{MapGeometry, VoxelKind},
     items::item_manifest::ItemManifest,
-    player_interaction::{
-        camera::{CameraMode, CameraSettings},
-        selection::CurrentSelection,
-        InteractionSystem,
-    },
+    player_interaction::{selection::CurrentSelection, InteractionSystem},
     signals::Signals,
     structures::structure_manifest::StructureManifest,
     terrain::terrain_manifest::TerrainManifest,
-    units::unit_manifest::UnitManifest,
+    units::{trace::EntityTraces, unit_manifest::UnitManifest},
     world_gen::WorldGenState,
 };
 
@@ -44,7 +40,6 @@ impl Plugin for SelectionDetailsPlugin {
                     .run_if(in_state(AssetState::FullyLoaded))
                     .run_if(in_state(WorldGenState::Complete)),
             )
-            .add_system(change_camera_mode.after(update_selection_details))
             .add_system(update_selection_details.run_if(in_state(AssetState::FullyLoaded)));
     }
 }
@@ -96,6 +91,8 @@ fn populate_selection_panel(
                 visibility: Visibility::Hidden,
                 ..default()
             },
+            // Block clicks from selecting the tiles behind the panel
+            Interaction::default(),
             SelectionPanel,
         ))
         .id();
@@ -116,21 +113,6 @@ fn populate_selection_panel(
         .add_child(unit_details);
 }
 
-/// Changes the camera mode when the "follow unit" button is pressed.
-fn change_camera_mode(
-    mut camera_query: Query<&mut CameraSettings>,
-    keyboard_input: Res<Input<KeyCode>>,
-) {
-    // FIXME: This should be a button press, not a key press.
-    if keyboard_input.just_pressed(KeyCode::C) {
-        let mut camera_settings = camera_query.single_mut();
-        camera_settings.camera_mode = match camera_settings.camera_mode {
-            CameraMode::FollowUnit => CameraMode::Free,
-            CameraMode::Free => CameraMode::FollowUnit,
-        };
-    }
-}
-
 /// Updates UI elements for selection details panel based on new information.
 fn update_selection_details(
     selection_details: Res<SelectionDetails>,
@@ -282,7 +264,9 @@ pub(crate) enum SelectionDetails {
     /// A ghost of a structure is selected
     GhostStructure(GhostStructureDetails),
     /// A structure is selected
-    Structure(StructureDetails),
+    ///
+    /// Boxed, as structures carry far more details than any other selection.
+    Structure(Box<StructureDetails>),
     /// A tile is selected.
     Terrain(TerrainDetails),
     /// A unit is selected
@@ -305,6 +289,7 @@ fn get_details(
     structure_manifest: Res<StructureManifest>,
     unit_manifest: Res<UnitManifest>,
     signals: Res<Signals>,
+    entity_traces: Res<EntityTraces>,
 ) -> Result<(), QueryEntityError> {
     *selection_details = match &*current_selection {
         CurrentSelection::Voxels(selected_voxels) => {
@@ -357,7 +342,7 @@ fn get_details(
                         oxygen_pool: query_item.oxygen_pool.clone(),
                     });
 
-                        SelectionDetails::Structure(StructureDetails {
+                        SelectionDetails::Structure(Box::new(StructureDetails {
                             entity: structure_query_item.entity,
                             voxel_pos: *structure_query_item.voxel_pos,
                             structure_id: *structure_query_item.structure_id,
@@ -369,11 +354,13 @@ fn get_details(
                             output_inventory: structure_query_item.output_inventory.cloned(),
                             crafting_state: structure_query_item.crafting_state.cloned(),
                             active_recipe: structure_query_item.active_recipe.cloned(),
+                            production_queue: structure_query_item.production_queue.cloned(),
                             workers_present: structure_query_item.workers_present.cloned(),
+                            adjacency_bonus: structure_query_item.adjacency_bonus.copied(),
                             vegetative_reproduction: structure_query_item
                                 .vegetative_reproduction
                                 .cloned(),
-                        })
+                        }))
                     }
                     VoxelKind::GhostStructure => {
                         let ghost_query_item = ghost_structure_query.get(voxel_object.entity)?;
@@ -421,6 +408,9 @@ fn get_details(
                 walkable_neighbors: map_geometry
                     .walkable_neighbors(*unit_query_item.voxel_pos)
                     .collect(),
+                energy_trace: entity_traces
+                    .get(unit_query_item.entity)
+                    .map(|trace| trace.energy_sparkline()),
             })
         }
         CurrentSelection::None => SelectionDetails::None,
@@ -589,6 +579,7 @@ mod structure_details {
         construction::demolition::MarkedForDemolition,
         crafting::{
             inventories::{CraftingState, InputInventory, OutputInventory, StorageInventory},
+            production_queue::ProductionQueue,
             recipe::{ActiveRecipe, RecipeManifest},
             workers::WorkersPresent,
         },
@@ -596,7 +587,10 @@ mod structure_details {
         items::item_manifest::ItemManifest,
         organisms::vegetative_reproduction::VegetativeReproduction,
         signals::Emitter,
-        structures::structure_manifest::{Structure, StructureManifest},
+        structures::{
+            adjacency::AdjacencyBonus,
+            structure_manifest::{Structure, StructureManifest},
+        },
         terrain::terrain_manifest::TerrainManifest,
         units::unit_manifest::UnitManifest,
         water::emitters::WaterEmitter,
@@ -619,10 +613,14 @@ mod structure_details {
         pub(crate) storage_inventory: Option<&'static StorageInventory>,
         /// The recipe used, if any.
         pub(crate) active_recipe: Option<&'static ActiveRecipe>,
+        /// Orders that take priority over the active recipe.
+        pub(crate) production_queue: Option<&'static ProductionQueue>,
         /// The state of the ongoing crafting process.
         pub(crate) crafting_state: Option<&'static CraftingState>,
         /// The workers present at this structure.
         pub(crate) workers_present: Option<&'static WorkersPresent>,
+        /// The production multiplier granted by neighboring tiles.
+        pub(crate) adjacency_bonus: Option<&'static AdjacencyBonus>,
         /// Is this structure marked for removal?
         pub(super) marked_for_removal: Option<&'static MarkedForDemolition>,
         /// What signals is this structure emitting?
@@ -656,10 +654,14 @@ mod structure_details {
         pub(crate) storage_inventory: Option<StorageInventory>,
         /// The recipe used, if any.
         pub(crate) active_recipe: Option<ActiveRecipe>,
+        /// Orders that take priority over the active recipe.
+        pub(crate) production_queue: Option<ProductionQueue>,
         /// The state of the ongoing crafting process.
         pub(crate) crafting_state: Option<CraftingState>,
         /// The number of workers that are presently working on this.
         pub(crate) workers_present: Option<WorkersPresent>,
+        /// The production multiplier granted by neighboring tiles.
+        pub(crate) adjacency_bonus: Option<AdjacencyBonus>,
         /// The vegetative reproduction strategy, if any.
         pub(crate) vegetative_reproduction: Option<VegetativeReproduction>,
     }
@@ -726,10 +728,21 @@ Height: {height}"
                 string += &format!("\nCrafting state: {crafting_state}");
             }
 
+            if let Some(production_queue) = &self.production_queue {
+                string += &format!(
+                    "\nProduction queue: {}",
+                    production_queue.display(recipe_manifest)
+                );
+            }
+
             if let Some(workers_present) = &self.workers_present {
                 string += &format!("\nWorkers present: {workers_present}");
             }
 
+            if let Some(adjacency_bonus) = &self.adjacency_bonus {
+                string += &format!("\nAdjacency bonus: {adjacency_bonus}");
+            }
+
             if let Some(root_zone) = &structure_manifest.get(self.structure_id).root_zone {
                 string += &format!("\n{root_zone}",);
             }
@@ -964,6 +977,8 @@ mod unit_details {
         pub(super) age: Age,
         /// The set of voxels that this unit can walk to
         pub(super) walkable_neighbors: Vec<VoxelPos>,
+        /// A plot of this unit's energy over time, if it is being traced.
+        pub(super) energy_trace: Option<String>,
     }
 
     impl UnitDetails {
@@ -998,6 +1013,10 @@ mod unit_details {
                 .map(|neighbor| format!("{}", neighbor))
                 .collect::<Vec<_>>()
                 .join("\n ");
+            let energy_trace = match &self.energy_trace {
+                Some(sparkline) => format!("\nEnergy trace: {sparkline}"),
+                None => String::new(),
+            };
 
             format!(
                 "Entity: {entity:?}
@@ -1010,7 +1029,7 @@ Goal: {goal}
 Action: {action}
 Impatience: {impatience_pool}
 Age: {age}
-{organism_details}"
+{organism_details}{energy_trace}"
             )
         }
     }
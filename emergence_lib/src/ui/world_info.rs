@@ -0,0 +1,328 @@
+//! Describes the current world while the game is paused, so that it can be shared or reported.
+
+use std::fmt::Display;
+
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+
+use crate::{
+    geometry::MapGeometry,
+    player_interaction::PlayerAction,
+    simulation::{time::InGameTime, PauseState},
+    world_gen::{
+        BugReport, Difficulty, GameVersion, GenerationConfig, GenerationStrategy, MapCode,
+        MapCodeError, WorldGenState,
+    },
+};
+
+use super::{FiraSansFontFamily, RightPanel};
+
+/// Displays the [`WorldInfo`] panel and copies bug reports.
+pub(super) struct WorldInfoPlugin;
+
+impl Plugin for WorldInfoPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SystemClipboard>()
+            .init_resource::<GameVersion>()
+            .add_startup_system(spawn_world_info_panel)
+            .add_systems(
+                (update_world_info_panel, copy_bug_report)
+                    .distributive_run_if(in_state(WorldGenState::Complete)),
+            );
+    }
+}
+
+/// Everything needed to recognize, share or recreate the current world.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct WorldInfo {
+    /// The seed used to generate the world.
+    seed: u64,
+    /// The code that can be used to regenerate this world.
+    map_code: Result<String, MapCodeError>,
+    /// The radius of the map, in tiles.
+    map_radius: u32,
+    /// The difficulty preset that was applied.
+    difficulty: Difficulty,
+    /// The named configuration the world was generated from.
+    strategy: GenerationStrategy,
+    /// How many in-game days have passed.
+    elapsed_days: f32,
+}
+
+impl WorldInfo {
+    /// Collects the information about the current world from its resources.
+    fn new(
+        config: &GenerationConfig,
+        map_geometry: &MapGeometry,
+        in_game_time: &InGameTime,
+    ) -> Self {
+        WorldInfo {
+            seed: config.seed,
+            map_code: MapCode::encode(config),
+            map_radius: map_geometry.radius,
+            difficulty: config.difficulty,
+            strategy: config.strategy,
+            elapsed_days: in_game_time.elapsed_days(),
+        }
+    }
+}
+
+impl Display for WorldInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "About this world")?;
+        writeln!(f, "Seed: {}", self.seed)?;
+        match &self.map_code {
+            Ok(map_code) => writeln!(f, "Map code: {map_code}")?,
+            Err(error) => writeln!(f, "Map code: unavailable ({error})")?,
+        }
+        writeln!(f, "Radius: {}", self.map_radius)?;
+        writeln!(f, "Difficulty: {:?}", self.difficulty)?;
+        writeln!(f, "Generation: {:?}", self.strategy)?;
+        write!(f, "Day: {:.2}", self.elapsed_days)
+    }
+}
+
+/// Somewhere to put text that the player has copied.
+pub(crate) trait ClipboardBackend: Send + Sync + 'static {
+    /// Replaces the contents of the clipboard with `text`.
+    fn set_text(&mut self, text: String);
+}
+
+/// Copies text to the operating system's clipboard.
+struct ArboardClipboard(arboard::Clipboard);
+
+impl ClipboardBackend for ArboardClipboard {
+    fn set_text(&mut self, text: String) {
+        match self.0.set_text(text) {
+            Ok(()) => info!("Copied to clipboard"),
+            Err(error) => warn!("Could not copy to the clipboard: {error}"),
+        }
+    }
+}
+
+/// Writes copied text to the log.
+///
+/// This is used when there is no system clipboard, such as in headless builds.
+#[derive(Debug, Default)]
+struct LogClipboard;
+
+impl ClipboardBackend for LogClipboard {
+    fn set_text(&mut self, text: String) {
+        info!("Copied to clipboard: {text}");
+    }
+}
+
+/// The clipboard used for copying text out of the game.
+///
+/// Not to be confused with the [`Tool`](crate::player_interaction::clipboard::Tool) clipboard,
+/// which holds structures that the player is placing.
+#[derive(Resource)]
+pub(crate) struct SystemClipboard(Box<dyn ClipboardBackend>);
+
+/// Uses the operating system's clipboard if one is available, and the log otherwise.
+impl Default for SystemClipboard {
+    fn default() -> Self {
+        match arboard::Clipboard::new() {
+            Ok(clipboard) => SystemClipboard(Box::new(ArboardClipboard(clipboard))),
+            Err(error) => {
+                warn!("No system clipboard is available, so copied text will be logged: {error}");
+                SystemClipboard(Box::new(LogClipboard))
+            }
+        }
+    }
+}
+
+/// Marker component for the text of the world information panel.
+#[derive(Component)]
+struct WorldInfoText;
+
+/// Creates the hidden world information panel.
+fn spawn_world_info_panel(
+    mut commands: Commands,
+    right_panel_query: Query<Entity, With<RightPanel>>,
+    fonts: Res<FiraSansFontFamily>,
+) {
+    let style = TextStyle {
+        font: fonts.regular.clone_weak(),
+        font_size: 20.,
+        color: Color::WHITE,
+    };
+
+    let world_info_entity = commands
+        .spawn((
+            TextBundle {
+                text: Text::from_section("", style),
+                background_color: Color::rgba(0., 0., 0., 0.9).into(),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            WorldInfoText,
+        ))
+        .id();
+
+    let right_panel_entity = right_panel_query.single();
+    commands
+        .entity(right_panel_entity)
+        .add_child(world_info_entity);
+}
+
+/// Shows the world information while the game is paused.
+///
+/// The text is only rebuilt when the game is paused or the world changes, not every frame.
+fn update_world_info_panel(
+    mut query: Query<(&mut Text, &mut Visibility), With<WorldInfoText>>,
+    pause_state: Res<State<PauseState>>,
+    config: Res<GenerationConfig>,
+    map_geometry: Res<MapGeometry>,
+    in_game_time: Res<InGameTime>,
+    game_version: Res<GameVersion>,
+) {
+    if !pause_state.is_changed() && !config.is_changed() && !in_game_time.is_changed() {
+        return;
+    }
+
+    let (mut text, mut visibility) = query.single_mut();
+
+    if pause_state.0 == PauseState::Paused {
+        let world_info = WorldInfo::new(&config, &map_geometry, &in_game_time);
+        let bug_report = BugReport::new(*game_version, &config, in_game_time.elapsed_days());
+        text.sections[0].value =
+            format!("{world_info}\n\nPress F12 to copy a bug report:\n{bug_report}");
+        *visibility = Visibility::Visible;
+    } else {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+/// Copies a [`BugReport`] for the current world when the player asks for one.
+fn copy_bug_report(
+    actions: Res<ActionState<PlayerAction>>,
+    mut clipboard: ResMut<SystemClipboard>,
+    config: Res<GenerationConfig>,
+    in_game_time: Res<InGameTime>,
+    game_version: Res<GameVersion>,
+) {
+    if actions.just_pressed(PlayerAction::CopyBugReport) {
+        let bug_report = BugReport::new(*game_version, &config, in_game_time.elapsed_days());
+        clipboard.0.set_text(bug_report.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    /// A clipboard that remembers everything copied to it.
+    #[derive(Default, Clone)]
+    struct RecordingClipboard(Arc<Mutex<Vec<String>>>);
+
+    impl ClipboardBackend for RecordingClipboard {
+        fn set_text(&mut self, text: String) {
+            self.0.lock().unwrap().push(text);
+        }
+    }
+
+    /// Information about a freshly generated testing world.
+    fn world_info() -> WorldInfo {
+        let config = GenerationConfig::testing();
+        let map_geometry = MapGeometry::new(&mut World::new(), 3);
+
+        WorldInfo::new(&config, &map_geometry, &InGameTime::default())
+    }
+
+    #[test]
+    fn world_info_is_assembled_from_resources() {
+        let world_info = world_info();
+
+        assert_eq!(world_info.seed, 0);
+        assert_eq!(
+            world_info.map_code,
+            MapCode::encode(&GenerationConfig::testing())
+        );
+        assert_eq!(world_info.map_radius, 3);
+        assert_eq!(world_info.difficulty, Difficulty::Normal);
+        assert_eq!(world_info.strategy, GenerationStrategy::Testing);
+        assert_eq!(world_info.elapsed_days, 0.);
+
+        let text = world_info.to_string();
+        for label in [
+            "Seed",
+            "Map code",
+            "Radius",
+            "Difficulty",
+            "Generation",
+            "Day",
+        ] {
+            assert!(text.contains(label), "{label} is missing from:\n{text}");
+        }
+    }
+
+    #[test]
+    fn bug_reports_are_copied_on_request() {
+        let recording = RecordingClipboard::default();
+        let mut app = App::new();
+        app.insert_resource(SystemClipboard(Box::new(recording.clone())))
+            .insert_resource(GenerationConfig::testing())
+            .insert_resource(InGameTime::default())
+            .insert_resource(GameVersion("1.2.3"))
+            .init_resource::<ActionState<PlayerAction>>()
+            .add_system(copy_bug_report);
+
+        app.update();
+        assert!(recording.0.lock().unwrap().is_empty());
+
+        app.world
+            .resource_mut::<ActionState<PlayerAction>>()
+            .press(PlayerAction::CopyBugReport);
+        app.update();
+
+        let copied = recording.0.lock().unwrap();
+        assert_eq!(copied.len(), 1);
+        assert_eq!(
+            copied[0],
+            BugReport::new(GameVersion("1.2.3"), &GenerationConfig::testing(), 0.).to_string()
+        );
+        assert!(copied[0].contains("version=1.2.3"));
+    }
+
+    #[test]
+    fn panel_is_only_rebuilt_when_the_world_changes() {
+        let mut app = App::new();
+        app.insert_resource(State(PauseState::Paused))
+            .insert_resource(GenerationConfig::testing())
+            .insert_resource(InGameTime::default())
+            .init_resource::<GameVersion>()
+            .add_system(update_world_info_panel);
+        let map_geometry = MapGeometry::new(&mut app.world, 3);
+        app.insert_resource(map_geometry);
+        let panel = app
+            .world
+            .spawn((
+                Text::from_section("", TextStyle::default()),
+                Visibility::Hidden,
+                WorldInfoText,
+            ))
+            .id();
+
+        app.update();
+        assert!(app.world.get::<Text>(panel).unwrap().sections[0]
+            .value
+            .contains("Seed: 0"));
+
+        // Nothing has changed, so the text is left alone
+        app.world.get_mut::<Text>(panel).unwrap().sections[0].value = "stale".to_string();
+        app.update();
+        assert_eq!(
+            app.world.get::<Text>(panel).unwrap().sections[0].value,
+            "stale"
+        );
+
+        app.world.resource_mut::<GenerationConfig>().seed = 7;
+        app.update();
+        assert!(app.world.get::<Text>(panel).unwrap().sections[0]
+            .value
+            .contains("Seed: 7"));
+    }
+}
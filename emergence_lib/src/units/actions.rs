@@ -6,7 +6,7 @@ use bevy::{
     utils::Duration,
 };
 use leafwing_abilities::prelude::Pool;
-use rand::{rngs::ThreadRng, seq::SliceRandom, thread_rng};
+use rand::{rngs::ThreadRng, seq::SliceRandom, thread_rng, Rng};
 
 use crate::{
     asset_management::manifest::Id,
@@ -22,21 +22,24 @@ use crate::{
         item_tags::ItemKind,
         workers::WorkersPresent,
     },
-    geometry::{Facing, Height, MapGeometry, RotationDirection, VoxelPos},
+    geometry::{Facing, MapGeometry, RotationDirection, VoxelPos},
     items::{errors::AddOneItemError, item_manifest::ItemManifest, ItemCount},
     litter::{Litter, LitterCommandsExt},
     organisms::{energy::EnergyPool, lifecycle::Lifecycle},
     signals::{SignalType, Signals},
+    simulation::ChanceAudit,
     structures::{commands::StructureCommandsExt, structure_manifest::Structure},
     terrain::terrain_manifest::{Terrain, TerrainManifest},
     water::WaterDepth,
 };
 
 use super::{
+    deliveries::FoodDeliveries,
     goals::Goal,
     impatience::ImpatiencePool,
     item_interaction::UnitInventory,
     unit_manifest::{Unit, UnitManifest},
+    Nest,
 };
 
 /// Ticks the timer for each [`CurrentAction`].
@@ -53,16 +56,16 @@ pub(super) fn advance_action_timer(
 
 /// Choose the unit's action for this turn
 pub(super) fn choose_actions(
-    mut units_query: Query<
-        (
-            &VoxelPos,
-            &Facing,
-            &Goal,
-            &mut CurrentAction,
-            &UnitInventory,
-        ),
-        With<Id<Unit>>,
-    >,
+    mut units_query: Query<(
+        Entity,
+        &Id<Unit>,
+        &VoxelPos,
+        &Facing,
+        &Goal,
+        &mut CurrentAction,
+        &UnitInventory,
+        &Nest,
+    )>,
     // We shouldn't be dropping off new stuff at structures that are about to be destroyed!
     input_inventory_query: Query<&InputInventory, Without<MarkedForDemolition>>,
     // But we can take their items away
@@ -77,12 +80,25 @@ pub(super) fn choose_actions(
     water_depth_query: Query<&WaterDepth>,
     terrain_manifest: Res<TerrainManifest>,
     item_manifest: Res<ItemManifest>,
+    unit_manifest: Res<UnitManifest>,
+    mut chance_audit: ChanceAudit,
 ) {
     let rng = &mut thread_rng();
 
-    for (&unit_pos, facing, goal, mut current_action, unit_inventory) in units_query.iter_mut() {
+    for (
+        unit_entity,
+        &unit_id,
+        &unit_pos,
+        facing,
+        goal,
+        mut current_action,
+        unit_inventory,
+        nest,
+    ) in units_query.iter_mut()
+    {
         if current_action.finished() {
             let previous_action = current_action.action.clone();
+            let unit_data = unit_manifest.get(unit_id);
 
             *current_action = match goal {
                 // Drop whatever you're holding before wandering further
@@ -96,13 +112,19 @@ pub(super) fn choose_actions(
                         &terrain_query,
                         rng,
                     ),
-                    None => CurrentAction::wander(
+                    None => CurrentAction::wander_idly(
+                        unit_entity,
                         previous_action,
+                        unit_data.idle_behavior_probability,
                         unit_pos,
+                        facing,
+                        nest,
+                        unit_data.nest_radius,
                         &map_geometry,
                         &terrain_query,
                         &terrain_manifest,
                         rng,
+                        &mut chance_audit,
                     ),
                 },
                 Goal::Fetch(item_kind)
@@ -130,6 +152,7 @@ pub(super) fn choose_actions(
                             goal.purpose(),
                             unit_pos,
                             facing,
+                            unit_data.steering_momentum,
                             goal,
                             &input_inventory_query,
                             &output_inventory_query,
@@ -167,6 +190,7 @@ pub(super) fn choose_actions(
                             Purpose::Instrumental,
                             unit_pos,
                             facing,
+                            unit_data.steering_momentum,
                             goal,
                             &input_inventory_query,
                             &output_inventory_query,
@@ -185,6 +209,7 @@ pub(super) fn choose_actions(
                     *structure_id,
                     unit_pos,
                     facing,
+                    unit_data.steering_momentum,
                     &workplace_query,
                     &signals,
                     rng,
@@ -197,6 +222,7 @@ pub(super) fn choose_actions(
                     *structure_id,
                     unit_pos,
                     facing,
+                    unit_data.steering_momentum,
                     &demolition_query,
                     &signals,
                     rng,
@@ -229,6 +255,17 @@ pub(super) fn choose_actions(
     }
 }
 
+/// Cancels idle behaviors like resting and grooming as soon as the unit has something better to do.
+///
+/// This runs after goals are chosen, so the replacement action is picked in the same tick.
+pub(super) fn interrupt_idle_behaviors(mut units_query: Query<(&Goal, &mut CurrentAction)>) {
+    for (goal, mut current_action) in units_query.iter_mut() {
+        if !matches!(goal, Goal::Wander { .. }) && current_action.action.is_idle_behavior() {
+            current_action.interrupt();
+        }
+    }
+}
+
 /// Exhaustively handles the setup for each planned action
 pub(super) fn start_actions(
     mut unit_query: Query<(Entity, &mut CurrentAction)>,
@@ -269,6 +306,7 @@ pub(super) fn finish_actions(
     unit_manifest: Res<UnitManifest>,
     signals: Res<Signals>,
     map_geometry: Res<MapGeometry>,
+    mut food_deliveries: ResMut<FoodDeliveries>,
     mut commands: Commands,
 ) {
     let item_manifest = &*item_manifest;
@@ -288,6 +326,13 @@ pub(super) fn finish_actions(
                 UnitAction::Idle => {
                     unit.impatience.increment();
                 }
+                UnitAction::Rest => {
+                    let rest_energy_recovery =
+                        unit_manifest.get(*unit.unit_id).rest_energy_recovery;
+                    let proposed = unit.energy_pool.current() + rest_energy_recovery;
+                    unit.energy_pool.set_current(proposed);
+                }
+                UnitAction::Groom => (),
                 UnitAction::PickUp {
                     item_kind,
                     output_entity,
@@ -410,6 +455,13 @@ pub(super) fn finish_actions(
                                     match transfer_result {
                                         Ok(()) => {
                                             unit.unit_inventory.held_item = None;
+
+                                            let diet = &unit_manifest.get(*unit.unit_id).diet;
+                                            if diet.item_kind().matches(held_item_id, item_manifest)
+                                            {
+                                                food_deliveries.record_delivery();
+                                            }
+
                                             Goal::default()
                                         }
                                         Err(..) => {
@@ -527,8 +579,8 @@ pub(super) struct ActionDataQuery {
 }
 
 /// An action that a unit can take.
-#[derive(Default, Clone, Debug)]
-pub(super) enum UnitAction {
+#[derive(Default, Clone, Debug, Reflect, FromReflect)]
+pub(crate) enum UnitAction {
     /// Do nothing for now
     #[default]
     Idle,
@@ -567,9 +619,18 @@ pub(super) enum UnitAction {
     Eat,
     /// Abandon whatever you are currently holding, dropping it on the ground
     Abandon,
+    /// Stand still for a while, recovering a little energy.
+    Rest,
+    /// Stand still and clean yourself.
+    Groom,
 }
 
 impl UnitAction {
+    /// Is this an optional idle behavior, which should be dropped as soon as there is real work to do?
+    fn is_idle_behavior(&self) -> bool {
+        matches!(self, UnitAction::Rest | UnitAction::Groom)
+    }
+
     /// Gets the workplace [`Entity`] that this action is targeting, if any.
     fn workplace(&self) -> Option<Entity> {
         match self {
@@ -613,6 +674,8 @@ impl UnitAction {
             UnitAction::MoveForward => "Moving forward".to_string(),
             UnitAction::Eat => "Eating".to_string(),
             UnitAction::Abandon => "Abandoning held object".to_string(),
+            UnitAction::Rest => "Resting".to_string(),
+            UnitAction::Groom => "Grooming".to_string(),
         }
     }
 
@@ -629,13 +692,15 @@ impl UnitAction {
             UnitAction::Idle => 0.1,
             UnitAction::Spin { .. } => 0.1,
             UnitAction::MoveForward => 0.3,
+            UnitAction::Rest => 1.5,
+            UnitAction::Groom => 0.8,
         };
 
         Duration::from_secs_f32(seconds)
     }
 }
 
-#[derive(Component, Clone, Debug)]
+#[derive(Component, Clone, Debug, Reflect, FromReflect)]
 /// The action a unit is undertaking.
 pub(crate) struct CurrentAction {
     /// The type of action being undertaken.
@@ -675,15 +740,32 @@ impl CurrentAction {
     }
 
     /// Get the action that the unit is currently undertaking.
-    pub(super) fn action(&self) -> &UnitAction {
+    pub(crate) fn action(&self) -> &UnitAction {
         &self.action
     }
 
+    /// Sends a unit that was about to pick up items from `old_entity` to `new_entity` instead.
+    ///
+    /// This is used when piles of litter are merged, as the original pile no longer exists.
+    pub(crate) fn retarget_pickup(&mut self, old_entity: Entity, new_entity: Entity) {
+        if let UnitAction::PickUp { output_entity, .. } = &mut self.action {
+            if *output_entity == old_entity {
+                *output_entity = new_entity;
+            }
+        }
+    }
+
     /// Have we waited long enough to perform this action?
     pub(super) fn finished(&self) -> bool {
         self.timer.finished()
     }
 
+    /// Immediately finishes this action, allowing a new one to be chosen.
+    fn interrupt(&mut self) {
+        let remaining = self.timer.remaining();
+        self.timer.tick(remaining);
+    }
+
     /// Atempts to find a place to pick up or drop off an item.
     ///
     /// If the `purpose` is [`Purpose::Intrinsic`], items will not be picked up from or dropped off at a [`StorageInventory`].
@@ -697,6 +779,7 @@ impl CurrentAction {
         purpose: Purpose,
         unit_pos: VoxelPos,
         facing: &Facing,
+        steering_momentum: f32,
         goal: &Goal,
         input_inventory_query: &Query<&InputInventory, Without<MarkedForDemolition>>,
         output_inventory_query: &Query<&OutputInventory>,
@@ -717,6 +800,9 @@ impl CurrentAction {
             return CurrentAction::idle();
         }
 
+        // Only the largest pile of litter is worth picking up from, to keep the number of piles down
+        let mut largest_litter: Option<(u32, Entity, VoxelPos)> = None;
+
         for voxel_pos in unit_pos.reachable_neighbors() {
             if let Some(candidate) = map_geometry.get_candidate(voxel_pos, delivery_mode) {
                 match (delivery_mode, purpose) {
@@ -750,7 +836,14 @@ impl CurrentAction {
 
                         if let Ok(litter) = litter_query.get(candidate) {
                             if litter.contains_kind(item_kind, item_manifest) {
-                                candidates.push((candidate, voxel_pos));
+                                let count = litter
+                                    .matching_item_id(item_kind, item_manifest)
+                                    .map_or(0, |item_id| litter.item_count(item_id));
+                                if largest_litter
+                                    .is_none_or(|(largest_count, ..)| count > largest_count)
+                                {
+                                    largest_litter = Some((count, candidate, voxel_pos));
+                                }
                             }
                         }
                     }
@@ -782,6 +875,10 @@ impl CurrentAction {
             }
         }
 
+        if let Some((_, entity, voxel_pos)) = largest_litter {
+            candidates.push((entity, voxel_pos));
+        }
+
         if let Some((entity, voxel_pos)) = candidates.choose(rng) {
             match delivery_mode {
                 DeliveryMode::PickUp => {
@@ -791,8 +888,14 @@ impl CurrentAction {
                     CurrentAction::dropoff(item_kind, *entity, facing, unit_pos, *voxel_pos)
                 }
             }
-        } else if let Some(upstream) = signals.upstream(unit_pos, goal, item_manifest, map_geometry)
-        {
+        } else if let Some(upstream) = signals.upstream(
+            unit_pos,
+            goal,
+            facing.direction,
+            steering_momentum,
+            item_manifest,
+            map_geometry,
+        ) {
             CurrentAction::move_or_spin(
                 unit_pos,
                 upstream,
@@ -811,6 +914,7 @@ impl CurrentAction {
         workplace_id: WorkplaceId,
         unit_pos: VoxelPos,
         facing: &Facing,
+        steering_momentum: f32,
         workplace_query: &WorkplaceQuery,
         signals: &Signals,
         rng: &mut ThreadRng,
@@ -853,6 +957,8 @@ impl CurrentAction {
             } else if let Some(upstream) = signals.upstream(
                 unit_pos,
                 &Goal::Work(workplace_id),
+                facing.direction,
+                steering_momentum,
                 item_manifest,
                 map_geometry,
             ) {
@@ -875,6 +981,7 @@ impl CurrentAction {
         structure_id: Id<Structure>,
         unit_pos: VoxelPos,
         facing: &Facing,
+        steering_momentum: f32,
         demolition_query: &DemolitionQuery,
         signals: &Signals,
         rng: &mut ThreadRng,
@@ -918,6 +1025,8 @@ impl CurrentAction {
             } else if let Some(upstream) = signals.upstream(
                 unit_pos,
                 &Goal::Demolish(structure_id),
+                facing.direction,
+                steering_momentum,
                 item_manifest,
                 map_geometry,
             ) {
@@ -1047,7 +1156,7 @@ impl CurrentAction {
     }
 
     /// Picks up the `item_id` at the `output_entity`.
-    pub(super) fn pickup(
+    pub(crate) fn pickup(
         item_kind: ItemKind,
         output_entity: Entity,
         facing: &Facing,
@@ -1127,6 +1236,65 @@ impl CurrentAction {
         }
     }
 
+    /// Wander around randomly, stopping to rest or groom with a chance of `idle_behavior_probability`.
+    ///
+    /// Units never chain idle behaviors back to back, so they will keep moving around.
+    /// Units that are more than `nest_radius` tiles away from their [`Nest`] head back towards it instead.
+    pub(super) fn wander_idly(
+        unit_entity: Entity,
+        previous_action: UnitAction,
+        idle_behavior_probability: f64,
+        unit_pos: VoxelPos,
+        facing: &Facing,
+        nest: &Nest,
+        nest_radius: u32,
+        map_geometry: &MapGeometry,
+        terrain_query: &Query<&Id<Terrain>>,
+        terrain_manifest: &TerrainManifest,
+        rng: &mut ThreadRng,
+        chance_audit: &mut ChanceAudit,
+    ) -> Self {
+        let idle = !previous_action.is_idle_behavior() && {
+            let roll = rng.gen::<f64>();
+            let idle = roll < idle_behavior_probability;
+            crate::record_chance!(
+                audit: *chance_audit,
+                system: "wander_idly",
+                probability: idle_behavior_probability as f32,
+                rolled: roll as f32,
+                outcome: idle,
+                subject: Some(unit_entity),
+            );
+            idle
+        };
+
+        if idle {
+            if rng.gen_bool(0.5) {
+                CurrentAction::new(UnitAction::Rest)
+            } else {
+                CurrentAction::new(UnitAction::Groom)
+            }
+        } else if unit_pos.hex.unsigned_distance_to(nest.0.hex) > nest_radius {
+            CurrentAction::move_or_spin(
+                unit_pos,
+                nest.0,
+                facing,
+                terrain_query,
+                terrain_manifest,
+                map_geometry,
+            )
+        } else {
+            CurrentAction::wander(
+                previous_action,
+                unit_pos,
+                map_geometry,
+                terrain_query,
+                terrain_manifest,
+                rng,
+            )
+        }
+    }
+
     /// Wander around randomly.
     ///
     /// This will alternate between moving forward and spinning.
@@ -1261,7 +1429,7 @@ impl<'w, 's> WorkplaceQuery<'w, 's> {
         map_geometry: &MapGeometry,
     ) -> Option<Entity> {
         // This is only a viable target if the unit can reach it!
-        if current.abs_height_diff(target) > Height::MAX_STEP {
+        if current.abs_height_diff(target) > map_geometry.traversal().max_climb {
             return None;
         }
 
@@ -1306,3 +1474,266 @@ pub(crate) enum Purpose {
     /// This will take / place items from storage.
     Instrumental,
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::items::item_manifest::{Item, ItemData};
+    use crate::organisms::energy::Energy;
+    use crate::terrain::terrain_manifest::TerrainData;
+    use crate::units::{basic_needs::Diet, unit_manifest::UnitData};
+
+    use super::*;
+
+    #[test]
+    fn real_goals_interrupt_resting() {
+        let mut app = App::new();
+        app.add_system(interrupt_idle_behaviors);
+
+        let wanderer = app
+            .world
+            .spawn((
+                Goal::Wander {
+                    remaining_actions: None,
+                },
+                CurrentAction::new(UnitAction::Rest),
+            ))
+            .id();
+        let breather = app
+            .world
+            .spawn((Goal::Breathe, CurrentAction::new(UnitAction::Rest)))
+            .id();
+
+        app.update();
+
+        assert!(!app.world.get::<CurrentAction>(wanderer).unwrap().finished());
+        assert!(app.world.get::<CurrentAction>(breather).unwrap().finished());
+    }
+
+    #[test]
+    fn only_idle_behaviors_are_interrupted() {
+        let mut app = App::new();
+        app.add_system(interrupt_idle_behaviors);
+
+        let mover = app
+            .world
+            .spawn((Goal::Breathe, CurrentAction::new(UnitAction::MoveForward)))
+            .id();
+
+        app.update();
+
+        assert!(!app.world.get::<CurrentAction>(mover).unwrap().finished());
+    }
+
+    #[test]
+    fn resting_recovers_energy() {
+        let mut app = App::new();
+        let map_geometry = MapGeometry::new(&mut app.world, 1);
+        let mut unit_manifest = UnitManifest::new();
+        unit_manifest.insert(
+            "ant".to_string(),
+            UnitData {
+                rest_energy_recovery: Energy(2.),
+                ..UnitData::simple(
+                    "ant",
+                    Diet::new(Id::from_name("leaf".to_string()), Energy(1.)),
+                )
+            },
+        );
+        app.insert_resource(map_geometry)
+            .insert_resource(unit_manifest)
+            .insert_resource(ItemManifest::new())
+            .insert_resource(Signals::default())
+            .init_resource::<FoodDeliveries>()
+            .add_system(finish_actions);
+
+        let mut energy_pool = EnergyPool::new_full(Energy(10.), Energy(0.));
+        energy_pool.set_current(Energy(5.));
+        let mut rest = CurrentAction::new(UnitAction::Rest);
+        rest.interrupt();
+
+        let resting_unit = app
+            .world
+            .spawn((
+                Id::<Unit>::from_name("ant".to_string()),
+                Goal::Wander {
+                    remaining_actions: None,
+                },
+                rest,
+                Lifecycle::default(),
+                UnitInventory::default(),
+                Transform::default(),
+                VoxelPos::ZERO,
+                energy_pool,
+                ImpatiencePool::new(10),
+                Facing::default(),
+            ))
+            .id();
+
+        app.update();
+
+        let energy_pool = app.world.get::<EnergyPool>(resting_unit).unwrap();
+        assert_eq!(energy_pool.current(), Energy(7.));
+    }
+
+    /// Repeatedly chooses and immediately carries out a [`CurrentAction::wander_idly`] for each unit.
+    fn wander_idly_and_move(
+        mut unit_query: Query<(
+            Entity,
+            &mut VoxelPos,
+            &mut Facing,
+            &mut CurrentAction,
+            &Nest,
+        )>,
+        map_geometry: Res<MapGeometry>,
+        terrain_query: Query<&Id<Terrain>>,
+        terrain_manifest: Res<TerrainManifest>,
+        mut chance_audit: ChanceAudit,
+    ) {
+        let rng = &mut thread_rng();
+
+        for (entity, mut voxel_pos, mut facing, mut current_action, nest) in unit_query.iter_mut() {
+            *current_action = CurrentAction::wander_idly(
+                entity,
+                current_action.action.clone(),
+                0.,
+                *voxel_pos,
+                &facing,
+                nest,
+                2,
+                &map_geometry,
+                &terrain_query,
+                &terrain_manifest,
+                rng,
+                &mut chance_audit,
+            );
+
+            match current_action.action {
+                UnitAction::Spin {
+                    rotation_direction: RotationDirection::Left,
+                } => facing.rotate_counterclockwise(),
+                UnitAction::Spin {
+                    rotation_direction: RotationDirection::Right,
+                } => facing.rotate_clockwise(),
+                UnitAction::MoveForward => {
+                    let target = voxel_pos.neighbor(facing.direction);
+                    if map_geometry.is_valid(target.hex) {
+                        *voxel_pos = target;
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    #[test]
+    fn idle_wandering_stays_near_the_nest() {
+        let mut app = App::new();
+        let mut map_geometry = MapGeometry::new(&mut app.world, 10);
+        let grass = Id::<Terrain>::from_name("grass".to_string());
+        let terrain_entities: Vec<Entity> = app
+            .world
+            .query_filtered::<Entity, With<VoxelPos>>()
+            .iter(&app.world)
+            .collect();
+        for entity in terrain_entities {
+            let hex = app.world.get::<VoxelPos>(entity).unwrap().hex;
+            app.world.entity_mut(entity).insert(grass);
+            map_geometry.update_terrain_id(hex, grass);
+        }
+        let mut terrain_manifest = TerrainManifest::new();
+        terrain_manifest.insert("grass".to_string(), TerrainData::default());
+
+        app.insert_resource(map_geometry)
+            .insert_resource(terrain_manifest)
+            .add_system(wander_idly_and_move);
+
+        let nest = Nest(VoxelPos::ZERO);
+        let unit = app
+            .world
+            .spawn((
+                VoxelPos::ZERO,
+                Facing::default(),
+                CurrentAction::default(),
+                nest,
+            ))
+            .id();
+
+        // Without the pull of the nest, a random walk of this length would usually leave the radius
+        let mut furthest = 0;
+        for _ in 0..2000 {
+            app.update();
+            let voxel_pos = *app.world.get::<VoxelPos>(unit).unwrap();
+            furthest = furthest.max(voxel_pos.hex.unsigned_distance_to(nest.0.hex));
+        }
+
+        // Units only notice that they have strayed once they have stepped outside the radius
+        assert!(furthest <= 3, "Strayed {furthest} tiles from the nest");
+    }
+
+    #[test]
+    fn only_food_deliveries_are_counted() {
+        let leaf = Id::<Item>::from_name("leaf".to_string());
+        let stick = Id::<Item>::from_name("stick".to_string());
+
+        let mut app = App::new();
+        let map_geometry = MapGeometry::new(&mut app.world, 1);
+        let mut unit_manifest = UnitManifest::new();
+        unit_manifest.insert(
+            "ant".to_string(),
+            UnitData::simple("ant", Diet::new(leaf, Energy(1.))),
+        );
+        let mut item_manifest = ItemManifest::new();
+        for name in ["leaf", "stick"] {
+            item_manifest.insert(
+                name.to_string(),
+                ItemData {
+                    stack_size: 10,
+                    compostable: false,
+                    fluid: false,
+                    buoyant: false,
+                    seed: None,
+                },
+            );
+        }
+        app.insert_resource(map_geometry)
+            .insert_resource(unit_manifest)
+            .insert_resource(item_manifest)
+            .insert_resource(Signals::default())
+            .init_resource::<FoodDeliveries>()
+            .add_system(finish_actions);
+
+        let storage = app.world.spawn(StorageInventory::new(2, None)).id();
+        for item_id in [leaf, stick] {
+            let mut drop_off = CurrentAction::new(UnitAction::DropOff {
+                item_kind: ItemKind::Single(item_id),
+                input_entity: storage,
+            });
+            drop_off.interrupt();
+
+            app.world.spawn((
+                Id::<Unit>::from_name("ant".to_string()),
+                Goal::Deliver(ItemKind::Single(item_id)),
+                drop_off,
+                Lifecycle::default(),
+                UnitInventory {
+                    held_item: Some(item_id),
+                },
+                Transform::default(),
+                VoxelPos::ZERO,
+                EnergyPool::new_full(Energy(10.), Energy(0.)),
+                ImpatiencePool::new(10),
+                Facing::default(),
+            ));
+        }
+
+        app.update();
+
+        let storage_inventory = app.world.get::<StorageInventory>(storage).unwrap();
+        assert_eq!(storage_inventory.item_count(leaf), 1);
+        assert_eq!(storage_inventory.item_count(stick), 1);
+
+        let mut expected = FoodDeliveries::default();
+        expected.record_delivery();
+        assert_eq!(*app.world.resource::<FoodDeliveries>(), expected);
+    }
+}
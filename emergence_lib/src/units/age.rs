@@ -12,7 +12,10 @@ use serde::{Deserialize, Serialize};
 use crate::simulation::time::{Days, InGameTime};
 
 /// The age of a unit, in in-game days.
-#[derive(Component, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(
+    Component, Clone, Debug, Default, PartialEq, Serialize, Deserialize, Reflect, FromReflect,
+)]
+#[reflect(Component)]
 pub struct Age {
     /// The current age of the unit.
     current: Days,
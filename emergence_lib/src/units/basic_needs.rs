@@ -77,7 +77,7 @@ impl Diet {
     }
 
     /// The kind of item that this unit must consume.
-    pub(super) fn item_kind(&self) -> ItemKind {
+    pub(crate) fn item_kind(&self) -> ItemKind {
         self.item_kind
     }
 
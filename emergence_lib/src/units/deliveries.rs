@@ -0,0 +1,118 @@
+//! Counts the food that units deliver, so we can measure how efficiently the colony forages.
+
+use bevy::prelude::*;
+
+use crate::{asset_management::manifest::Id, simulation::sim_resources::SimResource};
+
+use super::unit_manifest::Unit;
+
+/// The food that units have delivered, and how much unit time it took.
+#[derive(Resource, Debug, Default, Clone, PartialEq, Eq, Reflect)]
+#[reflect(Resource)]
+pub(crate) struct FoodDeliveries {
+    /// The number of food items that units have dropped off at structures.
+    food_delivered: u64,
+    /// The number of units alive on each tick, summed over every tick.
+    unit_ticks: u64,
+}
+
+impl FoodDeliveries {
+    /// Records that a unit dropped off a single food item.
+    pub(crate) fn record_delivery(&mut self) {
+        self.food_delivered += 1;
+    }
+
+    /// Records that a tick passed with `n_units` units alive.
+    pub(crate) fn record_tick(&mut self, n_units: usize) {
+        self.unit_ticks += n_units as u64;
+    }
+
+    /// The number of food items delivered per unit, per 1000 ticks.
+    ///
+    /// Returns 0 if no units have been alive yet.
+    pub(crate) fn foraging_efficiency(&self) -> f32 {
+        if self.unit_ticks == 0 {
+            return 0.;
+        }
+
+        (self.food_delivered as f64 * 1000. / self.unit_ticks as f64) as f32
+    }
+}
+
+impl SimResource for FoodDeliveries {
+    const NAME: &'static str = "food_deliveries";
+    const PERSIST: bool = true;
+
+    fn reset_to_default(&mut self) {
+        *self = FoodDeliveries::default();
+    }
+}
+
+/// Adds the number of living units to the [`FoodDeliveries`], once per tick.
+pub(super) fn count_unit_ticks(
+    mut food_deliveries: ResMut<FoodDeliveries>,
+    unit_query: Query<(), With<Id<Unit>>>,
+) {
+    food_deliveries.record_tick(unit_query.iter().len());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_units_means_no_efficiency() {
+        let mut food_deliveries = FoodDeliveries::default();
+        food_deliveries.record_delivery();
+
+        assert_eq!(food_deliveries.foraging_efficiency(), 0.);
+    }
+
+    #[test]
+    fn efficiency_is_food_per_unit_per_thousand_ticks() {
+        let mut food_deliveries = FoodDeliveries::default();
+
+        // Four units forage for 500 ticks, delivering a total of 6 items
+        for _ in 0..500 {
+            food_deliveries.record_tick(4);
+        }
+        for _ in 0..6 {
+            food_deliveries.record_delivery();
+        }
+
+        // 6 items / (4 units * 500 ticks) * 1000 ticks
+        assert_eq!(food_deliveries.foraging_efficiency(), 3.);
+    }
+
+    #[test]
+    fn unit_ticks_follow_the_population() {
+        let mut food_deliveries = FoodDeliveries::default();
+
+        // Two units for 100 ticks, then one unit for 200 ticks, is as much work as one unit for 400 ticks
+        for _ in 0..100 {
+            food_deliveries.record_tick(2);
+        }
+        for _ in 0..200 {
+            food_deliveries.record_tick(1);
+        }
+        for _ in 0..2 {
+            food_deliveries.record_delivery();
+        }
+
+        assert_eq!(food_deliveries.foraging_efficiency(), 5.);
+    }
+
+    #[test]
+    fn living_units_are_counted_each_tick() {
+        let mut app = App::new();
+        app.init_resource::<FoodDeliveries>()
+            .add_system(count_unit_ticks);
+
+        app.world.spawn(Id::<Unit>::from_name("ant".to_string()));
+        app.world.spawn(Id::<Unit>::from_name("ant".to_string()));
+        app.update();
+        app.update();
+
+        assert_eq!(app.world.resource::<FoodDeliveries>().unit_ticks, 4);
+    }
+}
@@ -27,7 +27,7 @@ use super::WanderingBehavior;
 /// Once a goal is complete, they will typically transition back into [`Goal::Wander`] and attempt to find something new to do.
 ///
 /// This component serves as a state machine.
-#[derive(Component, PartialEq, Clone, Debug)]
+#[derive(Component, PartialEq, Clone, Debug, Reflect, FromReflect)]
 pub(crate) enum Goal {
     /// Attempting to find something useful to do
     ///
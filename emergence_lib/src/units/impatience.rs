@@ -7,7 +7,8 @@ use core::fmt::Display;
 /// The patience of a unit.
 ///
 /// If current >= max, they will abandon their current goal.
-#[derive(Debug, Clone, PartialEq, Component, Resource)]
+#[derive(Debug, Clone, Default, PartialEq, Component, Resource, Reflect, FromReflect)]
+#[reflect(Component)]
 pub(crate) struct ImpatiencePool {
     /// The current impatience of this unit.
     current: u8,
@@ -17,7 +18,7 @@ pub(crate) struct ImpatiencePool {
 
 impl ImpatiencePool {
     /// Creates a new impatience pool with the provided `max` value.
-    pub(super) fn new(max: u8) -> Self {
+    pub(crate) fn new(max: u8) -> Self {
         ImpatiencePool { current: 0, max }
     }
 
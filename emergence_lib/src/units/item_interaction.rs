@@ -8,7 +8,8 @@ use crate::{
 };
 
 /// The item(s) that a unit is carrying.
-#[derive(Component, Default, Clone, Debug, Deref, DerefMut)]
+#[derive(Component, Default, Clone, Debug, PartialEq, Deref, DerefMut, Reflect, FromReflect)]
+#[reflect(Component)]
 pub(crate) struct UnitInventory {
     /// The single item the unit is currently holding
     pub(crate) held_item: Option<Id<Item>>,
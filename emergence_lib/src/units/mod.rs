@@ -8,7 +8,10 @@ use crate::{
     geometry::{Facing, VoxelPos},
     player_interaction::InteractionSystem,
     signals::{Emitter, SignalStrength, SignalType},
-    simulation::SimulationSet,
+    simulation::{
+        phases::{SimulationAppExt, TickPhase},
+        sim_resources::SimResourceAppExt,
+    },
 };
 use bevy::prelude::*;
 use bevy_mod_raycast::RaycastMesh;
@@ -30,9 +33,12 @@ use crate::organisms::OrganismBundle;
 pub(crate) mod actions;
 pub mod age;
 pub mod basic_needs;
+pub(crate) mod deliveries;
 pub(crate) mod goals;
 pub(crate) mod impatience;
 pub(crate) mod item_interaction;
+pub mod trace;
+pub(crate) mod traffic;
 pub(crate) mod unit_assets;
 pub mod unit_manifest;
 
@@ -71,6 +77,13 @@ impl FromIterator<(u16, f32)> for WanderingBehavior {
     }
 }
 
+/// The tile that this unit calls home.
+///
+/// Idle units that stray further than their [`UnitData::nest_radius`] from it will head back,
+/// so the colony clusters around its nests rather than spreading across the whole map.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Reflect, FromReflect)]
+pub(crate) struct Nest(pub(crate) VoxelPos);
+
 /// An organism that can move around freely.
 #[derive(Bundle)]
 pub(crate) struct UnitBundle {
@@ -80,6 +93,8 @@ pub(crate) struct UnitBundle {
     voxel_pos: VoxelPos,
     /// The direction that the unit is facing.
     facing: Facing,
+    /// The tile that this unit returns to when idle.
+    nest: Nest,
     /// What is the unit working towards.
     current_goal: Goal,
     /// How frustrated this unit is.
@@ -126,6 +141,7 @@ impl UnitBundle {
             unit_id,
             voxel_pos,
             facing: Facing::default(),
+            nest: Nest(voxel_pos),
             current_goal: Goal::default(),
             impatience: ImpatiencePool::new(unit_data.max_impatience),
             current_action: CurrentAction::default(),
@@ -170,6 +186,7 @@ impl UnitBundle {
             unit_id,
             voxel_pos,
             facing: Facing::default(),
+            nest: Nest(voxel_pos),
             current_goal: Goal::default(),
             impatience: ImpatiencePool::new(unit_data.max_impatience),
             current_action: CurrentAction::default(),
@@ -208,6 +225,7 @@ impl UnitBundle {
             unit_id,
             voxel_pos,
             facing: Facing::default(),
+            nest: Nest(voxel_pos),
             current_goal: Goal::default(),
             impatience: ImpatiencePool::new(unit_data.max_impatience),
             current_action: CurrentAction::default(),
@@ -250,22 +268,18 @@ impl Plugin for UnitsPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugin(ManifestPlugin::<RawUnitManifest>::new())
             .add_asset_collection::<UnitHandles>()
-            .add_systems(
+            .init_resource::<traffic::TrafficMap>()
+            .init_resource::<deliveries::FoodDeliveries>()
+            .init_resource::<trace::EntityTraces>()
+            .register_sim_resource::<traffic::TrafficMap>()
+            .register_sim_resource::<deliveries::FoodDeliveries>()
+            .register_sim_resource::<trace::EntityTraces>()
+            .add_simulation_systems(
+                TickPhase::Decision,
                 (
-                    actions::advance_action_timer.in_set(UnitSystem::AdvanceTimers),
-                    actions::start_actions
-                        .in_set(UnitSystem::Act)
-                        .before(actions::finish_actions),
-                    actions::finish_actions
-                        .in_set(UnitSystem::Act)
-                        .after(UnitSystem::AdvanceTimers)
-                        // This must occur after MarkedForDemolition is added,
-                        // or we'll get a panic due to inserting a component on a despawned entity
-                        .after(InteractionSystem::ManagePreviews),
                     goals::choose_goal.in_set(UnitSystem::ChooseGoal),
                     actions::choose_actions
                         .in_set(UnitSystem::ChooseNewAction)
-                        .after(UnitSystem::Act)
                         .after(UnitSystem::ChooseGoal),
                     basic_needs::check_for_hunger
                         // Avoid a delay
@@ -274,10 +288,36 @@ impl Plugin for UnitsPlugin {
                         .after(UnitSystem::ChooseGoal),
                     // Oxygen is more important than hunger, so it should overwrite
                     basic_needs::check_for_oxygen.after(basic_needs::check_for_hunger),
+                    actions::interrupt_idle_behaviors
+                        .in_set(UnitSystem::ChooseNewAction)
+                        .after(basic_needs::check_for_oxygen)
+                        .before(actions::choose_actions),
+                ),
+            )
+            .add_simulation_systems(
+                TickPhase::Action,
+                (
+                    actions::advance_action_timer.in_set(UnitSystem::AdvanceTimers),
+                    actions::start_actions
+                        .in_set(UnitSystem::Act)
+                        .before(actions::finish_actions),
+                    actions::finish_actions
+                        .in_set(UnitSystem::Act)
+                        .after(UnitSystem::AdvanceTimers)
+                        // This must occur after MarkedForDemolition is added,
+                        // or we'll get a panic due to inserting a component on a despawned entity
+                        .after(InteractionSystem::ManagePreviews),
+                ),
+            )
+            .add_simulation_systems(
+                TickPhase::Bookkeeping,
+                (
                     age::aging,
-                )
-                    .in_set(SimulationSet)
-                    .in_schedule(CoreSchedule::FixedUpdate),
+                    traffic::record_traffic,
+                    traffic::decay_traffic.after(traffic::record_traffic),
+                    trace::record_traces,
+                    deliveries::count_unit_ticks,
+                ),
             );
     }
 }
@@ -0,0 +1,664 @@
+//! Records the history of individual units, to answer questions like "why did this ant starve?".
+//!
+//! Tracing is opt-in: call [`start_trace`] for the unit of interest,
+//! or follow a unit with the camera to trace it automatically.
+
+use std::{collections::VecDeque, fmt::Display, path::Path};
+
+use bevy::{prelude::*, utils::HashMap};
+use leafwing_abilities::prelude::Pool;
+
+use crate::{
+    asset_management::manifest::Id,
+    geometry::VoxelPos,
+    organisms::energy::EnergyPool,
+    player_interaction::{
+        camera::{CameraMode, CameraSettings},
+        selection::CurrentSelection,
+    },
+    simulation::sim_resources::SimResource,
+    utils::memory::{MemoryFootprint, MemoryUsage},
+};
+
+use super::{
+    actions::CurrentAction, goals::Goal, item_interaction::UnitInventory, unit_manifest::Unit,
+};
+
+/// The state of a traced unit on a single tick.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceSample {
+    /// The tick on which this sample was taken, as counted by [`EntityTraces`].
+    pub tick: u64,
+    /// Where the unit was.
+    pub voxel_pos: VoxelPos,
+    /// How much energy the unit had left.
+    pub energy: f32,
+    /// What the unit was trying to achieve.
+    pub goal: String,
+    /// How many items the unit was carrying.
+    pub items_held: u32,
+    /// What the unit was doing.
+    pub action: String,
+}
+
+impl TraceSample {
+    /// The header row of the CSV produced by [`EntityTrace::to_csv`].
+    pub const CSV_HEADER: &'static str = "tick,x,y,height,energy,goal,items_held,action";
+
+    /// Formats this sample as a row of CSV.
+    fn to_csv_row(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{},{}",
+            self.tick,
+            self.voxel_pos.hex.x,
+            self.voxel_pos.hex.y,
+            self.voxel_pos.height.0,
+            self.energy,
+            csv_field(&self.goal),
+            self.items_held,
+            csv_field(&self.action),
+        )
+    }
+}
+
+/// Quotes `value` so that any commas or quotes it contains survive in a CSV file.
+fn csv_field(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "\"\""))
+}
+
+/// The recorded history of a single unit.
+///
+/// Only the most recent samples are kept.
+#[derive(Debug, Clone, Default)]
+pub struct EntityTrace {
+    /// The order in which this trace was started, relative to the others in [`EntityTraces`].
+    started: u64,
+    /// The samples, from oldest to newest.
+    samples: VecDeque<TraceSample>,
+    /// Has the unit died (or otherwise vanished)?
+    ///
+    /// Once this is set, no more samples are taken, but the existing ones are kept.
+    ended: bool,
+}
+
+impl MemoryFootprint for EntityTrace {
+    fn memory_usage(&self) -> MemoryUsage {
+        self.samples
+            .iter()
+            .fold(MemoryUsage::of_vec_deque(&self.samples), |total, sample| {
+                total
+                    + MemoryUsage::of_string(&sample.goal)
+                    + MemoryUsage::of_string(&sample.action)
+            })
+    }
+}
+
+impl EntityTrace {
+    /// The samples recorded so far, from oldest to newest.
+    pub fn samples(&self) -> impl ExactSizeIterator<Item = &TraceSample> {
+        self.samples.iter()
+    }
+
+    /// Has sampling stopped because the unit no longer exists?
+    pub fn ended(&self) -> bool {
+        self.ended
+    }
+
+    /// Formats the samples as CSV, with a header row.
+    pub fn to_csv(&self) -> String {
+        let mut csv = TraceSample::CSV_HEADER.to_string();
+        for sample in &self.samples {
+            csv.push('\n');
+            csv.push_str(&sample.to_csv_row());
+        }
+        csv
+    }
+
+    /// A one-line plot of the unit's energy over time.
+    ///
+    /// Each character is a sample, scaled between the lowest and highest energy recorded.
+    pub fn energy_sparkline(&self) -> String {
+        /// The characters used for each level, from lowest to highest.
+        const LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+        let (min, max) = self
+            .samples
+            .iter()
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), sample| {
+                (min.min(sample.energy), max.max(sample.energy))
+            });
+        let range = max - min;
+
+        self.samples
+            .iter()
+            .map(|sample| {
+                let fraction = if range > 0. {
+                    (sample.energy - min) / range
+                } else {
+                    1.
+                };
+                LEVELS[(fraction * (LEVELS.len() - 1) as f32).round() as usize]
+            })
+            .collect()
+    }
+
+    /// Records a new sample, discarding the oldest one if there are already `max_samples`.
+    fn push(&mut self, sample: TraceSample, max_samples: usize) {
+        while self.samples.len() >= max_samples {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+}
+
+/// The ways that working with traces can fail.
+#[derive(Debug)]
+pub enum TraceError {
+    /// The entity does not exist, or is not a unit.
+    NotAUnit(Entity),
+    /// No more units can be traced until an existing trace is cleared.
+    TooManyTraces {
+        /// The maximum number of units that can be traced at once.
+        max_traced: usize,
+    },
+    /// The entity is not being traced.
+    NotTraced(Entity),
+    /// The trace could not be written to disk.
+    Io(std::io::Error),
+}
+
+impl Display for TraceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TraceError::NotAUnit(entity) => write!(f, "{entity:?} is not a living unit"),
+            TraceError::TooManyTraces { max_traced } => write!(
+                f,
+                "at most {max_traced} units can be traced at once: clear an existing trace first"
+            ),
+            TraceError::NotTraced(entity) => write!(f, "{entity:?} is not being traced"),
+            TraceError::Io(error) => write!(f, "could not write the trace: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for TraceError {}
+
+impl From<std::io::Error> for TraceError {
+    fn from(error: std::io::Error) -> Self {
+        TraceError::Io(error)
+    }
+}
+
+/// The histories of every traced unit.
+///
+/// Memory use is capped at [`max_traced`](Self::max_traced) times [`max_samples`](Self::max_samples) samples.
+#[derive(Resource, Debug, Reflect)]
+pub struct EntityTraces {
+    /// The maximum number of units that can be traced at once, including those that have died.
+    max_traced: usize,
+    /// The maximum number of samples kept for each unit.
+    max_samples: usize,
+    /// How many ticks pass between samples.
+    sample_interval: u64,
+    /// The number of ticks that have passed.
+    tick: u64,
+    /// The number of traces that have been started, used to find the oldest.
+    n_started: u64,
+    /// The trace of each unit.
+    #[reflect(ignore)]
+    traces: HashMap<Entity, EntityTrace>,
+}
+
+impl Default for EntityTraces {
+    fn default() -> Self {
+        EntityTraces::new(8, 1024, 1)
+    }
+}
+
+impl MemoryFootprint for EntityTraces {
+    fn memory_usage(&self) -> MemoryUsage {
+        self.traces
+            .values()
+            .map(EntityTrace::memory_usage)
+            .fold(MemoryUsage::of_hash_map(&self.traces), |total, usage| {
+                total + usage
+            })
+    }
+}
+
+/// Traces refer to units on the current map, so they are discarded when a new map is generated.
+impl SimResource for EntityTraces {
+    const NAME: &'static str = "entity_traces";
+    const PERSIST: bool = false;
+
+    fn reset_to_default(&mut self) {
+        self.traces.clear();
+        self.tick = 0;
+    }
+
+    fn measure_memory(&self) -> Option<MemoryUsage> {
+        Some(self.memory_usage())
+    }
+}
+
+impl EntityTraces {
+    /// Creates an empty set of traces with the provided limits.
+    pub fn new(max_traced: usize, max_samples: usize, sample_interval: u64) -> Self {
+        EntityTraces {
+            max_traced: max_traced.max(1),
+            max_samples: max_samples.max(1),
+            sample_interval: sample_interval.max(1),
+            tick: 0,
+            n_started: 0,
+            traces: HashMap::default(),
+        }
+    }
+
+    /// The maximum number of units that can be traced at once.
+    pub fn max_traced(&self) -> usize {
+        self.max_traced
+    }
+
+    /// The maximum number of samples kept for each unit.
+    pub fn max_samples(&self) -> usize {
+        self.max_samples
+    }
+
+    /// The trace of `entity`, if it is being traced.
+    pub fn get(&self, entity: Entity) -> Option<&EntityTrace> {
+        self.traces.get(&entity)
+    }
+
+    /// Stops tracing `entity` and discards its history.
+    pub fn clear(&mut self, entity: Entity) -> Option<EntityTrace> {
+        self.traces.remove(&entity)
+    }
+
+    /// Writes the trace of `entity` to the CSV file at `path`.
+    pub fn dump(&self, entity: Entity, path: impl AsRef<Path>) -> Result<(), TraceError> {
+        let trace = self.get(entity).ok_or(TraceError::NotTraced(entity))?;
+        std::fs::write(path, trace.to_csv())?;
+        Ok(())
+    }
+
+    /// Begins tracing `entity`, which must be a unit.
+    ///
+    /// Tracing a unit that is already traced does nothing.
+    fn begin(&mut self, entity: Entity) -> Result<(), TraceError> {
+        if self.traces.contains_key(&entity) {
+            return Ok(());
+        }
+
+        if self.traces.len() >= self.max_traced {
+            return Err(TraceError::TooManyTraces {
+                max_traced: self.max_traced,
+            });
+        }
+
+        self.traces.insert(
+            entity,
+            EntityTrace {
+                started: self.n_started,
+                ..default()
+            },
+        );
+        self.n_started += 1;
+        Ok(())
+    }
+
+    /// Begins tracing `entity`, discarding the oldest trace if there is no room for another.
+    ///
+    /// Returns the entity whose trace was discarded, if any.
+    fn begin_evicting_oldest(&mut self, entity: Entity) -> Option<Entity> {
+        let evicted = if !self.traces.contains_key(&entity) && self.traces.len() >= self.max_traced
+        {
+            self.traces
+                .iter()
+                .min_by_key(|(_, trace)| trace.started)
+                .map(|(&oldest, _)| oldest)
+        } else {
+            None
+        };
+
+        if let Some(oldest) = evicted {
+            self.traces.remove(&oldest);
+        }
+
+        self.begin(entity)
+            .expect("there is always room after evicting the oldest trace");
+        evicted
+    }
+
+    /// Advances by one tick, returning `true` if a sample should be taken on this tick.
+    fn advance(&mut self) -> bool {
+        self.tick += 1;
+        self.tick.is_multiple_of(self.sample_interval)
+    }
+}
+
+/// Begins tracing the unit `entity`.
+///
+/// Fails if `entity` is not a living unit, or if too many units are already being traced.
+pub fn start_trace(world: &mut World, entity: Entity) -> Result<(), TraceError> {
+    if world.get::<Id<Unit>>(entity).is_none() {
+        return Err(TraceError::NotAUnit(entity));
+    }
+
+    world
+        .get_resource_or_insert_with(EntityTraces::default)
+        .begin(entity)
+}
+
+/// Records a [`TraceSample`] for each traced unit, every few ticks.
+///
+/// Units that have died are marked as ended, and their history is kept.
+pub(super) fn record_traces(
+    mut traces: ResMut<EntityTraces>,
+    unit_query: Query<(
+        &VoxelPos,
+        &EnergyPool,
+        &Goal,
+        &UnitInventory,
+        &CurrentAction,
+    )>,
+) {
+    if traces.traces.is_empty() || !traces.advance() {
+        return;
+    }
+
+    let tick = traces.tick;
+    let max_samples = traces.max_samples;
+    for (&entity, trace) in traces.traces.iter_mut().filter(|(_, trace)| !trace.ended) {
+        let Ok((voxel_pos, energy_pool, goal, inventory, action)) = unit_query.get(entity) else {
+            trace.ended = true;
+            continue;
+        };
+
+        trace.push(
+            TraceSample {
+                tick,
+                voxel_pos: *voxel_pos,
+                energy: energy_pool.current().0,
+                goal: format!("{goal:?}"),
+                items_held: inventory.held_item.is_some() as u32,
+                action: format!("{:?}", action.action()),
+            },
+            max_samples,
+        );
+    }
+}
+
+/// Traces the unit that the camera is following.
+///
+/// A trace is only started when the camera starts following a different unit.
+/// If too many units are already traced, the oldest trace is discarded to make room.
+pub(crate) fn trace_followed_unit(
+    mut traces: ResMut<EntityTraces>,
+    mut previously_followed: Local<Option<Entity>>,
+    selection: Res<CurrentSelection>,
+    camera_query: Query<&CameraSettings>,
+    unit_query: Query<(), With<Id<Unit>>>,
+) {
+    let followed = match (camera_query.get_single(), &*selection) {
+        (Ok(settings), CurrentSelection::Unit(entity))
+            if settings.camera_mode == CameraMode::FollowUnit && unit_query.contains(*entity) =>
+        {
+            Some(*entity)
+        }
+        _ => None,
+    };
+
+    if followed == *previously_followed {
+        return;
+    }
+    *previously_followed = followed;
+
+    if let Some(entity) = followed {
+        if let Some(evicted) = traces.begin_evicting_oldest(entity) {
+            info!("Stopped tracing {evicted:?} to make room for the followed unit");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A headless app that records traces with the provided limits.
+    fn app(traces: EntityTraces) -> App {
+        let mut app = App::new();
+        app.insert_resource(traces).add_system(record_traces);
+        app
+    }
+
+    /// Spawns a unit with everything that is recorded in a trace.
+    fn spawn_unit(app: &mut App) -> Entity {
+        app.world
+            .spawn((
+                Id::<Unit>::from_name("ant".to_string()),
+                VoxelPos::ZERO,
+                EnergyPool::simple(100.),
+                Goal::default(),
+                UnitInventory::default(),
+                CurrentAction::default(),
+            ))
+            .id()
+    }
+
+    /// The number of samples recorded for `entity`.
+    fn n_samples(app: &App, entity: Entity) -> usize {
+        app.world
+            .resource::<EntityTraces>()
+            .get(entity)
+            .unwrap()
+            .samples()
+            .len()
+    }
+
+    #[test]
+    fn samples_are_taken_at_the_configured_interval() {
+        let mut app = app(EntityTraces::new(4, 100, 3));
+        let unit = spawn_unit(&mut app);
+        start_trace(&mut app.world, unit).unwrap();
+
+        for _ in 0..9 {
+            app.update();
+        }
+
+        let traces = app.world.resource::<EntityTraces>();
+        let ticks: Vec<u64> = traces
+            .get(unit)
+            .unwrap()
+            .samples()
+            .map(|sample| sample.tick)
+            .collect();
+        assert_eq!(ticks, vec![3, 6, 9]);
+    }
+
+    #[test]
+    fn history_is_capped() {
+        let mut app = app(EntityTraces::new(4, 5, 1));
+        let unit = spawn_unit(&mut app);
+        start_trace(&mut app.world, unit).unwrap();
+
+        for _ in 0..20 {
+            app.update();
+        }
+
+        assert_eq!(n_samples(&app, unit), 5);
+        // The oldest samples are the ones discarded
+        let trace = app.world.resource::<EntityTraces>().get(unit).unwrap();
+        assert_eq!(trace.samples().next().unwrap().tick, 16);
+    }
+
+    #[test]
+    fn number_of_traced_units_is_capped() {
+        let mut app = app(EntityTraces::new(2, 5, 1));
+        let units: Vec<Entity> = (0..3).map(|_| spawn_unit(&mut app)).collect();
+
+        start_trace(&mut app.world, units[0]).unwrap();
+        start_trace(&mut app.world, units[1]).unwrap();
+        // Tracing the same unit twice is fine
+        start_trace(&mut app.world, units[1]).unwrap();
+        assert!(matches!(
+            start_trace(&mut app.world, units[2]),
+            Err(TraceError::TooManyTraces { max_traced: 2 })
+        ));
+
+        // Clearing a trace makes room for another
+        app.world.resource_mut::<EntityTraces>().clear(units[0]);
+        start_trace(&mut app.world, units[2]).unwrap();
+    }
+
+    /// A headless app that traces whichever unit the camera follows.
+    fn following_app(traces: EntityTraces) -> App {
+        let mut app = App::new();
+        app.insert_resource(traces)
+            .init_resource::<CurrentSelection>()
+            .add_system(trace_followed_unit);
+        let mut settings = CameraSettings::default();
+        settings.camera_mode = CameraMode::FollowUnit;
+        app.world.spawn(settings);
+        app
+    }
+
+    #[test]
+    fn followed_units_replace_the_oldest_trace() {
+        let mut app = following_app(EntityTraces::new(2, 5, 1));
+        let units: Vec<Entity> = (0..3).map(|_| spawn_unit(&mut app)).collect();
+
+        for &unit in &units {
+            *app.world.resource_mut::<CurrentSelection>() = CurrentSelection::Unit(unit);
+            app.update();
+        }
+
+        let traces = app.world.resource::<EntityTraces>();
+        assert!(traces.get(units[0]).is_none());
+        assert!(traces.get(units[1]).is_some());
+        assert!(traces.get(units[2]).is_some());
+    }
+
+    #[test]
+    fn following_the_same_unit_does_not_restart_its_trace() {
+        let mut app = following_app(EntityTraces::new(2, 5, 1));
+        let unit = spawn_unit(&mut app);
+        *app.world.resource_mut::<CurrentSelection>() = CurrentSelection::Unit(unit);
+        app.update();
+
+        // Clearing the trace while still following the unit does not start a new one
+        app.world.resource_mut::<EntityTraces>().clear(unit);
+        app.update();
+        assert!(app.world.resource::<EntityTraces>().get(unit).is_none());
+    }
+
+    #[test]
+    fn csv_has_a_header_and_a_row_per_sample() {
+        let mut app = app(EntityTraces::new(4, 100, 1));
+        let unit = spawn_unit(&mut app);
+        start_trace(&mut app.world, unit).unwrap();
+
+        for _ in 0..4 {
+            app.update();
+        }
+
+        let csv = app
+            .world
+            .resource::<EntityTraces>()
+            .get(unit)
+            .unwrap()
+            .to_csv();
+        let mut lines = csv.lines();
+        let header = lines.next().unwrap();
+        assert_eq!(header, TraceSample::CSV_HEADER);
+
+        let n_columns = header.split(',').count();
+        let rows: Vec<&str> = lines.collect();
+        assert_eq!(rows.len(), 4);
+        for row in rows {
+            // Goals and actions are quoted, so count the columns outside of the quotes
+            let n_separators = row
+                .split('"')
+                .step_by(2)
+                .map(|unquoted| unquoted.matches(',').count())
+                .sum::<usize>();
+            assert_eq!(n_separators + 1, n_columns, "{row}");
+        }
+    }
+
+    #[test]
+    fn traces_can_be_dumped_to_disk() {
+        let mut app = app(EntityTraces::new(4, 100, 1));
+        let unit = spawn_unit(&mut app);
+        start_trace(&mut app.world, unit).unwrap();
+        app.update();
+
+        let path = std::env::temp_dir().join(format!("emergence_trace_{}.csv", unit.to_bits()));
+        let traces = app.world.resource::<EntityTraces>();
+        traces.dump(unit, &path).unwrap();
+        let written = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(written, traces.get(unit).unwrap().to_csv());
+        assert!(matches!(
+            traces.dump(Entity::from_raw(999), &path),
+            Err(TraceError::NotTraced(_))
+        ));
+    }
+
+    #[test]
+    fn death_stops_sampling_but_keeps_history() {
+        let mut app = app(EntityTraces::new(4, 100, 1));
+        let unit = spawn_unit(&mut app);
+        start_trace(&mut app.world, unit).unwrap();
+
+        for _ in 0..3 {
+            app.update();
+        }
+        app.world.despawn(unit);
+        for _ in 0..3 {
+            app.update();
+        }
+
+        let trace = app.world.resource::<EntityTraces>().get(unit).unwrap();
+        assert!(trace.ended());
+        assert_eq!(trace.samples().len(), 3);
+    }
+
+    #[test]
+    fn tracing_a_nonexistent_unit_fails() {
+        let mut app = app(EntityTraces::default());
+        let unit = spawn_unit(&mut app);
+        app.world.despawn(unit);
+
+        assert!(matches!(
+            start_trace(&mut app.world, unit),
+            Err(TraceError::NotAUnit(entity)) if entity == unit
+        ));
+
+        let not_a_unit = app.world.spawn(VoxelPos::ZERO).id();
+        assert!(matches!(
+            start_trace(&mut app.world, not_a_unit),
+            Err(TraceError::NotAUnit(_))
+        ));
+    }
+
+    #[test]
+    fn sparkline_follows_energy() {
+        let mut trace = EntityTrace::default();
+        for (tick, energy) in [100., 50., 0., 50.].into_iter().enumerate() {
+            trace.push(
+                TraceSample {
+                    tick: tick as u64,
+                    voxel_pos: VoxelPos::ZERO,
+                    energy,
+                    goal: String::new(),
+                    items_held: 0,
+                    action: String::new(),
+                },
+                10,
+            );
+        }
+
+        assert_eq!(trace.energy_sparkline(), "█▅▁▅");
+    }
+}
@@ -0,0 +1,293 @@
+//! Records where units walk, so we can measure how strongly their paths have converged into trails.
+
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+
+use crate::{
+    asset_management::manifest::Id,
+    geometry::VoxelPos,
+    simulation::sim_resources::SimResource,
+    utils::{
+        memory::{MemoryFootprint, MemoryUsage},
+        slicer::Slicer,
+    },
+};
+
+use super::unit_manifest::Unit;
+
+/// How much traffic units have recently brought to each tile.
+///
+/// Each visit adds one unit of traffic, which then fades away over time.
+/// Decay is spread across several ticks by a [`Slicer`], so no single tick has to touch every visited tile.
+#[derive(Resource, Debug, Clone, PartialEq, Reflect)]
+pub(crate) struct TrafficMap {
+    /// The decayed number of visits to each tile that has ever been visited.
+    visits: HashMap<VoxelPos, f32>,
+    /// Every tile in `visits`, in the order in which they were first visited.
+    ///
+    /// New tiles are always appended, as the [`Slicer`] requires.
+    visited_tiles: Vec<VoxelPos>,
+    /// Chooses which of the `visited_tiles` to decay each tick.
+    #[reflect(ignore)]
+    decay_slicer: Slicer,
+}
+
+impl Default for TrafficMap {
+    fn default() -> Self {
+        TrafficMap {
+            visits: HashMap::default(),
+            visited_tiles: Vec::new(),
+            decay_slicer: Slicer::new(0, TrafficMap::DECAY_TICKS_PER_CYCLE),
+        }
+    }
+}
+
+impl TrafficMap {
+    /// The fraction of traffic that remains on a tile after each tick.
+    const RETENTION_PER_TICK: f32 = 0.9995;
+
+    /// The number of ticks over which every visited tile is decayed once.
+    const DECAY_TICKS_PER_CYCLE: u32 = 20;
+
+    /// Records a single visit to `voxel_pos`.
+    pub(crate) fn record(&mut self, voxel_pos: VoxelPos) {
+        let visits = self.visits.entry(voxel_pos).or_insert_with(|| {
+            self.visited_tiles.push(voxel_pos);
+            0.
+        });
+        *visits += 1.;
+    }
+
+    /// The decayed number of times units have entered `voxel_pos`.
+    #[cfg(test)]
+    pub(crate) fn visits(&self, voxel_pos: VoxelPos) -> f32 {
+        self.visits.get(&voxel_pos).copied().unwrap_or_default()
+    }
+
+    /// Decays the traffic on this tick's share of the visited tiles.
+    ///
+    /// Each tile is decayed once per cycle, by the amount that it would have lost over the whole cycle.
+    fn decay(&mut self) {
+        let retention_per_cycle =
+            TrafficMap::RETENTION_PER_TICK.powi(TrafficMap::DECAY_TICKS_PER_CYCLE as i32);
+
+        self.decay_slicer.set_total_items(self.visited_tiles.len());
+        for index in self.decay_slicer.next_range() {
+            let voxel_pos = self.visited_tiles[index];
+            if let Some(visits) = self.visits.get_mut(&voxel_pos) {
+                *visits *= retention_per_cycle;
+            }
+        }
+    }
+
+    /// The Shannon entropy of the distribution of traffic across tiles, in bits.
+    ///
+    /// This is low when units have converged on a few strong trails,
+    /// and equal to `log2(n)` when traffic is spread evenly across `n` tiles.
+    /// Returns 0 if no tile has been visited.
+    pub(crate) fn trail_entropy(&self) -> f32 {
+        let total: f64 = self.visits.values().map(|&count| count as f64).sum();
+        if total <= 0. {
+            return 0.;
+        }
+
+        let entropy: f64 = self
+            .visits
+            .values()
+            .filter(|&&count| count > 0.)
+            .map(|&count| {
+                let p = count as f64 / total;
+                -p * p.log2()
+            })
+            .sum();
+
+        entropy as f32
+    }
+
+    /// The fraction of `passable` tiles that have ever been visited, between 0 and 1.
+    ///
+    /// Visited tiles that are no longer passable are not counted.
+    pub(crate) fn exploration_coverage(&self, passable: &HashSet<VoxelPos>) -> f32 {
+        if passable.is_empty() {
+            return 0.;
+        }
+
+        let n_visited = passable
+            .iter()
+            .filter(|voxel_pos| self.visits.contains_key(voxel_pos))
+            .count();
+
+        n_visited as f32 / passable.len() as f32
+    }
+}
+
+impl MemoryFootprint for TrafficMap {
+    fn memory_usage(&self) -> MemoryUsage {
+        MemoryUsage::of_hash_map(&self.visits) + MemoryUsage::of_vec(&self.visited_tiles)
+    }
+}
+
+impl SimResource for TrafficMap {
+    const NAME: &'static str = "traffic";
+    const PERSIST: bool = true;
+
+    fn reset_to_default(&mut self) {
+        *self = TrafficMap::default();
+    }
+
+    fn measure_memory(&self) -> Option<MemoryUsage> {
+        Some(MemoryFootprint::memory_usage(self))
+    }
+}
+
+/// Counts a visit whenever a unit enters a new tile, or is spawned into one.
+pub(super) fn record_traffic(
+    mut traffic_map: ResMut<TrafficMap>,
+    unit_query: Query<&VoxelPos, (With<Id<Unit>>, Changed<VoxelPos>)>,
+) {
+    for &voxel_pos in unit_query.iter() {
+        traffic_map.record(voxel_pos);
+    }
+}
+
+/// Fades away the traffic on a slice of the visited tiles, so unused trails are forgotten.
+pub(super) fn decay_traffic(mut traffic_map: ResMut<TrafficMap>) {
+    traffic_map.decay();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A tile on a flat map, identified by its index along a line.
+    fn tile(i: i32) -> VoxelPos {
+        VoxelPos::from_xy(i, 0)
+    }
+
+    #[test]
+    fn empty_traffic_has_no_entropy() {
+        assert_eq!(TrafficMap::default().trail_entropy(), 0.);
+    }
+
+    #[test]
+    fn uniform_traffic_has_maximum_entropy() {
+        let mut traffic_map = TrafficMap::default();
+        let n_tiles = 64;
+        for i in 0..n_tiles {
+            for _ in 0..10 {
+                traffic_map.record(tile(i));
+            }
+        }
+
+        let expected = (n_tiles as f32).log2();
+        assert!((traffic_map.trail_entropy() - expected).abs() < 1e-4);
+    }
+
+    #[test]
+    fn single_trail_has_near_zero_entropy() {
+        let mut traffic_map = TrafficMap::default();
+        for _ in 0..10_000 {
+            traffic_map.record(tile(0));
+        }
+        // A little stray wandering
+        for i in 1..5 {
+            traffic_map.record(tile(i));
+        }
+
+        assert!(traffic_map.trail_entropy() < 0.01);
+    }
+
+    #[test]
+    fn coverage_counts_only_passable_tiles() {
+        let mut traffic_map = TrafficMap::default();
+        traffic_map.record(tile(0));
+        traffic_map.record(tile(0));
+        traffic_map.record(tile(1));
+        // No longer passable
+        traffic_map.record(tile(10));
+
+        let passable: HashSet<VoxelPos> = (0..4).map(tile).collect();
+        assert_eq!(traffic_map.exploration_coverage(&passable), 0.5);
+        assert_eq!(traffic_map.visits(tile(0)), 2.);
+        assert_eq!(traffic_map.visits(tile(2)), 0.);
+    }
+
+    #[test]
+    fn moving_units_are_recorded() {
+        let mut app = App::new();
+        app.init_resource::<TrafficMap>().add_system(record_traffic);
+
+        let unit = app
+            .world
+            .spawn((Id::<Unit>::from_name("ant".to_string()), tile(0)))
+            .id();
+        app.update();
+
+        *app.world.get_mut::<VoxelPos>(unit).unwrap() = tile(1);
+        app.update();
+        // Standing still does not count as a new visit
+        app.update();
+
+        let traffic_map = app.world.resource::<TrafficMap>();
+        assert_eq!(traffic_map.visits(tile(0)), 1.);
+        assert_eq!(traffic_map.visits(tile(1)), 1.);
+    }
+
+    #[test]
+    fn sliced_decay_preserves_the_long_run_decay_rate() {
+        let mut traffic_map = TrafficMap::default();
+        for i in 0..50 {
+            traffic_map.record(tile(i));
+        }
+
+        let n_ticks = 10 * TrafficMap::DECAY_TICKS_PER_CYCLE;
+        for tick in 0..n_ticks {
+            traffic_map.decay();
+
+            // Tiles visited for the first time partway through a cycle are picked up too
+            if tick == 7 {
+                traffic_map.record(tile(100));
+            }
+        }
+
+        let expected = TrafficMap::RETENTION_PER_TICK.powi(n_ticks as i32);
+        for i in 0..50 {
+            assert!((traffic_map.visits(tile(i)) - expected).abs() < 1e-3);
+        }
+
+        // Decayed at most once per cycle, and at least once in each full cycle since it was added
+        let late_tile = traffic_map.visits(tile(100));
+        let retention_per_cycle =
+            TrafficMap::RETENTION_PER_TICK.powi(TrafficMap::DECAY_TICKS_PER_CYCLE as i32);
+        assert!(late_tile <= retention_per_cycle.powi(9) + 1e-3);
+        assert!(late_tile >= retention_per_cycle.powi(10) - 1e-3);
+    }
+
+    #[test]
+    fn decay_touches_a_bounded_number_of_tiles_per_tick() {
+        let mut traffic_map = TrafficMap::default();
+        for i in 0..100 {
+            traffic_map.record(tile(i));
+        }
+
+        let max_per_tick = 100_usize.div_ceil(TrafficMap::DECAY_TICKS_PER_CYCLE as usize);
+        for _ in 0..TrafficMap::DECAY_TICKS_PER_CYCLE {
+            let before = traffic_map.visits.clone();
+            traffic_map.decay();
+            let n_decayed = before
+                .iter()
+                .filter(|(voxel_pos, &visits)| traffic_map.visits[*voxel_pos] != visits)
+                .count();
+            assert!(n_decayed <= max_per_tick);
+        }
+
+        // After one full cycle, every tile has decayed exactly once
+        let retention_per_cycle =
+            TrafficMap::RETENTION_PER_TICK.powi(TrafficMap::DECAY_TICKS_PER_CYCLE as i32);
+        for i in 0..100 {
+            assert_eq!(traffic_map.visits(tile(i)), retention_per_cycle);
+        }
+    }
+}
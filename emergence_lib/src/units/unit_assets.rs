@@ -8,7 +8,7 @@ use crate::{
 use bevy::{asset::LoadState, prelude::*, utils::HashMap};
 
 /// Stores material handles for the different tile types.
-#[derive(Resource)]
+#[derive(Resource, Reflect)]
 pub(crate) struct UnitHandles {
     /// The scene for each type of structure
     pub(crate) scenes: HashMap<Id<Unit>, Handle<Scene>>,
@@ -8,7 +8,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     asset_management::manifest::loader::IsRawManifest,
-    organisms::{OrganismVariety, RawOrganismVariety},
+    organisms::{energy::Energy, OrganismVariety, RawOrganismVariety},
     simulation::time::Days,
     units::{basic_needs::Diet, WanderingBehavior},
 };
@@ -36,6 +36,16 @@ pub struct UnitData {
     ///
     /// This stores a [`WeightedIndex`](rand::distributions::WeightedIndex) to allow for multimodal distributions.
     pub wandering_behavior: WanderingBehavior,
+    /// How strongly units of this type keep heading in the same direction when following signals.
+    ///
+    /// At 0, units follow the gradient of the signal exactly; values closer to 1 smooth out zig-zags on noisy signals.
+    pub steering_momentum: f32,
+    /// The probability that a wandering unit of this type will stop to rest or groom instead of moving.
+    pub idle_behavior_probability: f64,
+    /// The amount of energy recovered each time a unit of this type finishes resting.
+    pub rest_energy_recovery: Energy,
+    /// How many tiles idle units of this type will wander from their [`Nest`](super::Nest) before heading back.
+    pub nest_radius: u32,
 }
 
 impl UnitData {
@@ -48,6 +58,10 @@ impl UnitData {
             max_impatience: 10,
             max_age: Days(10.0),
             wandering_behavior: WanderingBehavior::default(),
+            steering_momentum: 0.5,
+            idle_behavior_probability: 0.05,
+            rest_energy_recovery: Energy(0.5),
+            nest_radius: 5,
         }
     }
 }
@@ -67,6 +81,16 @@ pub struct RawUnitData {
     ///
     /// This stores a [`WeightedIndex`](rand::distributions::WeightedIndex) to allow for multimodal distributions.
     pub wandering_behavior: WanderingBehavior,
+    /// How strongly units of this type keep heading in the same direction when following signals.
+    ///
+    /// At 0, units follow the gradient of the signal exactly; values closer to 1 smooth out zig-zags on noisy signals.
+    pub steering_momentum: f32,
+    /// The probability that a wandering unit of this type will stop to rest or groom instead of moving.
+    pub idle_behavior_probability: f64,
+    /// The amount of energy recovered each time a unit of this type finishes resting.
+    pub rest_energy_recovery: f32,
+    /// How many tiles idle units of this type will wander from their nest before heading back.
+    pub nest_radius: u32,
 }
 
 impl From<RawUnitData> for UnitData {
@@ -76,6 +100,21 @@ impl From<RawUnitData> for UnitData {
             "Unit max age must be positive (got {})",
             raw.max_age
         );
+        assert!(
+            (0.0..=1.0).contains(&raw.steering_momentum),
+            "Unit steering momentum must be between 0 and 1 (got {})",
+            raw.steering_momentum
+        );
+        assert!(
+            (0.0..=1.0).contains(&raw.idle_behavior_probability),
+            "Unit idle behavior probability must be between 0 and 1 (got {})",
+            raw.idle_behavior_probability
+        );
+        assert!(
+            raw.rest_energy_recovery >= 0.0 && raw.rest_energy_recovery.is_finite(),
+            "Unit rest energy recovery must be non-negative (got {})",
+            raw.rest_energy_recovery
+        );
 
         Self {
             organism_variety: raw.organism_variety.into(),
@@ -83,6 +122,10 @@ impl From<RawUnitData> for UnitData {
             max_impatience: raw.max_impatience,
             max_age: Days(raw.max_age),
             wandering_behavior: raw.wandering_behavior,
+            steering_momentum: raw.steering_momentum,
+            idle_behavior_probability: raw.idle_behavior_probability,
+            rest_energy_recovery: Energy(raw.rest_energy_recovery),
+            nest_radius: raw.nest_radius,
         }
     }
 }
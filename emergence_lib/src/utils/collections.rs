@@ -0,0 +1,81 @@
+//! Collections with deterministic iteration order.
+//!
+//! The default [`HashMap`](bevy::utils::HashMap) is seeded randomly each run, so its iteration order changes between runs.
+//! Any simulation code whose results depend on iteration order (for example, because it draws from a seeded RNG in the loop)
+//! must use the tools in this module instead.
+
+use std::hash::BuildHasherDefault;
+
+use bevy::utils::AHasher;
+
+/// A [`HashMap`](hashbrown::HashMap) whose hasher uses fixed keys.
+///
+/// Iteration order depends only on the sequence of insertions and removals, not on the run.
+pub type StableHashMap<K, V> = hashbrown::HashMap<K, V, BuildHasherDefault<AHasher>>;
+
+/// Iterates over the key-value pairs of a map, sorted by key.
+///
+/// Unlike [`StableHashMap`], the order does not depend on the order in which items were inserted.
+pub fn ordered_iter<K: Ord, V>(
+    map: impl IntoIterator<Item = (K, V)>,
+) -> impl DoubleEndedIterator<Item = (K, V)> {
+    let mut pairs: Vec<(K, V)> = map.into_iter().collect();
+    pairs.sort_by(|(a, _), (b, _)| a.cmp(b));
+    pairs.into_iter()
+}
+
+/// Iterates over the items of a set, in sorted order.
+///
+/// Unlike [`StableHashMap`], the order does not depend on the order in which items were inserted.
+pub fn ordered<T: Ord>(set: impl IntoIterator<Item = T>) -> impl DoubleEndedIterator<Item = T> {
+    let mut items: Vec<T> = set.into_iter().collect();
+    items.sort();
+    items.into_iter()
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::utils::{HashMap, HashSet};
+
+    use super::*;
+
+    #[test]
+    fn ordered_iter_ignores_insertion_order() {
+        let mut forwards = HashMap::new();
+        let mut backwards = HashMap::new();
+
+        for i in 0..100 {
+            forwards.insert(i, i * 2);
+            backwards.insert(99 - i, (99 - i) * 2);
+        }
+
+        let forwards: Vec<_> = ordered_iter(&forwards).collect();
+        let backwards: Vec<_> = ordered_iter(&backwards).collect();
+        assert_eq!(forwards, backwards);
+        assert!(forwards.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+
+    #[test]
+    fn ordered_ignores_insertion_order() {
+        let forwards: HashSet<u32> = (0..100).collect();
+        let backwards: HashSet<u32> = (0..100).rev().collect();
+
+        assert_eq!(
+            ordered(&forwards).collect::<Vec<_>>(),
+            ordered(&backwards).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn stable_maps_with_the_same_history_iterate_identically() {
+        let mut a = StableHashMap::default();
+        let mut b = StableHashMap::default();
+
+        for i in 0..100 {
+            a.insert(i, ());
+            b.insert(i, ());
+        }
+
+        assert!(a.keys().eq(b.keys()));
+    }
+}
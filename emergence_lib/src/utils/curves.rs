@@ -1,6 +1,9 @@
 //! Curves that are commonly useful when defining interesting game mechanics.
 
+use std::fmt::Display;
+
 use bevy::math::Vec2;
+use serde::{Deserialize, Serialize};
 
 /// A type which maps from an input value to an output value that lies on a curve.
 pub trait Mapping {
@@ -230,3 +233,372 @@ impl Mapping for Sigmoid {
             + self.vertical_offset
     }
 }
+
+/// A curve whose shape is chosen by configuration, rather than in code.
+///
+/// This is intended to be loaded from assets, so should be [`validate`](Curve::validate)d once loaded.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Curve {
+    /// `slope * t + intercept`.
+    Linear {
+        /// The change in output per unit of input.
+        slope: f32,
+        /// The output value when the input value is `0.0`.
+        intercept: f32,
+    },
+    /// `initial * e^(rate * t)`.
+    ///
+    /// Use a negative `rate` for decay.
+    Exponential {
+        /// The output value when the input value is `0.0`.
+        initial: f32,
+        /// The continuous growth rate.
+        rate: f32,
+    },
+    /// An S-shaped curve that rises from `min` to `max`.
+    ///
+    /// Formally: `min + (max - min) * normal_sigmoid(steepness * (t - midpoint))`.
+    Logistic {
+        /// The asymptotic output value at `-infinity`.
+        min: f32,
+        /// The asymptotic output value at `+infinity`.
+        max: f32,
+        /// The input value which produces an output halfway between `min` and `max`.
+        midpoint: f32,
+        /// How quickly the output transitions from `min` to `max`.
+        steepness: f32,
+    },
+    /// Straight lines between `(input, output)` points, sorted by input.
+    ///
+    /// Inputs outside of the points are clamped to the first or last point.
+    PiecewiseLinear {
+        /// The points that the curve passes through.
+        points: Vec<(f32, f32)>,
+    },
+    /// Another curve, whose input is first clamped to lie within `min..=max`.
+    ///
+    /// If `min` is larger than `max`, every input is moved to `max`.
+    Clamped {
+        /// The underlying curve.
+        curve: Box<Curve>,
+        /// The smallest input value passed to `curve`.
+        min: f32,
+        /// The largest input value passed to `curve`.
+        max: f32,
+    },
+}
+
+impl Curve {
+    /// A flat line, which always evaluates to `value`.
+    pub fn constant(value: f32) -> Curve {
+        Curve::Linear {
+            slope: 0.,
+            intercept: value,
+        }
+    }
+
+    /// Wraps this curve so that its inputs are clamped to lie within `min..=max`.
+    pub fn clamped(self, min: f32, max: f32) -> Curve {
+        Curve::Clamped {
+            curve: Box::new(self),
+            min,
+            max,
+        }
+    }
+
+    /// Evaluates the curve at `t`.
+    ///
+    /// This never panics, even for curves that fail [`validate`](Curve::validate) or a NaN `t`.
+    pub fn eval(&self, t: f32) -> f32 {
+        match self {
+            Curve::Linear { slope, intercept } => slope * t + intercept,
+            Curve::Exponential { initial, rate } => initial * f32::exp(rate * t),
+            Curve::Logistic {
+                min,
+                max,
+                midpoint,
+                steepness,
+            } => min + (max - min) * normal_sigmoid(steepness * (t - midpoint)),
+            Curve::PiecewiseLinear { points } => {
+                let (Some(&first), Some(&last)) = (points.first(), points.last()) else {
+                    return 0.;
+                };
+
+                if t.is_nan() || t <= first.0 {
+                    return first.1;
+                }
+                if t >= last.0 {
+                    return last.1;
+                }
+
+                // The first point strictly to the right of t: this is never the first point
+                let i = points.partition_point(|&(x, _)| x <= t);
+                let (x0, y0) = points[i - 1];
+                let (x1, y1) = points[i];
+                linear_combination(y1, y0, (t - x0) / (x1 - x0))
+            }
+            // f32::clamp panics when min > max or either bound is NaN
+            Curve::Clamped { curve, min, max } => curve.eval(t.max(*min).min(*max)),
+        }
+    }
+
+    /// Checks that this curve is well-formed.
+    ///
+    /// All parameters must be finite, piecewise curves must have at least one point and be sorted by input,
+    /// and clamped domains must not be empty.
+    pub fn validate(&self) -> Result<(), CurveError> {
+        /// Returns an error if any of the `values` are NaN or infinite.
+        fn finite(values: &[f32]) -> Result<(), CurveError> {
+            match values.iter().find(|value| !value.is_finite()) {
+                Some(&value) => Err(CurveError::NonFinite(value)),
+                None => Ok(()),
+            }
+        }
+
+        match self {
+            Curve::Linear { slope, intercept } => finite(&[*slope, *intercept]),
+            Curve::Exponential { initial, rate } => finite(&[*initial, *rate]),
+            Curve::Logistic {
+                min,
+                max,
+                midpoint,
+                steepness,
+            } => finite(&[*min, *max, *midpoint, *steepness]),
+            Curve::PiecewiseLinear { points } => {
+                if points.is_empty() {
+                    return Err(CurveError::NoPoints);
+                }
+
+                for &(x, y) in points {
+                    finite(&[x, y])?;
+                }
+
+                for (i, pair) in points.windows(2).enumerate() {
+                    if pair[0].0 >= pair[1].0 {
+                        return Err(CurveError::UnsortedPoints { index: i + 1 });
+                    }
+                }
+
+                Ok(())
+            }
+            Curve::Clamped { curve, min, max } => {
+                finite(&[*min, *max])?;
+                if min > max {
+                    return Err(CurveError::EmptyDomain {
+                        min: *min,
+                        max: *max,
+                    });
+                }
+                curve.validate()
+            }
+        }
+    }
+}
+
+impl Mapping for Curve {
+    fn map(&self, x: f32) -> f32 {
+        self.eval(x)
+    }
+}
+
+/// The ways in which a [`Curve`] can be malformed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CurveError {
+    /// A parameter was NaN or infinite.
+    NonFinite(f32),
+    /// A piecewise linear curve had no points.
+    NoPoints,
+    /// The point at `index` in a piecewise linear curve did not have a larger input than the point before it.
+    UnsortedPoints {
+        /// The index of the first out-of-order point.
+        index: usize,
+    },
+    /// The lower bound of a clamped curve was larger than its upper bound.
+    EmptyDomain {
+        /// The lower bound.
+        min: f32,
+        /// The upper bound.
+        max: f32,
+    },
+}
+
+impl Display for CurveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CurveError::NonFinite(value) => write!(f, "curve parameters must be finite, found {value}"),
+            CurveError::NoPoints => write!(f, "piecewise linear curves need at least one point"),
+            CurveError::UnsortedPoints { index } => write!(
+                f,
+                "piecewise linear points must be sorted by strictly increasing input, but point {index} is not"
+            ),
+            CurveError::EmptyDomain { min, max } => {
+                write!(f, "curve domain {min}..={max} is empty")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CurveError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Asserts that `a` and `b` are equal, up to floating point error.
+    fn assert_close(a: f32, b: f32) {
+        assert!((a - b).abs() < 1e-5, "{a} != {b}");
+    }
+
+    #[test]
+    fn linear_curve_is_a_line() {
+        let curve = Curve::Linear {
+            slope: 2.,
+            intercept: -1.,
+        };
+
+        assert_close(curve.eval(0.), -1.);
+        assert_close(curve.eval(3.), 5.);
+        assert_close(curve.map(3.), Line::new(2., -1.).map(3.));
+    }
+
+    #[test]
+    fn exponential_curve_grows_and_decays() {
+        let growth = Curve::Exponential {
+            initial: 3.,
+            rate: 0.5,
+        };
+        assert_close(growth.eval(0.), 3.);
+        assert_close(growth.eval(2.), 3. * std::f32::consts::E);
+
+        let decay = Curve::Exponential {
+            initial: 1.,
+            rate: -f32::ln(2.),
+        };
+        assert_close(decay.eval(1.), 0.5);
+        assert_close(decay.eval(3.), 0.125);
+    }
+
+    #[test]
+    fn logistic_curve_is_centered_on_midpoint() {
+        let curve = Curve::Logistic {
+            min: 1.,
+            max: 5.,
+            midpoint: 10.,
+            steepness: 2.,
+        };
+
+        assert_close(curve.eval(10.), 3.);
+        assert_close(curve.eval(11.), 1. + 4. * normal_sigmoid(2.));
+        assert!(curve.eval(-100.) - 1. < 1e-5);
+        assert!(5. - curve.eval(100.) < 1e-5);
+    }
+
+    #[test]
+    fn piecewise_linear_curve_interpolates_and_clamps() {
+        let curve = Curve::PiecewiseLinear {
+            points: vec![(0., 0.), (1., 10.), (3., 0.)],
+        };
+
+        assert_close(curve.eval(0.5), 5.);
+        assert_close(curve.eval(1.), 10.);
+        assert_close(curve.eval(2.5), 2.5);
+        assert_close(curve.eval(-4.), 0.);
+        assert_close(curve.eval(7.), 0.);
+    }
+
+    #[test]
+    fn clamped_curve_clamps_its_input() {
+        let curve = Curve::Linear {
+            slope: 1.,
+            intercept: 0.,
+        }
+        .clamped(0., 2.);
+
+        assert_close(curve.eval(-1.), 0.);
+        assert_close(curve.eval(1.5), 1.5);
+        assert_close(curve.eval(5.), 2.);
+    }
+
+    #[test]
+    fn invalid_curves_do_not_panic_when_evaluated() {
+        let inverted = Curve::constant(1.).clamped(1., 0.);
+        assert_close(inverted.eval(0.5), 1.);
+
+        let nan_bounds = Curve::Linear {
+            slope: 1.,
+            intercept: 0.,
+        }
+        .clamped(f32::NAN, f32::NAN);
+        assert!(nan_bounds.eval(0.5).is_finite());
+
+        let unsorted = Curve::PiecewiseLinear {
+            points: vec![(0., 0.), (2., 1.), (1., 2.)],
+        };
+        unsorted.eval(1.5);
+
+        let piecewise = Curve::PiecewiseLinear {
+            points: vec![(0., 3.), (1., 4.)],
+        };
+        assert_close(piecewise.eval(f32::NAN), 3.);
+    }
+
+    #[test]
+    fn well_formed_curves_are_valid() {
+        assert_eq!(Curve::constant(1.).validate(), Ok(()));
+        let piecewise = Curve::PiecewiseLinear {
+            points: vec![(0., 1.), (1., 2.)],
+        };
+        assert_eq!(piecewise.clamped(0., 1.).validate(), Ok(()));
+    }
+
+    #[test]
+    fn unsorted_piecewise_points_are_invalid() {
+        let curve = Curve::PiecewiseLinear {
+            points: vec![(0., 0.), (2., 1.), (1., 2.)],
+        };
+        assert_eq!(
+            curve.validate(),
+            Err(CurveError::UnsortedPoints { index: 2 })
+        );
+
+        let duplicated = Curve::PiecewiseLinear {
+            points: vec![(0., 0.), (0., 1.)],
+        };
+        assert_eq!(
+            duplicated.validate(),
+            Err(CurveError::UnsortedPoints { index: 1 })
+        );
+    }
+
+    #[test]
+    fn malformed_curves_are_invalid() {
+        let non_finite = Curve::Exponential {
+            initial: f32::INFINITY,
+            rate: 1.,
+        };
+        assert_eq!(
+            non_finite.validate(),
+            Err(CurveError::NonFinite(f32::INFINITY))
+        );
+
+        let empty = Curve::PiecewiseLinear { points: Vec::new() };
+        assert_eq!(empty.validate(), Err(CurveError::NoPoints));
+
+        let empty_domain = Curve::constant(0.).clamped(1., 0.);
+        assert_eq!(
+            empty_domain.validate(),
+            Err(CurveError::EmptyDomain { min: 1., max: 0. })
+        );
+    }
+
+    #[test]
+    fn curves_round_trip_through_json() {
+        let curve = Curve::PiecewiseLinear {
+            points: vec![(0., 0.), (1., 2.)],
+        }
+        .clamped(0., 1.);
+
+        let json = serde_json::to_string(&curve).unwrap();
+        assert_eq!(serde_json::from_str::<Curve>(&json).unwrap(), curve);
+    }
+}
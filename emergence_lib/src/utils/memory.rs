@@ -0,0 +1,248 @@
+//! Estimates of how much memory large resources use.
+//!
+//! Sizes are computed from collection lengths, capacities and element sizes,
+//! rather than by inspecting the allocator.
+//! They undercount heap data owned by the elements themselves, but are cheap and deterministic.
+
+use std::{
+    collections::VecDeque,
+    fmt::Display,
+    mem::size_of,
+    ops::{Add, AddAssign},
+};
+
+use bevy::prelude::*;
+
+/// The estimated memory used by a data structure, in bytes.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryUsage {
+    /// The bytes occupied by stored elements.
+    pub used_bytes: usize,
+    /// The bytes reserved, including spare capacity.
+    ///
+    /// For sparse structures this can be much larger than `used_bytes`.
+    pub allocated_bytes: usize,
+}
+
+impl MemoryUsage {
+    /// Estimates the memory used by a [`Vec`].
+    pub fn of_vec<T>(vec: &Vec<T>) -> Self {
+        MemoryUsage {
+            used_bytes: vec.len() * size_of::<T>(),
+            allocated_bytes: vec.capacity() * size_of::<T>(),
+        }
+    }
+
+    /// Estimates the memory used by a [`VecDeque`].
+    pub fn of_vec_deque<T>(vec_deque: &VecDeque<T>) -> Self {
+        MemoryUsage {
+            used_bytes: vec_deque.len() * size_of::<T>(),
+            allocated_bytes: vec_deque.capacity() * size_of::<T>(),
+        }
+    }
+
+    /// Estimates the memory used by a [`String`].
+    pub fn of_string(string: &String) -> Self {
+        MemoryUsage {
+            used_bytes: string.len(),
+            allocated_bytes: string.capacity(),
+        }
+    }
+
+    /// Estimates the memory used by a [`HashSet`](hashbrown::HashSet), whatever its hasher.
+    ///
+    /// Each bucket stores a value plus one control byte.
+    pub fn of_hash_set<T, S>(set: &hashbrown::HashSet<T, S>) -> Self {
+        let bucket_size = size_of::<T>() + 1;
+
+        MemoryUsage {
+            used_bytes: set.len() * bucket_size,
+            allocated_bytes: set.capacity() * bucket_size,
+        }
+    }
+
+    /// Estimates the memory used by a [`HashMap`](hashbrown::HashMap), whatever its hasher.
+    ///
+    /// Each bucket stores a key-value pair plus one control byte.
+    pub fn of_hash_map<K, V, S>(map: &hashbrown::HashMap<K, V, S>) -> Self {
+        let bucket_size = size_of::<(K, V)>() + 1;
+
+        MemoryUsage {
+            used_bytes: map.len() * bucket_size,
+            allocated_bytes: map.capacity() * bucket_size,
+        }
+    }
+}
+
+impl Add for MemoryUsage {
+    type Output = MemoryUsage;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        MemoryUsage {
+            used_bytes: self.used_bytes + rhs.used_bytes,
+            allocated_bytes: self.allocated_bytes + rhs.allocated_bytes,
+        }
+    }
+}
+
+impl AddAssign for MemoryUsage {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Display for MemoryUsage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:.1} KiB used, {:.1} KiB allocated",
+            self.used_bytes as f32 / 1024.,
+            self.allocated_bytes as f32 / 1024.
+        )
+    }
+}
+
+/// Data structures that can estimate their own memory usage.
+pub trait MemoryFootprint {
+    /// Estimates the memory used by this value.
+    fn memory_usage(&self) -> MemoryUsage;
+
+    /// The number of bytes occupied by stored elements.
+    fn bytes(&self) -> usize {
+        self.memory_usage().used_bytes
+    }
+}
+
+/// A function that measures the memory used by a single resource, if it exists.
+type MeasureFn = fn(&World) -> Option<MemoryUsage>;
+
+/// The registry of resources whose memory usage should be reported.
+///
+/// Add entries with [`MemoryReportAppExt::register_memory_footprint`].
+#[derive(Resource, Default, Reflect)]
+#[reflect(Resource)]
+pub struct MemoryReport {
+    /// The name of each registered resource, and how to measure it.
+    #[reflect(ignore)]
+    entries: Vec<(&'static str, MeasureFn)>,
+}
+
+impl MemoryReport {
+    /// The names of all registered resources.
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.entries.iter().map(|(name, _)| *name)
+    }
+
+    /// Measures every registered resource that is currently present in the `world`.
+    pub fn measure(&self, world: &World) -> Vec<(&'static str, MemoryUsage)> {
+        self.entries
+            .iter()
+            .filter_map(|(name, measure)| measure(world).map(|usage| (*name, usage)))
+            .collect()
+    }
+
+    /// Renders the measurements for the `world` as a table, ending with the total.
+    pub fn display(&self, world: &World) -> String {
+        let measurements = self.measure(world);
+        let mut total = MemoryUsage::default();
+        let mut string = String::new();
+
+        for (name, usage) in measurements {
+            total += usage;
+            string += &format!("{name}: {usage}\n");
+        }
+
+        string += &format!("Total: {total}");
+        string
+    }
+}
+
+/// An [`App`] extension trait to add resources to the [`MemoryReport`].
+pub trait MemoryReportAppExt {
+    /// Includes the resource `R` in the [`MemoryReport`] under the provided `name`.
+    fn register_memory_footprint<R: Resource + MemoryFootprint>(
+        &mut self,
+        name: &'static str,
+    ) -> &mut Self;
+
+    /// Includes an entry in the [`MemoryReport`] under the provided `name`, measured by the `measure` function.
+    ///
+    /// The entry is skipped whenever `measure` returns [`None`].
+    fn register_memory_measurement(
+        &mut self,
+        name: &'static str,
+        measure: fn(&World) -> Option<MemoryUsage>,
+    ) -> &mut Self;
+}
+
+impl MemoryReportAppExt for App {
+    fn register_memory_footprint<R: Resource + MemoryFootprint>(
+        &mut self,
+        name: &'static str,
+    ) -> &mut Self {
+        self.register_memory_measurement(name, |world: &World| {
+            world.get_resource::<R>().map(R::memory_usage)
+        })
+    }
+
+    fn register_memory_measurement(
+        &mut self,
+        name: &'static str,
+        measure: fn(&World) -> Option<MemoryUsage>,
+    ) -> &mut Self {
+        self.init_resource::<MemoryReport>();
+        let mut report = self.world.resource_mut::<MemoryReport>();
+        report.entries.push((name, measure));
+
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A resource that always reports the same usage.
+    #[derive(Resource)]
+    struct Fixed;
+
+    impl MemoryFootprint for Fixed {
+        fn memory_usage(&self) -> MemoryUsage {
+            MemoryUsage {
+                used_bytes: 10,
+                allocated_bytes: 20,
+            }
+        }
+    }
+
+    #[test]
+    fn vec_reports_len_and_capacity_separately() {
+        let mut vec: Vec<u32> = Vec::with_capacity(10);
+        vec.push(1);
+
+        let usage = MemoryUsage::of_vec(&vec);
+        assert_eq!(usage.used_bytes, 4);
+        assert_eq!(usage.allocated_bytes, 40);
+    }
+
+    #[test]
+    fn missing_resources_are_skipped() {
+        let mut app = App::new();
+        app.register_memory_footprint::<Fixed>("fixed");
+
+        let report = app.world.resource::<MemoryReport>();
+        assert_eq!(report.names().collect::<Vec<_>>(), vec!["fixed"]);
+        assert!(report.measure(&app.world).is_empty());
+    }
+
+    #[test]
+    fn registered_resources_are_measured() {
+        let mut app = App::new();
+        app.insert_resource(Fixed);
+        app.register_memory_footprint::<Fixed>("fixed");
+
+        let report = app.world.resource::<MemoryReport>();
+        let measurements = report.measure(&app.world);
+        assert_eq!(measurements, vec![("fixed", Fixed.memory_usage())]);
+    }
+}
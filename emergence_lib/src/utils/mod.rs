@@ -1,5 +1,8 @@
 //! Simple gameplay-agnostic utilities.
 
+pub mod collections;
 pub mod curves;
 pub mod fallible_commands;
+pub mod memory;
 pub mod noise;
+pub mod slicer;
@@ -4,10 +4,14 @@ use hexx::Hex;
 use noisy_bevy::fbm_simplex_2d_seeded;
 
 use crate::geometry::Height;
-use bevy::math::Vec2;
+use bevy::{
+    math::Vec2,
+    reflect::{FromReflect, Reflect},
+};
+use serde::{Deserialize, Serialize};
 
 /// A settings struct for [`simplex_noise`].
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Reflect, FromReflect)]
 pub struct SimplexSettings {
     /// Controls the size of the features in the noise function.
     ///
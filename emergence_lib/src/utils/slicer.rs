@@ -0,0 +1,145 @@
+//! Spreads expensive maintenance work across several ticks.
+
+use std::ops::Range;
+
+/// Splits a list of items into chunks, so that each item is processed once per cycle of several ticks.
+///
+/// Call [`Slicer::next_range`] once per tick, and process the items whose indexes lie in the returned range.
+/// This bounds the work done each tick to roughly `total_items / ticks_per_cycle`,
+/// at the cost of each item being updated less frequently.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Slicer {
+    /// The number of items that must be processed each cycle.
+    total_items: usize,
+    /// The number of ticks it takes to visit every item once.
+    ticks_per_cycle: u32,
+    /// How many ticks of the current cycle have elapsed.
+    tick_in_cycle: u32,
+    /// The first index that has not yet been processed this cycle.
+    next_index: usize,
+}
+
+impl Slicer {
+    /// Creates a new [`Slicer`], which will visit `total_items` items once every `ticks_per_cycle` ticks.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ticks_per_cycle` is 0.
+    pub fn new(total_items: usize, ticks_per_cycle: u32) -> Self {
+        assert!(ticks_per_cycle > 0);
+
+        Slicer {
+            total_items,
+            ticks_per_cycle,
+            tick_in_cycle: 0,
+            next_index: 0,
+        }
+    }
+
+    /// Updates the number of items to process.
+    ///
+    /// Items are assumed to be added or removed at the end of the list.
+    /// Items already processed this cycle are not revisited,
+    /// and the remaining items are spread over the rest of the cycle.
+    pub fn set_total_items(&mut self, total_items: usize) {
+        self.total_items = total_items;
+    }
+
+    /// The maximum number of items processed in a single tick, as long as the item count is not changed.
+    pub fn max_items_per_tick(&self) -> usize {
+        self.total_items.div_ceil(self.ticks_per_cycle as usize)
+    }
+
+    /// Returns the range of item indexes to process this tick, and advances to the next tick.
+    pub fn next_range(&mut self) -> Range<usize> {
+        let ticks_elapsed = self.tick_in_cycle as usize + 1;
+        let ticks_per_cycle = self.ticks_per_cycle as usize;
+
+        // Rounding up ensures that the final tick of each cycle always reaches the end of the list
+        let end = (self.total_items * ticks_elapsed).div_ceil(ticks_per_cycle);
+        let start = self.next_index.min(self.total_items);
+        let end = end.max(start);
+
+        self.tick_in_cycle += 1;
+        if self.tick_in_cycle == self.ticks_per_cycle {
+            self.tick_in_cycle = 0;
+            self.next_index = 0;
+        } else {
+            self.next_index = end;
+        }
+
+        start..end
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_item_is_visited_once_per_cycle() {
+        for total_items in [0, 1, 7, 100, 101] {
+            for ticks_per_cycle in [1, 3, 10, 200] {
+                let mut slicer = Slicer::new(total_items, ticks_per_cycle);
+                let mut visits = vec![0; total_items];
+
+                for _ in 0..ticks_per_cycle {
+                    let range = slicer.next_range();
+                    assert!(range.len() <= slicer.max_items_per_tick());
+
+                    for index in range {
+                        visits[index] += 1;
+                    }
+                }
+
+                assert!(visits.iter().all(|&n| n == 1));
+            }
+        }
+    }
+
+    #[test]
+    fn cycles_repeat() {
+        let mut slicer = Slicer::new(10, 4);
+
+        let first_cycle: Vec<Range<usize>> = (0..4).map(|_| slicer.next_range()).collect();
+        let second_cycle: Vec<Range<usize>> = (0..4).map(|_| slicer.next_range()).collect();
+
+        assert_eq!(first_cycle, second_cycle);
+    }
+
+    #[test]
+    fn growing_item_counts_are_covered() {
+        let ticks_per_cycle = 5;
+        let mut total_items = 20;
+        let mut slicer = Slicer::new(total_items, ticks_per_cycle);
+        let mut visits = vec![0; 100];
+
+        for _ in 0..ticks_per_cycle {
+            for index in slicer.next_range() {
+                visits[index] += 1;
+            }
+
+            total_items += 3;
+            slicer.set_total_items(total_items);
+        }
+
+        // The items added after the final tick will be processed next cycle
+        let last_processed = total_items - 3;
+        assert!(visits[..last_processed].iter().all(|&n| n == 1));
+        assert!(visits[last_processed..].iter().all(|&n| n == 0));
+    }
+
+    #[test]
+    fn shrinking_item_counts_are_safe() {
+        let mut slicer = Slicer::new(20, 4);
+        slicer.next_range();
+        slicer.next_range();
+
+        slicer.set_total_items(5);
+        assert!(slicer.next_range().is_empty());
+        assert!(slicer.next_range().is_empty());
+
+        // The next cycle starts from the beginning
+        assert_eq!(slicer.next_range(), 0..2);
+    }
+}
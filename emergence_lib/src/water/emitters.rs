@@ -36,7 +36,7 @@ pub(super) fn produce_water_from_emitters(
 }
 
 /// An entity that produces water.
-#[derive(Component, Debug, Clone)]
+#[derive(Component, Debug, Clone, Reflect, FromReflect)]
 pub(crate) struct WaterEmitter {
     /// The maximum height of water that this emitter can be covered with before it stops producing.
     ///
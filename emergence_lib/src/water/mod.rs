@@ -17,7 +17,10 @@ use crate::{
     asset_management::manifest::Id,
     geometry::{Height, Volume},
     items::item_manifest::{Item, ItemManifest},
-    simulation::SimulationSet,
+    simulation::{
+        phases::{SimulationAppExt, TickPhase},
+        sim_resources::SimResourceAppExt,
+    },
     structures::structure_manifest::StructureManifest,
 };
 
@@ -37,7 +40,7 @@ pub mod water_dynamics;
 /// Controls the key parameters of water movement and behavior.
 ///
 /// Note that soil properties are stored seperately for each soil type in [`TerrainData`](crate::terrain::terrain_manifest::TerrainData).
-#[derive(Resource, Debug, Clone, Copy)]
+#[derive(Resource, Debug, Clone, Copy, Reflect)]
 pub struct WaterConfig {
     /// The starting amount of water in each tile.
     pub initial_water: Volume,
@@ -145,52 +148,51 @@ pub(crate) enum WaterSet {
 impl Plugin for WaterPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(WaterConfig::IN_GAME)
-            .init_resource::<Ocean>();
+            .init_resource::<Ocean>()
+            .register_sim_resource::<Ocean>();
 
         app.edit_schedule(CoreSchedule::FixedUpdate, |schedule| {
-            schedule
-                .configure_sets(
-                    (
-                        WaterSet::VerticalWaterMovement,
-                        WaterSet::HorizontalWaterMovement,
-                        WaterSet::Synchronization,
-                    )
-                        .in_set(SimulationSet)
-                        .chain(),
+            schedule.configure_sets(
+                (
+                    WaterSet::VerticalWaterMovement,
+                    WaterSet::HorizontalWaterMovement,
+                    WaterSet::Synchronization,
                 )
-                .add_system(
-                    cache_water_volume
-                        .before(WaterSet::VerticalWaterMovement)
-                        // This needs to respect pausing
-                        .in_set(SimulationSet),
-                )
-                .add_systems(
-                    (
-                        tides,
-                        produce_water_from_emitters,
-                        precipitation,
-                        // This system pulls in a ton of dependencies, so it's best to fail silently when they don't exist
-                        // to allow for integration testing of water behavior.
-                        draw_water_from_roots
-                            .run_if(resource_exists::<StructureManifest>())
-                            .run_if(resource_exists::<ItemManifest>()),
-                        evaporation,
-                    )
-                        .chain()
-                        .in_set(WaterSet::VerticalWaterMovement),
-                )
-                .add_system(
-                    // It is important that the computed height of the water is accurate before we start moving it around.
-                    update_water_depth
-                        .after(WaterSet::VerticalWaterMovement)
-                        .before(WaterSet::HorizontalWaterMovement)
-                        .in_set(SimulationSet),
-                )
-                .add_system(horizontal_water_movement.in_set(WaterSet::HorizontalWaterMovement))
-                .add_systems(
-                    (add_water_emitters, update_water_depth).in_set(WaterSet::Synchronization),
-                );
-        });
+                    .in_set(TickPhase::Resolution)
+                    .chain(),
+            );
+        })
+        .add_simulation_systems(
+            TickPhase::Resolution,
+            (
+                tides,
+                produce_water_from_emitters,
+                precipitation,
+                // This system pulls in a ton of dependencies, so it's best to fail silently when they don't exist
+                // to allow for integration testing of water behavior.
+                draw_water_from_roots
+                    .run_if(resource_exists::<StructureManifest>())
+                    .run_if(resource_exists::<ItemManifest>()),
+                evaporation,
+            )
+                .chain()
+                .in_set(WaterSet::VerticalWaterMovement),
+        )
+        .add_simulation_systems(
+            TickPhase::Resolution,
+            (
+                cache_water_volume.before(WaterSet::VerticalWaterMovement),
+                // It is important that the computed height of the water is accurate before we start moving it around.
+                update_water_depth
+                    .after(WaterSet::VerticalWaterMovement)
+                    .before(WaterSet::HorizontalWaterMovement),
+                horizontal_water_movement.in_set(WaterSet::HorizontalWaterMovement),
+            ),
+        )
+        .add_simulation_systems(
+            TickPhase::Resolution,
+            (add_water_emitters, update_water_depth).in_set(WaterSet::Synchronization),
+        );
     }
 }
 
@@ -225,7 +227,8 @@ pub struct WaterBundle {
 }
 
 /// The depth of the water table at a given tile relative to the soil surface.
-#[derive(Component, Debug, Clone, Copy, PartialEq, Default)]
+#[derive(Component, Debug, Clone, Copy, PartialEq, Default, Reflect, FromReflect)]
+#[reflect(Component)]
 pub enum WaterDepth {
     /// The water table is completely empty.
     #[default]
@@ -313,7 +316,9 @@ impl Display for WaterDepth {
 ///
 /// This is relative to water above the soil, which has a value of 1.0.
 /// As a result, this value is always between 0.0 and 1.0.
-#[derive(Component, Clone, Copy, Debug, Add, Sub, PartialEq, Serialize, Deserialize)]
+#[derive(
+    Component, Clone, Copy, Debug, Add, Sub, PartialEq, Serialize, Deserialize, Reflect, FromReflect,
+)]
 pub struct SoilWaterCapacity(pub f32);
 
 impl Default for SoilWaterCapacity {
@@ -324,8 +329,21 @@ impl Default for SoilWaterCapacity {
 
 /// The amount of water stored on this terrain tile.
 #[derive(
-    Component, Default, Clone, Copy, Debug, Add, Sub, PartialEq, PartialOrd, Serialize, Deserialize,
+    Component,
+    Default,
+    Clone,
+    Copy,
+    Debug,
+    Add,
+    Sub,
+    PartialEq,
+    PartialOrd,
+    Serialize,
+    Deserialize,
+    Reflect,
+    FromReflect,
 )]
+#[reflect(Component)]
 pub struct WaterVolume(Volume);
 
 impl Mul<f32> for WaterVolume {
@@ -392,7 +410,10 @@ impl WaterVolume {
 }
 
 /// The water volume at this tile on the previous tick.
-#[derive(Component, Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+#[derive(
+    Component, Clone, PartialEq, Debug, Default, Serialize, Deserialize, Reflect, FromReflect,
+)]
+#[reflect(Component)]
 pub struct PreviousWaterVolume(pub(crate) WaterVolume);
 
 /// Updates the depth of water at each tile based on the volume of water and soil properties.
@@ -414,7 +435,20 @@ pub fn update_water_depth(
 }
 
 /// The rate and direction of lateral water flow.
-#[derive(Component, Debug, Default, PartialEq, Clone, Add, AddAssign, Sub, SubAssign)]
+#[derive(
+    Component,
+    Debug,
+    Default,
+    PartialEq,
+    Clone,
+    Add,
+    AddAssign,
+    Sub,
+    SubAssign,
+    Reflect,
+    FromReflect,
+)]
+#[reflect(Component)]
 pub struct FlowVelocity {
     /// The x component (in world coordinates) of the flow velocity.
     x: Volume,
@@ -3,12 +3,13 @@
 use bevy::prelude::*;
 
 use crate::geometry::Height;
+use crate::simulation::sim_resources::SimResource;
 use crate::simulation::time::{Days, InGameTime};
 
 use super::WaterConfig;
 
 /// Controls the dynamics of the tides.
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Reflect, FromReflect)]
 pub struct TideSettings {
     /// The amplitude of the tide.
     pub amplitude: Height,
@@ -19,12 +20,23 @@ pub struct TideSettings {
 }
 
 /// Stores data about the current state of the ocean.
-#[derive(Resource, Debug, Default)]
+#[derive(Resource, Debug, Default, Reflect)]
+#[reflect(Resource)]
 pub struct Ocean {
     /// The global height of the ocean.
     height: Height,
 }
 
+/// The ocean level is recomputed from the in-game time on every tick.
+impl SimResource for Ocean {
+    const NAME: &'static str = "ocean";
+    const PERSIST: bool = false;
+
+    fn reset_to_default(&mut self) {
+        *self = Ocean::default();
+    }
+}
+
 impl Ocean {
     /// The current height of the ocean.
     pub(crate) fn height(&self) -> Height {
@@ -20,7 +20,9 @@ use super::{ocean::Ocean, FlowVelocity, WaterConfig, WaterDepth, WaterVolume};
 /// This varies by terrain type, and is a multiplier on the evaporation rate.
 /// Open water has a value of 1.0.
 /// As a result, this should always be greater than 0.0 and typically less than 1.0.
-#[derive(Component, Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[derive(
+    Component, Debug, PartialEq, Clone, Copy, Serialize, Deserialize, Reflect, FromReflect,
+)]
 pub struct SoilWaterEvaporationRate(pub f32);
 
 impl Default for SoilWaterEvaporationRate {
@@ -98,7 +100,9 @@ pub(super) fn precipitation(
 /// The relative rate at which water flows between soil of this type.
 ///
 /// This should be less than 1.0, as 1.0 is the rate at which surface water flows.
-#[derive(Component, Clone, Copy, Debug, Add, Sub, PartialEq, Serialize, Deserialize)]
+#[derive(
+    Component, Clone, Copy, Debug, Add, Sub, PartialEq, Serialize, Deserialize, Reflect, FromReflect,
+)]
 pub struct SoilWaterFlowRate(pub f32);
 
 impl Default for SoilWaterFlowRate {
@@ -425,9 +429,9 @@ mod tests {
     use crate as emergence_lib;
     use crate::enum_iter::IterableEnum;
     use crate::geometry::{DiscreteHeight, VoxelPos};
+    use crate::simulation::phases::{SimulationAppExt, TickPhase};
     use crate::simulation::time::advance_in_game_time;
     use crate::simulation::weather::{Weather, WeatherPlugin};
-    use crate::simulation::SimulationSet;
     use crate::water::{WaterBundle, WaterPlugin};
 
     use super::*;
@@ -458,11 +462,7 @@ mod tests {
             .add_plugin(WaterPlugin)
             .add_plugin(WeatherPlugin)
             .init_resource::<InGameTime>()
-            .add_system(
-                advance_in_game_time
-                    .in_set(SimulationSet)
-                    .in_schedule(CoreSchedule::FixedUpdate),
-            );
+            .add_simulation_system(TickPhase::Bookkeeping, advance_in_game_time);
 
         let map_geometry = scenario
             .map_shape
@@ -0,0 +1,140 @@
+//! Divides the map into regions, each with its own mix of terrain.
+
+use bevy::{prelude::*, utils::HashMap};
+use hexx::Hex;
+use rand::{seq::SliceRandom, Rng};
+
+use crate::asset_management::manifest::Id;
+
+use super::terrain_generation::TerrainWeights;
+
+/// The marker type for [`Id<Biome>`](super::Id).
+///
+/// Each terrain tile has an [`Id<Biome>`] component when the map is generated with biomes.
+#[derive(Reflect, FromReflect, Clone, Copy, PartialEq, Eq)]
+pub struct Biome;
+
+/// Splits the map into regions around randomly chosen centers, and assigns each region a biome.
+///
+/// Each tile belongs to the region whose center is closest.
+#[derive(Debug, Clone)]
+pub(crate) struct BiomeSettings {
+    /// The number of regions.
+    regions: u32,
+    /// Each biome and the terrain weights used within it, sorted by [`Id`].
+    biomes: Vec<(Id<Biome>, TerrainWeights)>,
+}
+
+impl BiomeSettings {
+    /// Creates a new set of biomes.
+    ///
+    /// Regions are assigned each of the `biomes` in turn, so every biome is used if there are enough regions.
+    /// Returns [`None`] if there are no regions or no biomes.
+    pub(crate) fn new(
+        regions: u32,
+        biomes: impl IntoIterator<Item = (Id<Biome>, TerrainWeights)>,
+    ) -> Option<Self> {
+        let mut biomes: Vec<(Id<Biome>, TerrainWeights)> = biomes.into_iter().collect();
+        biomes.sort_by_key(|(biome_id, _)| *biome_id);
+
+        (regions > 0 && !biomes.is_empty()).then_some(BiomeSettings { regions, biomes })
+    }
+
+    /// Chooses the region centers from among the `hexes`, and assigns each hex the biome of its region.
+    ///
+    /// Ties between equally distant centers go to the center that was chosen first.
+    pub(crate) fn assign(&self, hexes: &[Hex], rng: &mut impl Rng) -> HashMap<Hex, Id<Biome>> {
+        let centers: Vec<(Hex, Id<Biome>)> = hexes
+            .choose_multiple(rng, self.regions as usize)
+            .zip(self.biomes.iter().cycle())
+            .map(|(&center, &(biome_id, _))| (center, biome_id))
+            .collect();
+
+        hexes
+            .iter()
+            .filter_map(|&hex| {
+                centers
+                    .iter()
+                    .min_by_key(|(center, _)| hex.unsigned_distance_to(*center))
+                    .map(|&(_, biome_id)| (hex, biome_id))
+            })
+            .collect()
+    }
+
+    /// The terrain weights used for tiles in the provided biome.
+    pub(crate) fn terrain_weights(&self, biome_id: Id<Biome>) -> &TerrainWeights {
+        &self
+            .biomes
+            .iter()
+            .find(|(id, _)| *id == biome_id)
+            .expect("Biome was not one of the configured biomes")
+            .1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hexx::shapes::hexagon;
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    use super::*;
+
+    /// Terrain weights that always produce terrain with the provided `name`.
+    fn only(name: &str) -> TerrainWeights {
+        TerrainWeights::builder()
+            .with(Id::from_name(name.to_string()), 1.)
+            .build()
+            .unwrap()
+    }
+
+    /// Three regions, split between a rocky and a grassy biome.
+    fn settings() -> BiomeSettings {
+        BiomeSettings::new(
+            3,
+            [
+                (Id::from_name("mountains".to_string()), only("rocky")),
+                (Id::from_name("plains".to_string()), only("grassy")),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn every_tile_gets_exactly_one_biome() {
+        let hexes: Vec<Hex> = hexagon(Hex::ZERO, 10).collect();
+        let biome_map = settings().assign(&hexes, &mut SmallRng::seed_from_u64(0));
+
+        assert_eq!(biome_map.len(), hexes.len());
+        for hex in &hexes {
+            assert!(biome_map.contains_key(hex));
+        }
+
+        // With enough regions, every biome is used
+        let n_biomes = biome_map
+            .values()
+            .copied()
+            .collect::<bevy::utils::HashSet<_>>()
+            .len();
+        assert_eq!(n_biomes, 2);
+    }
+
+    #[test]
+    fn biomes_are_deterministic() {
+        let hexes: Vec<Hex> = hexagon(Hex::ZERO, 10).collect();
+        let settings = settings();
+
+        assert_eq!(
+            settings.assign(&hexes, &mut SmallRng::seed_from_u64(7)),
+            settings.assign(&hexes, &mut SmallRng::seed_from_u64(7))
+        );
+    }
+
+    #[test]
+    fn biomes_need_regions_and_weights() {
+        assert!(
+            BiomeSettings::new(0, [(Id::from_name("plains".to_string()), only("grassy"))])
+                .is_none()
+        );
+        assert!(BiomeSettings::new(3, []).is_none());
+    }
+}
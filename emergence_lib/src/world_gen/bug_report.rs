@@ -0,0 +1,211 @@
+//! Compact descriptions of a running game, so that bugs can be reproduced from an issue.
+
+use std::fmt::Display;
+
+use bevy::prelude::Resource;
+
+use super::{GenerationConfig, MapCode, MapCodeError, MapCodeSettings};
+
+/// The version of the game that is running, as written into [`BugReport`]s.
+///
+/// The game binary should insert this with its own `CARGO_PKG_VERSION`,
+/// since the library's version does not change when the game is released.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GameVersion(pub &'static str);
+
+impl Default for GameVersion {
+    fn default() -> Self {
+        GameVersion("unknown")
+    }
+}
+
+/// The context needed to reproduce a bug, in a form that can be pasted into an issue.
+///
+/// Use [`parse_bug_report`] to read these back.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BugReport {
+    /// The version of the game.
+    pub version: String,
+    /// The seed used to generate the world.
+    pub seed: u64,
+    /// The code that can be used to regenerate the world.
+    ///
+    /// This is `None` if the world's settings could not be encoded.
+    pub map_code: Option<String>,
+    /// How many in-game days had passed.
+    pub elapsed_days: f32,
+}
+
+impl BugReport {
+    /// The text that every bug report starts with.
+    const PREFIX: &'static str = "emergence-bug-report";
+
+    /// The value written in place of a missing map code.
+    const NO_MAP_CODE: &'static str = "none";
+
+    /// Describes a game running `version`, generated from `config`, after `elapsed_days` have passed.
+    pub fn new(version: GameVersion, config: &GenerationConfig, elapsed_days: f32) -> Self {
+        BugReport {
+            version: version.0.to_string(),
+            seed: config.seed,
+            map_code: MapCode::encode(config).ok(),
+            elapsed_days,
+        }
+    }
+
+    /// Recreates the [`GenerationConfig`] of the reported game from its map code.
+    pub fn generation_config(&self) -> Result<GenerationConfig, MapCodeError> {
+        let map_code = self.map_code.as_ref().ok_or(MapCodeError::NoMapCode)?;
+        MapCode::decode(map_code).and_then(MapCodeSettings::into_config)
+    }
+}
+
+impl Display for BugReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Floats are written with full precision, so that they can be parsed back exactly
+        write!(
+            f,
+            "{} version={} seed={} map={} day={}",
+            BugReport::PREFIX,
+            self.version,
+            self.seed,
+            self.map_code.as_deref().unwrap_or(BugReport::NO_MAP_CODE),
+            self.elapsed_days
+        )
+    }
+}
+
+/// The ways in which a bug report string can fail to parse.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BugReportError {
+    /// The string does not start with [`BugReport::PREFIX`].
+    MissingPrefix,
+    /// A required field was not found.
+    MissingField(&'static str),
+    /// A field could not be parsed.
+    InvalidField {
+        /// The name of the field.
+        field: &'static str,
+        /// The text that could not be parsed.
+        value: String,
+    },
+}
+
+impl Display for BugReportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BugReportError::MissingPrefix => {
+                write!(f, "bug reports must start with {}", BugReport::PREFIX)
+            }
+            BugReportError::MissingField(field) => {
+                write!(f, "the bug report has no {field} field")
+            }
+            BugReportError::InvalidField { field, value } => {
+                write!(f, "{value} is not a valid value for the {field} field")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BugReportError {}
+
+/// Reads a [`BugReport`] written by its [`Display`] implementation.
+///
+/// Surrounding whitespace is ignored, and fields may appear in any order.
+pub fn parse_bug_report(string: &str) -> Result<BugReport, BugReportError> {
+    let mut words = string.split_whitespace();
+    if words.next() != Some(BugReport::PREFIX) {
+        return Err(BugReportError::MissingPrefix);
+    }
+
+    let fields: Vec<(&str, &str)> = words.filter_map(|word| word.split_once('=')).collect();
+    let field = |name: &'static str| -> Result<&str, BugReportError> {
+        fields
+            .iter()
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| *value)
+            .ok_or(BugReportError::MissingField(name))
+    };
+    let invalid = |field: &'static str, value: &str| BugReportError::InvalidField {
+        field,
+        value: value.to_string(),
+    };
+
+    let seed = field("seed")?;
+    let map_code = field("map")?;
+    let day = field("day")?;
+
+    Ok(BugReport {
+        version: field("version")?.to_string(),
+        seed: seed.parse().map_err(|_| invalid("seed", seed))?,
+        map_code: (map_code != BugReport::NO_MAP_CODE).then(|| map_code.to_string()),
+        elapsed_days: day.parse().map_err(|_| invalid("day", day))?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bug_reports_round_trip() {
+        let mut bug_report = BugReport::new(
+            GameVersion("0.1.0"),
+            &GenerationConfig::testing(),
+            12.345_678,
+        );
+        bug_report.seed = u64::MAX;
+
+        let parsed = parse_bug_report(&format!("  {bug_report}\n")).unwrap();
+        assert_eq!(parsed, bug_report);
+    }
+
+    #[test]
+    fn bug_reports_recreate_the_world() {
+        let mut config = GenerationConfig::testing();
+        config.seed = 42;
+
+        let bug_report =
+            parse_bug_report(&BugReport::new(GameVersion("0.1.0"), &config, 3.).to_string())
+                .unwrap();
+        let recreated = bug_report.generation_config().unwrap();
+        assert_eq!(recreated.seed, 42);
+        assert_eq!(recreated.map_radius, config.map_radius);
+        assert_eq!(recreated.strategy, config.strategy);
+    }
+
+    #[test]
+    fn oversized_worlds_are_reported_without_a_map_code() {
+        let mut config = GenerationConfig::testing();
+        config.map_radius = u32::MAX;
+
+        let bug_report = BugReport::new(GameVersion("0.1.0"), &config, 1.);
+        assert_eq!(bug_report.map_code, None);
+
+        let parsed = parse_bug_report(&bug_report.to_string()).unwrap();
+        assert_eq!(parsed, bug_report);
+        assert_eq!(
+            parsed.generation_config().map(|config| config.seed),
+            Err(MapCodeError::NoMapCode)
+        );
+    }
+
+    #[test]
+    fn malformed_bug_reports_are_rejected() {
+        assert_eq!(
+            parse_bug_report("version=0.1.0 seed=3"),
+            Err(BugReportError::MissingPrefix)
+        );
+        assert_eq!(
+            parse_bug_report("emergence-bug-report version=0.1.0 seed=3 day=1"),
+            Err(BugReportError::MissingField("map"))
+        );
+        assert_eq!(
+            parse_bug_report("emergence-bug-report version=0.1.0 seed=three map=A day=1"),
+            Err(BugReportError::InvalidField {
+                field: "seed",
+                value: "three".to_string()
+            })
+        );
+    }
+}
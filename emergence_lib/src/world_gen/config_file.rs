@@ -0,0 +1,770 @@
+//! Loads a [`GenerationConfig`] from a JSON file, so that worlds can be tweaked without recompiling.
+
+use std::{fmt::Display, ops::RangeInclusive, path::Path};
+
+use bevy::utils::HashMap;
+use hexx::Hex;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asset_management::manifest::Id,
+    geometry::{parse_ascii_map, AsciiMapParseError, TraversalConfig},
+    terrain::terrain_manifest::Terrain,
+    utils::{curves::CurveError, noise::SimplexSettings},
+};
+
+use super::{
+    biomes::BiomeSettings,
+    terrain_generation::{TerrainWeights, TerrainWeightsError},
+    Difficulty, GenerationConfig, GenerationStrategy, PlacementStrategy, TerrainSmoothing,
+};
+
+/// The serialized form of a [`GenerationConfig`].
+///
+/// Organisms and terrain are referred to by name, as in the manifests.
+/// Unknown fields are rejected, to catch typos.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawGenerationConfig {
+    /// The seed used to generate the world.
+    pub seed: u64,
+    /// Radius of the map.
+    pub map_radius: u32,
+    /// How long to simulate the world before starting the game.
+    pub number_of_burn_in_ticks: u32,
+    /// Chance that each tile contains a landmark of the given type.
+    pub landmark_chances: HashMap<String, f32>,
+    /// Chance that each tile contains a unit of the given type.
+    pub unit_chances: HashMap<String, f32>,
+    /// Chance that each tile contains a structure of the given type.
+    pub structure_chances: HashMap<String, f32>,
+    /// How each kind of structure is scattered across the map.
+    #[serde(default)]
+    pub structure_placement: PlacementStrategy,
+    /// Relative probability of generating tiles of each terrain type.
+    pub terrain_weights: HashMap<String, f32>,
+    /// Splits the map into regions with their own terrain weights.
+    ///
+    /// If this is set, `terrain_weights` is not used.
+    #[serde(default)]
+    pub biomes: Option<RawBiomeSettings>,
+    /// Removes isolated specks of terrain after it is generated.
+    ///
+    /// Disabled by default.
+    #[serde(default)]
+    pub terrain_smoothing: TerrainSmoothing,
+    /// Surrounds the map with a ring of impassable terrain, so units cannot reach its edge.
+    ///
+    /// Disabled by default.
+    #[serde(default)]
+    pub impassable_border: bool,
+    /// How far units can climb up and drop down between neighboring tiles.
+    ///
+    /// Uses [`TraversalConfig::IN_GAME`] by default.
+    #[serde(default)]
+    pub traversal: TraversalConfig,
+    /// A hand-drawn layout of the terrain.
+    ///
+    /// If this is set, `terrain_weights`, `biomes` and `terrain_smoothing` are not used.
+    #[serde(default)]
+    pub terrain_map: Option<RawTerrainMap>,
+    /// Controls the noise added to produce the larger land forms.
+    pub low_frequency_noise: SimplexSettings,
+    /// Controls the noise added to the terrain heights.
+    pub high_frequency_noise: SimplexSettings,
+}
+
+/// The serialized form of the biomes used to generate a map.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawBiomeSettings {
+    /// The number of regions the map is split into.
+    pub regions: u32,
+    /// The relative probability of generating tiles of each terrain type, for each biome.
+    pub terrain_weights: HashMap<String, HashMap<String, f32>>,
+}
+
+/// The serialized form of a hand-drawn terrain layout.
+///
+/// The map is drawn in the format produced by [`render_ascii_map`](crate::geometry::render_ascii_map).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawTerrainMap {
+    /// The name of the terrain type drawn with each character.
+    pub legend: HashMap<char, String>,
+    /// The lines of the map, which must cover every tile within `map_radius`.
+    pub rows: Vec<String>,
+    /// Terrain types for individual tiles, which replace those drawn in `rows`.
+    #[serde(default)]
+    pub overrides: Vec<RawTerrainOverride>,
+}
+
+/// The terrain type of a single tile, in a [`RawTerrainMap`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawTerrainOverride {
+    /// The x coordinate of the tile's [`Hex`].
+    pub x: i32,
+    /// The y coordinate of the tile's [`Hex`].
+    pub y: i32,
+    /// The name of the terrain type.
+    pub terrain: String,
+}
+
+/// The ways in which loading a [`GenerationConfig`] from a file can fail.
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The file could not be read.
+    Io(std::io::Error),
+    /// The file is not valid JSON, or does not match [`RawGenerationConfig`].
+    Parse(serde_json::Error),
+    /// The map radius was outside of [`RawGenerationConfig::MAP_RADIUS_RANGE`].
+    InvalidMapRadius(u32),
+    /// A spawn chance was not between 0 and 1.
+    InvalidChance {
+        /// The name of the organism or landmark.
+        name: String,
+        /// The invalid chance.
+        chance: f32,
+    },
+    /// The chances of placing something on each tile add up to more than 1,
+    /// so more would be requested than there are tiles to hold them.
+    Overcrowded {
+        /// Which kind of chances were too high.
+        kind: &'static str,
+        /// The sum of those chances.
+        total_chance: f32,
+    },
+    /// The falloff curve of `structure_placement` was malformed.
+    InvalidPlacement(CurveError),
+    /// The terrain weights could not be used.
+    TerrainWeights(TerrainWeightsError),
+    /// The smoothing threshold could never be met, or would always be met.
+    InvalidSmoothingThreshold(u32),
+    /// Biomes were requested, but there were no regions or no biomes to fill them with.
+    NoBiomes,
+    /// The traversal thresholds were not finite, or units could not climb a single step or drop as far as they climb.
+    InvalidTraversal(TraversalConfig),
+    /// The hand-drawn terrain map could not be read.
+    TerrainMap(AsciiMapParseError),
+    /// A terrain override was for a tile that is not on the map.
+    OverrideOffMap {
+        /// The x coordinate of the tile.
+        x: i32,
+        /// The y coordinate of the tile.
+        y: i32,
+    },
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::Io(error) => write!(f, "could not read the generation config: {error}"),
+            ConfigError::Parse(error) => {
+                write!(f, "could not parse the generation config: {error}")
+            }
+            ConfigError::InvalidMapRadius(map_radius) => write!(
+                f,
+                "map_radius must be between {} and {}, but was {map_radius}",
+                RawGenerationConfig::MAP_RADIUS_RANGE.start(),
+                RawGenerationConfig::MAP_RADIUS_RANGE.end()
+            ),
+            ConfigError::InvalidChance { name, chance } => write!(
+                f,
+                "the spawn chance for {name} must be between 0 and 1, but was {chance}"
+            ),
+            ConfigError::Overcrowded { kind, total_chance } => write!(
+                f,
+                "the {kind} chances add up to {total_chance}, but there can be at most one per tile: their sum must not exceed 1"
+            ),
+            ConfigError::InvalidPlacement(error) => {
+                write!(f, "structure_placement has an invalid falloff: {error}")
+            }
+            ConfigError::TerrainWeights(error) => write!(f, "{error}"),
+            ConfigError::InvalidSmoothingThreshold(min_neighbors) => write!(
+                f,
+                "terrain_smoothing.min_neighbors must be between 1 and 6, but was {min_neighbors}"
+            ),
+            ConfigError::NoBiomes => {
+                write!(f, "biomes must have at least one region and at least one biome")
+            }
+            ConfigError::InvalidTraversal(traversal) => write!(
+                f,
+                "traversal.max_climb must be at least 1 and no more than traversal.max_drop, but they were {} and {}",
+                traversal.max_climb, traversal.max_drop
+            ),
+            ConfigError::TerrainMap(error) => write!(f, "could not read the terrain map: {error}"),
+            ConfigError::OverrideOffMap { x, y } => write!(
+                f,
+                "the terrain override for ({x}, {y}) is not within map_radius of the center"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl From<std::io::Error> for ConfigError {
+    fn from(error: std::io::Error) -> Self {
+        ConfigError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for ConfigError {
+    fn from(error: serde_json::Error) -> Self {
+        ConfigError::Parse(error)
+    }
+}
+
+impl From<TerrainWeightsError> for ConfigError {
+    fn from(error: TerrainWeightsError) -> Self {
+        ConfigError::TerrainWeights(error)
+    }
+}
+
+impl From<AsciiMapParseError> for ConfigError {
+    fn from(error: AsciiMapParseError) -> Self {
+        ConfigError::TerrainMap(error)
+    }
+}
+
+impl RawGenerationConfig {
+    /// The map radii that can be generated.
+    ///
+    /// A radius of 0 would be a single tile, while the largest maps take seconds to generate and simulate.
+    pub const MAP_RADIUS_RANGE: RangeInclusive<u32> = 1..=200;
+
+    /// Checks the settings and converts them into a usable [`GenerationConfig`].
+    ///
+    /// The result uses [`GenerationStrategy::Custom`], as its settings do not match any named strategy.
+    pub fn process(self) -> Result<GenerationConfig, ConfigError> {
+        if !Self::MAP_RADIUS_RANGE.contains(&self.map_radius) {
+            return Err(ConfigError::InvalidMapRadius(self.map_radius));
+        }
+
+        let unit_chances = process_chances(self.unit_chances, "unit")?;
+        // Landmarks and structures are placed on the same tiles, so they compete for space
+        let landmark_chances = process_chances(self.landmark_chances, "landmark")?;
+        let structure_chances = process_chances(self.structure_chances, "structure")?;
+        let total_chance =
+            landmark_chances.values().sum::<f32>() + structure_chances.values().sum::<f32>();
+        if total_chance > 1. {
+            return Err(ConfigError::Overcrowded {
+                kind: "structure and landmark",
+                total_chance,
+            });
+        }
+
+        self.structure_placement
+            .validate()
+            .map_err(ConfigError::InvalidPlacement)?;
+
+        let terrain_weights = process_terrain_weights(self.terrain_weights)?;
+
+        let biomes = match self.biomes {
+            Some(raw_biomes) => {
+                let biomes = raw_biomes
+                    .terrain_weights
+                    .into_iter()
+                    .map(|(name, weights)| {
+                        Ok((Id::from_name(name), process_terrain_weights(weights)?))
+                    })
+                    .collect::<Result<Vec<_>, ConfigError>>()?;
+                Some(BiomeSettings::new(raw_biomes.regions, biomes).ok_or(ConfigError::NoBiomes)?)
+            }
+            None => None,
+        };
+
+        let min_neighbors = self.terrain_smoothing.min_neighbors;
+        if !(1..=6).contains(&min_neighbors) {
+            return Err(ConfigError::InvalidSmoothingThreshold(min_neighbors));
+        }
+
+        if !self.traversal.is_valid() {
+            return Err(ConfigError::InvalidTraversal(self.traversal));
+        }
+
+        let terrain_layout = match self.terrain_map {
+            Some(terrain_map) => Some(terrain_map.process(self.map_radius)?),
+            None => None,
+        };
+
+        Ok(GenerationConfig {
+            seed: self.seed,
+            map_radius: self.map_radius,
+            number_of_burn_in_ticks: self.number_of_burn_in_ticks,
+            landmark_chances,
+            unit_chances,
+            structure_chances,
+            structure_placement: self.structure_placement,
+            terrain_weights,
+            biomes,
+            terrain_smoothing: self.terrain_smoothing,
+            impassable_border: self.impassable_border,
+            traversal: self.traversal,
+            terrain_layout,
+            low_frequency_noise: self.low_frequency_noise,
+            high_frequency_noise: self.high_frequency_noise,
+            difficulty: Difficulty::Normal,
+            strategy: GenerationStrategy::Custom,
+        })
+    }
+}
+
+impl RawTerrainMap {
+    /// Reads the terrain type of every tile of a map with the provided `radius`.
+    ///
+    /// Overrides take precedence over the drawn map.
+    fn process(self, radius: u32) -> Result<HashMap<Hex, Id<Terrain>>, ConfigError> {
+        let legend: HashMap<char, Id<Terrain>> = self
+            .legend
+            .into_iter()
+            .map(|(symbol, name)| (symbol, Id::from_name(name)))
+            .collect();
+
+        let mut terrain_layout = parse_ascii_map(&self.rows.join("\n"), radius, &legend)?;
+
+        for RawTerrainOverride { x, y, terrain } in self.overrides {
+            let hex = Hex::new(x, y);
+            if hex.unsigned_distance_to(Hex::ZERO) > radius {
+                return Err(ConfigError::OverrideOffMap { x, y });
+            }
+
+            terrain_layout.insert(hex, Id::from_name(terrain));
+        }
+
+        Ok(terrain_layout)
+    }
+}
+
+/// Converts the terrain weights from names to [`Id`]s, and checks that they can be used.
+fn process_terrain_weights(weights: HashMap<String, f32>) -> Result<TerrainWeights, ConfigError> {
+    let terrain_weights = weights
+        .into_iter()
+        .fold(TerrainWeights::builder(), |builder, (name, weight)| {
+            builder.with(Id::from_name(name), weight)
+        })
+        .build()?;
+
+    Ok(terrain_weights)
+}
+
+/// Checks that each of the `chances` is a probability, and that they add up to at most 1.
+fn process_chances<T>(
+    chances: HashMap<String, f32>,
+    kind: &'static str,
+) -> Result<HashMap<Id<T>, f32>, ConfigError> {
+    let mut total_chance = 0.;
+    let mut processed = HashMap::default();
+
+    for (name, chance) in chances {
+        if !(0. ..=1.).contains(&chance) {
+            return Err(ConfigError::InvalidChance { name, chance });
+        }
+
+        total_chance += chance;
+        processed.insert(Id::from_name(name), chance);
+    }
+
+    if total_chance > 1. {
+        return Err(ConfigError::Overcrowded { kind, total_chance });
+    }
+
+    Ok(processed)
+}
+
+impl GenerationConfig {
+    /// Reads a [`RawGenerationConfig`] from the JSON file at `path`, and processes it.
+    ///
+    /// Map codes only record the strategy, difficulty, radius and seed,
+    /// so worlds generated from a file cannot be shared that way:
+    /// the config is marked as [`GenerationStrategy::Custom`] instead.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let contents = std::fs::read_to_string(path)?;
+        GenerationConfig::from_json(&contents)
+    }
+
+    /// Parses a [`RawGenerationConfig`] from a JSON string, and processes it.
+    pub fn from_json(json: &str) -> Result<Self, ConfigError> {
+        let raw: RawGenerationConfig = serde_json::from_str(json)?;
+        raw.process()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use crate::{
+        asset_management::manifest::DummyManifestPlugin,
+        geometry::{render_ascii_map, terrain_glyphs, AsciiMapOptions, Height, MapGeometry},
+        simulation::rng::GlobalRng,
+        structures::structure_manifest::Structure,
+        terrain::terrain_manifest::TerrainManifest,
+        units::unit_manifest::Unit,
+        utils::curves::Curve,
+        world_gen::terrain_generation::generate_terrain,
+    };
+
+    use super::*;
+
+    /// The path to the example config that ships with the game.
+    const SAMPLE_PATH: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../emergence_game/assets/generation.json"
+    );
+
+    /// A small, valid config.
+    fn raw_config() -> RawGenerationConfig {
+        RawGenerationConfig {
+            seed: 7,
+            map_radius: 5,
+            number_of_burn_in_ticks: 0,
+            landmark_chances: HashMap::from_iter([("spring".to_string(), 0.01)]),
+            unit_chances: HashMap::from_iter([("basket_crab".to_string(), 0.1)]),
+            structure_chances: HashMap::from_iter([("acacia".to_string(), 0.2)]),
+            structure_placement: PlacementStrategy::exponential_clusters(2, 3.),
+            terrain_weights: HashMap::from_iter([
+                ("grassy".to_string(), 1.),
+                ("rocky".to_string(), 0.5),
+            ]),
+            biomes: Some(RawBiomeSettings {
+                regions: 4,
+                terrain_weights: HashMap::from_iter([
+                    (
+                        "plains".to_string(),
+                        HashMap::from_iter([("grassy".to_string(), 1.)]),
+                    ),
+                    (
+                        "marsh".to_string(),
+                        HashMap::from_iter([
+                            ("swampy".to_string(), 1.),
+                            ("grassy".to_string(), 0.2),
+                        ]),
+                    ),
+                ]),
+            }),
+            terrain_smoothing: TerrainSmoothing {
+                iterations: 2,
+                min_neighbors: 4,
+            },
+            impassable_border: true,
+            traversal: TraversalConfig {
+                max_climb: Height::ONE,
+                max_drop: Height(3.),
+            },
+            terrain_map: None,
+            low_frequency_noise: SimplexSettings {
+                frequency: 1e-2,
+                amplitude: 8.0,
+                octaves: 4,
+                lacunarity: 1.,
+                gain: 0.5,
+            },
+            high_frequency_noise: SimplexSettings {
+                frequency: 0.1,
+                amplitude: 1.0,
+                octaves: 2,
+                lacunarity: 2.3,
+                gain: 0.5,
+            },
+        }
+    }
+
+    /// A radius 1 map of grass, with rock in the center.
+    fn terrain_map() -> RawTerrainMap {
+        RawTerrainMap {
+            legend: HashMap::from_iter([('g', "grassy".to_string()), ('r', "rocky".to_string())]),
+            rows: ["  g", "g   g", "  g", "g   g", "  g"]
+                .map(String::from)
+                .to_vec(),
+            overrides: vec![RawTerrainOverride {
+                x: 0,
+                y: 0,
+                terrain: "rocky".to_string(),
+            }],
+        }
+    }
+
+    /// Generates the terrain described by `config`.
+    fn generate(config: GenerationConfig) -> App {
+        let mut app = App::new();
+        app.add_plugin(DummyManifestPlugin);
+        app.insert_resource(config);
+        app.insert_resource(GlobalRng::new(0));
+        app.add_startup_system(generate_terrain);
+        app.update();
+        app
+    }
+
+    /// The terrain type of every tile of a generated world.
+    fn generated_terrain(app: &App) -> HashMap<Hex, Id<Terrain>> {
+        let map_geometry = app.world.resource::<MapGeometry>();
+        map_geometry
+            .all_hexes()
+            .map(|&hex| {
+                let entity = map_geometry.get_terrain(hex).unwrap();
+                (hex, *app.world.get::<Id<Terrain>>(entity).unwrap())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn raw_config_round_trips_through_json() {
+        let mut raw = raw_config();
+        raw.terrain_map = Some(terrain_map());
+        let json = serde_json::to_string_pretty(&raw).unwrap();
+        let parsed: RawGenerationConfig = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(parsed, raw);
+    }
+
+    #[test]
+    fn processing_preserves_settings() {
+        let config = raw_config().process().unwrap();
+
+        assert_eq!(config.seed, 7);
+        assert_eq!(config.map_radius, 5);
+        assert_eq!(
+            config.structure_chances[&Id::<Structure>::from_name("acacia".to_string())],
+            0.2
+        );
+        assert_eq!(
+            config.unit_chances[&Id::<Unit>::from_name("basket_crab".to_string())],
+            0.1
+        );
+        assert!(config.impassable_border);
+    }
+
+    #[test]
+    fn sample_config_is_valid() {
+        let config = GenerationConfig::from_file(SAMPLE_PATH).unwrap();
+        let standard = GenerationConfig::standard();
+
+        assert_eq!(config.map_radius, standard.map_radius);
+        assert_eq!(config.unit_chances, standard.unit_chances);
+        assert_eq!(config.structure_chances, standard.structure_chances);
+        assert_eq!(config.landmark_chances, standard.landmark_chances);
+    }
+
+    #[test]
+    fn unknown_fields_are_rejected() {
+        let mut json: serde_json::Value = serde_json::to_value(raw_config()).unwrap();
+        json["n_ants"] = serde_json::Value::from(3);
+
+        let result = GenerationConfig::from_json(&json.to_string());
+        assert!(matches!(result, Err(ConfigError::Parse(_))));
+    }
+
+    #[test]
+    fn missing_files_are_reported() {
+        let result = GenerationConfig::from_file("this/file/does/not/exist.json");
+        assert!(matches!(result, Err(ConfigError::Io(_))));
+    }
+
+    #[test]
+    fn map_radius_must_be_in_range() {
+        let mut raw = raw_config();
+        raw.map_radius = 0;
+        assert!(matches!(
+            raw.process(),
+            Err(ConfigError::InvalidMapRadius(0))
+        ));
+
+        let mut raw = raw_config();
+        raw.map_radius = u16::MAX as u32 + 1;
+        assert!(matches!(
+            raw.process(),
+            Err(ConfigError::InvalidMapRadius(65536))
+        ));
+
+        let mut raw = raw_config();
+        raw.map_radius = *RawGenerationConfig::MAP_RADIUS_RANGE.end();
+        assert!(raw.process().is_ok());
+    }
+
+    #[test]
+    fn loaded_configs_are_custom_and_have_no_map_code() {
+        let config = raw_config().process().unwrap();
+
+        assert_eq!(config.strategy, GenerationStrategy::Custom);
+        assert_eq!(
+            crate::world_gen::MapCode::encode(&config),
+            Err(crate::world_gen::MapCodeError::CustomSettings)
+        );
+    }
+
+    #[test]
+    fn overcrowded_maps_are_rejected() {
+        let mut raw = raw_config();
+        raw.unit_chances.insert("ant".to_string(), 0.95);
+
+        let error = raw.process().unwrap_err();
+        assert!(matches!(
+            error,
+            ConfigError::Overcrowded { kind: "unit", .. }
+        ));
+        assert!(error.to_string().contains("at most one per tile"));
+
+        let mut raw = raw_config();
+        raw.landmark_chances.insert("big_rock".to_string(), 0.9);
+        assert!(matches!(
+            raw.process(),
+            Err(ConfigError::Overcrowded {
+                kind: "structure and landmark",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn invalid_chances_and_weights_are_rejected() {
+        let mut raw = raw_config();
+        raw.structure_chances.insert("leuco".to_string(), -0.1);
+        assert!(matches!(
+            raw.process(),
+            Err(ConfigError::InvalidChance { name, .. }) if name == "leuco"
+        ));
+
+        let mut raw = raw_config();
+        raw.terrain_smoothing.min_neighbors = 7;
+        assert!(matches!(
+            raw.process(),
+            Err(ConfigError::InvalidSmoothingThreshold(7))
+        ));
+
+        let mut raw = raw_config();
+        raw.biomes.as_mut().unwrap().regions = 0;
+        assert!(matches!(raw.process(), Err(ConfigError::NoBiomes)));
+
+        let mut raw = raw_config();
+        raw.biomes
+            .as_mut()
+            .unwrap()
+            .terrain_weights
+            .insert("desert".to_string(), HashMap::default());
+        assert!(matches!(
+            raw.process(),
+            Err(ConfigError::TerrainWeights(
+                TerrainWeightsError::NoPositiveWeight
+            ))
+        ));
+
+        let mut raw = raw_config();
+        raw.terrain_weights.insert("swampy".to_string(), f32::NAN);
+        assert!(matches!(
+            raw.process(),
+            Err(ConfigError::TerrainWeights(
+                TerrainWeightsError::NonFinite { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn malformed_falloff_curves_are_rejected() {
+        let mut raw = raw_config();
+        raw.structure_placement = PlacementStrategy::Clustered {
+            clusters: 2,
+            falloff: Curve::PiecewiseLinear {
+                points: vec![(3., 0.), (0., 1.)],
+            },
+        };
+
+        assert!(matches!(
+            raw.process(),
+            Err(ConfigError::InvalidPlacement(CurveError::UnsortedPoints {
+                index: 1
+            }))
+        ));
+    }
+
+    #[test]
+    fn invalid_traversal_thresholds_are_rejected() {
+        for (max_climb, max_drop) in [(0., 2.), (2., 1.), (1., f32::INFINITY), (f32::NAN, 2.)] {
+            let mut raw = raw_config();
+            raw.traversal = TraversalConfig {
+                max_climb: Height(max_climb),
+                max_drop: Height(max_drop),
+            };
+
+            assert!(matches!(
+                raw.process(),
+                Err(ConfigError::InvalidTraversal(_))
+            ));
+        }
+
+        let config = raw_config().process().unwrap();
+        assert_eq!(config.traversal.max_drop, Height(3.));
+    }
+
+    #[test]
+    fn explicit_tiles_override_drawn_tiles() {
+        let mut raw = raw_config();
+        raw.map_radius = 1;
+        raw.terrain_map = Some(terrain_map());
+
+        let app = generate(raw.process().unwrap());
+        let terrain = generated_terrain(&app);
+
+        assert_eq!(terrain.len(), 7);
+        assert_eq!(terrain[&Hex::ZERO], Id::from_name("rocky".to_string()));
+        let grassy = Id::from_name("grassy".to_string());
+        assert_eq!(terrain.values().filter(|&&id| id == grassy).count(), 6);
+    }
+
+    #[test]
+    fn generated_worlds_reload_from_their_ascii_map() {
+        let mut original = generate(GenerationConfig::testing());
+        let rendered = render_ascii_map(
+            &mut original.world,
+            &AsciiMapOptions {
+                show_objects: false,
+                ..Default::default()
+            },
+        );
+
+        let terrain_manifest = original.world.resource::<TerrainManifest>();
+        let legend = terrain_glyphs(terrain_manifest)
+            .into_iter()
+            .map(|(id, glyph)| (glyph, terrain_manifest.name(id).to_string()))
+            .collect();
+
+        let mut raw = raw_config();
+        raw.map_radius = GenerationConfig::testing().map_radius;
+        raw.terrain_map = Some(RawTerrainMap {
+            legend,
+            rows: rendered.lines().map(String::from).collect(),
+            overrides: Vec::new(),
+        });
+
+        let reloaded = generate(raw.process().unwrap());
+        assert_eq!(generated_terrain(&reloaded), generated_terrain(&original));
+    }
+
+    #[test]
+    fn invalid_terrain_maps_are_rejected() {
+        let mut raw = raw_config();
+        raw.map_radius = 1;
+        let mut bad_symbol = terrain_map();
+        bad_symbol.rows[3] = "g   x".to_string();
+        raw.terrain_map = Some(bad_symbol);
+        assert!(matches!(
+            raw.process(),
+            Err(ConfigError::TerrainMap(AsciiMapParseError::UnknownSymbol {
+                symbol: 'x',
+                line: 4,
+                column: 5
+            }))
+        ));
+
+        let mut raw = raw_config();
+        raw.map_radius = 1;
+        let mut off_map = terrain_map();
+        off_map.overrides[0].x = 2;
+        raw.terrain_map = Some(off_map);
+        assert!(matches!(
+            raw.process(),
+            Err(ConfigError::OverrideOffMap { x: 2, y: 0 })
+        ));
+    }
+}
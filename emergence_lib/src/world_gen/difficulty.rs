@@ -0,0 +1,337 @@
+//! Difficulty presets, which adjust the world generation settings and the pressures of the simulation.
+
+use bevy::{
+    prelude::{Commands, Res},
+    reflect::{FromReflect, Reflect},
+};
+use std::{fmt::Display, str::FromStr};
+
+use crate::{organisms::energy::UnitEnergyDrain, simulation::weather::WeatherChances};
+
+use super::GenerationConfig;
+
+/// A named bundle of adjustments applied on top of a [`GenerationConfig`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect, FromReflect)]
+pub enum Difficulty {
+    /// Abundant plants and water, and units use energy more slowly.
+    Peaceful,
+    /// The settings of the provided [`GenerationConfig`] are used unchanged.
+    #[default]
+    Normal,
+    /// Scarce plants, water and rain.
+    Harsh,
+}
+
+impl Difficulty {
+    /// Every difficulty level, from easiest to hardest.
+    pub const ALL: [Difficulty; 3] = [Difficulty::Peaceful, Difficulty::Normal, Difficulty::Harsh];
+
+    /// The overrides that define this difficulty level.
+    pub fn preset(self) -> DifficultyPreset {
+        match self {
+            Difficulty::Peaceful => DifficultyPreset {
+                structure_chance_multiplier: Some(1.5),
+                landmark_chance_multiplier: Some(2.0),
+                unit_energy_drain_multiplier: Some(0.5),
+                ..Default::default()
+            },
+            Difficulty::Normal => DifficultyPreset::default(),
+            Difficulty::Harsh => DifficultyPreset {
+                structure_chance_multiplier: Some(0.5),
+                landmark_chance_multiplier: Some(0.5),
+                unit_chance_multiplier: Some(0.75),
+                // Dry, windless days are common, and rain is rare
+                weather_chances: Some(WeatherChances {
+                    clear: 2.,
+                    still: 2.,
+                    cloudy: 1.,
+                    rainy: 0.5,
+                }),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+impl Display for Difficulty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Difficulty::Peaceful => "peaceful",
+            Difficulty::Normal => "normal",
+            Difficulty::Harsh => "harsh",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = UnknownDifficulty;
+
+    /// Parses the lowercase name of a difficulty level, as produced by its [`Display`] impl.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Difficulty::ALL
+            .into_iter()
+            .find(|difficulty| difficulty.to_string() == s)
+            .ok_or_else(|| UnknownDifficulty(s.to_string()))
+    }
+}
+
+/// The provided name did not match any [`Difficulty`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownDifficulty(pub String);
+
+impl Display for UnknownDifficulty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "unknown difficulty {}, expected peaceful, normal or harsh",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for UnknownDifficulty {}
+
+/// Overrides applied by a [`Difficulty`].
+///
+/// The spawn chances adjust the [`GenerationConfig`], while the unit energy and weather sections adjust the simulation.
+/// Fields that are [`None`] leave the corresponding settings untouched.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DifficultyPreset {
+    /// Scales the chance that each tile contains a unit.
+    pub unit_chance_multiplier: Option<f32>,
+    /// Scales the chance that each tile contains a structure, such as a plant.
+    pub structure_chance_multiplier: Option<f32>,
+    /// Scales the chance that each tile contains a landmark, such as a spring.
+    pub landmark_chance_multiplier: Option<f32>,
+    /// Scales how quickly units lose energy, as stored in the [`UnitEnergyDrain`].
+    pub unit_energy_drain_multiplier: Option<f32>,
+    /// Replaces the chance of each kind of weather being rolled each day.
+    pub weather_chances: Option<WeatherChances>,
+}
+
+impl DifficultyPreset {
+    /// Modifies the `config` according to the spawn chance overrides in this preset.
+    ///
+    /// Chances are capped at 1.
+    pub fn apply(&self, config: &mut GenerationConfig) {
+        if let Some(multiplier) = self.unit_chance_multiplier {
+            for chance in config.unit_chances.values_mut() {
+                *chance = (*chance * multiplier).min(1.);
+            }
+        }
+
+        if let Some(multiplier) = self.structure_chance_multiplier {
+            for chance in config.structure_chances.values_mut() {
+                *chance = (*chance * multiplier).min(1.);
+            }
+        }
+
+        if let Some(multiplier) = self.landmark_chance_multiplier {
+            for chance in config.landmark_chances.values_mut() {
+                *chance = (*chance * multiplier).min(1.);
+            }
+        }
+    }
+}
+
+/// Sets the simulation resources controlled by the [`Difficulty`] of the world that is about to be generated.
+///
+/// The resources are replaced outright, so regenerating the map never stacks presets.
+/// The spawn chances are applied separately by each generation step, as the stored [`GenerationConfig`] must stay unmodified.
+pub(super) fn apply_difficulty_preset(config: Res<GenerationConfig>, mut commands: Commands) {
+    let preset = config.difficulty.preset();
+
+    commands.insert_resource(UnitEnergyDrain {
+        multiplier: preset.unit_energy_drain_multiplier.unwrap_or(1.),
+    });
+    commands.insert_resource(preset.weather_chances.unwrap_or_default());
+}
+
+impl GenerationConfig {
+    /// Sets the [`GenerationConfig::difficulty`], replacing any previous choice.
+    ///
+    /// Presets do not stack: the spawn chances stored in the config are left untouched,
+    /// and the preset is only applied when the world is generated.
+    pub fn with_difficulty(mut self, difficulty: Difficulty) -> Self {
+        self.difficulty = difficulty;
+        self
+    }
+
+    /// A copy of this config with the preset for its [`Difficulty`] applied to the spawn chances.
+    pub(super) fn with_preset_applied(&self) -> GenerationConfig {
+        let mut config = self.clone();
+        self.difficulty.preset().apply(&mut config);
+        config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::App;
+
+    use crate::asset_management::manifest::Id;
+
+    use super::*;
+
+    #[test]
+    fn normal_difficulty_changes_nothing() {
+        let standard = GenerationConfig::standard();
+        let normal = GenerationConfig::standard()
+            .with_difficulty(Difficulty::Normal)
+            .with_preset_applied();
+
+        assert_eq!(standard.unit_chances, normal.unit_chances);
+        assert_eq!(standard.structure_chances, normal.structure_chances);
+        assert_eq!(standard.landmark_chances, normal.landmark_chances);
+    }
+
+    #[test]
+    fn presets_scale_spawn_chances() {
+        let acacia = Id::from_name("acacia".to_string());
+        let spring = Id::from_name("spring".to_string());
+        let crab = Id::from_name("basket_crab".to_string());
+
+        let standard = GenerationConfig::standard();
+        let peaceful = GenerationConfig::standard()
+            .with_difficulty(Difficulty::Peaceful)
+            .with_preset_applied();
+        let harsh = GenerationConfig::standard()
+            .with_difficulty(Difficulty::Harsh)
+            .with_preset_applied();
+
+        assert_eq!(peaceful.difficulty, Difficulty::Peaceful);
+        assert_eq!(
+            peaceful.structure_chances[&acacia],
+            standard.structure_chances[&acacia] * 1.5
+        );
+        assert_eq!(
+            peaceful.landmark_chances[&spring],
+            standard.landmark_chances[&spring] * 2.0
+        );
+        assert_eq!(peaceful.unit_chances[&crab], standard.unit_chances[&crab]);
+
+        assert_eq!(harsh.difficulty, Difficulty::Harsh);
+        assert_eq!(
+            harsh.structure_chances[&acacia],
+            standard.structure_chances[&acacia] * 0.5
+        );
+        assert_eq!(
+            harsh.landmark_chances[&spring],
+            standard.landmark_chances[&spring] * 0.5
+        );
+        assert_eq!(
+            harsh.unit_chances[&crab],
+            standard.unit_chances[&crab] * 0.75
+        );
+    }
+
+    #[test]
+    fn chances_are_capped() {
+        let config = GenerationConfig::testing()
+            .with_difficulty(Difficulty::Peaceful)
+            .with_preset_applied();
+        assert!(config
+            .structure_chances
+            .values()
+            .all(|&chance| chance <= 1.));
+
+        let preset = DifficultyPreset {
+            unit_chance_multiplier: Some(10.),
+            ..Default::default()
+        };
+        let mut config = GenerationConfig::testing();
+        preset.apply(&mut config);
+        assert!(config.unit_chances.values().all(|&chance| chance == 1.));
+    }
+
+    #[test]
+    fn partial_presets_leave_other_settings_untouched() {
+        let preset = DifficultyPreset {
+            structure_chance_multiplier: Some(3.0),
+            ..Default::default()
+        };
+
+        let standard = GenerationConfig::standard();
+        let mut config = GenerationConfig::standard();
+        preset.apply(&mut config);
+        assert_ne!(config.structure_chances, standard.structure_chances);
+        assert_eq!(config.unit_chances, standard.unit_chances);
+        assert_eq!(config.landmark_chances, standard.landmark_chances);
+    }
+
+    /// Runs [`apply_difficulty_preset`] for a world of the given `difficulty`.
+    fn simulation_settings(difficulty: Difficulty) -> (UnitEnergyDrain, WeatherChances) {
+        let mut app = App::new();
+        app.insert_resource(GenerationConfig::testing().with_difficulty(difficulty))
+            .add_system(apply_difficulty_preset);
+        app.update();
+
+        (
+            *app.world.resource::<UnitEnergyDrain>(),
+            app.world.resource::<WeatherChances>().clone(),
+        )
+    }
+
+    #[test]
+    fn presets_adjust_the_simulation() {
+        let (drain, weather) = simulation_settings(Difficulty::Normal);
+        assert_eq!(drain, UnitEnergyDrain::default());
+        assert_eq!(weather, WeatherChances::default());
+
+        let (drain, weather) = simulation_settings(Difficulty::Peaceful);
+        assert_eq!(drain.multiplier, 0.5);
+        assert_eq!(weather, WeatherChances::default());
+
+        let (drain, weather) = simulation_settings(Difficulty::Harsh);
+        assert_eq!(drain, UnitEnergyDrain::default());
+        assert!(weather.rainy < WeatherChances::default().rainy);
+        assert_eq!(
+            weather,
+            WeatherChances {
+                clear: 2.,
+                still: 2.,
+                cloudy: 1.,
+                rainy: 0.5,
+            }
+        );
+    }
+
+    #[test]
+    fn setting_difficulty_is_idempotent() {
+        let acacia = Id::from_name("acacia".to_string());
+
+        let harsh = GenerationConfig::standard()
+            .with_difficulty(Difficulty::Harsh)
+            .with_preset_applied();
+        let twice = GenerationConfig::standard()
+            .with_difficulty(Difficulty::Harsh)
+            .with_difficulty(Difficulty::Harsh)
+            .with_preset_applied();
+        assert_eq!(
+            twice.structure_chances[&acacia],
+            harsh.structure_chances[&acacia]
+        );
+
+        let standard = GenerationConfig::standard();
+        let restored = GenerationConfig::standard()
+            .with_difficulty(Difficulty::Peaceful)
+            .with_difficulty(Difficulty::Normal)
+            .with_preset_applied();
+        assert_eq!(restored.difficulty, Difficulty::Normal);
+        assert_eq!(restored.structure_chances, standard.structure_chances);
+        assert_eq!(restored.landmark_chances, standard.landmark_chances);
+    }
+
+    #[test]
+    fn difficulty_names_round_trip() {
+        for difficulty in Difficulty::ALL {
+            assert_eq!(difficulty.to_string().parse(), Ok(difficulty));
+        }
+
+        assert_eq!(
+            "nightmare".parse::<Difficulty>(),
+            Err(UnknownDifficulty("nightmare".to_string()))
+        );
+    }
+}
@@ -0,0 +1,497 @@
+//! Short, shareable codes that reproduce a generated world.
+//!
+//! A map code packs the seed, map radius, [`GenerationStrategy`] and [`Difficulty`] into 13 bytes:
+//! a 4-bit format version, the settings, and a CRC-16 checksum.
+//! These bytes are then written out using Crockford's base32 alphabet, in groups of 7 characters.
+//!
+//! Settings that are not encoded (such as the exact spawn chances) are taken from the [`GenerationStrategy`],
+//! so codes are only compatible between builds that share the same [`MapCode::VERSION`].
+
+use std::fmt::Display;
+
+use super::{Difficulty, GenerationConfig, GenerationStrategy};
+
+/// Encodes and decodes map codes.
+///
+/// See the [module-level docs](self) for the format.
+pub struct MapCode;
+
+/// The world generation settings that are stored in a map code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MapCodeSettings {
+    /// The seed used to generate the world.
+    pub seed: u64,
+    /// Radius of the map.
+    pub map_radius: u16,
+    /// The named configuration that the world is generated from.
+    pub strategy: GenerationStrategy,
+    /// The difficulty preset applied on top of the strategy.
+    pub difficulty: Difficulty,
+}
+
+impl MapCodeSettings {
+    /// Builds the complete [`GenerationConfig`] described by these settings.
+    ///
+    /// Fails if the strategy is [`GenerationStrategy::Custom`], since its settings are not stored in the code.
+    pub fn into_config(self) -> Result<GenerationConfig, MapCodeError> {
+        let mut config = GenerationConfig::from_strategy(self.strategy)
+            .ok_or(MapCodeError::CustomSettings)?
+            .with_difficulty(self.difficulty);
+        config.seed = self.seed;
+        config.map_radius = self.map_radius as u32;
+        Ok(config)
+    }
+}
+
+/// The reasons that a map code could not be created or read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MapCodeError {
+    /// The map is too large for its radius to be stored in a map code.
+    MapTooLarge {
+        /// The radius of the map.
+        map_radius: u32,
+    },
+    /// The world was generated from custom settings, which do not fit in a map code.
+    CustomSettings,
+    /// No map code was recorded, because the world's settings could not be encoded.
+    NoMapCode,
+    /// The code had the wrong number of characters.
+    WrongLength {
+        /// The number of characters found, ignoring separators.
+        found: usize,
+    },
+    /// The code contained a character outside of the base32 alphabet.
+    InvalidCharacter(char),
+    /// The checksum did not match: the code was mistyped or damaged.
+    ChecksumMismatch,
+    /// The code was created by an incompatible version of the game.
+    UnsupportedVersion {
+        /// The version stored in the code.
+        found: u8,
+    },
+    /// The checksum matched, but the settings stored in the code are not valid.
+    InvalidSettings,
+}
+
+impl Display for MapCodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MapCodeError::MapTooLarge { map_radius } => write!(
+                f,
+                "maps with a radius of {map_radius} are too large for a map code (the limit is {})",
+                u16::MAX
+            ),
+            MapCodeError::CustomSettings => write!(
+                f,
+                "worlds generated from a custom config file cannot be shared with a map code"
+            ),
+            MapCodeError::NoMapCode => {
+                write!(f, "no map code was recorded for this world")
+            }
+            MapCodeError::WrongLength { found } => write!(
+                f,
+                "map codes are {} characters long, but this one has {found}",
+                MapCode::CODE_LENGTH
+            ),
+            MapCodeError::InvalidCharacter(character) => {
+                write!(f, "'{character}' is not a valid map code character")
+            }
+            MapCodeError::ChecksumMismatch => {
+                write!(f, "this map code is damaged: check for typos")
+            }
+            MapCodeError::UnsupportedVersion { found } => write!(
+                f,
+                "this map code was made with an incompatible version of the game (format {found}, expected {})",
+                MapCode::VERSION
+            ),
+            MapCodeError::InvalidSettings => {
+                write!(f, "this map code contains invalid world settings")
+            }
+        }
+    }
+}
+
+impl std::error::Error for MapCodeError {}
+
+impl MapCode {
+    /// The format version written into new codes.
+    ///
+    /// This must be bumped whenever world generation changes such that old codes would produce different worlds.
+    pub const VERSION: u8 = 1;
+
+    /// The symbols used to write out a map code: Crockford's base32, which avoids easily-confused letters.
+    const ALPHABET: &'static [u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+    /// The number of bytes used to store the settings, before the checksum.
+    const PAYLOAD_BYTES: usize = 11;
+
+    /// The total number of bytes in a map code, including the checksum.
+    const TOTAL_BYTES: usize = Self::PAYLOAD_BYTES + 2;
+
+    /// The number of base32 characters in a map code, ignoring separators.
+    const CODE_LENGTH: usize = (Self::TOTAL_BYTES * 8).div_ceil(5);
+
+    /// Characters are displayed in groups of this size, separated by `-`.
+    const GROUP_SIZE: usize = 7;
+
+    /// Creates the map code that reproduces the world generated by `config`.
+    ///
+    /// Returns an error if the map radius does not fit in a [`u16`],
+    /// or if the world was generated from [custom](GenerationStrategy::Custom) settings.
+    pub fn encode(config: &GenerationConfig) -> Result<String, MapCodeError> {
+        let map_radius =
+            u16::try_from(config.map_radius).map_err(|_| MapCodeError::MapTooLarge {
+                map_radius: config.map_radius,
+            })?;
+        let strategy_bits = strategy_to_bits(config.strategy)?;
+
+        let header =
+            (Self::VERSION << 4) | (strategy_bits << 2) | difficulty_to_bits(config.difficulty);
+
+        let mut bytes = Vec::with_capacity(Self::TOTAL_BYTES);
+        bytes.push(header);
+        bytes.extend_from_slice(&map_radius.to_be_bytes());
+        bytes.extend_from_slice(&config.seed.to_be_bytes());
+        bytes.extend_from_slice(&crc16(&bytes).to_be_bytes());
+
+        let characters = to_base32(&bytes);
+        Ok(characters
+            .chunks(Self::GROUP_SIZE)
+            .map(|chunk| chunk.iter().collect::<String>())
+            .collect::<Vec<_>>()
+            .join("-"))
+    }
+
+    /// Reads the world generation settings stored in a map code.
+    ///
+    /// Letters are case-insensitive, and `-` and whitespace are ignored.
+    pub fn decode(code: &str) -> Result<MapCodeSettings, MapCodeError> {
+        let characters: Vec<char> = code
+            .chars()
+            .filter(|character| *character != '-' && !character.is_whitespace())
+            .collect();
+
+        if characters.len() != Self::CODE_LENGTH {
+            return Err(MapCodeError::WrongLength {
+                found: characters.len(),
+            });
+        }
+
+        let bytes = from_base32(&characters)?;
+        let (payload, checksum) = bytes.split_at(Self::PAYLOAD_BYTES);
+        if crc16(payload).to_be_bytes() != checksum {
+            return Err(MapCodeError::ChecksumMismatch);
+        }
+
+        let header = payload[0];
+        let version = header >> 4;
+        if version != Self::VERSION {
+            return Err(MapCodeError::UnsupportedVersion { found: version });
+        }
+
+        let strategy = strategy_from_bits((header >> 2) & 0b11)?;
+        let difficulty = difficulty_from_bits(header & 0b11)?;
+        let map_radius = u16::from_be_bytes([payload[1], payload[2]]);
+        let seed = u64::from_be_bytes(payload[3..11].try_into().unwrap());
+
+        Ok(MapCodeSettings {
+            seed,
+            map_radius,
+            strategy,
+            difficulty,
+        })
+    }
+}
+
+/// Packs a [`GenerationStrategy`] into two bits.
+///
+/// [`GenerationStrategy::Custom`] cannot be packed, as its settings would be lost.
+fn strategy_to_bits(strategy: GenerationStrategy) -> Result<u8, MapCodeError> {
+    match strategy {
+        GenerationStrategy::Standard => Ok(0),
+        GenerationStrategy::Flat => Ok(1),
+        GenerationStrategy::Testing => Ok(2),
+        GenerationStrategy::Custom => Err(MapCodeError::CustomSettings),
+    }
+}
+
+/// Unpacks a [`GenerationStrategy`] from two bits.
+fn strategy_from_bits(bits: u8) -> Result<GenerationStrategy, MapCodeError> {
+    match bits {
+        0 => Ok(GenerationStrategy::Standard),
+        1 => Ok(GenerationStrategy::Flat),
+        2 => Ok(GenerationStrategy::Testing),
+        _ => Err(MapCodeError::InvalidSettings),
+    }
+}
+
+/// Packs a [`Difficulty`] into two bits.
+fn difficulty_to_bits(difficulty: Difficulty) -> u8 {
+    match difficulty {
+        Difficulty::Peaceful => 0,
+        Difficulty::Normal => 1,
+        Difficulty::Harsh => 2,
+    }
+}
+
+/// Unpacks a [`Difficulty`] from two bits.
+fn difficulty_from_bits(bits: u8) -> Result<Difficulty, MapCodeError> {
+    match bits {
+        0 => Ok(Difficulty::Peaceful),
+        1 => Ok(Difficulty::Normal),
+        2 => Ok(Difficulty::Harsh),
+        _ => Err(MapCodeError::InvalidSettings),
+    }
+}
+
+/// Computes the CRC-16/CCITT-FALSE checksum of `bytes`.
+///
+/// This detects every error that is confined to a run of 16 bits or fewer,
+/// which includes any single mistyped character.
+fn crc16(bytes: &[u8]) -> u16 {
+    let mut crc: u16 = 0xFFFF;
+    for &byte in bytes {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// Writes out `bytes` as base32 characters, most significant bit first.
+///
+/// The final character is padded with zero bits.
+fn to_base32(bytes: &[u8]) -> Vec<char> {
+    let mut characters = Vec::new();
+    let mut buffer: u32 = 0;
+    let mut n_bits = 0;
+
+    for &byte in bytes {
+        buffer = (buffer << 8) | byte as u32;
+        n_bits += 8;
+        while n_bits >= 5 {
+            n_bits -= 5;
+            let index = (buffer >> n_bits) & 0b11111;
+            characters.push(MapCode::ALPHABET[index as usize] as char);
+        }
+    }
+
+    if n_bits > 0 {
+        let index = (buffer << (5 - n_bits)) & 0b11111;
+        characters.push(MapCode::ALPHABET[index as usize] as char);
+    }
+
+    characters
+}
+
+/// Reads the bytes stored in base32 `characters`.
+///
+/// Any padding bits must be zero, so that each code has exactly one spelling.
+fn from_base32(characters: &[char]) -> Result<Vec<u8>, MapCodeError> {
+    let mut bytes = Vec::new();
+    let mut buffer: u32 = 0;
+    let mut n_bits = 0;
+
+    for &character in characters {
+        let index = MapCode::ALPHABET
+            .iter()
+            .position(|&symbol| symbol as char == character.to_ascii_uppercase())
+            .ok_or(MapCodeError::InvalidCharacter(character))?;
+
+        buffer = (buffer << 5) | index as u32;
+        n_bits += 5;
+        if n_bits >= 8 {
+            n_bits -= 8;
+            bytes.push((buffer >> n_bits) as u8);
+        }
+    }
+
+    if buffer & ((1 << n_bits) - 1) != 0 {
+        return Err(MapCodeError::ChecksumMismatch);
+    }
+
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use super::*;
+    use crate::asset_management::manifest::DummyManifestPlugin;
+    use crate::geometry::{render_ascii_map, AsciiMapLayer, AsciiMapOptions};
+    use crate::simulation::rng::GlobalRng;
+    use crate::world_gen::structure_generation::generate_structures;
+    use crate::world_gen::terrain_generation::generate_terrain;
+    use crate::world_gen::unit_generation::generate_units;
+
+    /// Generates the world described by `config`, and renders its terrain heights.
+    fn render_terrain(config: GenerationConfig) -> String {
+        let mut app = App::new();
+        app.add_plugin(DummyManifestPlugin);
+        app.insert_resource(GlobalRng::new(config.seed));
+        app.insert_resource(config);
+        app.add_startup_systems((generate_terrain, generate_structures, generate_units).chain());
+
+        app.update();
+
+        render_ascii_map(
+            &mut app.world,
+            &AsciiMapOptions {
+                layer: AsciiMapLayer::Height,
+                show_objects: false,
+                selected: None,
+            },
+        )
+    }
+
+    #[test]
+    fn round_trips_all_fields() {
+        for strategy in [
+            GenerationStrategy::Standard,
+            GenerationStrategy::Flat,
+            GenerationStrategy::Testing,
+        ] {
+            for difficulty in [Difficulty::Peaceful, Difficulty::Normal, Difficulty::Harsh] {
+                for (seed, map_radius) in [(0, 0), (42, 30), (u64::MAX, u16::MAX)] {
+                    let settings = MapCodeSettings {
+                        seed,
+                        map_radius,
+                        strategy,
+                        difficulty,
+                    };
+
+                    let code = MapCode::encode(&settings.into_config().unwrap()).unwrap();
+                    assert_eq!(MapCode::decode(&code), Ok(settings), "Code: {code}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn decoding_is_forgiving_about_formatting() {
+        let config = GenerationConfig::standard();
+        let code = MapCode::encode(&config).unwrap();
+        let expected = MapCode::decode(&code).unwrap();
+
+        let lowercase = code.to_lowercase();
+        assert_eq!(MapCode::decode(&lowercase), Ok(expected));
+
+        let unseparated = code.replace('-', "");
+        assert_eq!(MapCode::decode(&unseparated), Ok(expected));
+    }
+
+    #[test]
+    fn single_character_corruption_is_detected() {
+        let mut config = GenerationConfig::standard();
+        config.seed = 0xDEAD_BEEF_1234_5678;
+        let code: Vec<char> = MapCode::encode(&config)
+            .unwrap()
+            .chars()
+            .filter(|character| *character != '-')
+            .collect();
+
+        for i in 0..code.len() {
+            for &symbol in MapCode::ALPHABET {
+                let symbol = symbol as char;
+                if symbol == code[i] {
+                    continue;
+                }
+
+                let mut corrupted = code.clone();
+                corrupted[i] = symbol;
+                let corrupted: String = corrupted.into_iter().collect();
+
+                assert_eq!(
+                    MapCode::decode(&corrupted),
+                    Err(MapCodeError::ChecksumMismatch),
+                    "Corrupted code {corrupted} was accepted"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn incompatible_versions_are_rejected() {
+        let mut bytes = vec![(MapCode::VERSION + 1) << 4];
+        bytes.extend_from_slice(&[0; MapCode::PAYLOAD_BYTES - 1]);
+        bytes.extend_from_slice(&crc16(&bytes).to_be_bytes());
+        let code: String = to_base32(&bytes).into_iter().collect();
+
+        assert_eq!(
+            MapCode::decode(&code),
+            Err(MapCodeError::UnsupportedVersion {
+                found: MapCode::VERSION + 1
+            })
+        );
+    }
+
+    #[test]
+    fn malformed_codes_are_rejected() {
+        assert_eq!(
+            MapCode::decode("ABC"),
+            Err(MapCodeError::WrongLength { found: 3 })
+        );
+
+        let mut code = MapCode::encode(&GenerationConfig::standard()).unwrap();
+        code.replace_range(0..1, "U");
+        assert_eq!(
+            MapCode::decode(&code),
+            Err(MapCodeError::InvalidCharacter('U'))
+        );
+    }
+
+    #[test]
+    fn oversized_maps_are_not_encoded() {
+        let mut config = GenerationConfig::testing();
+        config.map_radius = u16::MAX as u32 + 1;
+
+        assert_eq!(
+            MapCode::encode(&config),
+            Err(MapCodeError::MapTooLarge {
+                map_radius: u16::MAX as u32 + 1
+            })
+        );
+    }
+
+    #[test]
+    fn custom_settings_are_not_encoded() {
+        let mut config = GenerationConfig::testing();
+        config.strategy = GenerationStrategy::Custom;
+        assert_eq!(MapCode::encode(&config), Err(MapCodeError::CustomSettings));
+
+        let settings = MapCodeSettings {
+            seed: 0,
+            map_radius: 3,
+            strategy: GenerationStrategy::Custom,
+            difficulty: Difficulty::Normal,
+        };
+        assert_eq!(
+            settings.into_config().unwrap_err(),
+            MapCodeError::CustomSettings
+        );
+    }
+
+    #[test]
+    fn non_encoded_settings_do_not_change_terrain() {
+        let mut config = GenerationConfig::testing();
+        config.seed = 1337;
+        let code = MapCode::encode(&config).unwrap();
+
+        let original = MapCode::decode(&code).unwrap().into_config().unwrap();
+        let mut crowded = original.clone();
+        for chance in crowded.structure_chances.values_mut() {
+            *chance = 1.;
+        }
+        for chance in crowded.unit_chances.values_mut() {
+            *chance = 0.;
+        }
+
+        assert_eq!(render_terrain(original), render_terrain(crowded));
+    }
+}
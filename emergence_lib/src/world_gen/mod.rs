@@ -1,25 +1,44 @@
 //! Generating starting terrain and organisms
 use crate::asset_management::manifest::Id;
 use crate::asset_management::AssetState;
+use crate::geometry::{MapGeometry, TraversalConfig};
+use crate::simulation::sim_resources::SimResourceAppExt;
 use crate::structures::structure_manifest::Structure;
 use crate::terrain::terrain_manifest::Terrain;
 use crate::units::unit_manifest::Unit;
 use crate::utils::noise::SimplexSettings;
+use crate::world_gen::biomes::BiomeSettings;
 use crate::world_gen::structure_generation::generate_structures;
 use crate::world_gen::unit_generation::{generate_units, randomize_starting_organisms};
 
 use crate::world_gen::terrain_generation::{
-    generate_landmarks, generate_terrain, initialize_water_table,
+    flag_basins, generate_landmarks, generate_terrain, initialize_water_table, TerrainWeights,
 };
 
 use bevy::prelude::*;
 use bevy::utils::HashMap;
 use bevy_framepace::{FramepaceSettings, Limiter};
-
+use hexx::Hex;
+
+mod biomes;
+mod bug_report;
+mod config_file;
+mod difficulty;
+mod map_code;
+mod regeneration;
 mod structure_generation;
 mod terrain_generation;
 mod unit_generation;
 
+pub use biomes::Biome;
+pub use bug_report::{parse_bug_report, BugReport, BugReportError, GameVersion};
+pub use config_file::{ConfigError, RawGenerationConfig, RawTerrainMap, RawTerrainOverride};
+pub use difficulty::{Difficulty, DifficultyPreset, UnknownDifficulty};
+pub use map_code::{MapCode, MapCodeError, MapCodeSettings};
+pub use regeneration::RegenerateMapEvent;
+pub use structure_generation::PlacementStrategy;
+pub use terrain_generation::{TerrainSmoothing, TerrainWeightsError};
+
 /// Generate the world.
 pub(super) struct GenerationPlugin {
     /// Configuration settings for world generation
@@ -30,9 +49,13 @@ impl Plugin for GenerationPlugin {
     fn build(&self, app: &mut App) {
         info!("Building Generation plugin...");
         app.add_state::<WorldGenState>()
+            .add_event::<RegenerateMapEvent>()
             .insert_resource(self.config.clone())
+            // The map itself is only inserted once the terrain is generated
+            .register_sim_resource::<MapGeometry>()
             .add_systems(
                 (
+                    difficulty::apply_difficulty_preset,
                     generate_terrain,
                     apply_system_buffers,
                     generate_landmarks,
@@ -40,6 +63,7 @@ impl Plugin for GenerationPlugin {
                     apply_system_buffers,
                     generate_structures,
                     apply_system_buffers,
+                    flag_basins,
                     generate_units,
                     apply_system_buffers,
                     randomize_starting_organisms,
@@ -47,6 +71,7 @@ impl Plugin for GenerationPlugin {
                     .chain()
                     .in_schedule(OnEnter(WorldGenState::Generating)),
             )
+            .add_system(regeneration::regenerate_map.in_base_set(CoreSet::PreUpdate))
             .add_system(
                 WorldGenState::manage_state
                     .in_base_set(CoreSet::PreUpdate)
@@ -99,6 +124,12 @@ impl WorldGenState {
                 }
             }
             WorldGenState::Generating => {
+                match MapCode::encode(&generation_config) {
+                    Ok(map_code) => info!("Map code: {map_code}"),
+                    Err(error) => warn!("No map code is available: {error}"),
+                }
+                // The map may be regenerated, so burn in must start over
+                *number_of_burn_in_ticks = 0;
                 next_world_gen_state.set(WorldGenState::BurningIn);
             }
             WorldGenState::BurningIn => {
@@ -126,7 +157,7 @@ impl WorldGenState {
 }
 
 /// Controls world generation strategy
-#[derive(Resource, Debug, Clone)]
+#[derive(Resource, Debug, Clone, Reflect)]
 pub struct GenerationConfig {
     /// The seed used to generate the world.
     pub seed: u64,
@@ -140,22 +171,77 @@ pub struct GenerationConfig {
     unit_chances: HashMap<Id<Unit>, f32>,
     /// Chance that each tile contains a structure of the given type.
     structure_chances: HashMap<Id<Structure>, f32>,
+    /// How each kind of structure is scattered across the map.
+    #[reflect(ignore)]
+    structure_placement: PlacementStrategy,
     /// Relative probability of generating tiles of each terrain type.
-    terrain_weights: HashMap<Id<Terrain>, f32>,
+    ///
+    /// This is only used when there are no [`biomes`](Self::biomes).
+    #[reflect(ignore)]
+    terrain_weights: TerrainWeights,
+    /// Splits the map into regions with their own terrain weights, if set.
+    #[reflect(ignore)]
+    biomes: Option<BiomeSettings>,
+    /// Removes isolated specks of terrain after it is generated.
+    terrain_smoothing: TerrainSmoothing,
+    /// Surrounds the map with a ring of [`ImpassableTerrain`](crate::terrain::ImpassableTerrain), one tile thick.
+    impassable_border: bool,
+    /// How far units can climb up and drop down between neighboring tiles.
+    traversal: TraversalConfig,
+    /// The terrain type of every tile, if the map was drawn by hand.
+    ///
+    /// This replaces the [`terrain_weights`](Self::terrain_weights), [`biomes`](Self::biomes)
+    /// and [`terrain_smoothing`](Self::terrain_smoothing).
+    #[reflect(ignore)]
+    terrain_layout: Option<HashMap<Hex, Id<Terrain>>>,
     /// Controls the noise added to produce the larger land forms.
     low_frequency_noise: SimplexSettings,
     /// Controls the noise added to the terrain heights.
     high_frequency_noise: SimplexSettings,
+    /// The difficulty preset that has been applied to this config.
+    pub difficulty: Difficulty,
+    /// The named configuration that these settings were built from.
+    pub strategy: GenerationStrategy,
+}
+
+/// The named starting points for a [`GenerationConfig`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect, FromReflect)]
+pub enum GenerationStrategy {
+    /// See [`GenerationConfig::standard`].
+    #[default]
+    Standard,
+    /// See [`GenerationConfig::flat`].
+    Flat,
+    /// See [`GenerationConfig::testing`].
+    Testing,
+    /// Settings loaded from a [`RawGenerationConfig`], which cannot be rebuilt from a name.
+    ///
+    /// Worlds generated this way cannot be shared with a [`MapCode`].
+    Custom,
 }
 
 impl GenerationConfig {
+    /// Creates the [`GenerationConfig`] corresponding to the provided `strategy`.
+    ///
+    /// Returns [`None`] for [`GenerationStrategy::Custom`], whose settings are not known.
+    pub fn from_strategy(strategy: GenerationStrategy) -> Option<Self> {
+        match strategy {
+            GenerationStrategy::Standard => Some(Self::standard()),
+            GenerationStrategy::Flat => Some(Self::flat()),
+            GenerationStrategy::Testing => Some(Self::testing()),
+            GenerationStrategy::Custom => None,
+        }
+    }
+
     /// The default world generation configuration.
     pub fn standard() -> Self {
-        let mut terrain_weights: HashMap<Id<Terrain>, f32> = HashMap::new();
         // FIXME: load from file somehow
-        terrain_weights.insert(Id::from_name("grassy".to_string()), 1.0);
-        terrain_weights.insert(Id::from_name("swampy".to_string()), 0.3);
-        terrain_weights.insert(Id::from_name("rocky".to_string()), 0.2);
+        let terrain_weights = TerrainWeights::builder()
+            .with(Id::from_name("grassy".to_string()), 1.0)
+            .with(Id::from_name("swampy".to_string()), 0.3)
+            .with(Id::from_name("rocky".to_string()), 0.2)
+            .build()
+            .unwrap();
 
         let mut landmark_chances: HashMap<Id<Structure>, f32> = HashMap::new();
         landmark_chances.insert(Id::from_name("spring".to_string()), 5e-4);
@@ -176,7 +262,13 @@ impl GenerationConfig {
             unit_chances,
             landmark_chances,
             structure_chances,
+            structure_placement: PlacementStrategy::Uniform,
             terrain_weights,
+            biomes: None,
+            terrain_smoothing: TerrainSmoothing::default(),
+            impassable_border: false,
+            traversal: TraversalConfig::IN_GAME,
+            terrain_layout: None,
             low_frequency_noise: SimplexSettings {
                 frequency: 1e-2,
                 amplitude: 8.0,
@@ -191,16 +283,20 @@ impl GenerationConfig {
                 lacunarity: 2.3,
                 gain: 0.5,
             },
+            difficulty: Difficulty::Normal,
+            strategy: GenerationStrategy::Standard,
         }
     }
 
     /// A small flat map for testing.
     pub fn flat() -> Self {
-        let mut terrain_weights: HashMap<Id<Terrain>, f32> = HashMap::new();
         // FIXME: load from file somehow
-        terrain_weights.insert(Id::from_name("grassy".to_string()), 1.0);
-        terrain_weights.insert(Id::from_name("swampy".to_string()), 0.3);
-        terrain_weights.insert(Id::from_name("rocky".to_string()), 0.2);
+        let terrain_weights = TerrainWeights::builder()
+            .with(Id::from_name("grassy".to_string()), 1.0)
+            .with(Id::from_name("swampy".to_string()), 0.3)
+            .with(Id::from_name("rocky".to_string()), 0.2)
+            .build()
+            .unwrap();
 
         let mut landmark_chances: HashMap<Id<Structure>, f32> = HashMap::new();
         landmark_chances.insert(Id::from_name("spring".to_string()), 5e-4);
@@ -221,7 +317,13 @@ impl GenerationConfig {
             unit_chances,
             landmark_chances,
             structure_chances,
+            structure_placement: PlacementStrategy::Uniform,
             terrain_weights,
+            biomes: None,
+            terrain_smoothing: TerrainSmoothing::default(),
+            impassable_border: false,
+            traversal: TraversalConfig::IN_GAME,
+            terrain_layout: None,
             low_frequency_noise: SimplexSettings {
                 frequency: 1e-2,
                 amplitude: 0.0,
@@ -236,15 +338,19 @@ impl GenerationConfig {
                 lacunarity: 2.3,
                 gain: 0.5,
             },
+            difficulty: Difficulty::Normal,
+            strategy: GenerationStrategy::Flat,
         }
     }
 
     /// A tiny world gen config for testing.
     pub fn testing() -> Self {
-        let mut terrain_weights: HashMap<Id<Terrain>, f32> = HashMap::new();
         // FIXME: load from file somehow
-        terrain_weights.insert(Id::from_name("grassy".to_string()), 1.0);
-        terrain_weights.insert(Id::from_name("rocky".to_string()), 0.2);
+        let terrain_weights = TerrainWeights::builder()
+            .with(Id::from_name("grassy".to_string()), 1.0)
+            .with(Id::from_name("rocky".to_string()), 0.2)
+            .build()
+            .unwrap();
 
         let mut landmark_chances: HashMap<Id<Structure>, f32> = HashMap::new();
         landmark_chances.insert(Id::from_name("simple_landmark".to_string()), 1e-1);
@@ -263,7 +369,13 @@ impl GenerationConfig {
             unit_chances,
             landmark_chances,
             structure_chances,
+            structure_placement: PlacementStrategy::Uniform,
             terrain_weights,
+            biomes: None,
+            terrain_smoothing: TerrainSmoothing::default(),
+            impassable_border: false,
+            traversal: TraversalConfig::IN_GAME,
+            terrain_layout: None,
             low_frequency_noise: SimplexSettings {
                 frequency: 1e-2,
                 amplitude: 8.0,
@@ -278,6 +390,8 @@ impl GenerationConfig {
                 lacunarity: 2.3,
                 gain: 0.5,
             },
+            difficulty: Difficulty::Normal,
+            strategy: GenerationStrategy::Testing,
         }
     }
 }
@@ -285,9 +399,15 @@ impl GenerationConfig {
 #[cfg(test)]
 mod tests {
     use crate::asset_management::manifest::DummyManifestPlugin;
-    use crate::geometry::{MapGeometry, VoxelPos};
+    use crate::geometry::{render_ascii_map, AsciiMapOptions, MapGeometry, VoxelPos};
+    use crate::simulation::checksum::simulation_checksum;
     use crate::simulation::rng::GlobalRng;
+    use crate::terrain::{terrain_manifest::Terrain, ImpassableTerrain};
+    use crate::units::basic_needs::Diet;
+    use crate::units::unit_manifest::{UnitData, UnitManifest};
+    use crate::utils::collections::ordered_iter;
     use crate::water::WaterConfig;
+    use hexx::Hex;
 
     use super::*;
 
@@ -310,6 +430,109 @@ mod tests {
         app.add_startup_systems((generate_terrain, generate_structures).chain());
 
         app.update();
+
+        let rendered = render_ascii_map(&mut app.world, &AsciiMapOptions::default());
+        let golden_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/src/world_gen/golden/testing_world.txt"
+        );
+
+        // If generation changes intentionally, replace the golden file with the rendered map printed below
+        let golden = std::fs::read_to_string(golden_path).unwrap_or_else(|error| {
+            panic!("Could not read {golden_path}: {error}\nRendered map:\n{rendered}")
+        });
+        assert_eq!(rendered, golden, "Rendered map:\n{rendered}");
+    }
+
+    /// Builds an app that generates a world from `config` when it is first updated.
+    fn world_generation_app(config: GenerationConfig) -> App {
+        let mut app = App::new();
+        app.add_plugin(DummyManifestPlugin);
+        app.insert_resource(config);
+        app.insert_resource(GlobalRng::new(0));
+        app.add_startup_systems(
+            (
+                generate_terrain,
+                generate_landmarks,
+                generate_structures,
+                generate_units,
+            )
+                .chain(),
+        );
+        app
+    }
+
+    #[test]
+    fn generation_ignores_insertion_order() {
+        let simple_unit = Id::from_name("simple_unit".to_string());
+        let other_unit = Id::from_name("other_unit".to_string());
+
+        // With two kinds of units, the order in which their chances are rolled matters
+        let mut config = GenerationConfig::testing();
+        config.unit_chances.insert(simple_unit, 0.5);
+        config.unit_chances.insert(other_unit, 0.5);
+
+        // Rebuild each map of chances, inserting the entries in reverse order
+        let mut shuffled_config = config.clone();
+        shuffled_config.terrain_weights = config
+            .terrain_weights
+            .iter()
+            .rev()
+            .fold(TerrainWeights::builder(), |builder, (id, weight)| {
+                builder.with(id, weight)
+            })
+            .build()
+            .unwrap();
+        shuffled_config.structure_chances = ordered_iter(&config.structure_chances)
+            .rev()
+            .map(|(&id, &chance)| (id, chance))
+            .collect();
+        shuffled_config.landmark_chances = ordered_iter(&config.landmark_chances)
+            .rev()
+            .map(|(&id, &chance)| (id, chance))
+            .collect();
+        shuffled_config.unit_chances = ordered_iter(&config.unit_chances)
+            .rev()
+            .map(|(&id, &chance)| (id, chance))
+            .collect();
+
+        let generate = |config: GenerationConfig| {
+            let mut app = world_generation_app(config);
+            app.world.resource_mut::<UnitManifest>().insert(
+                "other_unit".to_string(),
+                UnitData::simple("other_unit", Diet::simple("food")),
+            );
+            app.update();
+
+            let rendered = render_ascii_map(&mut app.world, &AsciiMapOptions::default());
+            (rendered, simulation_checksum(&mut app.world))
+        };
+
+        let (rendered, checksum) = generate(config);
+        let (shuffled_rendered, shuffled_checksum) = generate(shuffled_config);
+        assert_eq!(rendered, shuffled_rendered);
+        assert_eq!(checksum, shuffled_checksum);
+    }
+
+    #[test]
+    fn every_walkable_tile_can_get_a_unit() {
+        let mut app = App::new();
+        app.add_plugin(DummyManifestPlugin);
+        // The testing config spawns a unit on every walkable tile
+        app.insert_resource(GenerationConfig::testing());
+        app.insert_resource(GlobalRng::new(0));
+        app.add_startup_systems((generate_terrain, generate_structures, generate_units).chain());
+
+        app.update();
+
+        let n_walkable = app.world.resource::<MapGeometry>().walkable_voxels().len();
+        let n_units = app
+            .world
+            .query_filtered::<(), With<Id<Unit>>>()
+            .iter(&app.world)
+            .count();
+        assert!(n_units > 0);
+        assert_eq!(n_units, n_walkable);
     }
 
     #[test]
@@ -400,6 +623,125 @@ mod tests {
         }
     }
 
+    #[test]
+    fn terrain_types_are_indexed() {
+        let mut app = App::new();
+        app.add_plugin(DummyManifestPlugin);
+        app.insert_resource(GenerationConfig::testing());
+        app.add_startup_system(generate_terrain);
+        app.insert_resource(GlobalRng::new(0));
+
+        app.update();
+
+        let map_geometry = app.world.resource::<MapGeometry>().clone();
+        let mut terrain_query = app.world.query::<(&VoxelPos, &Id<Terrain>)>();
+
+        let mut n_tiles = 0;
+        for (voxel_pos, &terrain_id) in terrain_query.iter(&app.world) {
+            assert_eq!(map_geometry.get_terrain_id(voxel_pos.hex), Ok(terrain_id));
+            n_tiles += 1;
+        }
+
+        assert_eq!(n_tiles, map_geometry.all_hexes().count());
+        for &hex in map_geometry.all_hexes() {
+            assert!(map_geometry.get_terrain_id(hex).is_ok());
+        }
+    }
+
+    #[test]
+    fn terrain_follows_biomes() {
+        let weights_for = |name: &str| {
+            TerrainWeights::builder()
+                .with(Id::from_name(name.to_string()), 1.)
+                .build()
+                .unwrap()
+        };
+        let mountains = Id::<Biome>::from_name("mountains".to_string());
+        let plains = Id::<Biome>::from_name("plains".to_string());
+
+        let mut config = GenerationConfig::testing();
+        config.biomes = BiomeSettings::new(
+            2,
+            [
+                (mountains, weights_for("rocky")),
+                (plains, weights_for("grassy")),
+            ],
+        );
+
+        let mut app = App::new();
+        app.insert_resource(config);
+        app.insert_resource(GlobalRng::new(0));
+        app.add_startup_system(generate_terrain);
+        app.update();
+
+        let n_tiles = app.world.resource::<MapGeometry>().all_hexes().count();
+        let mut tile_query = app.world.query::<(&Id<Terrain>, &Id<Biome>)>();
+        let mut n_tiles_per_biome: HashMap<Id<Biome>, usize> = HashMap::default();
+        for (&terrain_id, &biome_id) in tile_query.iter(&app.world) {
+            let expected = if biome_id == mountains {
+                "rocky"
+            } else {
+                "grassy"
+            };
+            assert_eq!(terrain_id, Id::from_name(expected.to_string()));
+            *n_tiles_per_biome.entry(biome_id).or_default() += 1;
+        }
+
+        assert_eq!(n_tiles_per_biome.values().sum::<usize>(), n_tiles);
+        assert_eq!(n_tiles_per_biome.len(), 2);
+    }
+
+    #[test]
+    fn border_ring_is_impassable() {
+        let mut config = GenerationConfig::testing();
+        config.impassable_border = true;
+        let radius = config.map_radius;
+
+        let mut app = App::new();
+        app.add_plugin(DummyManifestPlugin);
+        app.insert_resource(config);
+        app.insert_resource(GlobalRng::new(0));
+        app.add_startup_systems(
+            (
+                generate_terrain,
+                generate_landmarks,
+                generate_structures,
+                generate_units,
+            )
+                .chain(),
+        );
+        app.update();
+
+        let mut border_query = app
+            .world
+            .query_filtered::<&VoxelPos, (With<Id<Terrain>>, With<ImpassableTerrain>)>();
+        let border: Vec<Hex> = border_query
+            .iter(&app.world)
+            .map(|voxel_pos| voxel_pos.hex)
+            .collect();
+
+        // One tile thick all the way around, including the corners
+        assert_eq!(border.len(), 6 * radius as usize);
+        for hex in &border {
+            assert_eq!(hex.unsigned_distance_to(Hex::ZERO), radius);
+        }
+
+        let map_geometry = app.world.resource::<MapGeometry>();
+        assert!(map_geometry
+            .walkable_voxels()
+            .iter()
+            .all(|voxel_pos| !border.contains(&voxel_pos.hex)));
+
+        // Nothing is spawned on the border
+        let mut organism_query = app
+            .world
+            .query_filtered::<&VoxelPos, Or<(With<Id<Unit>>, With<Id<Structure>>)>>();
+        assert!(organism_query.iter(&app.world).count() > 0);
+        for voxel_pos in organism_query.iter(&app.world) {
+            assert!(!border.contains(&voxel_pos.hex));
+        }
+    }
+
     #[test]
     fn can_generate_landmarks() {
         let mut app = App::new();
@@ -0,0 +1,234 @@
+//! Throws away the current world and generates a new one, without restarting the app.
+
+use bevy::{hierarchy::despawn_with_children_recursive, prelude::*};
+
+use crate::{
+    geometry::VoxelPos,
+    simulation::{rng::GlobalRng, sim_resources::SimResources},
+};
+
+use super::{GenerationConfig, WorldGenState};
+
+/// Requests that the current world be discarded and replaced with a freshly generated one.
+///
+/// Only the last event sent each frame is acted on.
+/// Events sent before the first world has finished generating are ignored.
+#[derive(Debug, Clone, Default)]
+pub struct RegenerateMapEvent {
+    /// The settings used to generate the new world.
+    ///
+    /// If this is `None`, the current [`GenerationConfig`] is reused.
+    pub config: Option<GenerationConfig>,
+    /// The seed used to generate the new world, overriding the seed in `config`.
+    pub seed: Option<u64>,
+}
+
+/// Tears down the current world and restarts world generation when a [`RegenerateMapEvent`] is received.
+///
+/// Everything is despawned and reset here, before the state transition in the same frame re-runs generation,
+/// so no other system observes a partially torn down world.
+pub(super) fn regenerate_map(world: &mut World) {
+    let Some(event) = world
+        .resource_mut::<Events<RegenerateMapEvent>>()
+        .drain()
+        .last()
+    else {
+        return;
+    };
+
+    let world_gen_state = world.resource::<State<WorldGenState>>().0.clone();
+    if !matches!(
+        world_gen_state,
+        WorldGenState::BurningIn | WorldGenState::Complete
+    ) {
+        warn!("Ignoring request to regenerate the map while in {world_gen_state:?}");
+        return;
+    }
+
+    let mut config = event
+        .config
+        .unwrap_or_else(|| world.resource::<GenerationConfig>().clone());
+    if let Some(seed) = event.seed {
+        config.seed = seed;
+    }
+    info!("Regenerating the map with seed {}", config.seed);
+
+    // Terrain, structures, units, litter, ghosts and overlays all live at a voxel position
+    let root_entities: Vec<Entity> = world
+        .query_filtered::<Entity, (With<VoxelPos>, Without<Parent>)>()
+        .iter(world)
+        .collect();
+    for entity in root_entities {
+        despawn_with_children_recursive(world, entity);
+    }
+
+    // Clear out any state that refers to the old map
+    SimResources::reset_all(world);
+
+    world.insert_resource(GlobalRng::new(config.seed));
+    world.insert_resource(config);
+    world
+        .resource_mut::<NextState<WorldGenState>>()
+        .set(WorldGenState::Generating);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        asset_management::manifest::{DummyManifestPlugin, Id},
+        geometry::MapGeometry,
+        simulation::sim_resources::SimResourceAppExt,
+        structures::structure_manifest::Structure,
+        terrain::terrain_manifest::Terrain,
+        units::{
+            trace::{start_trace, EntityTraces},
+            unit_manifest::Unit,
+        },
+        water::WaterConfig,
+        world_gen::GenerationPlugin,
+    };
+
+    use super::*;
+
+    /// A headless app that generates a small world.
+    fn app() -> App {
+        app_with_seed(GenerationConfig::testing().seed)
+    }
+
+    /// A headless app that generates a small world from the provided `seed`.
+    fn app_with_seed(seed: u64) -> App {
+        let mut app = App::new();
+        app.add_plugin(GenerationPlugin {
+            config: GenerationConfig {
+                seed,
+                ..GenerationConfig::testing()
+            },
+        })
+        .add_plugin(DummyManifestPlugin)
+        .init_resource::<EntityTraces>()
+        .register_sim_resource::<EntityTraces>();
+        app.insert_resource(GlobalRng::new(seed));
+        app.insert_resource(WaterConfig::IN_GAME);
+
+        app
+    }
+
+    /// The number of entities of each kind in the world.
+    fn entity_counts(app: &mut App) -> [usize; 5] {
+        [
+            app.world.query::<&Id<Terrain>>().iter(&app.world).count(),
+            app.world.query::<&Id<Structure>>().iter(&app.world).count(),
+            app.world.query::<&Id<Unit>>().iter(&app.world).count(),
+            positioned_entities(app).len(),
+            app.world.entities().len() as usize,
+        ]
+    }
+
+    /// Runs the app until world generation is complete.
+    fn finish_generating(app: &mut App) {
+        for _ in 0..10 {
+            app.update();
+            if app.world.resource::<State<WorldGenState>>().0 == WorldGenState::Complete {
+                return;
+            }
+        }
+
+        panic!("World generation did not complete");
+    }
+
+    /// The set of entities that have a position on the map.
+    fn positioned_entities(app: &mut App) -> Vec<Entity> {
+        app.world
+            .query_filtered::<Entity, With<VoxelPos>>()
+            .iter(&app.world)
+            .collect()
+    }
+
+    #[test]
+    fn regenerating_replaces_every_entity() {
+        let mut app = app();
+        finish_generating(&mut app);
+
+        let radius = app.world.resource::<MapGeometry>().radius;
+        let expected_n_tiles = (3 * radius * (radius + 1) + 1) as usize;
+        let original_entities = positioned_entities(&mut app);
+
+        for _ in 0..2 {
+            let old_entities = positioned_entities(&mut app);
+            app.world.send_event(RegenerateMapEvent::default());
+            finish_generating(&mut app);
+
+            let n_tiles = app.world.query::<&Id<Terrain>>().iter(&app.world).count();
+            assert_eq!(n_tiles, expected_n_tiles);
+
+            let new_entities = positioned_entities(&mut app);
+            assert_eq!(new_entities.len(), original_entities.len());
+            for entity in old_entities {
+                assert!(
+                    app.world.get_entity(entity).is_none(),
+                    "{entity:?} survived regeneration"
+                );
+            }
+
+            let map_geometry = app.world.resource::<MapGeometry>();
+            for &hex in map_geometry.all_hexes() {
+                let terrain_entity = map_geometry.get_terrain(hex).unwrap();
+                assert!(app.world.get::<Id<Terrain>>(terrain_entity).is_some());
+            }
+        }
+    }
+
+    #[test]
+    fn regenerating_uses_the_requested_seed() {
+        let mut app = app();
+        finish_generating(&mut app);
+
+        app.world.send_event(RegenerateMapEvent {
+            config: None,
+            seed: Some(42),
+        });
+        finish_generating(&mut app);
+
+        assert_eq!(app.world.resource::<GenerationConfig>().seed, 42);
+    }
+
+    #[test]
+    fn regenerated_worlds_match_freshly_generated_ones() {
+        let mut fresh_app = app_with_seed(42);
+        finish_generating(&mut fresh_app);
+
+        let mut app = app();
+        finish_generating(&mut app);
+        app.world.send_event(RegenerateMapEvent {
+            config: None,
+            seed: Some(42),
+        });
+        finish_generating(&mut app);
+
+        assert_eq!(entity_counts(&mut app), entity_counts(&mut fresh_app));
+    }
+
+    #[test]
+    fn regenerating_discards_traces() {
+        let mut app = app();
+        finish_generating(&mut app);
+
+        let unit = app
+            .world
+            .query_filtered::<Entity, With<Id<Unit>>>()
+            .iter(&app.world)
+            .next()
+            .unwrap_or_else(|| {
+                app.world
+                    .spawn((Id::<Unit>::from_name("ant".to_string()), VoxelPos::ZERO))
+                    .id()
+            });
+        start_trace(&mut app.world, unit).unwrap();
+        assert!(app.world.resource::<EntityTraces>().get(unit).is_some());
+
+        app.world.send_event(RegenerateMapEvent::default());
+        finish_generating(&mut app);
+
+        assert!(app.world.resource::<EntityTraces>().get(unit).is_none());
+    }
+}
@@ -1,14 +1,21 @@
 //! Initializes organisms in the world.
 
-use crate::geometry::{Facing, MapGeometry};
+use crate::asset_management::manifest::Id;
+use crate::geometry::{Facing, MapGeometry, VoxelPos};
 use crate::organisms::energy::StartingEnergy;
 use crate::player_interaction::clipboard::ClipboardData;
 use crate::simulation::rng::GlobalRng;
 use crate::structures::commands::StructureCommandsExt;
-use crate::structures::structure_manifest::StructureManifest;
+use crate::structures::structure_manifest::{Structure, StructureManifest};
+use crate::utils::collections::{ordered, ordered_iter};
+use crate::utils::curves::{Curve, CurveError};
 
 use bevy::prelude::*;
+use bevy::utils::HashMap;
+use hexx::Hex;
+use rand::seq::SliceRandom;
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 
 use super::GenerationConfig;
 
@@ -22,10 +29,33 @@ pub(super) fn generate_structures(
     mut rng: ResMut<GlobalRng>,
 ) {
     info!("Generating structures...");
+    let config = config.with_preset_applied();
 
     // Collect out so we can mutate the height map to flatten the terrain while in the loop
-    for voxel_pos in map_geometry.walkable_voxels() {
-        for (&structure_id, &chance) in &config.structure_chances {
+    // Iteration order must be fixed, as the RNG is sampled in the loop
+    let walkable_voxels: Vec<VoxelPos> = ordered(map_geometry.walkable_voxels()).collect();
+
+    // Each kind of structure grows in its own patches
+    let walkable_hexes: Vec<Hex> = walkable_voxels
+        .iter()
+        .map(|voxel_pos| voxel_pos.hex)
+        .collect();
+    let placement_chances: HashMap<Id<Structure>, HashMap<Hex, f32>> =
+        ordered_iter(&config.structure_chances)
+            .filter_map(|(&structure_id, &chance)| {
+                config
+                    .structure_placement
+                    .placement_chances(&walkable_hexes, chance, rng.get_mut())
+                    .map(|chances| (structure_id, chances))
+            })
+            .collect();
+
+    for voxel_pos in walkable_voxels {
+        for (&structure_id, &chance) in ordered_iter(&config.structure_chances) {
+            let chance = placement_chances
+                .get(&structure_id)
+                .map_or(chance, |chances| chances[&voxel_pos.hex]);
+
             if rng.gen::<f32>() < chance {
                 let mut clipboard_data =
                     ClipboardData::generate_from_id(structure_id, &structure_manifest);
@@ -49,3 +79,297 @@ pub(super) fn generate_structures(
         }
     }
 }
+
+/// How organisms of a single kind are distributed across the map during world generation.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub enum PlacementStrategy {
+    /// Every tile is equally likely to be chosen.
+    #[default]
+    Uniform,
+    /// Organisms grow in patches around a few randomly chosen tiles.
+    ///
+    /// The total number of organisms placed is about the same as for [`PlacementStrategy::Uniform`].
+    Clustered {
+        /// The number of patches.
+        clusters: u32,
+        /// The relative chance of placement, as a function of the distance in tiles to the nearest patch center.
+        ///
+        /// Negative values are treated as 0.
+        falloff: Curve,
+    },
+}
+
+impl PlacementStrategy {
+    /// Patches whose chance of placement falls by a factor of e every `length` tiles from their center.
+    pub fn exponential_clusters(clusters: u32, length: f32) -> Self {
+        PlacementStrategy::Clustered {
+            clusters,
+            falloff: Curve::Exponential {
+                initial: 1.,
+                rate: -1. / length,
+            },
+        }
+    }
+
+    /// Checks that the falloff curve of clustered placement is well-formed.
+    pub fn validate(&self) -> Result<(), CurveError> {
+        match self {
+            PlacementStrategy::Uniform => Ok(()),
+            PlacementStrategy::Clustered { falloff, .. } => falloff.validate(),
+        }
+    }
+
+    /// The chance of placing an organism on each of the `hexes`, when the average chance per tile is `chance`.
+    ///
+    /// The chances average to `chance` across all of the `hexes`, so the expected total is unchanged.
+    /// No tile's chance exceeds 1: the excess near patch centers is shared out among the other tiles in the patch.
+    /// Only if the falloff leaves too few tiles with a positive weight to hold them all are fewer organisms placed.
+    ///
+    /// Returns [`None`] when the chances should not be changed, in which case the `rng` is not used.
+    pub(crate) fn placement_chances(
+        &self,
+        hexes: &[Hex],
+        chance: f32,
+        rng: &mut impl Rng,
+    ) -> Option<HashMap<Hex, f32>> {
+        let PlacementStrategy::Clustered { clusters, falloff } = self else {
+            return None;
+        };
+
+        if *clusters == 0 || hexes.is_empty() {
+            return None;
+        }
+
+        let cluster_centers: Vec<Hex> = hexes
+            .choose_multiple(rng, *clusters as usize)
+            .copied()
+            .collect();
+
+        let weights: Vec<f32> = hexes
+            .iter()
+            .map(|hex| {
+                let distance = cluster_centers
+                    .iter()
+                    .map(|center| hex.unsigned_distance_to(*center))
+                    .min()
+                    .unwrap_or_default();
+
+                falloff.eval(distance as f32).max(0.)
+            })
+            .collect();
+
+        // If the curve gives every tile a weight of zero, there is nothing to scale the chances by
+        let total_weight = weights.iter().sum::<f32>();
+        if !(total_weight.is_finite() && total_weight > 0.) {
+            return None;
+        }
+
+        let target_total = chance.clamp(0., 1.) * hexes.len() as f32;
+        let mut saturated = vec![false; hexes.len()];
+
+        // Scale the weights to hit the target, then cap any tiles that would exceed certainty.
+        // Capping moves the excess onto the remaining tiles, which may push more of them over, so repeat until stable.
+        let scale = loop {
+            let n_saturated = saturated
+                .iter()
+                .filter(|&&is_saturated| is_saturated)
+                .count();
+            let unsaturated_weight: f32 = weights
+                .iter()
+                .zip(&saturated)
+                .filter(|(_, &is_saturated)| !is_saturated)
+                .map(|(weight, _)| weight)
+                .sum();
+            if unsaturated_weight <= 0. {
+                break 0.;
+            }
+
+            let scale = (target_total - n_saturated as f32).max(0.) / unsaturated_weight;
+            let mut newly_saturated = false;
+            for (weight, is_saturated) in weights.iter().zip(saturated.iter_mut()) {
+                if !*is_saturated && weight * scale >= 1. {
+                    *is_saturated = true;
+                    newly_saturated = true;
+                }
+            }
+
+            if !newly_saturated {
+                break scale;
+            }
+        };
+
+        Some(
+            hexes
+                .iter()
+                .zip(weights.iter().zip(saturated))
+                .map(|(&hex, (weight, is_saturated))| {
+                    let chance = if is_saturated { 1. } else { weight * scale };
+                    (hex, chance)
+                })
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    use super::*;
+
+    /// The tiles of a large hexagonal map.
+    fn hexes() -> Vec<Hex> {
+        hexx::shapes::hexagon(Hex::ZERO, 20).collect()
+    }
+
+    /// Places organisms on `hexes` with the provided `strategy`, returning the chosen tiles.
+    fn place(strategy: &PlacementStrategy, hexes: &[Hex], seed: u64) -> Vec<Hex> {
+        let mut rng = SmallRng::seed_from_u64(seed);
+        let chances = strategy.placement_chances(hexes, 0.05, &mut rng);
+
+        hexes
+            .iter()
+            .copied()
+            .filter(|hex| {
+                let chance = chances.as_ref().map_or(0.05, |chances| chances[hex]);
+                rng.gen::<f32>() < chance
+            })
+            .collect()
+    }
+
+    /// The average distance between each pair of `hexes`.
+    fn mean_pairwise_distance(hexes: &[Hex]) -> f32 {
+        let mut total = 0;
+        let mut n_pairs = 0;
+        for (i, a) in hexes.iter().enumerate() {
+            for b in &hexes[i + 1..] {
+                total += a.unsigned_distance_to(*b);
+                n_pairs += 1;
+            }
+        }
+
+        total as f32 / n_pairs as f32
+    }
+
+    #[test]
+    fn uniform_placement_does_not_use_the_rng() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let mut untouched_rng = rng.clone();
+
+        assert!(PlacementStrategy::Uniform
+            .placement_chances(&hexes(), 0.05, &mut rng)
+            .is_none());
+        assert_eq!(rng.gen::<u64>(), untouched_rng.gen::<u64>());
+    }
+
+    #[test]
+    fn chances_average_to_the_requested_chance() {
+        let hexes = hexes();
+        let strategy = PlacementStrategy::exponential_clusters(4, 2.);
+        let chances = strategy
+            .placement_chances(&hexes, 0.05, &mut SmallRng::seed_from_u64(0))
+            .unwrap();
+
+        assert_eq!(chances.len(), hexes.len());
+        let mean = chances.values().sum::<f32>() / hexes.len() as f32;
+        assert!((mean - 0.05).abs() < 1e-4, "{mean}");
+    }
+
+    #[test]
+    fn chances_near_patch_centers_are_capped_without_losing_organisms() {
+        let hexes = hexes();
+        // Tight patches and a high chance would ask for far more than one organism per tile at the centers
+        let strategy = PlacementStrategy::exponential_clusters(2, 1.);
+        let chances = strategy
+            .placement_chances(&hexes, 0.3, &mut SmallRng::seed_from_u64(0))
+            .unwrap();
+
+        assert!(chances.values().all(|&chance| (0. ..=1.).contains(&chance)));
+        assert!(chances.values().any(|&chance| chance == 1.));
+        let mean = chances.values().sum::<f32>() / hexes.len() as f32;
+        assert!((mean - 0.3).abs() < 1e-3, "{mean}");
+    }
+
+    #[test]
+    fn patches_too_small_for_the_chance_are_filled() {
+        let hexes = hexes();
+        // Only the 7 tiles around the center can be chosen, but the chance asks for far more
+        let strategy = PlacementStrategy::Clustered {
+            clusters: 1,
+            falloff: Curve::PiecewiseLinear {
+                points: vec![(1., 1.), (1.5, 0.)],
+            },
+        };
+        let chances = strategy
+            .placement_chances(&hexes, 0.5, &mut SmallRng::seed_from_u64(0))
+            .unwrap();
+
+        assert_eq!(chances.values().filter(|&&chance| chance == 1.).count(), 7);
+        assert_eq!(chances.values().filter(|&&chance| chance > 0.).count(), 7);
+    }
+
+    #[test]
+    fn falloff_curves_shape_the_patches() {
+        let hexes = hexes();
+        // Only tiles within 2 of the single patch center can be chosen
+        let strategy = PlacementStrategy::Clustered {
+            clusters: 1,
+            falloff: Curve::PiecewiseLinear {
+                points: vec![(2., 1.), (2.5, 0.)],
+            },
+        };
+        let chances = strategy
+            .placement_chances(&hexes, 0.05, &mut SmallRng::seed_from_u64(0))
+            .unwrap();
+
+        let n_chosen = chances.values().filter(|&&chance| chance > 0.).count();
+        assert_eq!(n_chosen, hexx::shapes::hexagon(Hex::ZERO, 2).count());
+    }
+
+    #[test]
+    fn falloff_curves_that_are_never_positive_are_ignored() {
+        let strategy = PlacementStrategy::Clustered {
+            clusters: 2,
+            falloff: Curve::constant(-1.),
+        };
+
+        assert!(strategy
+            .placement_chances(&hexes(), 0.05, &mut SmallRng::seed_from_u64(0))
+            .is_none());
+    }
+
+    #[test]
+    fn clustered_placement_is_deterministic() {
+        let strategy = PlacementStrategy::exponential_clusters(3, 2.);
+
+        assert_eq!(place(&strategy, &hexes(), 7), place(&strategy, &hexes(), 7));
+    }
+
+    #[test]
+    fn clustered_placement_forms_patches() {
+        let hexes = hexes();
+        let clustered_strategy = PlacementStrategy::exponential_clusters(1, 3.);
+        let uniform = place(&PlacementStrategy::Uniform, &hexes, 0);
+        let clustered = place(&clustered_strategy, &hexes, 0);
+
+        // The same number of organisms are placed, up to sampling noise averaged over many maps
+        let seeds = 0..50;
+        let n_uniform: usize = seeds
+            .clone()
+            .map(|seed| place(&PlacementStrategy::Uniform, &hexes, seed).len())
+            .sum();
+        let n_clustered: usize = seeds
+            .map(|seed| place(&clustered_strategy, &hexes, seed).len())
+            .sum();
+        let ratio = n_clustered as f32 / n_uniform as f32;
+        assert!((0.9..1.1).contains(&ratio), "{ratio}");
+
+        // But they are much closer together
+        let uniform_distance = mean_pairwise_distance(&uniform);
+        let clustered_distance = mean_pairwise_distance(&clustered);
+        assert!(
+            clustered_distance < 0.5 * uniform_distance,
+            "{clustered_distance} vs {uniform_distance}"
+        );
+    }
+}
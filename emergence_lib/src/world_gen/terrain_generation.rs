@@ -10,37 +10,248 @@ use crate::{
     terrain::{
         terrain_assets::TerrainHandles,
         terrain_manifest::{Terrain, TerrainManifest},
-        TerrainBundle,
+        ImpassableTerrain, TerrainBundle,
+    },
+    utils::{
+        collections::{ordered, ordered_iter},
+        noise::simplex_noise,
     },
-    utils::noise::simplex_noise,
     water::{WaterConfig, WaterVolume},
 };
-use bevy::prelude::*;
+use bevy::{prelude::*, utils::HashMap};
 use hexx::{shapes::hexagon, Hex};
-use rand::{seq::SliceRandom, Rng};
+use rand::{
+    distributions::{Distribution, WeightedIndex},
+    Rng,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
 
 use super::GenerationConfig;
 
+/// The relative probability of generating each variety of terrain.
+///
+/// Construct this with [`TerrainWeights::builder`], which checks that the weights can actually be sampled from.
+#[derive(Debug, Clone)]
+pub(crate) struct TerrainWeights {
+    /// Each terrain variety that has a weight and its weight, sorted by [`Id`].
+    varieties: Vec<(Id<Terrain>, f32)>,
+    /// The distribution over indexes into `varieties`.
+    distribution: WeightedIndex<f32>,
+}
+
+impl TerrainWeights {
+    /// Starts building a new set of weights.
+    pub(crate) fn builder() -> TerrainWeightsBuilder {
+        TerrainWeightsBuilder::default()
+    }
+
+    /// Randomly chooses a variety of terrain, in proportion to its weight.
+    pub(crate) fn choose(&self, rng: &mut impl Rng) -> Id<Terrain> {
+        self.varieties[self.distribution.sample(rng)].0
+    }
+
+    /// The terrain varieties and their weights, sorted by [`Id`].
+    #[cfg(test)]
+    pub(crate) fn iter(&self) -> impl DoubleEndedIterator<Item = (Id<Terrain>, f32)> + '_ {
+        self.varieties.iter().copied()
+    }
+}
+
+/// Collects the weights for a [`TerrainWeights`], and validates them.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TerrainWeightsBuilder {
+    /// The weight of each terrain variety added so far.
+    weights: HashMap<Id<Terrain>, f32>,
+}
+
+impl TerrainWeightsBuilder {
+    /// Sets the weight of `terrain_id`, replacing any previous weight.
+    pub(crate) fn with(mut self, terrain_id: Id<Terrain>, weight: f32) -> Self {
+        self.weights.insert(terrain_id, weight);
+        self
+    }
+
+    /// Checks that every weight is finite and non-negative, and that at least one is positive.
+    ///
+    /// Varieties that are not included have no chance to be generated.
+    pub(crate) fn build(self) -> Result<TerrainWeights, TerrainWeightsError> {
+        let varieties: Vec<(Id<Terrain>, f32)> = ordered_iter(self.weights).collect();
+
+        for &(terrain_id, weight) in &varieties {
+            if !weight.is_finite() {
+                return Err(TerrainWeightsError::NonFinite { terrain_id, weight });
+            }
+
+            if weight < 0. {
+                return Err(TerrainWeightsError::Negative { terrain_id, weight });
+            }
+        }
+
+        let distribution = WeightedIndex::new(varieties.iter().map(|&(_, weight)| weight))
+            .map_err(|_| TerrainWeightsError::NoPositiveWeight)?;
+
+        Ok(TerrainWeights {
+            varieties,
+            distribution,
+        })
+    }
+}
+
+/// The ways in which a [`TerrainWeightsBuilder`] can fail to produce usable weights.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TerrainWeightsError {
+    /// A weight was NaN or infinite.
+    NonFinite {
+        /// The terrain variety with the bad weight.
+        terrain_id: Id<Terrain>,
+        /// The bad weight.
+        weight: f32,
+    },
+    /// A weight was less than zero.
+    Negative {
+        /// The terrain variety with the bad weight.
+        terrain_id: Id<Terrain>,
+        /// The bad weight.
+        weight: f32,
+    },
+    /// No terrain variety had a weight greater than zero, so nothing could be generated.
+    NoPositiveWeight,
+}
+
+impl Display for TerrainWeightsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TerrainWeightsError::NonFinite { terrain_id, weight } => {
+                write!(
+                    f,
+                    "terrain weight for {terrain_id:?} must be finite, but was {weight}"
+                )
+            }
+            TerrainWeightsError::Negative { terrain_id, weight } => write!(
+                f,
+                "terrain weight for {terrain_id:?} must not be negative, but was {weight}"
+            ),
+            TerrainWeightsError::NoPositiveWeight => {
+                write!(f, "at least one terrain weight must be greater than zero")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TerrainWeightsError {}
+
+/// A cellular automaton that removes isolated specks of terrain, run after terrain varieties are chosen.
+///
+/// On each iteration, every tile simultaneously adopts the terrain variety shared by
+/// at least [`min_neighbors`](Self::min_neighbors) of its six neighbors, if there is one.
+/// Tiles on the edge of the map have fewer neighbors, so the threshold is scaled down in proportion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Reflect, FromReflect)]
+pub struct TerrainSmoothing {
+    /// The number of times the rule is applied.
+    ///
+    /// Smoothing is disabled when this is zero.
+    pub iterations: u32,
+    /// How many of a tile's six neighbors must share a terrain variety for the tile to be converted to it.
+    ///
+    /// Values above 3 ensure that only one variety can qualify.
+    pub min_neighbors: u32,
+}
+
+impl Default for TerrainSmoothing {
+    fn default() -> Self {
+        TerrainSmoothing {
+            iterations: 0,
+            min_neighbors: 4,
+        }
+    }
+}
+
+impl TerrainSmoothing {
+    /// Applies the smoothing rule to the terrain variety of each hex.
+    ///
+    /// Hexes that are missing from `terrain` are treated as being off the edge of the map.
+    pub(crate) fn smooth(&self, terrain: &mut HashMap<Hex, Id<Terrain>>) {
+        for _ in 0..self.iterations {
+            let mut changed = false;
+            let smoothed: HashMap<Hex, Id<Terrain>> = terrain
+                .iter()
+                .map(|(&hex, &terrain_id)| {
+                    let new_terrain_id = self.smoothed_tile(hex, terrain).unwrap_or(terrain_id);
+                    changed |= new_terrain_id != terrain_id;
+                    (hex, new_terrain_id)
+                })
+                .collect();
+
+            *terrain = smoothed;
+            if !changed {
+                break;
+            }
+        }
+    }
+
+    /// The terrain variety that the tile at `hex` should be converted to, if any.
+    fn smoothed_tile(&self, hex: Hex, terrain: &HashMap<Hex, Id<Terrain>>) -> Option<Id<Terrain>> {
+        let mut counts: HashMap<Id<Terrain>, u32> = HashMap::default();
+        let mut n_neighbors = 0;
+        for neighbor in hex.all_neighbors() {
+            if let Some(&terrain_id) = terrain.get(&neighbor) {
+                *counts.entry(terrain_id).or_default() += 1;
+                n_neighbors += 1;
+            }
+        }
+
+        // Ties are broken by Id, so the result does not depend on iteration order
+        let (terrain_id, count) = ordered_iter(counts).max_by_key(|&(_, count)| count)?;
+        (count * 6 >= self.min_neighbors * n_neighbors).then_some(terrain_id)
+    }
+}
+
 /// Creates the world according to [`GenerationConfig`].
 pub(crate) fn generate_terrain(world: &mut World) {
     info!("Generating terrain...");
     let generation_config = world.resource::<GenerationConfig>().clone();
     let map_radius = generation_config.map_radius;
     let terrain_weights = generation_config.terrain_weights;
-    let terrain_variants: Vec<Id<Terrain>> = terrain_weights.keys().copied().collect();
 
-    let map_geometry = MapGeometry::new(world, map_radius);
+    let mut map_geometry = MapGeometry::new(world, map_radius);
+    map_geometry.set_traversal(generation_config.traversal);
     world.insert_resource(map_geometry);
 
-    for hex in hexagon(Hex::ZERO, map_radius) {
-        let mut rng = world.resource_mut::<GlobalRng>();
+    // Terrain varieties are all chosen up front, so they can be smoothed before anything is spawned
+    let hexes: Vec<Hex> = hexagon(Hex::ZERO, map_radius).collect();
+    let (biome_map, terrain_map) = match &generation_config.terrain_layout {
+        // Hand-drawn maps are used exactly as written
+        Some(terrain_layout) => (None, terrain_layout.clone()),
+        None => {
+            let mut rng = world.resource_mut::<GlobalRng>();
+            let (biome_map, mut terrain_map) = match &generation_config.biomes {
+                Some(biomes) => {
+                    let biome_map = biomes.assign(&hexes, rng.get_mut());
+                    let terrain_map: HashMap<Hex, Id<Terrain>> = hexes
+                        .iter()
+                        .map(|&hex| {
+                            let weights = biomes.terrain_weights(biome_map[&hex]);
+                            (hex, weights.choose(rng.get_mut()))
+                        })
+                        .collect();
+                    (Some(biome_map), terrain_map)
+                }
+                None => {
+                    let terrain_map: HashMap<Hex, Id<Terrain>> = hexes
+                        .iter()
+                        .map(|&hex| (hex, terrain_weights.choose(rng.get_mut())))
+                        .collect();
+                    (None, terrain_map)
+                }
+            };
+            generation_config.terrain_smoothing.smooth(&mut terrain_map);
+            (biome_map, terrain_map)
+        }
+    };
 
-        // FIXME: can we not just sample from our terrain_weights directly?
-        let &terrain_id = terrain_variants
-            .choose_weighted(rng.get_mut(), |terrain_type| {
-                terrain_weights.get(terrain_type).unwrap()
-            })
-            .unwrap();
+    for hex in hexes {
+        let terrain_id = terrain_map[&hex];
 
         // Heights are generated in f32 world coordinates to start
         let hex_height = simplex_noise(
@@ -73,6 +284,10 @@ pub(crate) fn generate_terrain(world: &mut World) {
         // This overwrites the existing VoxelPos component
         world.entity_mut(entity).insert(terrain_bundle);
 
+        if let Some(biome_map) = &biome_map {
+            world.entity_mut(entity).insert(biome_map[&hex]);
+        }
+
         // Spawn the column as the 0th child of the tile entity
         // The scene bundle will be added as the first child
         if let Some(handles) = world.get_resource::<TerrainHandles>() {
@@ -89,6 +304,21 @@ pub(crate) fn generate_terrain(world: &mut World) {
         // Update the index of what terrain is where
         let mut map_geometry = world.resource_mut::<MapGeometry>();
         map_geometry.update_height(hex, height);
+        map_geometry.update_terrain_id(hex, terrain_id);
+    }
+
+    if generation_config.impassable_border {
+        // The outermost ring is every tile exactly `map_radius` steps from the center, corners included
+        let border: Vec<Hex> = hexagon(Hex::ZERO, map_radius)
+            .filter(|hex| hex.unsigned_distance_to(Hex::ZERO) == map_radius)
+            .collect();
+
+        for &hex in &border {
+            let entity = world.resource::<MapGeometry>().get_terrain(hex).unwrap();
+            world.entity_mut(entity).insert(ImpassableTerrain);
+        }
+
+        world.resource_mut::<MapGeometry>().make_impassable(border);
     }
 }
 
@@ -101,9 +331,11 @@ pub(super) fn generate_landmarks(
     mut rng: ResMut<GlobalRng>,
 ) {
     info!("Generating landmarks...");
+    let generation_config = generation_config.with_preset_applied();
 
-    for voxel_pos in map_geometry.walkable_voxels() {
-        for (&structure_id, &chance) in &generation_config.landmark_chances {
+    // Iteration order must be fixed, as the RNG is sampled in the loop
+    for voxel_pos in ordered(map_geometry.walkable_voxels()) {
+        for (&structure_id, &chance) in ordered_iter(&generation_config.landmark_chances) {
             if rng.gen::<f32>() < chance {
                 let mut clipboard_data =
                     ClipboardData::generate_from_id(structure_id, &structure_manifest);
@@ -128,6 +360,17 @@ pub(super) fn generate_landmarks(
     }
 }
 
+/// Warns about any regions of the generated map that units could jump down into, but never climb out of.
+pub(super) fn flag_basins(map_geometry: Res<MapGeometry>) {
+    for basin in map_geometry.basins() {
+        warn!(
+            "Generated a basin of {} tiles around {} that units cannot escape from.",
+            basin.len(),
+            basin[0]
+        );
+    }
+}
+
 /// Sets the starting water table
 pub(super) fn initialize_water_table(
     mut water_query: Query<&mut WaterVolume>,
@@ -137,3 +380,139 @@ pub(super) fn initialize_water_table(
         *water_volume = WaterVolume::new(water_config.initial_water);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    use super::*;
+
+    /// The [`Id`] of a terrain variety with the provided `name`.
+    fn terrain(name: &str) -> Id<Terrain> {
+        Id::from_name(name.to_string())
+    }
+
+    #[test]
+    fn all_zero_weights_are_rejected() {
+        let error = TerrainWeights::builder()
+            .with(terrain("grassy"), 0.)
+            .with(terrain("rocky"), 0.)
+            .build()
+            .unwrap_err();
+
+        assert_eq!(error, TerrainWeightsError::NoPositiveWeight);
+        assert_eq!(
+            TerrainWeights::builder().build().unwrap_err(),
+            TerrainWeightsError::NoPositiveWeight
+        );
+    }
+
+    #[test]
+    fn non_finite_weights_are_rejected() {
+        let error = TerrainWeights::builder()
+            .with(terrain("grassy"), 1.)
+            .with(terrain("rocky"), f32::NAN)
+            .build()
+            .unwrap_err();
+
+        assert!(matches!(
+            error,
+            TerrainWeightsError::NonFinite { terrain_id, .. } if terrain_id == terrain("rocky")
+        ));
+    }
+
+    #[test]
+    fn negative_weights_are_rejected() {
+        let error = TerrainWeights::builder()
+            .with(terrain("grassy"), 1.)
+            .with(terrain("rocky"), -0.5)
+            .build()
+            .unwrap_err();
+
+        assert_eq!(
+            error,
+            TerrainWeightsError::Negative {
+                terrain_id: terrain("rocky"),
+                weight: -0.5
+            }
+        );
+    }
+
+    #[test]
+    fn weights_are_kept_in_sorted_order() {
+        let weights = TerrainWeights::builder()
+            .with(terrain("rocky"), 0.2)
+            .with(terrain("grassy"), 1.)
+            .build()
+            .unwrap();
+
+        let mut expected = vec![(terrain("grassy"), 1.), (terrain("rocky"), 0.2)];
+        expected.sort_by_key(|&(terrain_id, _)| terrain_id);
+        assert_eq!(weights.iter().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn choices_are_proportional_to_normalized_weights() {
+        let weights = TerrainWeights::builder()
+            .with(terrain("grassy"), 3.)
+            .with(terrain("rocky"), 1.)
+            .with(terrain("swampy"), 0.)
+            .build()
+            .unwrap();
+
+        let mut rng = SmallRng::seed_from_u64(0);
+        let n_samples = 10_000;
+        let mut counts: HashMap<Id<Terrain>, usize> = HashMap::default();
+        for _ in 0..n_samples {
+            *counts.entry(weights.choose(&mut rng)).or_default() += 1;
+        }
+
+        let fraction = |name: &str| {
+            counts.get(&terrain(name)).copied().unwrap_or_default() as f32 / n_samples as f32
+        };
+        assert!((fraction("grassy") - 0.75).abs() < 0.02);
+        assert!((fraction("rocky") - 0.25).abs() < 0.02);
+        assert_eq!(fraction("swampy"), 0.);
+    }
+
+    /// A grassy map with a rocky blob, a rocky speck in the middle of the grass and one on the edge of the map.
+    fn speckled_map() -> HashMap<Hex, Id<Terrain>> {
+        let mut terrain_map: HashMap<Hex, Id<Terrain>> = hexagon(Hex::ZERO, 5)
+            .map(|hex| (hex, terrain("grassy")))
+            .collect();
+
+        for hex in hexagon(Hex::new(-2, 0), 1) {
+            terrain_map.insert(hex, terrain("rocky"));
+        }
+        terrain_map.insert(Hex::new(3, -1), terrain("rocky"));
+        terrain_map.insert(Hex::new(5, 0), terrain("rocky"));
+
+        terrain_map
+    }
+
+    #[test]
+    fn smoothing_removes_specks_but_keeps_blobs() {
+        let mut terrain_map = speckled_map();
+        let smoothing = TerrainSmoothing {
+            iterations: 3,
+            min_neighbors: 4,
+        };
+        smoothing.smooth(&mut terrain_map);
+
+        assert_eq!(terrain_map[&Hex::new(3, -1)], terrain("grassy"));
+        // Corner tiles only have three neighbors
+        assert_eq!(terrain_map[&Hex::new(5, 0)], terrain("grassy"));
+        for hex in hexagon(Hex::new(-2, 0), 1) {
+            assert_eq!(terrain_map[&hex], terrain("rocky"), "{hex:?}");
+        }
+        assert_eq!(terrain_map.len(), hexagon(Hex::ZERO, 5).count());
+    }
+
+    #[test]
+    fn smoothing_is_disabled_by_default() {
+        let mut terrain_map = speckled_map();
+        TerrainSmoothing::default().smooth(&mut terrain_map);
+
+        assert_eq!(terrain_map, speckled_map());
+    }
+}
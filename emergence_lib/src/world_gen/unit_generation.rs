@@ -10,6 +10,7 @@ use crate::structures::structure_manifest::Structure;
 use crate::units::unit_assets::UnitHandles;
 use crate::units::unit_manifest::UnitManifest;
 use crate::units::UnitBundle;
+use crate::utils::collections::{ordered, ordered_iter};
 
 use bevy::prelude::*;
 use rand::Rng;
@@ -27,10 +28,15 @@ pub(super) fn generate_units(
     mut rng: ResMut<GlobalRng>,
 ) {
     info!("Generating units...");
+    let config = config.with_preset_applied();
+
+    // Bundles are spawned together at the end, which is much faster than spawning them one at a time
+    let mut unit_bundles = Vec::new();
 
     // Collect out so we can mutate the height map to flatten the terrain while in the loop
-    for voxel_pos in map_geometry.walkable_voxels() {
-        for (&unit_id, &chance) in &config.unit_chances {
+    // Iteration order must be fixed, as the RNG is sampled in the loop
+    for voxel_pos in ordered(map_geometry.walkable_voxels()) {
+        for (&unit_id, &chance) in ordered_iter(&config.unit_chances) {
             if rng.gen::<f32>() < chance {
                 let unit_bundle = if let Some(ref unit_handles) = maybe_unit_handles {
                     UnitBundle::randomized(
@@ -49,10 +55,12 @@ pub(super) fn generate_units(
                     )
                 };
 
-                commands.spawn(unit_bundle);
+                unit_bundles.push(unit_bundle);
             }
         }
     }
+
+    commands.spawn_batch(unit_bundles);
 }
 
 /// Sets all the starting organisms to a random state to avoid strange synchronization issues.
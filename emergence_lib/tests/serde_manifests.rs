@@ -18,6 +18,7 @@ use emergence_lib::{
         RawOrganismId, RawOrganismVariety,
     },
     structures::{
+        adjacency::{RawAdjacencyRule, RawNeighborCondition},
         structure_manifest::{RawStructureData, RawStructureKind, RawStructureManifest},
         Footprint,
     },
@@ -131,6 +132,10 @@ fn can_serialize_unit_manifest() {
                         (16, 0.1),
                     ]),
                     max_age: 10.,
+                    steering_momentum: 0.5,
+                    idle_behavior_probability: 0.05,
+                    rest_energy_recovery: 0.5,
+                    nest_radius: 5,
                 },
             ),
             (
@@ -145,6 +150,10 @@ fn can_serialize_unit_manifest() {
                     max_impatience: 0,
                     wandering_behavior: WanderingBehavior::from_iter([(0, 0.7), (16, 0.1)]),
                     max_age: 0.2,
+                    steering_momentum: 0.,
+                    idle_behavior_probability: 0.,
+                    rest_energy_recovery: 0.,
+                    nest_radius: 0,
                 },
             ),
         ]),
@@ -249,6 +258,10 @@ fn can_serialize_structure_manifest() {
                     can_walk_on_roof: false,
                     can_walk_through: false,
                     vegetative_reproduction: None,
+                    adjacency_rules: vec![RawAdjacencyRule {
+                        neighbor: RawNeighborCondition::Flooded,
+                        multiplier: 1.5,
+                    }],
                 },
             ),
             (
@@ -266,6 +279,7 @@ fn can_serialize_structure_manifest() {
                     can_walk_on_roof: false,
                     can_walk_through: true,
                     vegetative_reproduction: None,
+                    adjacency_rules: Vec::new(),
                 },
             ),
             (
@@ -280,6 +294,7 @@ fn can_serialize_structure_manifest() {
                     can_walk_on_roof: false,
                     can_walk_through: false,
                     vegetative_reproduction: None,
+                    adjacency_rules: Vec::new(),
                 },
             ),
             (
@@ -307,6 +322,7 @@ fn can_serialize_structure_manifest() {
                     can_walk_on_roof: false,
                     can_walk_through: false,
                     vegetative_reproduction: None,
+                    adjacency_rules: Vec::new(),
                 },
             ),
             (
@@ -335,6 +351,7 @@ fn can_serialize_structure_manifest() {
                         period: 10.,
                         energy_threshold: 30.,
                     }),
+                    adjacency_rules: Vec::new(),
                 },
             ),
             (
@@ -354,6 +371,7 @@ fn can_serialize_structure_manifest() {
                     can_walk_on_roof: false,
                     can_walk_through: false,
                     vegetative_reproduction: None,
+                    adjacency_rules: Vec::new(),
                 },
             ),
             (
@@ -374,6 +392,7 @@ fn can_serialize_structure_manifest() {
                     can_walk_on_roof: false,
                     can_walk_through: false,
                     vegetative_reproduction: None,
+                    adjacency_rules: Vec::new(),
                 },
             ),
         ]),